@@ -0,0 +1,97 @@
+use chrono::{TimeZone, Utc};
+use copilot_usage::models::{ModelUsage, Theme, UsageStats};
+use copilot_usage::themes::ThemeColors;
+use copilot_usage::ui::components::trend::TrendData;
+use copilot_usage::ui::render_ui;
+use copilot_usage::ui::state::{AppState, AppStateManager};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use ratatui::{Terminal, backend::TestBackend};
+
+const SIZES: [(u16, u16); 3] = [(80, 24), (120, 40), (200, 60)];
+const MODEL_COUNTS: [usize; 3] = [5, 50, 500];
+
+fn stats(model_count: usize) -> UsageStats {
+    let models: Vec<ModelUsage> = (0..model_count)
+        .map(|i| {
+            let used = (model_count - i) as f64;
+            ModelUsage {
+                name: format!("model-{}", i),
+                used,
+                limit: 300.0,
+                percentage: used / 3.0,
+                ..ModelUsage::default()
+            }
+        })
+        .collect();
+    let total_used = models.iter().map(|m| m.used).sum();
+
+    UsageStats {
+        total_used,
+        total_limit: 300.0,
+        percentage: total_used / 3.0,
+        reset_date: Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap(),
+        billing_cycle_day: 1,
+        models,
+        username: "benchuser".to_string(),
+        ..UsageStats::default()
+    }
+}
+
+fn states() -> Vec<(&'static str, AppState)> {
+    vec![
+        ("dashboard", AppState::Dashboard),
+        ("command_menu", AppState::CommandMenu),
+        ("theme_selector", AppState::ThemeSelector),
+        ("help", AppState::ShowHelp),
+        ("loading", AppState::LoadingRefresh),
+    ]
+}
+
+fn bench_render(c: &mut Criterion) {
+    let colors = ThemeColors::dark();
+    let daily_trend = TrendData {
+        daily: (0..30).map(|day| (day * 7 % 13) as u64).collect(),
+        ..TrendData::default()
+    };
+    let now = Utc::now();
+
+    for (name, state) in states() {
+        let mut group = c.benchmark_group(format!("render/{}", name));
+
+        for (width, height) in SIZES {
+            for model_count in MODEL_COUNTS {
+                let stats = stats(model_count);
+                let mut app = AppStateManager::new();
+                app.state = state.clone();
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+
+                group.bench_with_input(
+                    BenchmarkId::from_parameter(format!("{}x{}/{}", width, height, model_count)),
+                    &model_count,
+                    |b, _| {
+                        b.iter(|| {
+                            terminal
+                                .draw(|f| {
+                                    render_ui(
+                                        f,
+                                        &stats,
+                                        &daily_trend,
+                                        &colors,
+                                        &app,
+                                        Theme::Dark,
+                                        now,
+                                    );
+                                })
+                                .unwrap();
+                        })
+                    },
+                );
+            }
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_render);
+criterion_main!(benches);
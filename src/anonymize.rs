@@ -0,0 +1,134 @@
+//! Anonymization of live API payloads into committable test fixtures
+//! (used by `cargo xtask fetch-fixture`)
+
+use anyhow::{Context, Result, bail};
+use rand::Rng;
+
+use crate::models::{UsageData, UsageItem};
+
+/// Quantities are scaled by a random factor in this range
+const JITTER: std::ops::RangeInclusive<f64> = 0.8..=1.2;
+
+/// Replaces the username with a random one and jitters every quantity.
+/// Model names, SKUs, prices and dates are kept so the fixture stays
+/// realistic; fields unknown to `UsageData` are already dropped by serde.
+pub fn anonymize<R: Rng>(data: &UsageData, rng: &mut R) -> UsageData {
+    UsageData {
+        time_period: data.time_period.clone(),
+        user: format!("user-{:06x}", rng.random_range(0..0x100_0000u32)),
+        usage_items: data
+            .usage_items
+            .iter()
+            .map(|item| jitter_item(item, rng.random_range(JITTER)))
+            .collect(),
+        note: data.note.clone(),
+    }
+}
+
+/// Scales the gross and discounted quantities of a row by the same factor
+/// and recomputes net quantities and amounts so the row stays consistent
+fn jitter_item(item: &UsageItem, factor: f64) -> UsageItem {
+    let gross_quantity = round2(item.gross_quantity * factor);
+    let discount_quantity = round2(item.discount_quantity * factor).min(gross_quantity);
+    let net_quantity = round2(gross_quantity - discount_quantity);
+
+    UsageItem {
+        gross_quantity,
+        gross_amount: round2(gross_quantity * item.price_per_unit),
+        discount_quantity,
+        discount_amount: round2(discount_quantity * item.price_per_unit),
+        net_quantity,
+        net_amount: round2(net_quantity * item.price_per_unit),
+        ..item.clone()
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Schema check for a fixture: it must parse as `UsageData` and hold only
+/// finite, non-negative quantities with net = gross - discount
+pub fn validate_fixture(json: &str) -> Result<UsageData> {
+    let data: UsageData =
+        serde_json::from_str(json).context("Fixture does not match the usage schema")?;
+
+    for item in &data.usage_items {
+        let quantities = [
+            item.gross_quantity,
+            item.discount_quantity,
+            item.net_quantity,
+            item.price_per_unit,
+        ];
+        if quantities.iter().any(|q| !q.is_finite() || *q < 0.0) {
+            bail!("Invalid quantities for model '{}'", item.model);
+        }
+        if (item.gross_quantity - item.discount_quantity - item.net_quantity).abs() > 0.01 {
+            bail!(
+                "Net quantity doesn't match gross - discount for model '{}'",
+                item.model
+            );
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    const REAL_USER: &str = "cesar-real-handle";
+
+    fn live_payload() -> UsageData {
+        let mut data: UsageData =
+            serde_json::from_str(include_str!("../tests/fixtures/usage_day_sliced.json")).unwrap();
+        data.user = REAL_USER.to_string();
+        data
+    }
+
+    #[test]
+    fn test_no_identifying_strings_survive() {
+        let original = live_payload();
+        let raw = serde_json::to_string(&original).unwrap();
+        assert!(raw.contains(REAL_USER));
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let anonymized = anonymize(&original, &mut rng);
+        let json = serde_json::to_string_pretty(&anonymized).unwrap();
+
+        assert!(!json.contains(REAL_USER));
+        assert!(!json.contains("cesar"));
+        assert!(anonymized.user.starts_with("user-"));
+    }
+
+    #[test]
+    fn test_models_preserved_and_quantities_jittered() {
+        let original = live_payload();
+        let mut rng = StdRng::seed_from_u64(42);
+        let anonymized = anonymize(&original, &mut rng);
+
+        assert_eq!(anonymized.usage_items.len(), original.usage_items.len());
+        for (before, after) in original.usage_items.iter().zip(&anonymized.usage_items) {
+            assert_eq!(before.model, after.model);
+            assert_eq!(before.date, after.date);
+            assert!(after.gross_quantity >= round2(before.gross_quantity * 0.8));
+            assert!(after.gross_quantity <= round2(before.gross_quantity * 1.2));
+        }
+
+        let json = serde_json::to_string(&anonymized).unwrap();
+        validate_fixture(&json).unwrap();
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_fixtures() {
+        assert!(validate_fixture("{\"user\": \"x\"}").is_err());
+
+        let mut data = live_payload();
+        data.usage_items[0].net_quantity = 99.0;
+        let json = serde_json::to_string(&data).unwrap();
+        assert!(validate_fixture(&json).is_err());
+    }
+}
@@ -1,76 +1,231 @@
-use crate::models::{CacheEntry, CacheStatus, UsageData};
+use crate::api::ConditionalUsage;
+use crate::config::DEFAULT_PROFILE;
+use crate::history::{DEFAULT_RETENTION_DAYS, History};
+use crate::models::{CacheEntry, CacheStatus, CacheValidators, RateLimitInfo, UsageData};
+use crate::ownership;
 use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// How many quarantined cache files are kept next to the cache
+const MAX_QUARANTINED: usize = 2;
 
 pub struct Cache {
     cache_path: PathBuf,
-    ttl_minutes: u64,
+    ttl: Duration,
+    history: Option<History>,
 }
 
+/// Environment variable with a TTL for every run, like `--cache-ttl`
+pub const CACHE_TTL_ENV: &str = "COPILOT_USAGE_CACHE_TTL";
+
+/// Cache file used before profiles existed; it belongs to the default profile
+const LEGACY_CACHE_FILE: &str = "usage.json";
+
 impl Cache {
-    pub fn new(ttl_minutes: u64) -> Result<Self> {
+    /// Cache of one profile, so accounts never see each other's data
+    pub fn new(profile: &str, ttl_minutes: u64) -> Result<Self> {
         let proj_dirs = ProjectDirs::from("com", "copilot-usage", "copilot-usage")
             .context("Failed to determine cache directory")?;
 
         let cache_dir = proj_dirs.cache_dir();
         fs::create_dir_all(cache_dir)?;
 
-        let cache_path = cache_dir.join("usage.json");
-
         Ok(Self {
-            cache_path,
-            ttl_minutes,
+            cache_path: profile_cache_path(cache_dir, profile),
+            ttl: Duration::minutes(ttl_minutes as i64),
+            history: History::new(profile, DEFAULT_RETENTION_DAYS).ok(),
         })
     }
 
-    pub fn set(&self, data: &UsageData) -> Result<()> {
+    /// Cache file at `cache_path`, without the usage history: for tests,
+    /// or a tool embedding the library in its own directories
+    pub fn with_path(cache_path: PathBuf, ttl_minutes: u64) -> Self {
+        Self {
+            cache_path,
+            ttl: Duration::minutes(ttl_minutes as i64),
+            history: None,
+        }
+    }
+
+    /// Replaces the TTL for this invocation (`--max-age`)
+    pub fn with_max_age(mut self, max_age: std::time::Duration) -> Self {
+        self.ttl = Duration::from_std(max_age).unwrap_or(Duration::MAX);
+        self
+    }
+
+    /// Overrides how many days of snapshots the history file keeps
+    pub fn with_history_retention(mut self, days: u64) -> Self {
+        self.history = self.history.map(|history| history.with_retention(days));
+        self
+    }
+
+    pub fn set(&self, data: &UsageData, rate_limit: Option<RateLimitInfo>) -> Result<()> {
+        self.set_validated(data, rate_limit, CacheValidators::default())
+    }
+
+    /// Like `set`, keeping the validators for the next conditional request
+    pub fn set_validated(
+        &self,
+        data: &UsageData,
+        rate_limit: Option<RateLimitInfo>,
+        validators: CacheValidators,
+    ) -> Result<()> {
         let entry = CacheEntry {
             data: data.clone(),
             timestamp: Utc::now(),
+            rate_limit,
+            validators,
         };
 
         let content = serde_json::to_string_pretty(&entry)?;
-        fs::write(&self.cache_path, content)?;
+        write_atomic(&self.cache_path, content.as_bytes())?;
+        tracing::debug!(path = %self.cache_path.display(), "cache written");
+
+        // History is best-effort: a failed snapshot must not fail the cache write
+        if let Some(history) = &self.history {
+            let _ = history.record(data);
+        }
 
         Ok(())
     }
 
+    /// Caches the answer to a conditional fetch made with the validators of
+    /// `previous`. A 304 keeps its data and restarts its TTL.
+    pub fn store(
+        &self,
+        response: ConditionalUsage,
+        previous: Option<CacheEntry>,
+        rate_limit: Option<RateLimitInfo>,
+    ) -> Result<UsageData> {
+        let (data, validators) = match (response, previous) {
+            (ConditionalUsage::Fresh(data, validators), _) => (data, validators),
+            (ConditionalUsage::NotModified, Some(previous)) => (previous.data, previous.validators),
+            (ConditionalUsage::NotModified, None) => {
+                anyhow::bail!("GitHub answered 304 Not Modified, but there is no cached usage")
+            }
+        };
+        self.set_validated(&data, rate_limit, validators)?;
+        Ok(data)
+    }
+
     pub fn invalidate(&self) -> Result<()> {
         if self.cache_path.exists() {
             fs::remove_file(&self.cache_path)?;
+            tracing::debug!(path = %self.cache_path.display(), "cache invalidated");
         }
         Ok(())
     }
 
-    /// Returns detailed cache status
-    pub fn status(&self) -> CacheStatus {
-        if !self.cache_path.exists() {
-            return CacheStatus::Missing;
-        }
+    pub fn path(&self) -> &Path {
+        &self.cache_path
+    }
 
-        let content = match fs::read_to_string(&self.cache_path) {
-            Ok(c) => c,
-            Err(_) => return CacheStatus::Corrupted,
-        };
+    /// The cached response as stored, without looking at the TTL
+    pub fn entry(&self) -> Result<Option<CacheEntry>> {
+        self.read_entry().transpose()
+    }
 
-        let entry: CacheEntry = match serde_json::from_str(&content) {
-            Ok(e) => e,
-            Err(_) => return CacheStatus::Corrupted,
+    /// Returns detailed cache status. A corrupted file is moved aside and
+    /// reported as Missing, so the next fetch rewrites it; Corrupted only
+    /// when it can't be moved.
+    pub fn status(&self) -> CacheStatus {
+        let path = self.cache_path.display();
+        let entry = match self.read_entry() {
+            None => {
+                tracing::debug!(%path, "cache missing");
+                return CacheStatus::Missing;
+            }
+            Some(Ok(entry)) => entry,
+            Some(Err(e)) => {
+                tracing::warn!(%path, error = %e, "cache corrupted");
+                return match self.quarantine_at(Utc::now()) {
+                    Ok(_) => CacheStatus::Missing,
+                    Err(_) => CacheStatus::Corrupted,
+                };
+            }
         };
 
         let age = Utc::now() - entry.timestamp;
-        let ttl = Duration::minutes(self.ttl_minutes as i64);
+        let stale = age > self.ttl;
+        tracing::debug!(%path, age_secs = age.num_seconds(), stale, "cache read");
 
-        if age > ttl {
-            CacheStatus::Expired
+        if stale {
+            CacheStatus::Stale(entry.data)
         } else {
             CacheStatus::Fresh(entry.data)
         }
     }
 
+    /// Moves a corrupted cache file aside as `usage.json.corrupt-<timestamp>`
+    /// so the next read is a clean miss. Returns where it was moved to.
+    pub fn quarantine_if_corrupted(&self) -> Result<Option<PathBuf>> {
+        if !matches!(self.read_entry(), Some(Err(_))) {
+            return Ok(None);
+        }
+        self.quarantine_at(Utc::now()).map(Some)
+    }
+
+    /// None when there is no cache file; an error when it can't be read or parsed
+    fn read_entry(&self) -> Option<Result<CacheEntry>> {
+        if !self.cache_path.exists() {
+            return None;
+        }
+        Some(
+            fs::read_to_string(&self.cache_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str(&content)?)),
+        )
+    }
+
+    fn quarantine_at(&self, now: DateTime<Utc>) -> Result<PathBuf> {
+        let file_name = self
+            .cache_path
+            .file_name()
+            .context("Invalid cache path")?
+            .to_string_lossy()
+            .to_string();
+        let prefix = format!("{}.corrupt-", file_name);
+        let target = self.cache_path.with_file_name(format!(
+            "{}{}",
+            prefix,
+            now.format("%Y%m%dT%H%M%S%.6fZ")
+        ));
+
+        fs::rename(&self.cache_path, &target)
+            .with_context(|| format!("Failed to quarantine {}", self.cache_path.display()))?;
+        tracing::warn!(to = %target.display(), "corrupted cache moved aside");
+
+        // Keep only the newest few; timestamps sort chronologically by name
+        if let Some(dir) = self.cache_path.parent() {
+            let mut quarantined: Vec<PathBuf> = fs::read_dir(dir)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with(&prefix))
+                })
+                .collect();
+            quarantined.sort();
+            let excess = quarantined.len().saturating_sub(MAX_QUARANTINED);
+            for old in &quarantined[..excess] {
+                let _ = fs::remove_file(old);
+            }
+        }
+
+        Ok(target)
+    }
+
+    /// Rate-limit state stored with the cached response, if any
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        let content = fs::read_to_string(&self.cache_path).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+        entry.rate_limit
+    }
+
     pub fn last_updated(&self) -> Result<Option<DateTime<Utc>>> {
         if !self.cache_path.exists() {
             return Ok(None);
@@ -82,3 +237,730 @@ impl Cache {
         Ok(Some(entry.timestamp))
     }
 }
+
+/// Requests of single days for the daily view, one file per day under
+/// `days/<profile>/`
+pub struct DayCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DayEntry {
+    requests: f64,
+    timestamp: DateTime<Utc>,
+}
+
+impl DayCache {
+    pub fn new(profile: &str, ttl_minutes: u64) -> Result<Self> {
+        let proj_dirs = ProjectDirs::from("com", "copilot-usage", "copilot-usage")
+            .context("Failed to determine cache directory")?;
+
+        let dir = proj_dirs.cache_dir().join("days").join(profile);
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            ttl: Duration::minutes(ttl_minutes as i64),
+        })
+    }
+
+    /// Create a DayCache in a custom directory (for testing)
+    #[cfg(test)]
+    pub fn with_dir(dir: PathBuf, ttl_minutes: u64) -> Self {
+        Self {
+            dir,
+            ttl: Duration::minutes(ttl_minutes as i64),
+        }
+    }
+
+    /// Cached requests of `date`, if still good
+    pub fn get(&self, date: NaiveDate) -> Option<f64> {
+        self.get_at(date, Utc::now())
+    }
+
+    /// A day fetched after it ended (UTC) won't change anymore; until then
+    /// the entry follows the TTL like the month cache
+    fn get_at(&self, date: NaiveDate, now: DateTime<Utc>) -> Option<f64> {
+        let content = fs::read_to_string(self.day_path(date)).ok()?;
+        let entry: DayEntry = serde_json::from_str(&content).ok()?;
+
+        let day_end = date.succ_opt()?.and_time(Default::default()).and_utc();
+        let settled = entry.timestamp >= day_end;
+        (settled || now - entry.timestamp <= self.ttl).then_some(entry.requests)
+    }
+
+    pub fn set(&self, date: NaiveDate, requests: f64) -> Result<()> {
+        self.set_at(date, requests, Utc::now())
+    }
+
+    fn set_at(&self, date: NaiveDate, requests: f64, now: DateTime<Utc>) -> Result<()> {
+        let entry = DayEntry {
+            requests,
+            timestamp: now,
+        };
+        let content = serde_json::to_string(&entry)?;
+        write_atomic(&self.day_path(date), content.as_bytes())?;
+        Ok(())
+    }
+
+    /// Like `Cache::with_max_age`, for days that aren't over yet
+    pub fn with_max_age(mut self, max_age: std::time::Duration) -> Self {
+        self.ttl = Duration::from_std(max_age).unwrap_or(Duration::MAX);
+        self
+    }
+
+    /// Forgets every cached day of the profile
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    fn day_path(&self, date: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}.json", date.format("%Y-%m-%d")))
+    }
+}
+
+/// Usage of past months for `compare`, one file per month under
+/// `months/<profile>/`. A month fetched after it ended can't change, so
+/// it's kept for good; one fetched earlier is never served.
+pub struct MonthCache {
+    dir: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MonthEntry {
+    data: UsageData,
+    timestamp: DateTime<Utc>,
+}
+
+impl MonthCache {
+    pub fn new(profile: &str) -> Result<Self> {
+        let proj_dirs = ProjectDirs::from("com", "copilot-usage", "copilot-usage")
+            .context("Failed to determine cache directory")?;
+
+        let dir = proj_dirs.cache_dir().join("months").join(profile);
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self { dir })
+    }
+
+    /// Create a MonthCache in a custom directory (for testing)
+    #[cfg(test)]
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Cached usage of `year`/`month`, if it was fetched once the month was over
+    pub fn get(&self, year: i32, month: u32) -> Option<UsageData> {
+        let content = fs::read_to_string(self.month_path(year, month)).ok()?;
+        let entry: MonthEntry = serde_json::from_str(&content).ok()?;
+        let month_end = month_end(year, month)?;
+        (entry.timestamp >= month_end).then_some(entry.data)
+    }
+
+    /// Keeps `data` when the month is over; a month still running isn't
+    /// stored
+    pub fn set(&self, year: i32, month: u32, data: &UsageData) -> Result<()> {
+        self.set_at(year, month, data, Utc::now())
+    }
+
+    fn set_at(&self, year: i32, month: u32, data: &UsageData, now: DateTime<Utc>) -> Result<()> {
+        if month_end(year, month).is_none_or(|end| now < end) {
+            return Ok(());
+        }
+        let entry = MonthEntry {
+            data: data.clone(),
+            timestamp: now,
+        };
+        let content = serde_json::to_string(&entry)?;
+        write_atomic(&self.month_path(year, month), content.as_bytes())?;
+        Ok(())
+    }
+
+    /// Forgets every cached month of the profile
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    fn month_path(&self, year: i32, month: u32) -> PathBuf {
+        self.dir.join(format!("{:04}-{:02}.json", year, month))
+    }
+}
+
+/// First instant (UTC) after `year`/`month`
+fn month_end(year: i32, month: u32) -> Option<DateTime<Utc>> {
+    let (year, month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    Some(
+        NaiveDate::from_ymd_opt(year, month, 1)?
+            .and_time(Default::default())
+            .and_utc(),
+    )
+}
+
+/// When cached usage is good enough, from `--refresh` and `--max-age`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// The configured `cache_ttl_minutes`
+    Ttl,
+    /// Fetch only if the cache is older than this
+    MaxAge(std::time::Duration),
+    /// Always fetch
+    Refresh,
+}
+
+impl CachePolicy {
+    /// `--refresh` wins: it always invalidates
+    pub fn from_flags(refresh: bool, max_age: Option<std::time::Duration>) -> Self {
+        match (refresh, max_age) {
+            (true, _) => CachePolicy::Refresh,
+            (false, Some(max_age)) => CachePolicy::MaxAge(max_age),
+            (false, None) => CachePolicy::Ttl,
+        }
+    }
+
+    pub fn apply(self, cache: Cache) -> Cache {
+        match self {
+            CachePolicy::MaxAge(max_age) => cache.with_max_age(max_age),
+            CachePolicy::Ttl | CachePolicy::Refresh => cache,
+        }
+    }
+
+    pub fn apply_days(self, cache: DayCache) -> DayCache {
+        match self {
+            CachePolicy::MaxAge(max_age) => cache.with_max_age(max_age),
+            CachePolicy::Ttl | CachePolicy::Refresh => cache,
+        }
+    }
+}
+
+/// Parses `--cache-ttl` values: minutes, 0 to always fetch, `inf` or -1 to
+/// never expire
+pub fn parse_cache_ttl(value: &str) -> Result<std::time::Duration, String> {
+    match value.trim() {
+        "inf" | "-1" => Ok(std::time::Duration::MAX),
+        minutes => minutes
+            .parse::<u64>()
+            .ok()
+            .and_then(|minutes| minutes.checked_mul(60))
+            .map(std::time::Duration::from_secs)
+            .ok_or_else(|| format!("expected minutes, 'inf' or -1, got '{}'", value.trim())),
+    }
+}
+
+/// TTL that replaces `cache_ttl_minutes` for this run: the flag, else the
+/// environment variable. None keeps the config's.
+pub fn ttl_override(
+    flag: Option<std::time::Duration>,
+    env: Option<&str>,
+) -> Result<Option<std::time::Duration>> {
+    if flag.is_some() {
+        return Ok(flag);
+    }
+    env.map(|value| {
+        parse_cache_ttl(value).map_err(|e| anyhow::anyhow!("Invalid {}: {}", CACHE_TTL_ENV, e))
+    })
+    .transpose()
+}
+
+/// Parses `--max-age` values like `90s`, `10m`, `1h30m` or `2 days`
+pub fn parse_max_age(value: &str) -> Result<std::time::Duration, String> {
+    let mut secs: u64 = 0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return Err("empty duration, expected something like 90s or 10m".to_string());
+    }
+
+    while !rest.is_empty() {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (number, tail) = rest.split_at(digits);
+        let tail = tail.trim_start();
+        let letters = tail.len() - tail.trim_start_matches(|c: char| c.is_alphabetic()).len();
+        let (unit, tail) = tail.split_at(letters);
+
+        if number.is_empty() {
+            return Err(format!(
+                "expected a number in '{}', e.g. 90s or 10m",
+                value.trim()
+            ));
+        }
+        let unit_secs = match unit {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+            "d" | "day" | "days" => 24 * 60 * 60,
+            "" => {
+                return Err(format!(
+                    "missing unit after {}, e.g. {}s or {}m",
+                    number, number, number
+                ));
+            }
+            other => {
+                return Err(format!(
+                    "unknown unit '{}', use s, m, h or d (e.g. 90s, 10m)",
+                    other
+                ));
+            }
+        };
+        secs = number
+            .parse::<u64>()
+            .ok()
+            .and_then(|n| n.checked_mul(unit_secs))
+            .and_then(|n| secs.checked_add(n))
+            .ok_or_else(|| format!("'{}' is too long", value.trim()))?;
+        rest = tail.trim_start();
+    }
+
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+/// Writes a sibling temp file, syncs it and renames it over `path`: a
+/// process killed halfway leaves the old file, never a truncated one
+fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let file_name = path.file_name().context("Invalid cache path")?;
+    let tmp = path.with_file_name(format!(
+        "{}.tmp-{}",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    // 0600 desde el principio: el caché lleva el usuario y su consumo
+    let written = ownership::create_private(&tmp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("Failed to write {}", path.display()));
+    }
+    Ok(())
+}
+
+/// `usage-<profile>.json` in `cache_dir`
+fn profile_cache_path(cache_dir: &Path, profile: &str) -> PathBuf {
+    profile_file(cache_dir, LEGACY_CACHE_FILE, profile)
+}
+
+/// The profile's own copy of a file from before profiles existed, e.g.
+/// `usage.json` → `usage-work.json`. The default profile takes over the
+/// old single-account file if it's still around.
+pub(crate) fn profile_file(dir: &Path, legacy: &str, profile: &str) -> PathBuf {
+    let (stem, extension) = legacy.split_once('.').unwrap_or((legacy, ""));
+    let path = dir.join(format!("{}-{}.{}", stem, profile, extension));
+    let legacy = dir.join(legacy);
+    if profile == DEFAULT_PROFILE && !path.exists() && legacy.exists() {
+        let _ = fs::rename(&legacy, &path);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TimePeriod;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn usage() -> UsageData {
+        UsageData {
+            time_period: TimePeriod {
+                year: 2026,
+                month: Some(2),
+                day: None,
+            },
+            user: "testuser".to_string(),
+            usage_items: vec![],
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_corrupted_cache_is_quarantined_and_recovers() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage.json");
+        let cache = Cache::with_path(cache_path.clone(), 5);
+
+        fs::write(&cache_path, "{ this is not json").unwrap();
+        let quarantined = cache.quarantine_if_corrupted().unwrap().unwrap();
+        assert!(!cache_path.exists());
+        assert_eq!(
+            fs::read_to_string(&quarantined).unwrap(),
+            "{ this is not json"
+        );
+        assert!(
+            quarantined
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("usage.json.corrupt-")
+        );
+        assert!(matches!(cache.status(), CacheStatus::Missing));
+
+        // The refetch writes a fresh cache again
+        cache.set(&usage(), None).unwrap();
+        assert!(matches!(cache.status(), CacheStatus::Fresh(_)));
+        assert!(cache.quarantine_if_corrupted().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_status_heals_truncated_and_invalid_files() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage.json");
+        let cache = Cache::with_path(cache_path.clone(), 5);
+        cache.set(&usage(), None).unwrap();
+        let complete = fs::read_to_string(&cache_path).unwrap();
+
+        // Cortado a mitad de escritura, y directamente basura
+        let truncated = &complete[..complete.len() / 2];
+        for content in [truncated, "not json at all"] {
+            fs::write(&cache_path, content).unwrap();
+            assert!(matches!(cache.status(), CacheStatus::Missing));
+            assert!(!cache_path.exists());
+
+            cache.set(&usage(), None).unwrap();
+            assert!(matches!(cache.status(), CacheStatus::Fresh(_)));
+        }
+
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[0], "usage.json");
+        assert!(
+            names[1..]
+                .iter()
+                .all(|name| name.starts_with("usage.json.corrupt-"))
+        );
+    }
+
+    #[test]
+    fn test_not_modified_keeps_data_and_restarts_ttl() {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::with_path(dir.path().join("usage.json"), 5);
+        let validators = CacheValidators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        };
+        let fetched = ConditionalUsage::Fresh(usage(), validators.clone());
+        cache.store(fetched, None, None).unwrap();
+
+        let previous = cache.entry().unwrap().unwrap();
+        assert_eq!(previous.validators, validators);
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let data = cache
+            .store(ConditionalUsage::NotModified, Some(previous.clone()), None)
+            .unwrap();
+        assert_eq!(data.user, "testuser");
+        let entry = cache.entry().unwrap().unwrap();
+        assert_eq!(entry.validators, validators);
+        assert!(entry.timestamp > previous.timestamp);
+
+        // Sin copia anterior un 304 no tiene datos que mostrar
+        let missing = cache.store(ConditionalUsage::NotModified, None, None);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_writes_leave_no_temp_files() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage.json");
+        let cache = Cache::with_path(cache_path.clone(), 5);
+        cache.set(&usage(), None).unwrap();
+        cache.set(&usage(), None).unwrap();
+        DayCache::with_dir(dir.path().to_path_buf(), 5)
+            .set(NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(), 4.0)
+            .unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["2026-02-03.json", "usage.json"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_writes_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage.json");
+        Cache::with_path(cache_path.clone(), 5)
+            .set(&usage(), None)
+            .unwrap();
+        let mode = fs::metadata(&cache_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_quarantine_keeps_two_newest() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage.json");
+        let cache = Cache::with_path(cache_path.clone(), 5);
+
+        let mut targets = Vec::new();
+        for minute in 0..3 {
+            fs::write(&cache_path, format!("garbage {}", minute)).unwrap();
+            let now = Utc.with_ymd_and_hms(2026, 2, 1, 9, minute, 0).unwrap();
+            targets.push(cache.quarantine_at(now).unwrap());
+        }
+
+        assert!(!targets[0].exists());
+        assert!(targets[1].exists());
+        assert!(targets[2].exists());
+    }
+
+    #[test]
+    fn test_rate_limit_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::with_path(dir.path().join("usage.json"), 5);
+        assert_eq!(cache.rate_limit(), None);
+
+        let info = RateLimitInfo {
+            remaining: Some(4987),
+            reset_at: Some(Utc.with_ymd_and_hms(2026, 2, 1, 14, 32, 0).unwrap()),
+            retry_after_secs: None,
+        };
+        cache.set(&usage(), Some(info)).unwrap();
+        assert_eq!(cache.rate_limit(), Some(info));
+    }
+
+    #[test]
+    fn test_expired_cache_is_stale_with_data() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage.json");
+        let cache = Cache::with_path(cache_path.clone(), 5);
+
+        let entry = CacheEntry {
+            data: usage(),
+            timestamp: Utc::now() - Duration::minutes(10),
+            rate_limit: None,
+            validators: CacheValidators::default(),
+        };
+        fs::write(&cache_path, serde_json::to_string(&entry).unwrap()).unwrap();
+
+        match cache.status() {
+            CacheStatus::Stale(data) => assert_eq!(data.user, "testuser"),
+            other => panic!("expected stale cache, got {:?}", other),
+        }
+        assert!(cache.quarantine_if_corrupted().unwrap().is_none());
+
+        // Revalidating makes it fresh again
+        cache.set(&usage(), None).unwrap();
+        assert!(matches!(cache.status(), CacheStatus::Fresh(_)));
+    }
+
+    #[test]
+    fn test_max_age_overrides_ttl() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage.json");
+        let entry = CacheEntry {
+            data: usage(),
+            timestamp: Utc::now() - Duration::seconds(120),
+            rate_limit: None,
+            validators: CacheValidators::default(),
+        };
+        fs::write(&cache_path, serde_json::to_string(&entry).unwrap()).unwrap();
+        let status = |ttl_minutes: u64, policy: CachePolicy| {
+            policy
+                .apply(Cache::with_path(cache_path.clone(), ttl_minutes))
+                .status()
+        };
+        let minutes = |m: u64| std::time::Duration::from_secs(m * 60);
+
+        // Más largo que un TTL de 0 o de 1 minuto
+        assert!(matches!(status(0, CachePolicy::Ttl), CacheStatus::Stale(_)));
+        assert!(matches!(
+            status(0, CachePolicy::MaxAge(minutes(10))),
+            CacheStatus::Fresh(_)
+        ));
+        assert!(matches!(
+            status(1, CachePolicy::MaxAge(minutes(10))),
+            CacheStatus::Fresh(_)
+        ));
+        // Y más corto que el TTL configurado
+        assert!(matches!(status(60, CachePolicy::Ttl), CacheStatus::Fresh(_)));
+        assert!(matches!(
+            status(60, CachePolicy::MaxAge(std::time::Duration::from_secs(90))),
+            CacheStatus::Stale(_)
+        ));
+    }
+
+    #[test]
+    fn test_refresh_wins_over_max_age() {
+        let ten_minutes = std::time::Duration::from_secs(600);
+        assert_eq!(
+            CachePolicy::from_flags(true, Some(ten_minutes)),
+            CachePolicy::Refresh
+        );
+        assert_eq!(
+            CachePolicy::from_flags(false, Some(ten_minutes)),
+            CachePolicy::MaxAge(ten_minutes)
+        );
+        assert_eq!(CachePolicy::from_flags(false, None), CachePolicy::Ttl);
+    }
+
+    #[test]
+    fn test_cache_ttl_override() {
+        let minutes = |m: u64| std::time::Duration::from_secs(m * 60);
+        assert_eq!(parse_cache_ttl("30"), Ok(minutes(30)));
+        assert_eq!(parse_cache_ttl("0"), Ok(minutes(0)));
+        assert_eq!(parse_cache_ttl("inf"), Ok(std::time::Duration::MAX));
+        assert_eq!(parse_cache_ttl("-1"), Ok(std::time::Duration::MAX));
+        assert!(parse_cache_ttl("-2").is_err());
+        assert!(parse_cache_ttl("10m").is_err());
+
+        // Flag > variable > config
+        assert_eq!(
+            ttl_override(Some(minutes(2)), Some("30")).unwrap(),
+            Some(minutes(2))
+        );
+        assert_eq!(ttl_override(None, Some("30")).unwrap(), Some(minutes(30)));
+        assert_eq!(ttl_override(None, None).unwrap(), None);
+        let error = ttl_override(None, Some("soon")).unwrap_err().to_string();
+        assert!(error.contains(CACHE_TTL_ENV));
+        // Un flag válido no se ve afectado por una variable rota
+        assert!(ttl_override(Some(minutes(2)), Some("soon")).is_ok());
+    }
+
+    #[test]
+    fn test_ttl_extremes() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage.json");
+        let entry = CacheEntry {
+            data: usage(),
+            timestamp: Utc::now() - Duration::days(400),
+            rate_limit: None,
+            validators: CacheValidators::default(),
+        };
+        fs::write(&cache_path, serde_json::to_string(&entry).unwrap()).unwrap();
+        let status = |ttl: &str| {
+            CachePolicy::MaxAge(parse_cache_ttl(ttl).unwrap())
+                .apply(Cache::with_path(cache_path.clone(), 5))
+                .status()
+        };
+
+        assert!(matches!(status("inf"), CacheStatus::Fresh(_)));
+        assert!(matches!(status("0"), CacheStatus::Stale(_)));
+
+        // Recién escrito sigue expirado con 0: siempre se vuelve a pedir
+        Cache::with_path(cache_path.clone(), 5)
+            .set(&usage(), None)
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        assert!(matches!(status("0"), CacheStatus::Stale(_)));
+    }
+
+    #[test]
+    fn test_parse_max_age() {
+        let secs = |s: u64| Ok(std::time::Duration::from_secs(s));
+        assert_eq!(parse_max_age("90s"), secs(90));
+        assert_eq!(parse_max_age("10m"), secs(600));
+        assert_eq!(parse_max_age("1h30m"), secs(5400));
+        assert_eq!(parse_max_age(" 2 days "), secs(172_800));
+        assert_eq!(parse_max_age("1h 5min 3sec"), secs(3903));
+        assert_eq!(parse_max_age("0s"), secs(0));
+
+        let error = |input: &str| parse_max_age(input).unwrap_err();
+        assert_eq!(error("90"), "missing unit after 90, e.g. 90s or 90m");
+        assert!(error("10w").starts_with("unknown unit 'w'"));
+        assert!(error("m").starts_with("expected a number"));
+        assert!(error("").starts_with("empty duration"));
+        assert!(error("-5m").starts_with("expected a number"));
+        assert!(error("99999999999999999999d").ends_with("is too long"));
+    }
+
+    #[test]
+    fn test_profiles_have_separate_caches() {
+        let dir = TempDir::new().unwrap();
+        let personal = Cache::with_path(profile_cache_path(dir.path(), "personal"), 5);
+        let work = Cache::with_path(profile_cache_path(dir.path(), "work"), 5);
+
+        personal.set(&usage(), None).unwrap();
+        assert!(matches!(personal.status(), CacheStatus::Fresh(_)));
+        assert!(matches!(work.status(), CacheStatus::Missing));
+        assert!(dir.path().join("usage-personal.json").exists());
+    }
+
+    #[test]
+    fn test_default_profile_adopts_legacy_cache() {
+        let dir = TempDir::new().unwrap();
+        let legacy = dir.path().join(LEGACY_CACHE_FILE);
+        Cache::with_path(legacy.clone(), 5)
+            .set(&usage(), None)
+            .unwrap();
+
+        // Other profiles leave it alone
+        let work = profile_cache_path(dir.path(), "work");
+        assert!(legacy.exists() && !work.exists());
+
+        let path = profile_cache_path(dir.path(), DEFAULT_PROFILE);
+        assert_eq!(path, dir.path().join("usage-default.json"));
+        assert!(!legacy.exists());
+        assert!(matches!(
+            Cache::with_path(path, 5).status(),
+            CacheStatus::Fresh(_)
+        ));
+    }
+
+    #[test]
+    fn test_past_days_stay_cached() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = DayCache::with_dir(temp_dir.path().to_path_buf(), 5);
+        let day = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+        let at = |d, h| Utc.with_ymd_and_hms(2026, 2, d, h, 0, 0).unwrap();
+
+        assert_eq!(cache.get_at(day, at(3, 12)), None);
+
+        // Pedido durante el día: vale lo que el TTL
+        cache.set_at(day, 7.0, at(3, 12)).unwrap();
+        assert_eq!(cache.get_at(day, at(3, 12)), Some(7.0));
+        assert_eq!(cache.get_at(day, at(3, 13)), None);
+
+        // Pedido con el día ya cerrado: no caduca
+        cache.set_at(day, 9.0, at(4, 1)).unwrap();
+        assert_eq!(cache.get_at(day, at(20, 0)), Some(9.0));
+        assert_eq!(
+            fs::read_dir(temp_dir.path()).unwrap().count(),
+            1,
+            "one file per day"
+        );
+    }
+
+    #[test]
+    fn test_only_finished_months_are_cached() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = MonthCache::with_dir(temp_dir.path().to_path_buf());
+        let at = |m, d| Utc.with_ymd_and_hms(2026, m, d, 12, 0, 0).unwrap();
+
+        assert!(cache.get(2026, 9).is_none());
+
+        // Septiembre aún en curso: no se guarda
+        cache.set_at(2026, 9, &usage(), at(9, 30)).unwrap();
+        assert!(cache.get(2026, 9).is_none());
+
+        // Ya cerrado: se queda para siempre
+        cache.set_at(2026, 9, &usage(), at(10, 2)).unwrap();
+        assert_eq!(cache.get(2026, 9).unwrap().user, usage().user);
+        assert!(temp_dir.path().join("2026-09.json").exists());
+
+        // Diciembre cierra en enero del año siguiente
+        assert_eq!(
+            month_end(2026, 12).unwrap(),
+            Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap()
+        );
+    }
+}
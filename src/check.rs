@@ -0,0 +1,182 @@
+use anyhow::Result;
+
+use crate::models::{CheckConfig, UsageStats};
+use crate::percent::{format_percent, format_quantity, round_half_up};
+
+/// Result of `copilot-usage check`, with Nagios-style exit codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Critical,
+    /// No usage to compare: missing config, API failure, bad thresholds
+    Unknown,
+}
+
+impl CheckStatus {
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            CheckStatus::Ok => 0,
+            CheckStatus::Warning => 1,
+            CheckStatus::Critical => 2,
+            CheckStatus::Unknown => 3,
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "OK",
+            CheckStatus::Warning => "WARNING",
+            CheckStatus::Critical => "CRITICAL",
+            CheckStatus::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Usage percentages that start the warning and critical states
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub warn: f64,
+    pub crit: f64,
+}
+
+impl Thresholds {
+    /// `--warn` / `--crit` over the `[check]` table
+    pub fn new(config: &CheckConfig, warn: Option<f64>, crit: Option<f64>) -> Result<Self> {
+        let thresholds = Self {
+            warn: warn.unwrap_or(config.warn),
+            crit: crit.unwrap_or(config.crit),
+        };
+        if thresholds.warn > thresholds.crit {
+            anyhow::bail!(
+                "warn ({}) must not be above crit ({})",
+                thresholds.warn,
+                thresholds.crit
+            );
+        }
+        Ok(thresholds)
+    }
+
+    /// Compares the percentage as printed (one decimal), so the summary
+    /// line never shows 90.0% with a WARNING
+    pub fn status(&self, percentage: f64) -> CheckStatus {
+        let percentage = round_half_up(percentage, 1);
+        if percentage >= self.crit {
+            CheckStatus::Critical
+        } else if percentage >= self.warn {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Ok
+        }
+    }
+
+    /// `status` of the usage, critical regardless once the estimated cost
+    /// is past the dollar budget
+    pub fn evaluate(&self, stats: &UsageStats) -> CheckStatus {
+        if stats.over_budget() {
+            CheckStatus::Critical
+        } else {
+            self.status(stats.percentage)
+        }
+    }
+}
+
+/// `WARNING - 80.3% of premium requests used (241/300), resets Mar 01`,
+/// plus `, estimated cost $6.20 over the $5.00 budget` past the budget
+pub fn summary(status: CheckStatus, stats: &UsageStats) -> String {
+    let mut line = format!(
+        "{} - {}% of premium requests used ({}/{}), resets {}",
+        status.label(),
+        format_percent(stats.percentage, 1),
+        format_quantity(stats.total_used),
+        format_quantity(stats.total_limit),
+        stats.reset_date.format("%b %d")
+    );
+    if let Some(budget) = stats.cost_budget.filter(|_| stats.over_budget()) {
+        line.push_str(&format!(
+            ", estimated cost ${:.2} over the ${:.2} budget",
+            stats.estimated_cost, budget
+        ));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::percent::{ZONE_CRITICAL_START, ZONE_WARNING_START};
+    use crate::testing;
+
+    fn defaults() -> Thresholds {
+        Thresholds::new(&CheckConfig::default(), None, None).unwrap()
+    }
+
+    #[test]
+    fn test_defaults_follow_zones() {
+        let thresholds = defaults();
+        assert_eq!(thresholds.warn, ZONE_WARNING_START);
+        assert_eq!(thresholds.crit, ZONE_CRITICAL_START);
+    }
+
+    #[test]
+    fn test_threshold_to_exit_code() {
+        let thresholds = defaults();
+        let code = |percentage| thresholds.status(percentage).exit_code();
+
+        assert_eq!(code(0.0), 0);
+        assert_eq!(code(74.9), 0);
+        assert_eq!(code(75.0), 1);
+        // Se compara el valor impreso: 74.96 se muestra como 75.0%
+        assert_eq!(code(74.96), 1);
+        assert_eq!(code(89.9), 1);
+        assert_eq!(code(90.0), 2);
+        assert_eq!(code(160.0), 2);
+        assert_eq!(CheckStatus::Unknown.exit_code(), 3);
+    }
+
+    #[test]
+    fn test_flags_override_config() {
+        let config = CheckConfig {
+            warn: 50.0,
+            crit: 80.0,
+        };
+        let thresholds = Thresholds::new(&config, None, Some(95.0)).unwrap();
+        assert_eq!(
+            thresholds,
+            Thresholds {
+                warn: 50.0,
+                crit: 95.0
+            }
+        );
+
+        // Iguales vale: no hay zona de warning
+        let thresholds = Thresholds::new(&config, Some(90.0), Some(90.0)).unwrap();
+        assert_eq!(thresholds.status(89.0), CheckStatus::Ok);
+        assert_eq!(thresholds.status(90.0), CheckStatus::Critical);
+
+        let err = Thresholds::new(&config, Some(95.0), None).unwrap_err();
+        assert_eq!(err.to_string(), "warn (95) must not be above crit (80)");
+    }
+
+    #[test]
+    fn test_summary_line() {
+        let mut stats = testing::stats(241.0);
+        assert_eq!(
+            summary(defaults().status(stats.percentage), &stats),
+            "WARNING - 80.3% of premium requests used (241/300), resets Mar 01"
+        );
+
+        // Pasado el presupuesto en dólares es crítico aunque sobren peticiones
+        stats.cost_budget = Some(5.0);
+        stats.estimated_cost = 5.0;
+        assert_eq!(defaults().evaluate(&stats), CheckStatus::Warning);
+        stats.estimated_cost = 6.2;
+        let status = defaults().evaluate(&stats);
+        assert_eq!(status.exit_code(), 2);
+        assert_eq!(
+            summary(status, &stats),
+            "CRITICAL - 80.3% of premium requests used (241/300), resets Mar 01, \
+             estimated cost $6.20 over the $5.00 budget"
+        );
+    }
+}
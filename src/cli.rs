@@ -1,15 +1,52 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use std::fs;
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use clap::{ArgAction, ArgGroup, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 
-use crate::api::{ApiClient, calculate_stats};
-use crate::cache::Cache;
-use crate::config::ConfigManager;
-use crate::models::{CacheStatus, Theme};
+use crate::api::{
+    API_URL_ENV, ApiClient, DeviceFlow, ProxySetting, UsageProvider, config_stats,
+    observed_included_quantity, resolve_api_base_url,
+};
+use crate::cache::{
+    CACHE_TTL_ENV, Cache, CachePolicy, DayCache, MonthCache, parse_cache_ttl, parse_max_age,
+    ttl_override,
+};
+use crate::check::{self, CheckStatus, Thresholds};
+use crate::compare::{self, Comparison};
+use crate::completions;
+use crate::config::{
+    ConfigManager, TOKEN_EXPIRY_WARN_DAYS, env_token_var, parse_profile_name, parse_token_expires,
+    plan_mismatch_warning, print_token_diagnostics, refresh_conflicts,
+};
+#[cfg(unix)]
+use crate::daemon;
+use crate::export;
+use crate::history::{DEFAULT_RETENTION_DAYS, History, periods};
+use crate::i18n;
+use crate::logging;
+use crate::metrics;
+use crate::models::{CacheStatus, Config, LimitSource, Plan, Theme, UsageData};
+use crate::output::{self, waybar};
+use crate::ownership;
+use crate::period::DEFAULT_BILLING_DAY;
+use crate::redact::{redact, register_secret, token_hint};
+#[cfg(unix)]
+use crate::signals::{Signals, Wakeup};
+use crate::themes;
 use crate::ui;
-use crate::waybar;
+use crate::ui::async_handler::AppContext;
+use crate::ui::state::{DataSource, DataStatus};
+use crate::ui::styles;
+use crate::usage_range::{self, MonthRange, PeriodArg};
 
 #[derive(Parser)]
+#[command(group(ArgGroup::new("status_target").args(["waybar", "output_target"])))]
 #[command(name = "copilot-usage")]
 #[command(about = "GitHub Copilot Usage Tracker CLI")]
 #[command(version = "0.1.0")]
@@ -18,49 +55,527 @@ pub struct Cli {
     pub command: Option<Commands>,
 
     /// Force refresh data from API
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub refresh: bool,
 
-    /// Output for Waybar
-    #[arg(long)]
+    /// Fetch only if the cache is older than this (e.g. 90s, 10m), instead
+    /// of the configured TTL. --refresh still always fetches.
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_max_age)]
+    pub max_age: Option<Duration>,
+
+    /// Cache TTL in minutes for this run, instead of cache_ttl_minutes and
+    /// COPILOT_USAGE_CACHE_TTL. 0 always fetches, inf or -1 never expires.
+    #[arg(
+        long,
+        global = true,
+        value_name = "MINUTES",
+        value_parser = parse_cache_ttl,
+        allow_negative_numbers = true
+    )]
+    pub cache_ttl: Option<Duration>,
+
+    /// Profile to use instead of the default one (see `profile list`)
+    #[arg(long, global = true, value_parser = parse_profile_name)]
+    pub profile: Option<String>,
+
+    /// Log what's going on: -v info, -vv debug, -vvv trace. On stderr, or
+    /// in the log file while the dashboard is open (see `logs`)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Plain text output without colors (also when NO_COLOR is set)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Output for Waybar (same as --output-target waybar)
+    #[arg(long, conflicts_with = "output_target")]
     pub waybar: bool,
 
-    /// Use specific theme
+    /// One line of status for a bar: Waybar JSON, or text with polybar,
+    /// i3blocks or tmux color markup, filled from waybar_format
+    #[arg(long, value_enum, value_name = "TARGET")]
+    pub output_target: Option<output::Target>,
+
+    /// Keep running and print a new line on every refresh, for bars that
+    /// read a continuous script. SIGUSR1 refetches now, SIGUSR2 re-reads
+    /// the config; exits on SIGTERM or when the bar closes the pipe.
+    #[arg(
+        long = "loop",
+        requires = "status_target",
+        conflicts_with = "via_daemon"
+    )]
+    pub loop_output: bool,
+
+    /// Ask a running `copilot-usage daemon` first (status bar and prompt),
+    /// taking the normal path when none answers
+    #[arg(long, global = true)]
+    pub via_daemon: bool,
+
+    /// What status bar mode prints when it can't get usage data
+    #[arg(long, value_enum, default_value_t = WaybarOnError::ErrorJson)]
+    pub waybar_on_error: WaybarOnError,
+
+    /// Use specific theme ("auto" follows the terminal background)
     #[arg(short, long)]
     pub theme: Option<String>,
 
-    /// Show cache status
+    /// Show cache status (deprecated, use `cache status`)
     #[arg(long)]
     pub cache_status: bool,
+
+    /// Show a JSON export read-only instead of fetching (needs no config or token)
+    #[arg(long, value_name = "PATH")]
+    pub from_file: Option<PathBuf>,
+
+    /// Show frame timings in the TUI (toggle with F12)
+    #[cfg(any(debug_assertions, feature = "debug-overlay"))]
+    #[arg(long)]
+    pub debug_overlay: bool,
+}
+
+impl Cli {
+    /// The dashboard takes over the terminal, so it logs to a file
+    fn log_sink(&self) -> logging::Sink {
+        if self.runs_dashboard() {
+            logging::Sink::File
+        } else {
+            logging::Sink::Stderr
+        }
+    }
+
+    /// Whether this run ends in the dashboard (after setup, if needed)
+    fn runs_dashboard(&self) -> bool {
+        self.output_target().is_none()
+            && !self.cache_status
+            && matches!(
+                self.command,
+                None | Some(Commands::Reset) | Some(Commands::Reconfigure)
+            )
+    }
+
+    /// Only the dashboard, setup and doctor may ask questions; scripts,
+    /// bars, `check`, `metrics` and `prompt` must never block on a prompt
+    fn may_prompt(&self) -> bool {
+        self.runs_dashboard() || matches!(self.command, Some(Commands::Doctor))
+    }
+
+    /// `--waybar` is short for `--output-target waybar`
+    fn output_target(&self) -> Option<output::Target> {
+        self.output_target
+            .or(self.waybar.then_some(output::Target::Waybar))
+    }
+
+    /// --max-age wins over the TTL from --cache-ttl or the environment
+    fn cache_policy(&self) -> Result<CachePolicy> {
+        Ok(CachePolicy::from_flags(
+            self.refresh,
+            self.max_age.or(self.ttl()?),
+        ))
+    }
+
+    /// The policy after the first fetch, when --refresh is used up
+    fn ttl_policy(&self) -> Result<CachePolicy> {
+        Ok(CachePolicy::from_flags(false, self.max_age.or(self.ttl()?)))
+    }
+
+    fn ttl(&self) -> Result<Option<Duration>> {
+        ttl_override(self.cache_ttl, std::env::var(CACHE_TTL_ENV).ok().as_deref())
+    }
+
+    fn debug_overlay(&self) -> bool {
+        #[cfg(any(debug_assertions, feature = "debug-overlay"))]
+        return self.debug_overlay;
+        #[cfg(not(any(debug_assertions, feature = "debug-overlay")))]
+        return false;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WaybarOnError {
+    /// Print a warning module (`copilot-error` / `copilot-unconfigured` class)
+    ErrorJson,
+    /// Print nothing, hiding the module
+    Silent,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Show current configuration
-    Config,
+    /// Show current configuration, or change a setting
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+    /// Log in through the browser (GitHub device flow) instead of creating a token by hand
+    Login {
+        /// Client ID of a GitHub App or OAuth App with device flow enabled
+        /// (default: oauth_client_id in the profile)
+        #[arg(long)]
+        client_id: Option<String>,
+        /// Only print the URL, don't open the browser
+        #[arg(long)]
+        no_browser: bool,
+        /// Store the token in config.toml instead of the OS keyring
+        #[arg(long)]
+        no_keyring: bool,
+    },
+    /// Remove the profile's stored token (OS keyring and config.toml)
+    Logout,
     /// Reset and reconfigure settings
     Reset,
     /// Reconfigure (alias for reset)
     Reconfigure,
+    /// Show usage per day for the current billing month
+    History {
+        /// Show month totals and the month-over-month trend instead
+        #[arg(long)]
+        periods: bool,
+    },
+    /// Compare this month with the previous one, in total and per model
+    Compare,
+    /// Usage summed over whole months, per month and per model (default:
+    /// the year so far)
+    Stats {
+        /// One month or a whole year: 2025-03 or 2025
+        #[arg(long, value_parser = usage_range::parse_period, conflicts_with_all = ["from", "to"])]
+        period: Option<PeriodArg>,
+        /// First month of the range (YYYY-MM or YYYY)
+        #[arg(long, value_parser = usage_range::parse_period)]
+        from: Option<PeriodArg>,
+        /// Last month of the range (YYYY-MM or YYYY; default: this month)
+        #[arg(long, value_parser = usage_range::parse_period)]
+        to: Option<PeriodArg>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = StatsFormat::Table)]
+        format: StatsFormat,
+        /// File to write, or "-" for stdout
+        #[arg(long, short, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Manage profiles for different GitHub accounts
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Theme utilities (works without a token)
+    #[command(alias = "themes")]
+    Theme {
+        #[command(subcommand)]
+        action: ThemeAction,
+    },
+    /// Inspect or delete the cached usage
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Export the per-model usage table (uses the cache unless --refresh)
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// File to write, or "-" for stdout
+        #[arg(long, short, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Check the configuration for common problems
+    Doctor,
+    /// Print a one-line usage summary and exit 0 (ok), 1 (warn), 2 (crit)
+    /// or 3 (unknown), for cron jobs and monitoring. Never prompts.
+    Check {
+        /// Usage percentage that exits 1 (default: [check] warn, 75)
+        #[arg(long)]
+        warn: Option<f64>,
+        /// Usage percentage that exits 2 (default: [check] crit, 90)
+        #[arg(long)]
+        crit: Option<f64>,
+    },
+    /// Print the usage as OpenMetrics text for Prometheus. Never prompts.
+    Metrics {
+        /// Serve /metrics on this address (e.g. 127.0.0.1:9911) instead of
+        /// printing once; each scrape goes through the cache and its TTL
+        #[arg(long, value_name = "ADDR")]
+        listen: Option<SocketAddr>,
+    },
+    /// Print a short colored segment for a shell prompt (starship, PS1).
+    /// Reads only the cache unless --refresh; never prompts.
+    Prompt {
+        /// Text with the waybar_format placeholders (default: "<icon> {percentage}%")
+        #[arg(long)]
+        template: Option<String>,
+        /// Print nothing when the cached data is older than this many minutes
+        #[arg(long, value_name = "MINUTES")]
+        max_cache_age: Option<u64>,
+    },
+    /// Print the end of the log file, for bug reports
+    Logs {
+        /// How many lines
+        #[arg(long, default_value_t = 50)]
+        tail: usize,
+    },
+    /// Print a completion script for bash, zsh or fish
+    Completions { shell: completions::CompletionShell },
+    /// Theme names, one per line, for the completion scripts
+    #[command(name = "__complete-themes", hide = true)]
+    CompleteThemes,
+    /// Profile names, one per line, for the completion scripts
+    #[command(name = "__complete-profiles", hide = true)]
+    CompleteProfiles,
+    /// Keep the usage in memory, refreshed on the cache TTL, and serve it to
+    /// --via-daemon over a Unix socket in $XDG_RUNTIME_DIR
+    Daemon {
+        #[command(subcommand)]
+        action: Option<DaemonAction>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Change a setting, e.g. `config set plan free`
+    Set { key: ConfigKey, value: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConfigKey {
+    /// Copilot plan (free, pro, pro_plus, business, enterprise); also sets the limit
+    Plan,
+    /// UI language (en, es)
+    Language,
+    /// Day the token expires (YYYY-MM-DD, or none)
+    TokenExpires,
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// List profiles, marking the default one
+    List,
+    /// Set up a new profile
+    Add {
+        #[arg(value_parser = parse_profile_name)]
+        name: String,
+    },
+    /// Delete a profile with its cached usage and keyring token
+    Remove {
+        #[arg(value_parser = parse_profile_name)]
+        name: String,
+    },
+    /// Make a profile the default one
+    Use {
+        #[arg(value_parser = parse_profile_name)]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DaemonAction {
+    /// Show whether a daemon is running, and for which profile
+    Status,
+    /// Stop the running daemon
+    Stop,
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Show whether the cache is fresh, expired or missing
+    Status,
+    /// Delete the profile's cached usage, per-day requests and usage history
+    Clear {
+        /// Clear every profile's cache and history, not only the current one
+        #[arg(long)]
+        all_profiles: bool,
+    },
+    /// Print the cached usage data with when it was fetched
+    Show,
+}
+
+#[derive(Subcommand)]
+pub enum ThemeAction {
+    /// List available themes, one per line (name and source)
+    List {
+        /// Print the themes with their palettes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Save the theme to use, without launching the TUI
+    Set { name: String },
+    /// Print CSS custom properties for Waybar from a theme palette
+    Css {
+        /// Theme to export (defaults to the configured one)
+        #[arg(long)]
+        theme: Option<String>,
+        /// Print the palette as JSON instead of CSS
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 /// Ejecuta la CLI y maneja los comandos
 pub async fn run() -> Result<()> {
     let cli = Cli::parse();
-    let config_manager = ConfigManager::new()?;
+    // Antes que nada que pueda escribir en stderr o preguntar
+    match cli.command {
+        Some(Commands::Logs { tail }) => return print_logs(tail),
+        Some(Commands::Completions { shell }) => {
+            print!("{}", completions::script(shell));
+            return Ok(());
+        }
+        Some(Commands::CompleteThemes) => {
+            print!("{}", completions::lines(completions::theme_names()));
+            return Ok(());
+        }
+        Some(Commands::CompleteProfiles) => {
+            // Sin config no hay nada que completar, tampoco un error
+            let profiles = ConfigManager::new()
+                .and_then(|manager| manager.profiles())
+                .map(|(profiles, _)| profiles)
+                .unwrap_or_default();
+            print!("{}", completions::lines(profiles));
+            return Ok(());
+        }
+        _ => {}
+    }
+    let log_color = colors_enabled(cli.no_color, |var| std::env::var(var).ok())
+        && std::io::stderr().is_terminal();
+    logging::init(cli.verbose, cli.log_sink(), log_color);
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "copilot-usage started");
+    let mut config_manager = ConfigManager::new()?;
+    if let Some(profile) = &cli.profile {
+        config_manager = config_manager.with_profile(profile.as_str());
+    }
+    init_output(&config_manager, cli.no_color);
+    let policy = cli.cache_policy()?;
+
+    if ownership::running_as_root() {
+        warn_running_as_root(&config_manager);
+    }
+    warn_exposed_config(&config_manager, cli.may_prompt());
 
     // Comandos directos (no interactivos)
     if cli.cache_status {
-        return show_cache_status(&config_manager).await;
+        eprintln!(
+            "{} --cache-status is deprecated, use 'copilot-usage cache status'.",
+            "Warning:".yellow()
+        );
+        return show_cache_status(&config_manager, policy).await;
+    }
+
+    if let Some(path) = &cli.from_file {
+        return run_read_only(&config_manager, path, &cli).await;
     }
 
-    // Modo Waybar
-    if cli.waybar {
-        return run_waybar_mode(&config_manager, cli.refresh).await;
+    // Modo barra de estado (Waybar, polybar, i3blocks, tmux)
+    if let Some(target) = cli.output_target() {
+        let on_error = cli.waybar_on_error;
+        if cli.loop_output {
+            let ttl_policy = cli.ttl_policy()?;
+            return run_status_loop(&config_manager, policy, ttl_policy, target, on_error).await;
+        }
+        return run_status_mode(&config_manager, policy, target, on_error, cli.via_daemon).await;
     }
 
+    let ttl_policy = cli.ttl_policy()?;
     match cli.command {
-        Some(Commands::Config) => return show_config(&config_manager).await,
+        Some(Commands::Config { action: None }) => return show_config(&config_manager).await,
+        Some(Commands::Config {
+            action: Some(ConfigAction::Set { key, value }),
+        }) => return set_config(&config_manager, key, &value),
+        Some(Commands::Profile { action }) => {
+            return manage_profiles(&config_manager, action).await;
+        }
+        Some(Commands::History { periods: false }) => return show_history(&config_manager).await,
+        Some(Commands::History { periods: true }) => return show_periods(&config_manager),
+        Some(Commands::Compare) => return compare_months(&config_manager, policy).await,
+        Some(Commands::Stats {
+            period,
+            from,
+            to,
+            format,
+            output,
+        }) => {
+            let range = MonthRange::resolve(period, from, to, Utc::now())?;
+            return range_stats(&config_manager, range, format, &output).await;
+        }
+        Some(Commands::Theme {
+            action: ThemeAction::Css { theme, json },
+        }) => return print_theme_css(&config_manager, theme.as_deref(), json),
+        Some(Commands::Theme {
+            action: ThemeAction::List { json },
+        }) => {
+            if json {
+                println!("{}", themes::list_json());
+            } else {
+                print!("{}", themes::list_lines());
+            }
+            return Ok(());
+        }
+        Some(Commands::Theme {
+            action: ThemeAction::Set { name },
+        }) => return set_theme(&config_manager, &name),
+        Some(Commands::Cache { action }) => {
+            return match action {
+                CacheAction::Status => show_cache_status(&config_manager, policy).await,
+                CacheAction::Clear { all_profiles } => clear_cache(&config_manager, all_profiles),
+                CacheAction::Show => show_cache(&config_manager, policy),
+            };
+        }
+        Some(Commands::Export { format, output }) => {
+            return export_usage(&config_manager, format, &output, policy).await;
+        }
+        Some(Commands::Doctor) => return run_doctor(&config_manager).await,
+        Some(Commands::Login {
+            client_id,
+            no_browser,
+            no_keyring,
+        }) => return login(&config_manager, client_id, !no_browser, !no_keyring).await,
+        Some(Commands::Logout) => return logout(&config_manager),
+        Some(Commands::Check { warn, crit }) => {
+            let status = run_check(&config_manager, policy, warn, crit).await;
+            std::process::exit(status.exit_code());
+        }
+        Some(Commands::Metrics { listen }) => {
+            return run_metrics(&config_manager, policy, listen).await;
+        }
+        Some(Commands::Prompt {
+            template,
+            max_cache_age,
+        }) => {
+            let color = colors_enabled(cli.no_color, |var| std::env::var(var).ok());
+            let segment = output::PromptSegment {
+                template: template.unwrap_or_else(output::default_prompt_template),
+                max_cache_age: max_cache_age.map(|minutes| Duration::from_secs(minutes * 60)),
+                color,
+            };
+            let via_daemon = cli.via_daemon;
+            if let Some(text) = prompt_segment(&config_manager, policy, &segment, via_daemon).await
+            {
+                println!("{}", text);
+            }
+            return Ok(());
+        }
+        // Ya atendidos al principio
+        Some(
+            Commands::Logs { .. }
+            | Commands::Completions { .. }
+            | Commands::CompleteThemes
+            | Commands::CompleteProfiles,
+        ) => {
+            return Ok(());
+        }
+        Some(Commands::Daemon { action }) => {
+            return run_daemon(&config_manager, ttl_policy, action).await;
+        }
         Some(Commands::Reset) | Some(Commands::Reconfigure) => {
             reconfigure(&config_manager).await?;
             // Continue to interactive mode after reconfiguration
@@ -73,48 +588,497 @@ pub async fn run() -> Result<()> {
     run_interactive_mode(&config_manager, cli).await
 }
 
-async fn run_waybar_mode(config_manager: &ConfigManager, force_refresh: bool) -> Result<()> {
-    // Check config first to avoid interactive setup prompts in JSON output
-    if config_manager.load()?.is_none() {
-        eprintln!("Configuration missing. Run interactively first.");
-        return Ok(());
+/// Root has its own config and cache; files it creates in the user's
+/// directories (with `sudo -E` or a kept HOME) break later normal runs
+fn warn_running_as_root(config_manager: &ConfigManager) {
+    let config_dir = config_manager
+        .config_path()
+        .parent()
+        .unwrap_or(Path::new("/"));
+    eprintln!(
+        "{} running as root, so config and cache are root's ({}), and files created now \
+         can break later runs without sudo. Run copilot-usage as yourself; \
+         `copilot-usage doctor` finds root-owned files.",
+        "Warning:".yellow(),
+        config_dir.display()
+    );
+}
+
+/// A config other users can read exposes the token in it. With `may_prompt`
+/// it offers to fix it when there's someone at the terminal to ask, and
+/// otherwise says how.
+fn warn_exposed_config(config_manager: &ConfigManager, may_prompt: bool) {
+    let path = config_manager.config_path();
+    let Some(mode) = ownership::exposed_mode(path) else {
+        return;
+    };
+    eprintln!(
+        "{} {} is readable by other users (mode {:o}); it may contain your GitHub token.",
+        "Warning:".red().bold(),
+        path.display(),
+        mode
+    );
+    let interactive =
+        may_prompt && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let fix = interactive
+        && dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt("Make it readable only by you?")
+            .default(true)
+            .interact()
+            .unwrap_or(false);
+    if !fix {
+        eprintln!("  Fix it with: chmod 600 {}", path.display());
+        return;
+    }
+    match ownership::restrict_file(path) {
+        Ok(()) => eprintln!("{} Permissions set to 600", "✓".green()),
+        Err(e) => eprintln!("{} Could not change permissions: {}", "!".yellow(), e),
     }
+}
 
-    match fetch_usage_data(config_manager, force_refresh).await {
-        Ok(stats) => {
-            let config = config_manager.load()?.unwrap_or_default();
-            let output = waybar::generate_output(&stats, &config.waybar_format);
+/// Picks the UI language, icons and colors before anything is printed. A
+/// broken config is reported later by whichever command loads it.
+fn init_output(config_manager: &ConfigManager, no_color: bool) {
+    let config = config_manager.load().ok().flatten();
+    let env = |var: &str| std::env::var(var).ok();
+    i18n::set_language(i18n::Language::detect(
+        config.as_ref().and_then(|c| c.language.as_deref()),
+        env,
+    ));
+    styles::set_ascii_icons(styles::detect_ascii_icons(
+        config.as_ref().and_then(|c| c.ascii_icons),
+        env,
+    ));
+    if !colors_enabled(no_color, env) {
+        colored::control::set_override(false);
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+}
+
+/// `--no-color`, or a non-empty `NO_COLOR` (https://no-color.org)
+fn colors_enabled(no_color: bool, env: impl Fn(&str) -> Option<String>) -> bool {
+    !no_color && env("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// Always exits successfully so the bar keeps the module; failures are
+/// reported as a warning module unless `--waybar-on-error silent`
+async fn run_status_mode(
+    config_manager: &ConfigManager,
+    policy: CachePolicy,
+    target: output::Target,
+    on_error: WaybarOnError,
+    via_daemon: bool,
+) -> Result<()> {
+    if via_daemon {
+        #[cfg(unix)]
+        if let Some(output) = ask_daemon(&daemon::Request::Output {
+            profile: config_manager.profile().to_string(),
+            target,
+        })
+        .await
+        {
             println!("{}", output);
+            return Ok(());
         }
-        Err(_) => {
-            // Error is already printed to stderr by fetch_usage_data
+    }
+    if let Some(output) = status_output(config_manager, policy, target, on_error).await {
+        println!("{}", output);
+    }
+    Ok(())
+}
+
+/// `--loop`: a line per refresh until SIGTERM or a closed pipe. Each
+/// refresh goes through the cache; `policy` only applies to the first.
+#[cfg(unix)]
+async fn run_status_loop(
+    config_manager: &ConfigManager,
+    mut policy: CachePolicy,
+    ttl_policy: CachePolicy,
+    target: output::Target,
+    on_error: WaybarOnError,
+) -> Result<()> {
+    use std::io::Write;
+
+    // Antes de la primera línea: después ya pueden llegar señales
+    let mut signals = Signals::new()?;
+    loop {
+        if let Some(output) = status_output(config_manager, policy, target, on_error).await {
+            let mut stdout = std::io::stdout().lock();
+            if writeln!(stdout, "{}", output)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                // La barra cerró su extremo: nadie más va a leer
+                return Ok(());
+            }
+        }
+        policy = ttl_policy;
+
+        let delay = status_loop_delay(config_manager, ttl_policy);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            wakeup = signals.recv() => match wakeup {
+                Wakeup::Refresh => policy = CachePolicy::Refresh,
+                // status_output lee la config en cada vuelta
+                Wakeup::Reload => init_output(config_manager, false),
+                Wakeup::Exit => return Ok(()),
+            },
         }
     }
+}
+
+#[cfg(not(unix))]
+async fn run_status_loop(
+    _config_manager: &ConfigManager,
+    _policy: CachePolicy,
+    _ttl_policy: CachePolicy,
+    _target: output::Target,
+    _on_error: WaybarOnError,
+) -> Result<()> {
+    anyhow::bail!("--loop needs Unix signals, which this platform lacks")
+}
+
+/// Until the cached usage expires; soon again when there's none
+#[cfg(unix)]
+fn status_loop_delay(config_manager: &ConfigManager, policy: CachePolicy) -> Duration {
+    let config = config_manager.load().ok().flatten().unwrap_or_default();
+    let ttl = policy_ttl(policy, &config);
+    let age = Cache::new(config_manager.profile(), config.cache_ttl_minutes)
+        .ok()
+        .and_then(|cache| cache.last_updated().ok().flatten())
+        .map_or(ttl, |fetched_at| {
+            (Utc::now() - fetched_at).to_std().unwrap_or_default()
+        });
+    daemon::refresh_delay(ttl, age)
+}
+
+async fn status_output(
+    config_manager: &ConfigManager,
+    policy: CachePolicy,
+    target: output::Target,
+    on_error: WaybarOnError,
+) -> Option<String> {
+    let emit_errors = on_error == WaybarOnError::ErrorJson;
+
+    // Check config first to avoid interactive setup prompts in the bar
+    let options = match config_manager.load() {
+        Ok(Some(config)) if config_manager.resolve_token(&config).is_some() => {
+            output::Options::from_config(&config)
+        }
+        Ok(config) => {
+            eprintln!("Configuration missing. Run interactively first.");
+            let options =
+                config.map_or_else(Default::default, |c| output::Options::from_config(&c));
+            return emit_errors.then(|| output::unconfigured_output(target, &options));
+        }
+        Err(e) => {
+            eprintln!("Error: {}", redact(&format!("{:#}", e)));
+            let options = output::Options::default();
+            return emit_errors.then(|| output::error_output(target, &e, &options));
+        }
+    };
+
+    match fetch_usage_data(config_manager, policy, false, false).await {
+        Ok((stats, _)) => {
+            let config = config_manager.load().ok().flatten().unwrap_or_default();
+            Some(output::generate(
+                &stats,
+                target,
+                &config.waybar_format,
+                &output::Options::from_config(&config),
+            ))
+        }
+        Err(e) => {
+            eprintln!("Error: {}", redact(&format!("{:#}", e)));
+            // El último uso cacheado vale más que un ⚠ mientras no sea viejo
+            cached_status(config_manager, policy, target, Utc::now())
+                .or_else(|| emit_errors.then(|| output::error_output(target, &e, &options)))
+        }
+    }
+}
+
+/// The cached usage when a fetch failed, marked stale once it's past twice
+/// the TTL. `None` without a cache.
+fn cached_status(
+    config_manager: &ConfigManager,
+    policy: CachePolicy,
+    target: output::Target,
+    now: chrono::DateTime<Utc>,
+) -> Option<String> {
+    let config = config_manager.load().ok().flatten()?;
+    let cache = policy.apply(Cache::new(config_manager.profile(), config.cache_ttl_minutes).ok()?);
+    let (CacheStatus::Fresh(data) | CacheStatus::Stale(data)) = cache.status() else {
+        return None;
+    };
+    let fetched_at = cache.last_updated().ok().flatten()?;
+    let age = (now - fetched_at).to_std().unwrap_or_default();
+    let mut options = output::Options::from_config(&config);
+    if output::is_stale(age, policy_ttl(policy, &config)) {
+        options.stale_since = Some(fetched_at);
+    }
+    Some(output::generate(
+        &config_stats(&data, &config),
+        target,
+        &config.waybar_format,
+        &options,
+    ))
+}
+
+/// Prints the summary (or the error, on stderr) and returns the status the
+/// process exits with
+async fn run_check(
+    config_manager: &ConfigManager,
+    policy: CachePolicy,
+    warn: Option<f64>,
+    crit: Option<f64>,
+) -> CheckStatus {
+    let result = async {
+        let config = config_manager.load()?.unwrap_or_default();
+        let thresholds = Thresholds::new(&config.check, warn, crit)?;
+        let (stats, _) = fetch_usage_data(config_manager, policy, false, false).await?;
+        anyhow::Ok((thresholds.evaluate(&stats), stats))
+    };
+
+    match result.await {
+        Ok((status, stats)) => {
+            println!("{}", check::summary(status, &stats));
+            status
+        }
+        Err(e) => {
+            let status = CheckStatus::Unknown;
+            eprintln!("{} - {}", status.label(), redact(&format!("{:#}", e)));
+            status
+        }
+    }
+}
+
+/// The segment, or nothing when there's no usage to show: a prompt has no
+/// room for errors, so they only hide it
+async fn prompt_segment(
+    config_manager: &ConfigManager,
+    policy: CachePolicy,
+    segment: &output::PromptSegment,
+    via_daemon: bool,
+) -> Option<String> {
+    if via_daemon && policy != CachePolicy::Refresh {
+        // El daemon respondió: un cuerpo vacío es el segmento oculto
+        #[cfg(unix)]
+        if let Some(text) = ask_daemon(&daemon::Request::Prompt {
+            profile: config_manager.profile().to_string(),
+            segment: segment.clone(),
+        })
+        .await
+        {
+            return Some(text).filter(|text| !text.is_empty());
+        }
+    }
+    if policy == CachePolicy::Refresh {
+        let (stats, _) = fetch_usage_data(config_manager, policy, false, false)
+            .await
+            .ok()?;
+        let config = config_manager.load().ok().flatten().unwrap_or_default();
+        let options = output::Options::from_config(&config);
+        return segment.render(&stats, false, Duration::ZERO, &options);
+    }
+    let config = config_manager.load().ok().flatten()?;
+    let cache = policy.apply(Cache::new(config_manager.profile(), config.cache_ttl_minutes).ok()?);
+    cached_prompt(&config, &cache, segment, Utc::now())
+}
+
+/// Only the cache, never the network: stale data is shown marked as such
+fn cached_prompt(
+    config: &Config,
+    cache: &Cache,
+    segment: &output::PromptSegment,
+    now: chrono::DateTime<Utc>,
+) -> Option<String> {
+    let (data, stale) = match cache.status() {
+        CacheStatus::Fresh(data) => (data, false),
+        CacheStatus::Stale(data) => (data, true),
+        CacheStatus::Missing | CacheStatus::Corrupted => return None,
+    };
+    let fetched_at = cache.last_updated().ok().flatten()?;
+    let age = (now - fetched_at).to_std().unwrap_or_default();
+    let options = output::Options::from_config(config);
+    segment.render(&config_stats(&data, config), stale, age, &options)
+}
+
+/// Prints the metrics once, or serves them until interrupted
+async fn run_metrics(
+    config_manager: &ConfigManager,
+    policy: CachePolicy,
+    listen: Option<SocketAddr>,
+) -> Result<()> {
+    let Some(addr) = listen else {
+        print!("{}", scrape_metrics(config_manager, policy).await?);
+        return Ok(());
+    };
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    eprintln!(
+        "Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+    metrics::serve(listener, || scrape_metrics(config_manager, policy)).await
+}
+
+async fn scrape_metrics(config_manager: &ConfigManager, policy: CachePolicy) -> Result<String> {
+    let (stats, status) = fetch_usage_data(config_manager, policy, false, false).await?;
+    let age = (Utc::now() - status.fetched_at)
+        .to_std()
+        .unwrap_or_default();
+    Ok(metrics::to_openmetrics(&stats, age))
+}
+
+fn print_logs(lines: usize) -> Result<()> {
+    let dir = logging::log_dir()?;
+    let tail = logging::tail(&dir, lines)?;
+    if tail.is_empty() {
+        eprintln!(
+            "Nothing logged yet in {}. The dashboard logs there; other commands log to stderr with -v.",
+            dir.display()
+        );
+    }
+    for line in tail {
+        println!("{}", line);
+    }
     Ok(())
 }
 
+/// Serves the usage until stopped, or talks to the running daemon
+#[cfg(unix)]
+async fn run_daemon(
+    config_manager: &ConfigManager,
+    policy: CachePolicy,
+    action: Option<DaemonAction>,
+) -> Result<()> {
+    let path = daemon::socket_path();
+    match action {
+        None => {
+            eprintln!("Serving usage on {}", path.display());
+            daemon::run(&path, config_manager.profile(), |force| {
+                // Language and icons may have changed with the config
+                init_output(config_manager, false);
+                let policy = if force { CachePolicy::Refresh } else { policy };
+                // Corre en su propia tarea, con su copia del perfil
+                let config_manager = config_manager.clone();
+                async move { daemon_snapshot(&config_manager, policy).await }
+            })
+            .await
+        }
+        Some(DaemonAction::Status) => {
+            let status = daemon::send(&path, &daemon::Request::Status)
+                .await
+                .with_context(|| format!("No daemon is running on {}", path.display()))?;
+            println!("Running on {}", path.display());
+            println!("{}", status);
+            Ok(())
+        }
+        Some(DaemonAction::Stop) => {
+            daemon::send(&path, &daemon::Request::Stop)
+                .await
+                .with_context(|| format!("No daemon is running on {}", path.display()))?;
+            println!("Daemon stopped");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn run_daemon(
+    _config_manager: &ConfigManager,
+    _policy: CachePolicy,
+    _action: Option<DaemonAction>,
+) -> Result<()> {
+    anyhow::bail!("The daemon needs Unix domain sockets, which this platform lacks")
+}
+
+/// One refresh of the daemon: through the cache, like any other run
+#[cfg(unix)]
+async fn daemon_snapshot(
+    config_manager: &ConfigManager,
+    policy: CachePolicy,
+) -> Result<daemon::Snapshot> {
+    let (stats, status) = fetch_usage_data(config_manager, policy, false, false).await?;
+    let config = config_manager.load()?.unwrap_or_default();
+    let ttl = policy_ttl(policy, &config);
+    Ok(daemon::Snapshot {
+        stats,
+        fetched_at: status.fetched_at,
+        config,
+        ttl,
+    })
+}
+
+/// How long fetched usage is good for under `policy`
+#[cfg(unix)]
+fn policy_ttl(policy: CachePolicy, config: &Config) -> Duration {
+    match policy {
+        CachePolicy::MaxAge(max_age) => max_age,
+        CachePolicy::Ttl | CachePolicy::Refresh => {
+            Duration::from_secs(config.cache_ttl_minutes.saturating_mul(60))
+        }
+    }
+}
+
+/// The daemon's answer, or `None` (none running, or it has no data) to take
+/// the normal path
+#[cfg(unix)]
+async fn ask_daemon(request: &daemon::Request) -> Option<String> {
+    daemon::send(&daemon::socket_path(), request).await.ok()
+}
+
 async fn run_interactive_mode(config_manager: &ConfigManager, cli: Cli) -> Result<()> {
-    let mut force_refresh = cli.refresh;
-    let mut current_theme: Option<Theme> = cli.theme.as_ref().map(|t| Theme::from_str(t));
+    // The TUI can switch profiles; later iterations follow it
+    let mut config_manager = config_manager.clone();
+    let config_manager = &mut config_manager;
+
+    let mut policy = cli.cache_policy()?;
+    let ttl_policy = cli.ttl_policy()?;
+    let mut current_theme: Option<Theme> = cli.theme.as_deref().map(themes::resolve);
 
     // Main loop: allows reloading stats after reconfigure
     loop {
-        // Fetch data (fresh on first run if --refresh, or after reconfigure)
-        let stats = fetch_usage_data(config_manager, force_refresh).await?;
-        force_refresh = false;
+        // Fetch data (fresh on first run if --refresh, or after reconfigure).
+        // An expired cache is shown right away and refreshed in the background.
+        let (stats, status) = match fetch_usage_data(config_manager, policy, true, true).await {
+            Ok(result) => result,
+            Err(e) => {
+                if let Some(path) = export::recent_export() {
+                    eprintln!(
+                        "Your last export can still be viewed: copilot-usage --from-file {}",
+                        path.display()
+                    );
+                }
+                return Err(e);
+            }
+        };
+        policy = ttl_policy;
+
+        let config = config_manager.load()?.unwrap_or_default();
 
         // Get theme: use cached value or load from config
-        let theme = current_theme.unwrap_or_else(|| {
-            config_manager
-                .load()
-                .ok()
-                .flatten()
-                .map(|c| Theme::from_str(&c.theme))
-                .unwrap_or(Theme::Dark)
-        });
+        let theme = current_theme.unwrap_or_else(|| themes::resolve(&config.theme));
+
+        // Un cliente para todo el run; tras un reconfigure se construye otro
+        let mut context = AppContext::load(config_manager.clone()).with_cache_policy(ttl_policy);
 
         // Run UI with current stats
-        match ui::run_ui(&stats, theme)? {
+        let action = ui::run_ui(
+            &mut context,
+            &stats,
+            Some(status),
+            theme,
+            &config,
+            cli.debug_overlay(),
+            None,
+        )
+        .await?;
+        *config_manager = context.config_manager;
+
+        match action {
             None => break,
             Some(action) => match action.as_str() {
                 "quit" => break,
@@ -125,11 +1089,16 @@ async fn run_interactive_mode(config_manager: &ConfigManager, cli: Cli) -> Resul
                     save_theme_preference(config_manager, theme_name)?;
                 }
 
+                action if action.starts_with("export:") => {
+                    let path = PathBuf::from(action.strip_prefix("export:").unwrap());
+                    return run_read_only(config_manager, &path, &cli).await;
+                }
+
                 "reconfigure" => {
                     reconfigure(config_manager).await?;
                     // Reset theme to reload from new config
                     current_theme = None;
-                    force_refresh = true;
+                    policy = CachePolicy::Refresh;
                 }
 
                 _ => {}
@@ -140,9 +1109,41 @@ async fn run_interactive_mode(config_manager: &ConfigManager, cli: Cli) -> Resul
     Ok(())
 }
 
-async fn show_cache_status(config_manager: &ConfigManager) -> Result<()> {
+/// Shows a JSON export in the TUI without touching the API, cache or token
+async fn run_read_only(config_manager: &ConfigManager, path: &Path, cli: &Cli) -> Result<()> {
+    let stats = export::read_file(path)?;
+
+    // Este modo es para cuando falla todo, config incluida
+    let config = config_manager.load().ok().flatten().unwrap_or_default();
+    let theme = themes::resolve(cli.theme.as_deref().unwrap_or(&config.theme));
+
+    ui::run_ui(
+        &mut AppContext::load(config_manager.clone()),
+        &stats,
+        None,
+        theme,
+        &config,
+        cli.debug_overlay(),
+        Some(path),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn show_cache_status(config_manager: &ConfigManager, policy: CachePolicy) -> Result<()> {
     if let Some(config) = config_manager.load()? {
-        let cache = Cache::new(config.cache_ttl_minutes)?;
+        let cache = policy.apply(Cache::new(config_manager.profile(), config.cache_ttl_minutes)?);
+        // status() would move it aside silently; here it's reported
+        if let Some(path) = cache.quarantine_if_corrupted()? {
+            println!("Cache status: {}", "corrupted".red());
+            println!("Moved the corrupted cache to {}", path.display());
+            match fetch_usage_data(config_manager, CachePolicy::Ttl, false, true).await {
+                Ok(_) => println!("Refetched usage data: {}", "cache recovered".green()),
+                Err(e) => println!("Refetch failed: {:#}", e),
+            }
+            return Ok(());
+        }
+
         match cache.status() {
             CacheStatus::Fresh(_) => {
                 if let Some(timestamp) = cache.last_updated()? {
@@ -150,7 +1151,7 @@ async fn show_cache_status(config_manager: &ConfigManager) -> Result<()> {
                 }
                 println!("Cache status: {}", "fresh".green());
             }
-            CacheStatus::Expired => {
+            CacheStatus::Stale(_) => {
                 if let Some(timestamp) = cache.last_updated()? {
                     println!("Cache last updated: {}", timestamp);
                 }
@@ -169,6 +1170,74 @@ async fn show_cache_status(config_manager: &ConfigManager) -> Result<()> {
     Ok(())
 }
 
+fn clear_cache(config_manager: &ConfigManager, all_profiles: bool) -> Result<()> {
+    let current = config_manager.profile().to_string();
+    let mut profiles = vec![current.clone()];
+    if all_profiles {
+        let (names, _) = config_manager.profiles()?;
+        profiles.extend(names.into_iter().filter(|name| *name != current));
+    }
+
+    for profile in &profiles {
+        Cache::new(profile, 0)?.invalidate()?;
+        DayCache::new(profile, 0)?.clear()?;
+        MonthCache::new(profile)?.clear()?;
+        // Cada perfil tiene su historial: solo se borra el de los limpiados
+        History::new(profile, DEFAULT_RETENTION_DAYS)?.clear()?;
+        println!(
+            "Cleared the cache and usage history of profile '{}'",
+            profile
+        );
+    }
+    Ok(())
+}
+
+fn show_cache(config_manager: &ConfigManager, policy: CachePolicy) -> Result<()> {
+    let config = config_manager.load()?.unwrap_or_default();
+    let cache = policy.apply(Cache::new(
+        config_manager.profile(),
+        config.cache_ttl_minutes,
+    )?);
+    let entry = cache.entry().with_context(|| {
+        format!(
+            "Could not read {}. 'copilot-usage cache clear' removes it.",
+            cache.path().display()
+        )
+    })?;
+    let Some(entry) = entry else {
+        println!(
+            "No cached usage for profile '{}'.",
+            config_manager.profile()
+        );
+        return Ok(());
+    };
+
+    let freshness = match cache.status() {
+        CacheStatus::Fresh(_) => "fresh".green(),
+        _ => "expired".yellow(),
+    };
+    println!("Cache file: {}", cache.path().display());
+    println!(
+        "Fetched: {} ({} ago, {})",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+        format_age(chrono::Utc::now() - entry.timestamp),
+        freshness
+    );
+    println!("{}", serde_json::to_string_pretty(&entry.data)?);
+    Ok(())
+}
+
+/// "45s", "12m", "3h 5m", "2d 4h"
+fn format_age(age: chrono::Duration) -> String {
+    let secs = age.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d {}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
 async fn show_config(config_manager: &ConfigManager) -> Result<()> {
     let config = config_manager.load()?.unwrap_or_default();
 
@@ -176,24 +1245,751 @@ async fn show_config(config_manager: &ConfigManager) -> Result<()> {
         "Configuration file: {}",
         config_manager.config_path().display()
     );
-    if config.token.is_empty() {
-        println!("Token: {}", "(not set)".red());
-    } else {
-        let visible_chars = 10.min(config.token.len());
-        println!("Token: {}...", &config.token[..visible_chars]);
+    println!("Profile: {}", config_manager.profile());
+    match config_manager.resolve_token(&config) {
+        Some((token, source)) => println!("Token: {} (from {})", token_hint(&token), source),
+        None => println!("Token: {}", "(not set)".red()),
     }
     println!("Theme: {}", config.theme);
+    println!("Language: {}", i18n::language().code());
+    let ascii = styles::icons() == &styles::ASCII_ICONS;
+    println!("Icons: {}", if ascii { "ascii" } else { "nerd font" });
     println!("Cache TTL: {} minutes", config.cache_ttl_minutes);
+    match config.plan {
+        Some(plan) => println!(
+            "Premium request limit: {} ({})",
+            config.premium_request_limit,
+            plan.name()
+        ),
+        None => println!("Premium request limit: {}", config.premium_request_limit),
+    }
+    Ok(())
+}
+
+async fn run_doctor(config_manager: &ConfigManager) -> Result<()> {
+    let config = config_manager
+        .load()?
+        .context("Not configured yet. Run copilot-usage once to set it up.")?;
+
+    println!(
+        "Configuration file: {}",
+        config_manager.config_path().display()
+    );
+    println!("Profile: {}", config_manager.profile());
+    match std::env::var(API_URL_ENV) {
+        Ok(url) if !url.trim().is_empty() => println!("API: {} (from {})", url, API_URL_ENV),
+        _ => println!("API: {}", config.api_base_url),
+    }
+    let base_url = resolve_api_base_url(&config, std::env::var(API_URL_ENV).ok().as_deref())
+        .unwrap_or_else(|_| config.api_base_url.clone());
+    match ProxySetting::resolve(&config, &base_url, |name| std::env::var(name).ok()) {
+        Some(proxy) => println!("Proxy: {}", proxy),
+        None => println!("Proxy: none"),
+    }
+    if let Some(path) = &config.ca_bundle_path {
+        println!("Extra CA certificates: {}", path.display());
+    }
+
+    let mut problems = 0;
+    let mut reported_expiry = None;
+    match config_manager.resolve_token(&config) {
+        Some((token, source)) => {
+            println!("{} Token found (from {})", "✓".green(), source);
+            let checked = match ApiClient::for_config(token, &config) {
+                Ok(client) => client.validate_token().await,
+                Err(e) => Err(e),
+            };
+            match checked {
+                Ok(diagnostics) => {
+                    print_token_diagnostics(&diagnostics);
+                    reported_expiry = diagnostics.expires_on();
+                    if !diagnostics.is_ok() {
+                        problems += 1;
+                    }
+                }
+                Err(e) => {
+                    problems += 1;
+                    println!(
+                        "{} Could not check the token: {}",
+                        "!".yellow(),
+                        redact(&format!("{:#}", e))
+                    );
+                }
+            }
+        }
+        None => {
+            problems += 1;
+            println!("{} No token found", "!".yellow());
+            println!("  Set GITHUB_TOKEN or run 'copilot-usage reset'.");
+        }
+    }
+    problems += check_token_expiry(
+        config.token_expires,
+        reported_expiry,
+        Utc::now().date_naive(),
+    );
+
+    let conflicts = refresh_conflicts(&config);
+    for conflict in &conflicts {
+        println!("{} {}", "!".yellow(), conflict.summary());
+        println!("  {}", conflict.advice());
+    }
+    problems += conflicts.len();
+    if conflicts.is_empty() {
+        match config.waybar_interval_seconds {
+            Some(_) => println!("{} Refresh settings are consistent", "✓".green()),
+            None => println!(
+                "{} Refresh settings not checked (set waybar_interval_seconds to your module's interval)",
+                "-".dimmed()
+            ),
+        }
+    }
+
+    problems += check_ownership(config_manager);
+    if let Some(mode) = ownership::exposed_mode(config_manager.config_path()) {
+        problems += 1;
+        println!(
+            "{} Config file is readable by other users (mode {:o})",
+            "!".yellow(),
+            mode
+        );
+        println!(
+            "  Fix it with: chmod 600 {}",
+            config_manager.config_path().display()
+        );
+    }
+
+    if problems == 0 {
+        println!("\nNo problems found.");
+    } else {
+        println!("\n{} problem(s) found.", problems);
+    }
+    Ok(())
+}
+
+/// The expiry GitHub reported wins over the recorded one; a recorded date
+/// that disagrees is a problem too, it drives the warnings.
+/// Returns the number of problems found.
+fn check_token_expiry(
+    recorded: Option<NaiveDate>,
+    reported: Option<NaiveDate>,
+    today: NaiveDate,
+) -> usize {
+    let mut problems = 0;
+    if let (Some(recorded), Some(reported)) = (recorded, reported)
+        && recorded != reported
+    {
+        problems += 1;
+        println!(
+            "{} Recorded token expiry ({}) differs from GitHub's ({})",
+            "!".yellow(),
+            recorded,
+            reported
+        );
+        println!(
+            "  Fix it with: copilot-usage config set token-expires {}",
+            reported
+        );
+    }
+
+    let Some(expires) = reported.or(recorded) else {
+        println!(
+            "{} Token expiry unknown (record it with 'copilot-usage config set token-expires YYYY-MM-DD')",
+            "-".dimmed()
+        );
+        return problems;
+    };
+    let days = (expires - today).num_days();
+    if days < 0 {
+        println!("{} Token expired on {}", "!".yellow(), expires);
+    } else if days < TOKEN_EXPIRY_WARN_DAYS {
+        println!(
+            "{} Token expires in {} day(s), on {}: create a new one soon",
+            "!".yellow(),
+            days,
+            expires
+        );
+    } else {
+        println!(
+            "{} Token expires on {} ({} days left)",
+            "✓".green(),
+            expires,
+            days
+        );
+        return problems;
+    }
+    problems + 1
+}
+
+/// Files left by a `sudo` run that the user can no longer write.
+/// Returns the number of problems found.
+#[cfg(unix)]
+fn check_ownership(config_manager: &ConfigManager) -> usize {
+    let mut dirs: Vec<PathBuf> = config_manager
+        .config_path()
+        .parent()
+        .into_iter()
+        .map(Path::to_path_buf)
+        .collect();
+    dirs.extend(ownership::state_dirs());
+
+    let uid = ownership::current_uid();
+    if uid == 0 {
+        println!(
+            "{} Running as root: file ownership not checked",
+            "!".yellow()
+        );
+        return 1;
+    }
+
+    let foreign = ownership::foreign_files(&dirs, uid, ownership::file_owner);
+    if foreign.is_empty() {
+        println!("{} Config and cache files belong to you", "✓".green());
+        return 0;
+    }
+
+    println!(
+        "{} {} file(s) owned by another user (e.g. from a sudo run):",
+        "!".yellow(),
+        foreign.len()
+    );
+    for file in foreign.iter().take(5) {
+        let owner = if file.uid == 0 {
+            "root".to_string()
+        } else {
+            format!("uid {}", file.uid)
+        };
+        println!("  {} ({})", file.path.display(), owner);
+    }
+    if foreign.len() > 5 {
+        println!("  ... and {} more", foreign.len() - 5);
+    }
+    let user = std::env::var("USER").unwrap_or_else(|_| uid.to_string());
+    let dirs: Vec<PathBuf> = dirs.into_iter().filter(|dir| dir.exists()).collect();
+    println!("  Fix it with: {}", ownership::chown_command(&user, &dirs));
+    1
+}
+
+#[cfg(not(unix))]
+fn check_ownership(_config_manager: &ConfigManager) -> usize {
+    0
+}
+
+async fn manage_profiles(config_manager: &ConfigManager, action: ProfileAction) -> Result<()> {
+    match action {
+        ProfileAction::List => {
+            let (names, default) = config_manager.profiles()?;
+            if names.is_empty() {
+                println!("No profiles configured yet.");
+            }
+            for name in names {
+                if name == default {
+                    println!("* {} {}", name, "(default)".dimmed());
+                } else {
+                    println!("  {}", name);
+                }
+            }
+        }
+        ProfileAction::Add { name } => {
+            let profile = config_manager.clone().with_profile(name.as_str());
+            if profile.load()?.is_some() {
+                anyhow::bail!("Profile '{}' already exists", name);
+            }
+            profile.setup_interactive().await?;
+            println!("Use it with --profile {} or 'copilot-usage profile use {}'.", name, name);
+        }
+        ProfileAction::Remove { name } => {
+            config_manager.remove_profile(&name)?;
+            // Leftovers of a removed profile are best-effort
+            if let Ok(cache) = Cache::new(&name, 0) {
+                let _ = cache.invalidate();
+            }
+            let _ = config_manager.delete_keyring_token(&name);
+            println!("Removed profile '{}'", name);
+        }
+        ProfileAction::Use { name } => {
+            config_manager.set_default_profile(&name)?;
+            println!("Default profile is now '{}'", name);
+        }
+    }
+    Ok(())
+}
+
+fn set_config(config_manager: &ConfigManager, key: ConfigKey, value: &str) -> Result<()> {
+    let mut config = config_manager
+        .load()?
+        .context("Not configured yet. Run copilot-usage once to set it up.")?;
+
+    match key {
+        ConfigKey::Plan => {
+            let plan = Plan::parse(value).with_context(|| {
+                let ids: Vec<&str> = Plan::ALL.iter().map(|plan| plan.id()).collect();
+                format!("Unknown plan '{}'. Expected one of: {}", value, ids.join(", "))
+            })?;
+            config.plan = Some(plan);
+            config.premium_request_limit = plan.premium_request_limit();
+            config.limit_source = LimitSource::Plan;
+            config.plan_mismatch_warned = None;
+            config_manager.save(&config)?;
+            println!(
+                "Plan set to {} ({} premium requests)",
+                plan.name(),
+                plan.premium_request_limit()
+            );
+        }
+        ConfigKey::Language => {
+            let language = i18n::Language::parse(value).with_context(|| {
+                let codes: Vec<&str> = i18n::Language::ALL.map(i18n::Language::code).to_vec();
+                format!("Unknown language '{}'. Expected one of: {}", value, codes.join(", "))
+            })?;
+            config.language = Some(language.code().to_string());
+            config_manager.save(&config)?;
+            println!("Language set to {}", language.code());
+        }
+        ConfigKey::TokenExpires => {
+            config.token_expires = parse_token_expires(value)?;
+            config_manager.save(&config)?;
+            match config.token_expires {
+                Some(date) => println!("Token expiry set to {}", date),
+                None => println!("Token expiry cleared"),
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn show_history(config_manager: &ConfigManager) -> Result<()> {
+    let config = config_manager.load()?.unwrap_or_default();
+    let history = History::new(config_manager.profile(), config.history_retention_days)?;
+
+    let billing_day = config.billing_cycle_day.unwrap_or(DEFAULT_BILLING_DAY);
+    let (_, days) = history.load_current_period(billing_day, Utc::now())?;
+
+    if days.is_empty() {
+        println!("No history recorded for this month yet.");
+        return Ok(());
+    }
+
+    println!("{:<12} {:>10} {:>10}", "Date", "Requests", "Total");
+    for day in &days {
+        println!(
+            "{:<12} {:>10.0} {:>10.0}",
+            day.date.format("%Y-%m-%d"),
+            day.requests,
+            day.total_used
+        );
+    }
+    Ok(())
+}
+
+fn show_periods(config_manager: &ConfigManager) -> Result<()> {
+    use chrono::Utc;
+
+    let config = config_manager.load()?.unwrap_or_default();
+    let history = History::new(config_manager.profile(), config.history_retention_days)?;
+    let summaries = history.load_periods()?;
+
+    if summaries.is_empty() {
+        println!("No month totals recorded yet.");
+        return Ok(());
+    }
+
+    let limit = f64::from(config.premium_request_limit);
+    println!("{:<16} {:>10} {:>8}", "Month", "Requests", "Usage");
+    for period in &summaries {
+        println!(
+            "{:<16} {:>10.0} {:>7}%",
+            format!("{} {}", period.month_name(), period.year),
+            period.total_used,
+            period.percent_of(limit)
+        );
+    }
+
+    if let Some(sentence) =
+        periods::trend_sentence(&summaries, periods::current_period(Utc::now()), limit)
+    {
+        println!();
+        println!("{}", sentence);
+    }
     Ok(())
 }
 
+/// `copilot-usage compare`: the current month through the usual cache,
+/// the previous one through the month cache
+async fn compare_months(config_manager: &ConfigManager, policy: CachePolicy) -> Result<()> {
+    let (current, _) = fetch_usage_data(config_manager, policy, false, true).await?;
+    let config = config_manager.load()?.unwrap_or_default();
+    let api_client = ApiClient::for_config(config_manager.require_token(&config)?, &config)?;
+    let cache = MonthCache::new(config_manager.profile())?;
+    let now = Utc::now();
+    let period = compare::previous_period(now);
+    let data = compare::month_usage(&api_client, &cache, &current.username, period).await?;
+    let comparison = Comparison::new(&current, &config_stats(&data, &config));
+
+    let (year, month) = periods::current_period(now);
+    let (previous_year, previous_month) = period;
+    let name_width = comparison
+        .models
+        .iter()
+        .map(|model| model.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len());
+
+    let row = |name: &str, current: f64, previous: f64, change: Option<f64>| {
+        println!(
+            "{:<width$} {:>10.0} {:>10.0} {}",
+            name,
+            current,
+            previous,
+            colored_change(change),
+            width = name_width
+        );
+    };
+    println!(
+        "{:<width$} {:>10} {:>10} {:>9}",
+        "",
+        periods::month_name(year, month),
+        periods::month_name(previous_year, previous_month),
+        "Change",
+        width = name_width
+    );
+    row(
+        "Total",
+        comparison.current_total,
+        comparison.previous_total,
+        comparison.change(),
+    );
+    if !comparison.models.is_empty() {
+        println!();
+    }
+    for model in &comparison.models {
+        row(&model.name, model.current, model.previous, model.change());
+    }
+
+    if comparison.previous_total == 0.0 {
+        println!();
+        println!("No usage recorded last month, nothing to compare with.");
+    }
+    Ok(())
+}
+
+/// `copilot-usage stats`: each month of `range` fetched in turn, then
+/// summed up
+async fn range_stats(
+    config_manager: &ConfigManager,
+    range: MonthRange,
+    format: StatsFormat,
+    output: &Path,
+) -> Result<()> {
+    let config = match config_manager.load()? {
+        Some(config) => config,
+        None => anyhow::bail!("Configuration missing. Run copilot-usage interactively first."),
+    };
+    let api_client = ApiClient::for_config(config_manager.require_token(&config)?, &config)?;
+    let username = match &config.username {
+        Some(username) => username.clone(),
+        None => api_client
+            .get_authenticated_user()
+            .await
+            .context("Could not determine username from token")?,
+    };
+    let cache = MonthCache::new(config_manager.profile())?;
+
+    let (months, future) = usage_range::split_future(&range, Utc::now());
+    let progress = std::io::stderr().is_terminal();
+    let fetched = usage_range::fetch_months(
+        &api_client,
+        &cache,
+        &username,
+        &config,
+        &months,
+        |index, month| {
+            if progress {
+                eprint!(
+                    "\rFetching {} ({}/{})...",
+                    usage_range::month_key(month),
+                    index + 1,
+                    months.len()
+                );
+                let _ = std::io::Write::flush(&mut std::io::stderr());
+            }
+        },
+    )
+    .await;
+    if progress && !months.is_empty() {
+        eprint!("\r\x1b[K");
+    }
+    let usage = usage_range::aggregate(&range, &fetched?, &future);
+
+    let content = match format {
+        StatsFormat::Table => usage_range::to_table(&usage),
+        StatsFormat::Csv => usage_range::to_csv(&usage),
+        StatsFormat::Json => usage_range::to_json(&usage) + "\n",
+    };
+    // La tabla ya los lista; en CSV no caben
+    if format != StatsFormat::Table {
+        for skipped in &usage.skipped {
+            eprintln!("Skipped {} ({})", skipped.month, skipped.reason);
+        }
+    }
+
+    if output == Path::new("-") {
+        print!("{}", content);
+    } else {
+        fs::write(output, content)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        eprintln!(
+            "Wrote usage from {} to {} to {}",
+            usage.from,
+            usage.to,
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+/// More requests in red, fewer in green
+fn colored_change(change: Option<f64>) -> colored::ColoredString {
+    let text = format!("{:>9}", compare::format_change(change));
+    match change {
+        Some(change) if change > 0.0 => text.red(),
+        Some(change) if change < 0.0 => text.green(),
+        _ => text.dimmed(),
+    }
+}
+
+fn print_theme_css(
+    config_manager: &ConfigManager,
+    theme: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let theme = match theme {
+        Some(name) => themes::resolve(name),
+        None => themes::resolve(&config_manager.load()?.unwrap_or_default().theme),
+    };
+
+    if json {
+        println!("{}", waybar::theme_json(theme));
+    } else {
+        print!("{}", waybar::theme_css(theme));
+    }
+    Ok(())
+}
+
+async fn export_usage(
+    config_manager: &ConfigManager,
+    format: ExportFormat,
+    output: &Path,
+    policy: CachePolicy,
+) -> Result<()> {
+    let (stats, _) = fetch_usage_data(config_manager, policy, false, true).await?;
+
+    let content = match format {
+        ExportFormat::Csv => export::to_csv(&stats),
+        ExportFormat::Markdown => export::to_markdown(&stats),
+        ExportFormat::Json => export::to_json(&stats) + "\n",
+    };
+
+    if output == Path::new("-") {
+        print!("{}", content);
+    } else {
+        fs::write(output, content)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        eprintln!("Exported usage to {}", output.display());
+        // Solo el JSON se puede volver a cargar con --from-file
+        if format == ExportFormat::Json && export::remember_export(output).is_err() {
+            eprintln!("Warning: could not remember the export for --from-file fallbacks");
+        }
+    }
+    Ok(())
+}
+
+/// `copilot-usage login`: the device flow, then the token is stored like
+/// setup stores it
+async fn login(
+    config_manager: &ConfigManager,
+    client_id: Option<String>,
+    open_browser: bool,
+    use_keyring: bool,
+) -> Result<()> {
+    let mut config = config_manager.load()?.unwrap_or_default();
+    let client_id = client_id
+        .or_else(|| config.oauth_client_id.clone())
+        .filter(|id| !id.trim().is_empty())
+        .context(
+            "No OAuth client ID. copilot-usage doesn't ship one: register a GitHub App \
+             with device flow enabled and the Account permission 'Plan' (read), then \
+             pass its client ID with --client-id or set oauth_client_id in the profile.",
+        )?;
+
+    let flow = DeviceFlow::for_config(client_id.trim(), &config)?;
+    let code = flow.request_code().await?;
+    println!(
+        "Open {} and enter the code {}",
+        code.verification_uri.bold(),
+        code.user_code.bold().cyan()
+    );
+    if open_browser {
+        open_in_browser(&code.verification_uri);
+    }
+
+    let spinner = std::io::stderr().is_terminal();
+    let token = flow
+        .wait_for_token(&code, |delay| spin(delay, spinner))
+        .await;
+    if spinner {
+        eprint!("\r\x1b[2K");
+    }
+    let granted = token?;
+    let token = granted.access_token.clone();
+    register_secret(&token);
+    config.token_expires = granted.expires_on(chrono::Local::now());
+
+    let stored_in = if use_keyring {
+        match config_manager.store_token_in_keyring(&token) {
+            Ok(()) => {
+                config.token = String::new();
+                "the OS keyring".to_string()
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} {:#}. Saving token to config file instead.",
+                    "Warning:".yellow(),
+                    e
+                );
+                config.token = token.clone();
+                config_manager.config_path().display().to_string()
+            }
+        }
+    } else {
+        config.token = token.clone();
+        config_manager.config_path().display().to_string()
+    };
+    config_manager.save(&config)?;
+
+    let client = ApiClient::for_config(token, &config)?;
+    match client.get_authenticated_user().await {
+        Ok(user) => println!("{} Logged in as {}", "✓".green(), user),
+        Err(_) => println!("{} Logged in", "✓".green()),
+    }
+    println!("Token stored in {}", stored_in);
+    if let Some(expires) = config.token_expires {
+        // El refresh token no se guarda: renovar el token es volver a hacer login
+        eprintln!(
+            "{} The token expires on {}. copilot-usage doesn't refresh it: run login again \
+             then, or turn off user token expiration in the GitHub App's settings.",
+            "Note:".yellow(),
+            expires
+        );
+    }
+    warn_env_token_wins();
+    Ok(())
+}
+
+/// `copilot-usage logout`: forgets the profile's token wherever it's stored
+fn logout(config_manager: &ConfigManager) -> Result<()> {
+    config_manager.delete_keyring_token(config_manager.profile())?;
+    if let Some(mut config) = config_manager.load()?
+        && (!config.token.is_empty() || config.token_expires.is_some())
+    {
+        config.token.clear();
+        config.token_expires = None;
+        config_manager.save(&config)?;
+    }
+    println!(
+        "{} Removed the stored token of profile '{}'",
+        "✓".green(),
+        config_manager.profile()
+    );
+    warn_env_token_wins();
+    Ok(())
+}
+
+/// The environment wins over stored tokens, which login and logout change
+fn warn_env_token_wins() {
+    if let Some(var) = env_token_var() {
+        eprintln!(
+            "{} {} is set and takes precedence over the stored token.",
+            "Note:".yellow(),
+            var
+        );
+    }
+}
+
+/// Waits `delay` while a spinner turns on stderr
+async fn spin(delay: Duration, spinner: bool) {
+    const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+    const STEP: Duration = Duration::from_millis(100);
+    if !spinner {
+        tokio::time::sleep(delay).await;
+        return;
+    }
+    let mut waited = Duration::ZERO;
+    let mut frame = 0;
+    while waited < delay {
+        eprint!(
+            "\r{} Waiting for the code to be entered...",
+            FRAMES[frame % FRAMES.len()]
+        );
+        let _ = std::io::Write::flush(&mut std::io::stderr());
+        let step = STEP.min(delay - waited);
+        tokio::time::sleep(step).await;
+        waited += step;
+        frame += 1;
+    }
+}
+
+/// Best effort: the URL is printed anyway
+fn open_in_browser(url: &str) {
+    let command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open").arg(url).spawn()
+    } else if cfg!(windows) {
+        std::process::Command::new("cmd")
+            .args(["/C", "start", "", url])
+            .spawn()
+    } else {
+        std::process::Command::new("xdg-open").arg(url).spawn()
+    };
+    if let Ok(mut child) = command {
+        // Que no quede un zombi mientras se espera el código
+        std::thread::spawn(move || child.wait());
+    }
+}
+
 async fn reconfigure(config_manager: &ConfigManager) -> Result<()> {
     println!("Reconfiguring...");
-    config_manager.setup_interactive()?;
+    config_manager.setup_interactive().await?;
     println!("Configuration updated!");
     Ok(())
 }
 
+/// Saves the theme; an unconfigured profile gets a config with just the
+/// theme and runs the usual setup once it's started
+fn set_theme(config_manager: &ConfigManager, name: &str) -> Result<()> {
+    let theme = if name.eq_ignore_ascii_case(themes::AUTO) {
+        themes::AUTO
+    } else {
+        Theme::parse(name)
+            .with_context(|| {
+                let names: Vec<&str> = Theme::ALL.iter().map(|theme| theme.as_str()).collect();
+                format!(
+                    "Unknown theme '{}'. Available: {}, {}",
+                    name,
+                    names.join(", "),
+                    themes::AUTO
+                )
+            })?
+            .as_str()
+    };
+
+    let mut config = config_manager.load()?.unwrap_or_default();
+    config.theme = theme.to_string();
+    config_manager.save(&config)?;
+    println!("Theme set to {}", theme);
+    Ok(())
+}
+
 fn save_theme_preference(config_manager: &ConfigManager, theme_name: &str) -> Result<()> {
     if let Some(mut config) = config_manager.load()? {
         config.theme = theme_name.to_string();
@@ -202,73 +1998,201 @@ fn save_theme_preference(config_manager: &ConfigManager, theme_name: &str) -> Re
     Ok(())
 }
 
+/// Loads usage stats from the cache or the API. With `allow_stale`, an
+/// expired cache is returned as-is and the flag in the result is set so the
+/// caller can refresh it in the background.
+/// Without `interactive` nothing is prompted: a missing setup, username or
+/// token permission becomes an error instead
 async fn fetch_usage_data(
     config_manager: &ConfigManager,
-    force_refresh: bool,
-) -> Result<crate::models::UsageStats> {
+    policy: CachePolicy,
+    allow_stale: bool,
+    interactive: bool,
+) -> Result<(crate::models::UsageStats, DataStatus)> {
+    // Sin token (p. ej. solo `theme set`) todavía no está configurado
     let mut config = match config_manager.load()? {
-        Some(cfg) => cfg,
-        None => {
+        Some(cfg) if config_manager.resolve_token(&cfg).is_some() => cfg,
+        _ if !interactive => {
+            anyhow::bail!("Configuration missing. Run copilot-usage interactively first.")
+        }
+        _ => {
             println!("Welcome to GitHub Copilot Usage CLI!");
-            config_manager.setup_interactive()?
+            config_manager.setup_interactive().await?
         }
     };
 
-    let cache = Cache::new(config.cache_ttl_minutes)?;
+    let cache = policy.apply(
+        Cache::new(config_manager.profile(), config.cache_ttl_minutes)?
+            .with_history_retention(config.history_retention_days),
+    );
 
+    let force_refresh = policy == CachePolicy::Refresh;
     if force_refresh {
         cache.invalidate()?;
     }
 
-    let usage_data = match cache.status() {
-        CacheStatus::Fresh(data) => data,
+    if let Some(path) = cache.quarantine_if_corrupted()? {
+        eprintln!(
+            "{} The usage cache was corrupted and has been moved to {}. Refetching.",
+            "Warning:".yellow(),
+            path.display()
+        );
+    }
+
+    let (usage_data, source) = match cache.status() {
+        CacheStatus::Fresh(data) => (data, DataSource::Cache),
+        CacheStatus::Stale(data) if allow_stale => (data, DataSource::StaleCache),
         _ => {
-            let api_client = ApiClient::new(config.token.clone())?;
+            if config.needs_plan_detection(force_refresh) {
+                detect_plan(config_manager, &mut config).await?;
+            }
+            let api_client =
+                ApiClient::for_config(config_manager.require_token(&config)?, &config)?;
+            let data = fetch_into_cache(
+                config_manager,
+                &mut config,
+                &cache,
+                &api_client,
+                interactive,
+            )
+            .await?;
+            (data, DataSource::Api)
+        }
+    };
 
-            // Try to get username: 1) from config, 2) from API, 3) prompt user
-            let username = if let Some(ref cached_username) = config.username {
-                cached_username.clone()
-            } else {
-                match api_client.get_authenticated_user().await {
-                    Ok(user) => {
-                        // Cache the username for future use
-                        config.username = Some(user.clone());
-                        config_manager.save(&config)?;
-                        user
-                    }
-                    Err(_) => {
-                        println!("\nCould not determine username from token.");
-                        let user: String = dialoguer::Input::with_theme(
-                            &dialoguer::theme::ColorfulTheme::default(),
-                        )
+    // Lo cacheado tiene la hora de su fetch
+    let fetched_at = match source {
+        DataSource::Api => Utc::now(),
+        DataSource::Cache | DataSource::StaleCache => {
+            cache.last_updated().ok().flatten().unwrap_or_else(Utc::now)
+        }
+    };
+    let status = DataStatus { source, fetched_at };
+
+    warn_plan_mismatch(config_manager, &mut config, &usage_data)?;
+
+    Ok((config_stats(&usage_data, &config), status))
+}
+
+/// Fetches the usage into `cache`, asking for the username: 1) from the
+/// config, 2) from the provider, 3) with a prompt when `interactive`
+async fn fetch_into_cache(
+    config_manager: &ConfigManager,
+    config: &mut Config,
+    cache: &Cache,
+    provider: &dyn UsageProvider,
+    interactive: bool,
+) -> Result<UsageData> {
+    let username = if let Some(ref cached_username) = config.username {
+        cached_username.clone()
+    } else {
+        match provider.authenticated_user().await {
+            Ok(user) => {
+                // Cache the username for future use
+                config.username = Some(user.clone());
+                config_manager.save(config)?;
+                user
+            }
+            Err(e) if !interactive => {
+                return Err(e.context("Could not determine username from token"));
+            }
+            Err(_) => {
+                println!("\nCould not determine username from token.");
+                let user: String =
+                    dialoguer::Input::with_theme(&dialoguer::theme::ColorfulTheme::default())
                         .with_prompt("Enter your GitHub username")
                         .interact_text()?;
-                        // Cache the username for future use
-                        config.username = Some(user.clone());
-                        config_manager.save(&config)?;
-                        user
-                    }
-                }
-            };
+                // Cache the username for future use
+                config.username = Some(user.clone());
+                config_manager.save(config)?;
+                user
+            }
+        }
+    };
 
-            match api_client.fetch_usage(&username).await {
-                Ok(data) => {
-                    cache.set(&data)?;
-                    data
-                }
-                Err(e) => {
-                    handle_api_error(&e, config_manager).await?;
-                    return Err(e);
-                }
+    // Un cache expirado aún sirve para la petición condicional
+    let previous = cache.entry().ok().flatten();
+    let validators = previous
+        .as_ref()
+        .map(|entry| entry.validators.clone())
+        .unwrap_or_default();
+    match provider
+        .fetch_usage_conditional(&username, &validators)
+        .await
+    {
+        Ok(response) => cache.store(response, previous, provider.last_rate_limit()),
+        Err(e) => {
+            if interactive {
+                handle_api_error(&e, config_manager).await?;
             }
+            Err(e)
         }
+    }
+}
+
+/// Warns once per observed allowance when the billing data shows a different
+/// included quantity than the configured limit (e.g. Free set up as Pro)
+fn warn_plan_mismatch(
+    config_manager: &ConfigManager,
+    config: &mut Config,
+    usage_data: &UsageData,
+) -> Result<()> {
+    let Some(observed) = observed_included_quantity(usage_data) else {
+        return Ok(());
+    };
+    let Some(warning) = plan_mismatch_warning(config, observed) else {
+        return Ok(());
     };
 
-    Ok(calculate_stats(&usage_data))
+    let observed = observed.round() as u32;
+    if config.plan_mismatch_warned == Some(observed) {
+        return Ok(());
+    }
+    eprintln!("{} {}", "Warning:".yellow(), warning);
+    config.plan_mismatch_warned = Some(observed);
+    config_manager.save(config)
+}
+
+/// Asks the API for the Copilot plan and stores it with its limit.
+/// On failure the configured limit is kept, a warning is printed and the
+/// failure is saved so the next fetches don't retry until `--refresh`.
+async fn detect_plan(config_manager: &ConfigManager, config: &mut Config) -> Result<()> {
+    let api_client = ApiClient::for_config(config_manager.require_token(config)?, config)?;
+    let detected = api_client.fetch_plan().await;
+    apply_detected_plan(config, detected.as_ref().ok().copied());
+    config_manager.save(config)?;
+
+    if let Err(e) = detected {
+        eprintln!(
+            "{} Could not detect your Copilot plan ({}). Using the configured limit of {}.",
+            "Warning:".yellow(),
+            redact(&format!("{:#}", e)),
+            config.premium_request_limit
+        );
+    }
+    Ok(())
+}
+
+/// Records a detection result: a detected plan sets the limit unless the
+/// user typed their own, a failed one (`None`) keeps the limit as is
+fn apply_detected_plan(config: &mut Config, detected: Option<Plan>) {
+    match detected {
+        Some(plan) => {
+            config.plan = Some(plan);
+            if config.limit_source != LimitSource::Custom {
+                config.premium_request_limit = plan.premium_request_limit();
+                config.limit_source = LimitSource::Plan;
+            }
+        }
+        None if config.limit_source != LimitSource::Custom => {
+            config.limit_source = LimitSource::DetectionFailed;
+        }
+        None => {}
+    }
 }
 
 async fn handle_api_error(e: &anyhow::Error, config_manager: &ConfigManager) -> Result<()> {
-    let err_str = format!("{}", e);
+    let err_str = redact(&format!("{}", e));
 
     if err_str.contains("403") {
         eprintln!("\nAPI Access Denied! (403)");
@@ -285,7 +2209,7 @@ async fn handle_api_error(e: &anyhow::Error, config_manager: &ConfigManager) ->
                 .interact()?;
 
         if should_reconfigure {
-            config_manager.setup_interactive()?;
+            config_manager.setup_interactive().await?;
         }
     } else if err_str.contains("404") {
         eprintln!("\nNot Found (404)");
@@ -297,3 +2221,156 @@ async fn handle_api_error(e: &anyhow::Error, config_manager: &ConfigManager) ->
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::percent::format_quantity;
+    use crate::testing::MockProvider;
+    use tempfile::TempDir;
+
+    const FIXTURE: &str = "usage_duplicated_rows.json";
+
+    fn setup(dir: &TempDir) -> (ConfigManager, Config, Cache) {
+        let config_manager = ConfigManager::with_path(dir.path().join("config.toml"));
+        let config = Config {
+            token: "ghp_mock".to_string(),
+            ..Config::default()
+        };
+        config_manager.save(&config).unwrap();
+        let cache = Cache::with_path(dir.path().join("usage-default.json"), 5);
+        (config_manager, config, cache)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_into_cache_learns_the_username() {
+        let dir = TempDir::new().unwrap();
+        let (config_manager, mut config, cache) = setup(&dir);
+        let provider = MockProvider::from_fixture(FIXTURE).unwrap();
+
+        let data = fetch_into_cache(&config_manager, &mut config, &cache, &provider, false)
+            .await
+            .unwrap();
+        assert_eq!(provider.fetches(), 1);
+        assert_eq!(config.username.as_deref(), Some(data.user.as_str()));
+        let saved = config_manager.load().unwrap().unwrap();
+        assert_eq!(saved.username, config.username);
+        assert!(matches!(cache.status(), CacheStatus::Fresh(cached) if cached.user == data.user));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_into_cache_reports_failures() {
+        let dir = TempDir::new().unwrap();
+        let (config_manager, mut config, cache) = setup(&dir);
+        let provider = MockProvider::failing("octocat", "boom");
+
+        let error = fetch_into_cache(&config_manager, &mut config, &cache, &provider, false)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("boom"));
+        assert!(matches!(cache.status(), CacheStatus::Missing));
+    }
+
+    #[test]
+    fn test_plan_detection_keeps_a_custom_limit() {
+        let mut config = Config::default();
+        assert!(config.needs_plan_detection(false));
+
+        apply_detected_plan(&mut config, Some(Plan::Free));
+        assert_eq!(config.premium_request_limit, 50);
+        assert_eq!(config.limit_source, LimitSource::Plan);
+        assert!(!config.needs_plan_detection(false));
+        assert!(config.needs_plan_detection(true));
+
+        // Un límite escrito a mano no se pisa, ni con --refresh
+        let mut custom = Config {
+            premium_request_limit: 700,
+            limit_source: LimitSource::Custom,
+            ..Config::default()
+        };
+        assert!(!custom.needs_plan_detection(true));
+        apply_detected_plan(&mut custom, Some(Plan::Pro));
+        assert_eq!(custom.premium_request_limit, 700);
+        assert_eq!(custom.limit_source, LimitSource::Custom);
+
+        // Un fallo se recuerda y solo se reintenta con --refresh
+        let mut failed = Config::default();
+        apply_detected_plan(&mut failed, None);
+        assert_eq!(failed.limit_source, LimitSource::DetectionFailed);
+        assert_eq!(failed.premium_request_limit, 300);
+        assert!(!failed.needs_plan_detection(false));
+        assert!(failed.needs_plan_detection(true));
+    }
+
+    #[test]
+    fn test_only_interactive_commands_may_prompt() {
+        let may_prompt = |args: &[&str]| {
+            Cli::try_parse_from(["copilot-usage"].iter().chain(args))
+                .unwrap()
+                .may_prompt()
+        };
+        assert!(may_prompt(&[]));
+        assert!(may_prompt(&["doctor"]));
+        assert!(may_prompt(&["reconfigure"]));
+        for args in [
+            &["check"][..],
+            &["metrics"],
+            &["prompt"],
+            &["--waybar"],
+            &["cache", "status"],
+        ] {
+            assert!(!may_prompt(args), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_colors_enabled() {
+        let no_color =
+            |value: &'static str| move |var: &str| (var == "NO_COLOR").then(|| value.to_string());
+        assert!(colors_enabled(false, |_| None));
+        assert!(!colors_enabled(true, |_| None));
+        assert!(!colors_enabled(false, no_color("1")));
+        // Vacía no cuenta, como pide no-color.org
+        assert!(colors_enabled(false, no_color("")));
+
+        let cli = Cli::try_parse_from(["copilot-usage", "cache", "status", "--no-color"]).unwrap();
+        assert!(cli.no_color);
+    }
+
+    #[test]
+    fn test_prompt_reads_only_the_cache() {
+        let dir = TempDir::new().unwrap();
+        let (_, config, cache) = setup(&dir);
+        let segment = output::PromptSegment {
+            template: "{used}".to_string(),
+            max_cache_age: Some(Duration::from_secs(5 * 60)),
+            color: false,
+        };
+        let now = Utc::now();
+        // Sin caché no hay nada que mostrar, y no se pide a la red
+        assert_eq!(cached_prompt(&config, &cache, &segment, now), None);
+
+        let usage = serde_json::from_str::<UsageData>(include_str!(
+            "../tests/fixtures/usage_duplicated_rows.json"
+        ))
+        .unwrap();
+        cache.set(&usage, None).unwrap();
+        let used = format_quantity(config_stats(&usage, &config).total_used);
+        assert_eq!(
+            cached_prompt(&config, &cache, &segment, now),
+            Some(used.clone())
+        );
+
+        // Caducado: el valor viejo, marcado
+        let stale = Cache::with_path(cache.path().to_path_buf(), 0);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(
+            cached_prompt(&config, &stale, &segment, Utc::now()),
+            Some(format!("~{}", used))
+        );
+
+        // Más viejo que --max-cache-age: el prompt se oculta
+        let later = now + chrono::Duration::minutes(10);
+        assert_eq!(cached_prompt(&config, &cache, &segment, later), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! This month against the previous one, for `copilot-usage compare` and the
+//! dashboard's compare view. Months are calendar months, as the API's
+//! `year`/`month` filters count them.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::api::ApiClient;
+use crate::cache::MonthCache;
+use crate::history::periods;
+use crate::models::{UsageData, UsageStats};
+use crate::percent::{format_percent, round_half_up};
+use crate::ui::styles::icons;
+
+/// Shown instead of a change when last month had nothing to compare with
+pub const NOT_AVAILABLE: &str = "n/a";
+
+/// Requests of one model in both months
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelDelta {
+    pub name: String,
+    pub current: f64,
+    pub previous: f64,
+}
+
+impl ModelDelta {
+    pub fn change(&self) -> Option<f64> {
+        change(self.current, self.previous)
+    }
+}
+
+/// Totals of both months and every model used in either
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub current_total: f64,
+    pub previous_total: f64,
+    /// Most used this month first, then the ones only used last month
+    pub models: Vec<ModelDelta>,
+}
+
+impl Comparison {
+    /// Totals are the pool's (`total_used`); models are compared by their
+    /// grouped name, unmetered ones included
+    pub fn new(current: &UsageStats, previous: &UsageStats) -> Self {
+        let used = |stats: &UsageStats, name: &str| {
+            stats
+                .models
+                .iter()
+                .find(|model| model.name == name)
+                .map_or(0.0, |model| model.used)
+        };
+
+        let mut models: Vec<ModelDelta> = current
+            .models
+            .iter()
+            .chain(&previous.models)
+            .filter(|model| model.used > 0.0)
+            .fold(Vec::new(), |mut names: Vec<&str>, model| {
+                if !names.contains(&model.name.as_str()) {
+                    names.push(&model.name);
+                }
+                names
+            })
+            .into_iter()
+            .map(|name| ModelDelta {
+                name: name.to_string(),
+                current: used(current, name),
+                previous: used(previous, name),
+            })
+            .collect();
+        // Estable: a igual uso, el orden de la tabla
+        models.sort_by(|a, b| {
+            b.current
+                .total_cmp(&a.current)
+                .then(b.previous.total_cmp(&a.previous))
+        });
+
+        Self {
+            current_total: current.total_used,
+            previous_total: previous.total_used,
+            models,
+        }
+    }
+
+    pub fn change(&self) -> Option<f64> {
+        change(self.current_total, self.previous_total)
+    }
+}
+
+/// Change from `previous` to `current` in percent; `None` when there was
+/// nothing last month (a new user, a new model)
+pub fn change(current: f64, previous: f64) -> Option<f64> {
+    (previous > 0.0).then(|| (current - previous) / previous * 100.0)
+}
+
+/// "▲ 12.5%", "▼ 3.0%", "0.0%" or "n/a"
+pub fn format_change(change: Option<f64>) -> String {
+    match change {
+        None => NOT_AVAILABLE.to_string(),
+        // Redondeado primero: 0.04 no lleva flecha
+        Some(change) => match round_half_up(change, 1) {
+            rounded if rounded > 0.0 => format!("{} {}%", icons().up, format_percent(rounded, 1)),
+            rounded if rounded < 0.0 => {
+                format!("{} {}%", icons().down, format_percent(-rounded, 1))
+            }
+            _ => "0.0%".to_string(),
+        },
+    }
+}
+
+/// Year and month before the one `now` falls in
+pub fn previous_period(now: DateTime<Utc>) -> (i32, u32) {
+    periods::previous_month(periods::current_period(now))
+}
+
+/// Usage of `year`/`month`. A finished month can't change: once fetched
+/// it comes from the month cache for good.
+pub async fn month_usage(
+    client: &ApiClient,
+    cache: &MonthCache,
+    username: &str,
+    (year, month): (i32, u32),
+) -> Result<UsageData> {
+    if let Some(data) = cache.get(year, month) {
+        return Ok(data);
+    }
+    let data = client.fetch_usage_for_period(username, year, month).await?;
+    // El caché es una ayuda: si no se puede escribir, se sigue
+    if let Err(error) = cache.set(year, month, &data) {
+        tracing::warn!(%error, "could not cache the previous month");
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::calculate_stats;
+    use crate::models::{TimePeriod, UsageItem};
+    use chrono::TimeZone;
+
+    fn stats(month: u32, models: &[(&str, f64)]) -> UsageStats {
+        let usage_items = models
+            .iter()
+            .map(|&(model, quantity)| UsageItem {
+                product: "copilot".to_string(),
+                sku: "premium".to_string(),
+                model: model.to_string(),
+                unit_type: "request".to_string(),
+                price_per_unit: 0.04,
+                gross_quantity: quantity,
+                gross_amount: quantity * 0.04,
+                discount_quantity: quantity,
+                discount_amount: quantity * 0.04,
+                net_quantity: 0.0,
+                net_amount: 0.0,
+                date: None,
+            })
+            .collect();
+        let data = UsageData {
+            time_period: TimePeriod {
+                year: 2026,
+                month: Some(month),
+                day: None,
+            },
+            user: "testuser".to_string(),
+            usage_items,
+            note: None,
+        };
+        calculate_stats(&data, 300)
+    }
+
+    #[test]
+    fn test_compares_totals_and_models() {
+        let current = stats(10, &[("gpt-4.1", 30.0), ("claude-sonnet-4", 90.0)]);
+        let previous = stats(9, &[("claude-sonnet-4", 60.0), ("o3", 20.0)]);
+        let comparison = Comparison::new(&current, &previous);
+
+        assert_eq!(comparison.current_total, 120.0);
+        assert_eq!(comparison.previous_total, 80.0);
+        assert_eq!(comparison.change(), Some(50.0));
+
+        let rows: Vec<(&str, f64, f64)> = comparison
+            .models
+            .iter()
+            .map(|model| (model.name.as_str(), model.current, model.previous))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("claude-sonnet-4", 90.0, 60.0),
+                ("gpt-4.1", 30.0, 0.0),
+                ("o3", 0.0, 20.0),
+            ]
+        );
+        assert_eq!(comparison.models[0].change(), Some(50.0));
+        assert_eq!(comparison.models[1].change(), None);
+        assert_eq!(comparison.models[2].change(), Some(-100.0));
+    }
+
+    #[test]
+    fn test_new_user_has_no_change() {
+        let comparison = Comparison::new(&stats(10, &[("gpt-4.1", 12.0)]), &stats(9, &[]));
+        assert_eq!(comparison.change(), None);
+        assert_eq!(format_change(comparison.change()), "n/a");
+    }
+
+    #[test]
+    fn test_format_change() {
+        assert_eq!(format_change(Some(12.5)), "▲ 12.5%");
+        assert_eq!(format_change(Some(-3.0)), "▼ 3.0%");
+        assert_eq!(format_change(Some(0.0)), "0.0%");
+        // Mitades lejos de cero, como la barra
+        assert_eq!(format_change(Some(0.25)), "▲ 0.3%");
+        assert_eq!(format_change(Some(-0.25)), "▼ 0.3%");
+        assert_eq!(format_change(Some(0.04)), "0.0%");
+    }
+
+    #[test]
+    fn test_previous_period_wraps_the_year() {
+        let at = |y, m| Utc.with_ymd_and_hms(y, m, 15, 0, 0, 0).unwrap();
+        assert_eq!(previous_period(at(2026, 10)), (2026, 9));
+        assert_eq!(previous_period(at(2027, 1)), (2026, 12));
+    }
+}
@@ -0,0 +1,169 @@
+//! `copilot-usage completions <shell>`: the script clap_complete generates,
+//! with `--theme` and `--profile` completed by calling back into the hidden
+//! `__complete-themes` / `__complete-profiles` subcommands, so new themes
+//! and profiles show up without regenerating the script.
+
+use clap::{Command, CommandFactory, ValueEnum};
+
+use crate::cli::Cli;
+use crate::models::Theme;
+use crate::themes;
+
+/// Binary the scripts complete and call back into
+const BIN: &str = "copilot-usage";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Flag whose value comes from a hidden subcommand
+struct Dynamic {
+    long: &'static str,
+    short: Option<char>,
+    /// Value name in the generated script
+    value_name: &'static str,
+    subcommand: &'static str,
+}
+
+const DYNAMIC: [Dynamic; 2] = [
+    Dynamic {
+        long: "theme",
+        short: Some('t'),
+        value_name: "THEME",
+        subcommand: "__complete-themes",
+    },
+    Dynamic {
+        long: "profile",
+        short: None,
+        value_name: "PROFILE",
+        subcommand: "__complete-profiles",
+    },
+];
+
+/// The completion script for `shell`, to source or drop in its completion
+/// directory
+pub fn script(shell: CompletionShell) -> String {
+    let generator = match shell {
+        CompletionShell::Bash => clap_complete::Shell::Bash,
+        CompletionShell::Zsh => clap_complete::Shell::Zsh,
+        CompletionShell::Fish => clap_complete::Shell::Fish,
+    };
+    let mut out = Vec::new();
+    clap_complete::generate(generator, &mut visible_command(), BIN, &mut out);
+    let generated = String::from_utf8(out).unwrap_or_default();
+    match shell {
+        CompletionShell::Bash => bash(generated),
+        CompletionShell::Zsh => zsh(generated),
+        CompletionShell::Fish => fish(generated),
+    }
+}
+
+/// The CLI without its hidden subcommands: the generators would list them
+/// as commands to complete
+fn visible_command() -> Command {
+    let cli = Cli::command();
+    let mut visible = Command::new(BIN)
+        .version(env!("CARGO_PKG_VERSION"))
+        .args(cli.get_arguments().cloned())
+        .groups(cli.get_groups().cloned())
+        .subcommands(
+            cli.get_subcommands()
+                .filter(|sub| !sub.is_hide_set())
+                .cloned(),
+        );
+    if let Some(about) = cli.get_about() {
+        visible = visible.about(about.clone());
+    }
+    visible
+}
+
+/// Every `--theme)` / `-t)` case arm completes files; they get the
+/// callback instead
+fn bash(script: String) -> String {
+    const FILES: &str = "COMPREPLY=($(compgen -f \"${cur}\"))";
+    let mut out = String::with_capacity(script.len());
+    let mut arm: Option<&Dynamic> = None;
+    for line in script.lines() {
+        let body = line.trim_start();
+        match arm.take() {
+            Some(dynamic) if body == FILES => {
+                let indent = &line[..line.len() - body.len()];
+                out.push_str(&format!(
+                    "{}COMPREPLY=($(compgen -W \"$({} {} 2>/dev/null)\" -- \"${{cur}}\"))\n",
+                    indent, BIN, dynamic.subcommand
+                ));
+                continue;
+            }
+            _ => {}
+        }
+        arm = DYNAMIC.iter().find(|dynamic| {
+            body == format!("--{})", dynamic.long)
+                || dynamic
+                    .short
+                    .is_some_and(|short| body == format!("-{})", short))
+        });
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// `:THEME:_default` becomes a function that asks the binary
+fn zsh(mut script: String) -> String {
+    let mut functions = String::new();
+    for dynamic in &DYNAMIC {
+        let function = format!("_copilot-usage_{}s", dynamic.long);
+        script = script.replace(
+            &format!(":{}:_default", dynamic.value_name),
+            &format!(":{}:{}", dynamic.value_name, function),
+        );
+        functions.push_str(&format!(
+            "(( $+functions[{function}] )) ||\n{function}() {{\n    \
+             local values; values=(${{(f)\"$({} {} 2>/dev/null)\"}})\n    \
+             compadd -a values\n}}\n\n",
+            BIN,
+            dynamic.subcommand,
+            function = function,
+        ));
+    }
+    // Antes de que el script se llame a sí mismo al final
+    match script.rfind("if [ \"$funcstack[1]\"") {
+        Some(end) => script.insert_str(end, &functions),
+        None => script.push_str(&functions),
+    }
+    script
+}
+
+/// Each `-l theme ... -r` line also gets the values, without files
+fn fish(script: String) -> String {
+    script
+        .lines()
+        .map(|line| {
+            let dynamic = DYNAMIC
+                .iter()
+                .find(|dynamic| line.contains(&format!(" -l {} ", dynamic.long)));
+            match dynamic {
+                Some(dynamic) if line.ends_with(" -r") => format!(
+                    "{} -f -a \"({} {} 2>/dev/null)\"\n",
+                    line, BIN, dynamic.subcommand
+                ),
+                _ => format!("{}\n", line),
+            }
+        })
+        .collect()
+}
+
+/// What `__complete-themes` prints: `auto` and every built-in theme
+pub fn theme_names() -> Vec<String> {
+    std::iter::once(themes::AUTO.to_string())
+        .chain(Theme::ALL.iter().map(|theme| theme.as_str().to_string()))
+        .collect()
+}
+
+/// One value per line, as the scripts read them
+pub fn lines(values: Vec<String>) -> String {
+    values.into_iter().map(|value| value + "\n").collect()
+}
@@ -1,11 +1,461 @@
-use crate::models::Config;
+use crate::api::{ApiClient, GITHUB_API_URL, TokenDiagnostics, parse_api_base_url};
+use crate::i18n::{Language, tf};
+use crate::model_names::ModelNames;
+use crate::models::{Config, DEFAULT_PREMIUM_REQUEST_LIMIT, LimitSource, ModelLimit, Plan};
+use crate::ownership;
+use crate::redact::{redact, register_secret};
+use crate::ui::state::COMMAND_IDS;
 use anyhow::{Context, Result};
+use chrono::NaiveDate;
 use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 
+/// Environment variables checked for a token, in priority order
+pub const TOKEN_ENV_VARS: [&str; 2] = ["COPILOT_USAGE_TOKEN", "GITHUB_TOKEN"];
+
+/// Service name used for OS keyring entries
+const KEYRING_SERVICE: &str = "copilot-usage";
+
+/// Profile used when none is chosen, and the one old configs migrate to
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// On-disk layout of config.toml: one `[profiles.<name>]` section per account
+#[derive(Debug, Serialize, Deserialize)]
+struct ConfigFile {
+    #[serde(default = "default_profile_name")]
+    default_profile: String,
+    #[serde(default)]
+    profiles: BTreeMap<String, Config>,
+}
+
+fn default_profile_name() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        Self {
+            default_profile: default_profile_name(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+/// Profile names end up in file names and keyring entries, so keep them simple
+pub fn parse_profile_name(name: &str) -> Result<String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!("Profile names may only contain letters, digits, '-' and '_'");
+    }
+    Ok(name.to_string())
+}
+
+/// Where the token in use was read from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenSource {
+    Env(&'static str),
+    Keyring,
+    ConfigFile,
+}
+
+impl std::fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenSource::Env(var) => write!(f, "environment variable {}", var),
+            TokenSource::Keyring => write!(f, "OS keyring"),
+            TokenSource::ConfigFile => write!(f, "config file"),
+        }
+    }
+}
+
+/// The environment variable whose token wins over the stored one, if set
+pub fn env_token_var() -> Option<&'static str> {
+    TOKEN_ENV_VARS
+        .into_iter()
+        .find(|var| std::env::var(var).is_ok_and(|value| !value.trim().is_empty()))
+}
+
+/// Resolves the token: environment variables first, then the keyring,
+/// then the plaintext `token` field as a fallback
+fn resolve_token_with(
+    env: impl Fn(&str) -> Option<String>,
+    keyring: impl FnOnce() -> Option<String>,
+    config_token: &str,
+) -> Option<(String, TokenSource)> {
+    for var in TOKEN_ENV_VARS {
+        if let Some(token) = env(var).filter(|t| !t.trim().is_empty()) {
+            return Some((token.trim().to_string(), TokenSource::Env(var)));
+        }
+    }
+
+    if let Some(token) = keyring().filter(|t| !t.trim().is_empty()) {
+        return Some((token, TokenSource::Keyring));
+    }
+
+    if !config_token.trim().is_empty() {
+        return Some((config_token.trim().to_string(), TokenSource::ConfigFile));
+    }
+
+    None
+}
+
+/// Days before the token's expiry the dashboard, Waybar and `doctor` warn
+pub const TOKEN_EXPIRY_WARN_DAYS: i64 = 7;
+
+/// Days left before the token expires (negative once it has), when that's
+/// close enough to warn about
+pub fn token_expiry_warning(expires: Option<NaiveDate>, today: NaiveDate) -> Option<i64> {
+    let days = (expires? - today).num_days();
+    (days < TOKEN_EXPIRY_WARN_DAYS).then_some(days)
+}
+
+/// A `token_expires` value as typed: `YYYY-MM-DD`, or empty / `none` for
+/// a token that doesn't expire
+pub fn parse_token_expires(value: &str) -> Result<Option<NaiveDate>> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(Some)
+        .with_context(|| format!("'{}' is not a date like 2026-12-31", value))
+}
+
+/// The warning shown in the dashboard header and the Waybar tooltip when
+/// `token_expiry_warning` fires
+pub fn token_expiry_notice(expires: Option<NaiveDate>, today: NaiveDate) -> Option<String> {
+    let days = token_expiry_warning(expires, today)?;
+    let date = expires?.format("%Y-%m-%d");
+    let icon = crate::ui::styles::icons().warning;
+    Some(if days < 0 {
+        tf("header.token_expired", &[&icon, &date])
+    } else {
+        tf("header.token_expiring", &[&icon, &days, &date])
+    })
+}
+
+/// Copilot plans offered in setup, besides a custom limit
+pub const SETUP_PLANS: [Plan; 4] = [Plan::Free, Plan::Pro, Plan::ProPlus, Plan::Business];
+
+/// Preselected plan in the setup wizard
+const SETUP_DEFAULT_PLAN: usize = 1;
+
+/// Warning shown when the included quantity seen in the billing data
+/// doesn't match the configured limit, e.g. a Free account set up as Pro
+pub fn plan_mismatch_warning(config: &Config, observed_included: f64) -> Option<String> {
+    let observed = observed_included.round() as u32;
+    if observed == 0 || observed == config.premium_request_limit {
+        return None;
+    }
+
+    let configured = match config.plan {
+        Some(plan) => format!("{} ({})", config.premium_request_limit, plan.name()),
+        None => config.premium_request_limit.to_string(),
+    };
+    let hint = match Plan::ALL
+        .into_iter()
+        .find(|plan| plan.premium_request_limit() == observed)
+    {
+        Some(plan) => format!(
+            "Run `copilot-usage config set plan {}` to use the {} limit.",
+            plan.id(),
+            plan.name()
+        ),
+        None => format!(
+            "Set premium_request_limit = {} in config.toml, or pick your plan with `copilot-usage config set plan <plan>`.",
+            observed
+        ),
+    };
+    Some(format!(
+        "Your usage shows {} included premium requests, but the configured limit is {}. {}",
+        observed, configured, hint
+    ))
+}
+
+/// Below this Waybar interval, polling the API on every tick is flagged
+const MIN_API_POLL_SECS: u64 = 30;
+
+/// Refresh-related settings that work against each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshConflict {
+    /// The cache outlives the Waybar interval, so the module repeats
+    /// cached numbers for several ticks
+    TtlOutlastsWaybarInterval { ttl_secs: u64, interval_secs: u64 },
+    /// The cache expires before every tick, so each one calls the API
+    WaybarPollsApi { interval_secs: u64 },
+}
+
+impl RefreshConflict {
+    /// Headline for `doctor`
+    pub fn summary(&self) -> String {
+        match self {
+            RefreshConflict::TtlOutlastsWaybarInterval {
+                ttl_secs,
+                interval_secs,
+            } => format!(
+                "Cache TTL ({}) is longer than the Waybar interval ({})",
+                format_secs(*ttl_secs),
+                format_secs(*interval_secs)
+            ),
+            RefreshConflict::WaybarPollsApi { interval_secs } => format!(
+                "Waybar calls the GitHub API every {}",
+                format_secs(*interval_secs)
+            ),
+        }
+    }
+
+    /// What to change, for `doctor`
+    pub fn advice(&self) -> String {
+        match self {
+            RefreshConflict::TtlOutlastsWaybarInterval {
+                ttl_secs,
+                interval_secs,
+            } => {
+                let fix = if *interval_secs >= 60 {
+                    format!(
+                        "Set cache_ttl_minutes = {} or raise the module's interval to {}.",
+                        interval_secs / 60,
+                        ttl_secs
+                    )
+                } else {
+                    // El TTL va en minutos: solo queda subir el intervalo
+                    format!("Raise the module's interval to {}.", ttl_secs)
+                };
+                format!(
+                    "Waybar only shows new numbers every {} instead of every {}. {}",
+                    format_secs(*ttl_secs),
+                    format_secs(*interval_secs),
+                    fix
+                )
+            }
+            RefreshConflict::WaybarPollsApi { interval_secs } => format!(
+                "Every Waybar tick fetches from GitHub ({} calls/hour). \
+                Set cache_ttl_minutes = 1 or raise the module's interval to at least {}.",
+                3600 / interval_secs,
+                MIN_API_POLL_SECS
+            ),
+        }
+    }
+}
+
+/// One line per capability the token was checked for, as setup and
+/// `doctor` show them
+pub fn print_token_diagnostics(diagnostics: &TokenDiagnostics) {
+    use console::style;
+
+    let check = |ok: bool| {
+        if ok {
+            style("✓").green()
+        } else {
+            style("✗").red()
+        }
+    };
+
+    match &diagnostics.username {
+        Some(username) => println!("{} Token accepted (user {})", check(true), username),
+        None if diagnostics.user_ok => println!("{} Token accepted", check(true)),
+        None => println!("{} Token accepted", check(false)),
+    }
+    // Sin usuario no se llegó a probar
+    if diagnostics.user_ok {
+        println!(
+            "{} Premium request usage readable (Plan: read)",
+            check(diagnostics.plan_scope_ok)
+        );
+    }
+    if let Some(problem) = &diagnostics.problem {
+        for line in redact(problem).lines().filter(|line| !line.is_empty()) {
+            println!("  {}", line);
+        }
+    }
+    if let Some(expires) = &diagnostics.expires_hint {
+        println!("  Token expires: {}", expires);
+    }
+}
+
+fn format_secs(secs: u64) -> String {
+    if secs >= 60 && secs.is_multiple_of(60) {
+        format!("{} min", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Checks the cache TTL against the Waybar interval, when it's known
+pub fn refresh_conflicts(config: &Config) -> Vec<RefreshConflict> {
+    let Some(interval_secs) = config.waybar_interval_seconds else {
+        return Vec::new();
+    };
+    let ttl_secs = config.cache_ttl_minutes.saturating_mul(60);
+
+    if ttl_secs > interval_secs {
+        vec![RefreshConflict::TtlOutlastsWaybarInterval {
+            ttl_secs,
+            interval_secs,
+        }]
+    } else if interval_secs < MIN_API_POLL_SECS {
+        vec![RefreshConflict::WaybarPollsApi { interval_secs }]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Rejects settings that parse but make no sense
+fn validate(config: &Config) -> Result<()> {
+    if config.premium_request_limit == 0 {
+        anyhow::bail!("premium_request_limit must be greater than 0");
+    }
+    if config.budget.requests == Some(0) {
+        anyhow::bail!("budget must be greater than 0");
+    }
+    if config
+        .budget
+        .monthly_budget_usd
+        .is_some_and(|usd| usd <= 0.0)
+    {
+        anyhow::bail!("[budget] monthly_budget_usd must be greater than 0");
+    }
+    for (model, price) in &config.budget.price_overrides {
+        if *price < 0.0 || price.is_nan() {
+            anyhow::bail!(
+                "[budget] price_overrides: price of '{}' must not be negative",
+                model
+            );
+        }
+    }
+    ModelNames::new(&config.model_aliases).context("Invalid [model_aliases]")?;
+    for (pattern, limit) in &config.model_limits {
+        if *limit == ModelLimit::Requests(0) {
+            anyhow::bail!(
+                "[model_limits] limit of '{}' must be greater than 0 (or \"unmetered\")",
+                pattern
+            );
+        }
+    }
+    if config
+        .billing_cycle_day
+        .is_some_and(|day| !(1..=31).contains(&day))
+    {
+        anyhow::bail!("billing_cycle_day must be between 1 and 31");
+    }
+    if config.waybar_interval_seconds == Some(0) {
+        anyhow::bail!("waybar_interval_seconds must be greater than 0");
+    }
+    if !(1..=crate::api::MAX_API_ATTEMPTS).contains(&config.api_max_attempts) {
+        anyhow::bail!(
+            "api_max_attempts must be between 1 and {}",
+            crate::api::MAX_API_ATTEMPTS
+        );
+    }
+    if let Some(language) = &config.language
+        && Language::parse(language).is_none()
+    {
+        anyhow::bail!(
+            "Unknown language '{}'. Valid languages: {}",
+            language,
+            Language::ALL.map(Language::code).join(", ")
+        );
+    }
+    crate::api::parse_api_base_url(&config.api_base_url).context("Invalid api_base_url")?;
+    if let Some(proxy_url) = &config.proxy_url {
+        reqwest::Proxy::all(proxy_url).context("Invalid proxy_url")?;
+    }
+    if config.check.warn > config.check.crit {
+        anyhow::bail!("[check] warn must not be above crit");
+    }
+    let thresholds = &config.waybar_thresholds;
+    if thresholds.low > thresholds.warning || thresholds.warning > thresholds.critical {
+        anyhow::bail!("waybar_thresholds must go up: low, then warning, then critical");
+    }
+    for id in &config.menu.disabled {
+        if !COMMAND_IDS.contains(&id.as_str()) {
+            anyhow::bail!(
+                "Unknown command id '{}' in [menu] disabled. Valid ids: {}",
+                id,
+                COMMAND_IDS.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Where keyring tokens are kept: the OS keyring, or a map in tests
+#[derive(Debug, Clone, Default)]
+enum Keyring {
+    #[default]
+    Os,
+    #[cfg(test)]
+    Memory(std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>),
+}
+
+impl Keyring {
+    // Each call runs on a plain thread: some keyring backends block on
+    // their own executor
+    fn get(&self, profile: &str) -> Option<String> {
+        #[cfg(test)]
+        if let Keyring::Memory(tokens) = self {
+            return tokens.lock().unwrap().get(profile).cloned();
+        }
+        let profile = profile.to_string();
+        std::thread::spawn(move || {
+            keyring::Entry::new(KEYRING_SERVICE, &profile)
+                .ok()?
+                .get_password()
+                .ok()
+        })
+        .join()
+        .ok()
+        .flatten()
+    }
+
+    fn set(&self, profile: &str, token: &str) -> Result<()> {
+        #[cfg(test)]
+        if let Keyring::Memory(tokens) = self {
+            tokens
+                .lock()
+                .unwrap()
+                .insert(profile.to_string(), token.to_string());
+            return Ok(());
+        }
+        let (profile, token) = (profile.to_string(), token.to_string());
+        std::thread::spawn(move || -> Result<()> {
+            keyring::Entry::new(KEYRING_SERVICE, &profile)?.set_password(&token)?;
+            Ok(())
+        })
+        .join()
+        .map_err(|_| anyhow::anyhow!("Keyring access panicked"))?
+    }
+
+    fn delete(&self, profile: &str) -> Result<()> {
+        #[cfg(test)]
+        if let Keyring::Memory(tokens) = self {
+            tokens.lock().unwrap().remove(profile);
+            return Ok(());
+        }
+        let profile = profile.to_string();
+        std::thread::spawn(move || -> Result<()> {
+            match keyring::Entry::new(KEYRING_SERVICE, &profile)?.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .join()
+        .map_err(|_| anyhow::anyhow!("Keyring access panicked"))?
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ConfigManager {
     config_path: PathBuf,
+    profile: String,
+    keyring: Keyring,
 }
 
 impl ConfigManager {
@@ -16,41 +466,184 @@ impl ConfigManager {
         let config_dir = proj_dirs.config_dir();
         fs::create_dir_all(config_dir)?;
 
-        let config_path = config_dir.join("config.toml");
-
-        Ok(Self { config_path })
+        Ok(Self::at_path(config_dir.join("config.toml")))
     }
 
     /// Create a ConfigManager with a custom path (for testing)
     #[cfg(test)]
     pub fn with_path(config_path: PathBuf) -> Self {
-        Self { config_path }
+        Self::at_path(config_path)
+    }
+
+    /// Keeps keyring tokens in memory, shared by the clones of this manager
+    #[cfg(test)]
+    pub fn with_memory_keyring(mut self) -> Self {
+        self.keyring = Keyring::Memory(Default::default());
+        self
+    }
+
+    /// Starts on the file's default profile. Errors surface on `load`.
+    fn at_path(config_path: PathBuf) -> Self {
+        let mut manager = Self {
+            config_path,
+            profile: DEFAULT_PROFILE.to_string(),
+            keyring: Keyring::Os,
+        };
+        if let Ok(Some(file)) = manager.read_file() {
+            manager.profile = file.default_profile;
+        }
+        manager
+    }
+
+    /// Uses `profile` instead of the default one (`--profile`)
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = profile.into();
+        self
     }
 
     pub fn config_path(&self) -> &PathBuf {
         &self.config_path
     }
 
+    /// Name of the profile this manager reads and writes
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Loads the active profile, `None` when it hasn't been set up
     pub fn load(&self) -> Result<Option<Config>> {
+        let Some(file) = self.read_file()? else {
+            return Ok(None);
+        };
+        let Some(config) = file.profiles.get(&self.profile) else {
+            return Ok(None);
+        };
+        validate(config)?;
+
+        Ok(Some(config.clone()))
+    }
+
+    /// Saves the active profile, leaving the other profiles untouched
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let mut file = self.read_file()?.unwrap_or_else(|| ConfigFile {
+            default_profile: self.profile.clone(),
+            profiles: BTreeMap::new(),
+        });
+        file.profiles.insert(self.profile.clone(), config.clone());
+        self.write_file(&file)
+    }
+
+    /// Configured profile names and the default one
+    pub fn profiles(&self) -> Result<(Vec<String>, String)> {
+        let file = self.read_file()?.unwrap_or_default();
+        Ok((file.profiles.into_keys().collect(), file.default_profile))
+    }
+
+    /// Makes `name` the profile used without `--profile`
+    pub fn set_default_profile(&self, name: &str) -> Result<()> {
+        let mut file = self.read_file()?.unwrap_or_default();
+        if !file.profiles.contains_key(name) {
+            anyhow::bail!("No profile named '{}'", name);
+        }
+        file.default_profile = name.to_string();
+        self.write_file(&file)
+    }
+
+    /// Deletes a profile's settings. The default profile can't be removed
+    /// while other profiles point at it; switch with `profile use` first.
+    pub fn remove_profile(&self, name: &str) -> Result<()> {
+        let mut file = self.read_file()?.unwrap_or_default();
+        if !file.profiles.contains_key(name) {
+            anyhow::bail!("No profile named '{}'", name);
+        }
+        if file.default_profile == name && file.profiles.len() > 1 {
+            anyhow::bail!(
+                "'{}' is the default profile. Pick another one with 'copilot-usage profile use <name>' first.",
+                name
+            );
+        }
+        file.profiles.remove(name);
+        self.write_file(&file)
+    }
+
+    /// Reads config.toml, migrating a pre-profiles file (plain top-level
+    /// settings) into a `default` profile and saving it in the new layout
+    fn read_file(&self) -> Result<Option<ConfigFile>> {
         if !self.config_path.exists() {
             return Ok(None);
         }
 
         let content = fs::read_to_string(&self.config_path)?;
-        let config: Config = toml::from_str(&content)?;
+        let table: toml::Table = toml::from_str(&content)?;
+        if table.contains_key("profiles") {
+            return Ok(Some(table.try_into()?));
+        }
 
-        Ok(Some(config))
+        let legacy: Config = table.try_into()?;
+        let file = ConfigFile {
+            default_profile: default_profile_name(),
+            profiles: BTreeMap::from([(default_profile_name(), legacy)]),
+        };
+        self.write_file(&file)?;
+        Ok(Some(file))
     }
 
-    pub fn save(&self, config: &Config) -> Result<()> {
-        let content = toml::to_string_pretty(config)?;
-        fs::write(&self.config_path, content)?;
+    /// Writes the file readable by the user only (it may hold the token),
+    /// and closes its directory to other users
+    fn write_file(&self, file: &ConfigFile) -> Result<()> {
+        let content = toml::to_string_pretty(file)?;
+        if let Some(dir) = self.config_path.parent().filter(|dir| dir.is_dir()) {
+            ownership::restrict_dir(dir)
+                .with_context(|| format!("Failed to restrict {}", dir.display()))?;
+        }
+        ownership::create_private(&self.config_path)
+            .and_then(|mut out| out.write_all(content.as_bytes()))
+            .with_context(|| format!("Failed to write {}", self.config_path.display()))?;
         Ok(())
     }
 
-    pub fn setup_interactive(&self) -> Result<Config> {
+    /// Returns the token to use and where it came from, if any is available
+    pub fn resolve_token(&self, config: &Config) -> Option<(String, TokenSource)> {
+        let resolved = resolve_token_with(
+            |var| std::env::var(var).ok(),
+            || self.keyring_token(),
+            &config.token,
+        );
+        if let Some((token, _)) = &resolved {
+            register_secret(token);
+        }
+        resolved
+    }
+
+    /// Like `resolve_token`, but fails with setup guidance when no token is found
+    pub fn require_token(&self, config: &Config) -> Result<String> {
+        self.resolve_token(config).map(|(token, _)| token).with_context(|| {
+            tf("err.no_token", &[&TOKEN_ENV_VARS[0]])
+        })
+    }
+
+    /// Forgets the keyring token of a profile, if there is one
+    pub fn delete_keyring_token(&self, profile: &str) -> Result<()> {
+        self.keyring
+            .delete(profile)
+            .context("Failed to remove token from the OS keyring")
+    }
+
+    /// Stores the token in the OS keyring under the current profile
+    pub fn store_token_in_keyring(&self, token: &str) -> Result<()> {
+        self.keyring
+            .set(&self.profile, token)
+            .context("Failed to store token in the OS keyring")
+    }
+
+    /// The profile's token in the keyring, if it has one
+    pub fn keyring_token(&self) -> Option<String> {
+        self.keyring.get(&self.profile)
+    }
+
+    pub async fn setup_interactive(&self) -> Result<Config> {
         use console::style;
-        use dialoguer::{theme::ColorfulTheme, Input};
+        use dialoguer::{theme::ColorfulTheme, Confirm, Input};
 
         println!(
             "{}",
@@ -58,42 +651,139 @@ impl ConfigManager {
         );
         println!("{}", style("=================================").cyan());
         println!();
+
+        // Keep an Enterprise server chosen earlier preselected
+        let current_url = self
+            .load()
+            .ok()
+            .flatten()
+            .map(|config| config.api_base_url)
+            .filter(|url| url != GITHUB_API_URL);
+        let host_idx = dialoguer::Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("GitHub.com or Enterprise?")
+            .default(usize::from(current_url.is_some()))
+            .items(&["GitHub.com", "GitHub Enterprise Server"])
+            .interact()?;
+        let api_base_url = if host_idx == 0 {
+            GITHUB_API_URL.to_string()
+        } else {
+            let url: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("API URL (https://HOST/api/v3)")
+                .with_initial_text(current_url.unwrap_or_default())
+                .validate_with(|input: &String| {
+                    parse_api_base_url(input)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .interact_text()?;
+            parse_api_base_url(&url)?
+        };
+        println!();
+
         println!("{}", style("Please create a Personal Access Token:").dim());
-        println!("1. Go to: https://github.com/settings/personal-access-tokens/new");
+        if host_idx == 0 {
+            println!("1. Go to: https://github.com/settings/personal-access-tokens/new");
+        } else {
+            println!(
+                "1. Go to: Settings > Developer settings > Personal access tokens on your server"
+            );
+        }
         println!("2. Select 'Fine-grained tokens'");
         println!("3. Resource owner: Your account");
         println!("4. Permission: Plan (Read)");
         println!();
 
-        let token: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("GitHub Personal Access Token")
-            .validate_with(|input: &String| {
-                if input.starts_with("ghp_") || input.starts_with("github_pat_") {
-                    Ok(())
-                } else {
-                    Err("Token should start with 'ghp_' or 'github_pat_'")
+        let mut token_expires = None;
+        let token = loop {
+            let token: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("GitHub Personal Access Token")
+                .validate_with(|input: &String| {
+                    if input.starts_with("ghp_") || input.starts_with("github_pat_") {
+                        Ok(())
+                    } else {
+                        Err("Token should start with 'ghp_' or 'github_pat_'")
+                    }
+                })
+                .interact_text()?;
+            let token = token.trim().to_string();
+            register_secret(&token);
+
+            // Mejor enterarse ahora que en el primer fetch
+            println!();
+            let server = Config {
+                api_base_url: api_base_url.clone(),
+                ..Config::default()
+            };
+            let checked = match ApiClient::for_config(token.clone(), &server) {
+                Ok(client) => client.validate_token().await,
+                Err(e) => Err(e),
+            };
+            match checked {
+                Ok(diagnostics) => {
+                    print_token_diagnostics(&diagnostics);
+                    token_expires = diagnostics.expires_on();
+                    if diagnostics.is_ok() {
+                        break token;
+                    }
                 }
-            })
-            .interact_text()?;
+                Err(e) => println!(
+                    "{}",
+                    style(redact(&format!("Could not check the token: {:#}", e))).yellow()
+                ),
+            }
+            println!();
 
-        let theme_idx: usize = dialoguer::Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select theme")
-            .default(0)
-            .items(&[
-                "dark",
-                "nord",
-                "monokai",
-                "gruvbox",
-                "catppuccin",
-                "onedark",
-                "tokyonight",
-                "solarized",
-                "kanagawa",
-            ])
+            let retry = Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Re-enter the token?")
+                .default(true)
+                .interact()?;
+            if !retry {
+                break token;
+            }
+        };
+        println!();
+
+        // GitHub no la manda siempre (p. ej. si no se pudo comprobar)
+        if token_expires.is_none() {
+            let expires: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Token expiry date (YYYY-MM-DD, empty if it doesn't expire)")
+                .allow_empty(true)
+                .validate_with(|input: &String| {
+                    if input.trim().is_empty() || parse_token_expires(input).is_ok() {
+                        Ok(())
+                    } else {
+                        Err("Expected a date like 2026-12-31")
+                    }
+                })
+                .interact_text()?;
+            token_expires = parse_token_expires(&expires).ok().flatten();
+        }
+
+        let use_keyring = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Store the token in the OS keyring instead of config.toml?")
+            .default(true)
             .interact()?;
 
+        // Leave the token out of config.toml when the keyring accepted it
+        let file_token = if use_keyring {
+            match self.store_token_in_keyring(&token) {
+                Ok(()) => String::new(),
+                Err(e) => {
+                    println!(
+                        "{}",
+                        style(format!("{:#}. Saving token to config file instead.", e)).yellow()
+                    );
+                    token
+                }
+            }
+        } else {
+            token
+        };
+
         let themes = [
             "dark",
+            "light",
+            "auto",
             "nord",
             "monokai",
             "gruvbox",
@@ -104,12 +794,83 @@ impl ConfigManager {
             "kanagawa",
         ];
 
+        // Keep a theme chosen earlier (e.g. with `theme set`) preselected
+        let current_theme = self.load().ok().flatten().map(|config| config.theme);
+        let theme_idx: usize = dialoguer::Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select theme")
+            .default(
+                themes
+                    .iter()
+                    .position(|name| current_theme.as_deref() == Some(*name))
+                    .unwrap_or(0),
+            )
+            .items(&themes)
+            .interact()?;
+
+        let mut plan_items: Vec<String> = SETUP_PLANS
+            .iter()
+            .map(|plan| {
+                format!(
+                    "{} ({} premium requests)",
+                    plan.name(),
+                    plan.premium_request_limit()
+                )
+            })
+            .collect();
+        plan_items.push("Custom".to_string());
+
+        let plan_idx: usize = dialoguer::Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select your Copilot plan")
+            .default(SETUP_DEFAULT_PLAN)
+            .items(&plan_items)
+            .interact()?;
+
+        // The plan itself is detected from the API on the first fetch; a
+        // typed limit is kept over the detected one
+        let (premium_request_limit, limit_source) = match SETUP_PLANS.get(plan_idx) {
+            Some(plan) => (plan.premium_request_limit(), LimitSource::Pending),
+            None => (
+                Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Monthly premium request limit")
+                    .default(DEFAULT_PREMIUM_REQUEST_LIMIT)
+                    .validate_with(|input: &u32| {
+                        if *input > 0 {
+                            Ok(())
+                        } else {
+                            Err("Limit must be greater than 0")
+                        }
+                    })
+                    .interact_text()?,
+                LimitSource::Custom,
+            ),
+        };
+
+        println!();
+        println!("{}", style("Waybar text placeholders:").bold());
+        for (name, description) in crate::output::waybar::PLACEHOLDERS {
+            println!(
+                "  {:<22} {}",
+                style(format!("{{{}}}", name)).cyan(),
+                description
+            );
+        }
+        println!("  {}", style("Use {{ and }} for literal braces.").dim());
+        let waybar_format: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Waybar format")
+            .default("{percentage}%".to_string())
+            .interact_text()?;
+
         let config = Config {
-            token: token.trim().to_string(),
+            token: file_token,
             theme: themes[theme_idx].to_string(),
             cache_ttl_minutes: 5,
-            waybar_format: "{percentage}%".to_string(),
+            waybar_format,
             username: None,
+            premium_request_limit,
+            limit_source,
+            api_base_url,
+            token_expires,
+            ..Config::default()
         };
 
         self.save(&config)?;
@@ -125,6 +886,7 @@ impl ConfigManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::WaybarThresholds;
     use tempfile::TempDir;
 
     fn create_test_config() -> Config {
@@ -134,6 +896,7 @@ mod tests {
             cache_ttl_minutes: 5,
             waybar_format: "{percentage}%".to_string(),
             username: Some("testuser".to_string()),
+            ..Config::default()
         }
     }
 
@@ -163,6 +926,60 @@ mod tests {
         assert_eq!(loaded.username, Some("testuser".to_string()));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_save_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let config_dir = temp_dir.path().join("copilot-usage");
+        fs::create_dir(&config_dir).unwrap();
+        fs::set_permissions(&config_dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let config_path = config_dir.join("config.toml");
+        let manager = ConfigManager::with_path(config_path.clone());
+        manager.save(&create_test_config()).unwrap();
+        // Un config de antes, legible por todos
+        fs::set_permissions(&config_path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(ownership::exposed_mode(&config_path), Some(0o644));
+
+        manager.save(&create_test_config()).unwrap();
+
+        let mode =
+            |path: &std::path::Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&config_path), 0o600);
+        assert_eq!(mode(&config_dir), 0o700);
+        assert_eq!(ownership::exposed_mode(&config_path), None);
+    }
+
+    #[test]
+    fn test_token_expiry_warning() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let expires = |days| Some(today + chrono::Days::new(days));
+        assert_eq!(token_expiry_warning(expires(30), today), None);
+        assert_eq!(token_expiry_warning(expires(7), today), None);
+        assert_eq!(token_expiry_warning(expires(6), today), Some(6));
+        assert_eq!(token_expiry_warning(expires(0), today), Some(0));
+        assert_eq!(token_expiry_warning(today.pred_opt(), today), Some(-1));
+        assert_eq!(token_expiry_warning(None, today), None);
+
+        assert!(
+            token_expiry_notice(today.pred_opt(), today)
+                .unwrap()
+                .contains("expired on 2026-10-15")
+        );
+    }
+
+    #[test]
+    fn test_parse_token_expires() {
+        assert_eq!(
+            parse_token_expires(" 2026-12-31 ").unwrap(),
+            NaiveDate::from_ymd_opt(2026, 12, 31)
+        );
+        assert_eq!(parse_token_expires("").unwrap(), None);
+        assert_eq!(parse_token_expires("none").unwrap(), None);
+        assert!(parse_token_expires("31/12/2026").is_err());
+    }
+
     #[test]
     fn test_config_path() {
         let temp_dir = TempDir::new().unwrap();
@@ -201,10 +1018,476 @@ mod tests {
             cache_ttl_minutes: 5,
             waybar_format: "{percentage}%".to_string(),
             username: None,
+            ..Config::default()
         };
         manager.save(&config).unwrap();
 
         let loaded = manager.load().unwrap().unwrap();
         assert!(loaded.username.is_none());
     }
+
+    #[test]
+    fn test_resolve_token_env_wins() {
+        let env = |var: &str| match var {
+            "GITHUB_TOKEN" => Some("ghp_from_env".to_string()),
+            _ => None,
+        };
+        let resolved = resolve_token_with(env, || Some("ghp_keyring".to_string()), "ghp_file");
+        assert_eq!(
+            resolved,
+            Some((
+                "ghp_from_env".to_string(),
+                TokenSource::Env("GITHUB_TOKEN")
+            ))
+        );
+    }
+
+    #[test]
+    fn test_resolve_token_env_priority() {
+        let env = |_: &str| Some("ghp_any".to_string());
+        let (_, source) = resolve_token_with(env, || None, "").unwrap();
+        assert_eq!(source, TokenSource::Env("COPILOT_USAGE_TOKEN"));
+    }
+
+    #[test]
+    fn test_resolve_token_keyring_then_file() {
+        let no_env = |_: &str| None;
+
+        let resolved = resolve_token_with(no_env, || Some("ghp_keyring".to_string()), "ghp_file");
+        assert_eq!(resolved.unwrap().1, TokenSource::Keyring);
+
+        let resolved = resolve_token_with(no_env, || None, "ghp_file");
+        assert_eq!(
+            resolved,
+            Some(("ghp_file".to_string(), TokenSource::ConfigFile))
+        );
+    }
+
+    #[test]
+    fn test_resolve_token_ignores_blank_values() {
+        let env = |_: &str| Some("   ".to_string());
+        assert!(resolve_token_with(env, || Some(String::new()), "").is_none());
+    }
+
+    #[test]
+    fn test_load_config_without_newer_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            "token = \"ghp_old\"\ntheme = \"nord\"\ncache_ttl_minutes = 5\nwaybar_format = \"{percentage}%\"\n",
+        )
+        .unwrap();
+
+        let loaded = ConfigManager::with_path(config_path).load().unwrap().unwrap();
+        assert_eq!(loaded.history_retention_days, 90);
+        assert_eq!(loaded.premium_request_limit, 300);
+    }
+
+    #[test]
+    fn test_plan_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        config.plan = Some(Plan::ProPlus);
+        config.premium_request_limit = 1500;
+        manager.save(&config).unwrap();
+
+        let content = fs::read_to_string(manager.config_path()).unwrap();
+        assert!(content.contains("plan = \"pro_plus\""));
+
+        let loaded = manager.load().unwrap().unwrap();
+        assert_eq!(loaded.plan, Some(Plan::ProPlus));
+        assert_eq!(loaded.premium_request_limit, 1500);
+    }
+
+    #[test]
+    fn test_rejects_zero_premium_request_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        config.premium_request_limit = 0;
+        manager.save(&config).unwrap();
+
+        assert!(manager.load().is_err());
+    }
+
+    #[test]
+    fn test_api_max_attempts_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        for (attempts, valid) in [(0, false), (1, true), (10, true), (11, false)] {
+            config.api_max_attempts = attempts;
+            manager.save(&config).unwrap();
+            assert_eq!(manager.load().is_ok(), valid, "{}", attempts);
+        }
+    }
+
+    #[test]
+    fn test_billing_cycle_day_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        config.billing_cycle_day = Some(31);
+        manager.save(&config).unwrap();
+        assert_eq!(manager.load().unwrap().unwrap().billing_cycle_day, Some(31));
+
+        for day in [0, 32] {
+            config.billing_cycle_day = Some(day);
+            manager.save(&config).unwrap();
+            let err = manager.load().unwrap_err().to_string();
+            assert!(err.contains("billing_cycle_day"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_budget_round_trip_and_zero_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        manager.save(&config).unwrap();
+        assert!(!fs::read_to_string(manager.config_path()).unwrap().contains("budget"));
+
+        config.budget.requests = Some(200);
+        manager.save(&config).unwrap();
+        // Solo el de peticiones se sigue escribiendo en su forma corta
+        assert!(
+            fs::read_to_string(manager.config_path())
+                .unwrap()
+                .contains("budget = 200")
+        );
+        assert_eq!(manager.load().unwrap().unwrap().budget.requests, Some(200));
+
+        config.budget.requests = Some(0);
+        manager.save(&config).unwrap();
+        assert!(manager.load().is_err());
+    }
+
+    #[test]
+    fn test_budget_table() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            "default_profile = \"default\"\n\n[profiles.default]\ntoken = \"ghp_x\"\ntheme = \"dark\"\ncache_ttl_minutes = 5\nwaybar_format = \"{percentage}%\"\n\n[profiles.default.budget]\nmonthly_budget_usd = 5.0\nprice_overrides = { \"gpt-4.5\" = 0.08 }\n",
+        )
+        .unwrap();
+
+        let manager = ConfigManager::with_path(config_path);
+        let mut config = manager.load().unwrap().unwrap();
+        assert_eq!(config.budget.requests, None);
+        assert_eq!(config.budget.monthly_budget_usd, Some(5.0));
+        assert_eq!(config.budget.price_overrides["gpt-4.5"], 0.08);
+
+        config.budget.requests = Some(200);
+        manager.save(&config).unwrap();
+        assert_eq!(manager.load().unwrap().unwrap().budget, config.budget);
+
+        config.budget.monthly_budget_usd = Some(0.0);
+        manager.save(&config).unwrap();
+        let err = manager.load().unwrap_err().to_string();
+        assert!(err.contains("monthly_budget_usd"));
+
+        config.budget.monthly_budget_usd = None;
+        config.budget.price_overrides.insert("o3".to_string(), -1.0);
+        manager.save(&config).unwrap();
+        assert!(manager.load().unwrap_err().to_string().contains("'o3'"));
+    }
+
+    #[test]
+    fn test_model_limits_table() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            "default_profile = \"default\"\n\n[profiles.default]\ntoken = \"ghp_x\"\ntheme = \"dark\"\ncache_ttl_minutes = 5\nwaybar_format = \"{percentage}%\"\n\n[profiles.default.model_limits]\n\"gpt-4.1\" = \"unmetered\"\n\"claude-opus-*\" = 50\n",
+        )
+        .unwrap();
+
+        let manager = ConfigManager::with_path(config_path);
+        let mut config = manager.load().unwrap().unwrap();
+        assert_eq!(config.model_limits["gpt-4.1"], ModelLimit::Unmetered);
+        assert_eq!(
+            config.model_limits["claude-opus-*"],
+            ModelLimit::Requests(50)
+        );
+
+        manager.save(&config).unwrap();
+        assert_eq!(
+            manager.load().unwrap().unwrap().model_limits,
+            config.model_limits
+        );
+
+        config
+            .model_limits
+            .insert("o3".to_string(), ModelLimit::Requests(0));
+        manager.save(&config).unwrap();
+        assert!(manager.load().unwrap_err().to_string().contains("'o3'"));
+    }
+
+    #[test]
+    fn test_plan_mismatch_warning() {
+        let mut config = create_test_config();
+        config.plan = Some(Plan::Pro);
+        config.premium_request_limit = 300;
+
+        let warning = plan_mismatch_warning(&config, 50.0).unwrap();
+        assert!(warning.contains("shows 50 included premium requests"));
+        assert!(warning.contains("300 (Copilot Pro)"));
+        assert!(warning.contains("`copilot-usage config set plan free`"));
+
+        let custom = plan_mismatch_warning(&config, 120.0).unwrap();
+        assert!(custom.contains("premium_request_limit = 120"));
+
+        assert!(plan_mismatch_warning(&config, 300.0).is_none());
+        assert!(plan_mismatch_warning(&config, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_refresh_conflicts() {
+        let check = |ttl_minutes: u64, interval: Option<u64>| {
+            let mut config = create_test_config();
+            config.cache_ttl_minutes = ttl_minutes;
+            config.waybar_interval_seconds = interval;
+            refresh_conflicts(&config)
+        };
+
+        // Sin intervalo de Waybar no hay nada que comparar
+        assert!(check(60, None).is_empty());
+        assert!(check(0, None).is_empty());
+
+        // TTL más largo que el intervalo
+        assert_eq!(
+            check(60, Some(30)),
+            vec![RefreshConflict::TtlOutlastsWaybarInterval {
+                ttl_secs: 3600,
+                interval_secs: 30
+            }]
+        );
+        assert_eq!(check(5, Some(299)).len(), 1);
+
+        // TTL igual o menor, intervalo razonable
+        assert!(check(5, Some(300)).is_empty());
+        assert!(check(1, Some(60)).is_empty());
+        assert!(check(0, Some(MIN_API_POLL_SECS)).is_empty());
+
+        // El cache caduca antes de cada tick corto: cada uno llama a la API
+        assert_eq!(
+            check(0, Some(10)),
+            vec![RefreshConflict::WaybarPollsApi { interval_secs: 10 }]
+        );
+        // Un TTL que cubre el tick corto ya se avisa como TTL largo
+        assert!(matches!(
+            check(1, Some(10))[..],
+            [RefreshConflict::TtlOutlastsWaybarInterval { .. }]
+        ));
+        // Un TTL enorme no desborda
+        assert!(matches!(
+            check(u64::MAX, Some(30))[..],
+            [RefreshConflict::TtlOutlastsWaybarInterval {
+                ttl_secs: u64::MAX,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_refresh_conflict_advice() {
+        let long_ttl = RefreshConflict::TtlOutlastsWaybarInterval {
+            ttl_secs: 3600,
+            interval_secs: 120,
+        };
+        assert_eq!(
+            long_ttl.summary(),
+            "Cache TTL (60 min) is longer than the Waybar interval (2 min)"
+        );
+        assert!(long_ttl.advice().contains("cache_ttl_minutes = 2"));
+        assert!(long_ttl.advice().contains("interval to 3600"));
+
+        let sub_minute = RefreshConflict::TtlOutlastsWaybarInterval {
+            ttl_secs: 300,
+            interval_secs: 30,
+        };
+        assert!(!sub_minute.advice().contains("cache_ttl_minutes"));
+        assert!(sub_minute.advice().contains("interval to 300"));
+
+        let polling = RefreshConflict::WaybarPollsApi { interval_secs: 5 };
+        assert_eq!(polling.summary(), "Waybar calls the GitHub API every 5s");
+        assert!(polling.advice().contains("720 calls/hour"));
+    }
+
+    #[test]
+    fn test_zero_waybar_interval_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        config.waybar_interval_seconds = Some(0);
+        manager.save(&config).unwrap();
+        assert!(manager.load().unwrap_err().to_string().contains("waybar_interval_seconds"));
+    }
+
+    #[test]
+    fn test_waybar_thresholds() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            "default_profile = \"default\"\n\n[profiles.default]\ntoken = \"ghp_x\"\ntheme = \"dark\"\ncache_ttl_minutes = 5\nwaybar_format = \"{percentage}%\"\nwaybar_thresholds = { low = 40, warning = 60 }\n",
+        )
+        .unwrap();
+
+        let manager = ConfigManager::with_path(config_path);
+        let mut config = manager.load().unwrap().unwrap();
+        assert_eq!(
+            config.waybar_thresholds,
+            WaybarThresholds {
+                low: 40.0,
+                warning: 60.0,
+                critical: 90.0
+            }
+        );
+
+        config.waybar_thresholds.critical = 55.0;
+        manager.save(&config).unwrap();
+        let error = manager.load().unwrap_err().to_string();
+        assert!(error.contains("waybar_thresholds"));
+    }
+
+    #[test]
+    fn test_menu_disabled_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        config.menu.disabled = vec!["reconfigure".to_string(), "quit".to_string()];
+        manager.save(&config).unwrap();
+
+        let loaded = manager.load().unwrap().unwrap();
+        assert_eq!(loaded.menu.disabled, vec!["reconfigure", "quit"]);
+    }
+
+    #[test]
+    fn test_menu_disabled_rejects_unknown_ids() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        config.menu.disabled = vec!["selfdestruct".to_string()];
+        manager.save(&config).unwrap();
+
+        let err = manager.load().unwrap_err().to_string();
+        assert!(err.contains("'selfdestruct'"));
+        assert!(err.contains("refresh, daily, compare, profile, cache"));
+    }
+
+    #[test]
+    fn test_language_is_validated() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_path(temp_dir.path().join("config.toml"));
+
+        let mut config = create_test_config();
+        config.language = Some("es".to_string());
+        manager.save(&config).unwrap();
+        assert_eq!(
+            manager.load().unwrap().unwrap().language.as_deref(),
+            Some("es")
+        );
+
+        config.language = Some("klingon".to_string());
+        manager.save(&config).unwrap();
+        let err = manager.load().unwrap_err().to_string();
+        assert!(err.contains("'klingon'"));
+        assert!(err.contains("en, es"));
+    }
+
+    #[test]
+    fn test_single_profile_config_migrates_to_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            "token = \"ghp_old\"\ntheme = \"nord\"\ncache_ttl_minutes = 5\nwaybar_format = \"{percentage}%\"\nbudget = 200\n\n[menu]\ndisabled = [\"about\"]\n",
+        )
+        .unwrap();
+
+        let manager = ConfigManager::with_path(config_path.clone());
+        assert_eq!(manager.profile(), DEFAULT_PROFILE);
+        let loaded = manager.load().unwrap().unwrap();
+        assert_eq!(loaded.token, "ghp_old");
+        assert_eq!(loaded.budget.requests, Some(200));
+        assert_eq!(loaded.menu.disabled, vec!["about".to_string()]);
+
+        let migrated = fs::read_to_string(&config_path).unwrap();
+        assert!(migrated.contains("default_profile = \"default\""));
+        assert!(migrated.contains("[profiles.default]"));
+        assert!(!migrated.starts_with("token"));
+
+        // Loading the migrated file again gives the same settings
+        let reloaded = ConfigManager::with_path(config_path).load().unwrap().unwrap();
+        assert_eq!(reloaded.theme, "nord");
+        assert_eq!(reloaded.menu.disabled, loaded.menu.disabled);
+    }
+
+    #[test]
+    fn test_profiles_are_independent() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let personal = ConfigManager::with_path(config_path.clone()).with_profile("personal");
+        personal.save(&create_test_config()).unwrap();
+        let work = ConfigManager::with_path(config_path.clone()).with_profile("work");
+        assert!(work.load().unwrap().is_none());
+        work.save(&Config {
+            token: "ghp_work".to_string(),
+            ..create_test_config()
+        })
+        .unwrap();
+
+        assert_eq!(personal.load().unwrap().unwrap().token, "ghp_test123");
+        assert_eq!(work.load().unwrap().unwrap().token, "ghp_work");
+
+        // The first profile saved becomes the default
+        let manager = ConfigManager::with_path(config_path.clone());
+        assert_eq!(manager.profile(), "personal");
+        let (names, default) = manager.profiles().unwrap();
+        assert_eq!(names, vec!["personal", "work"]);
+        assert_eq!(default, "personal");
+
+        manager.set_default_profile("work").unwrap();
+        assert_eq!(ConfigManager::with_path(config_path.clone()).profile(), "work");
+        assert!(manager.set_default_profile("missing").is_err());
+    }
+
+    #[test]
+    fn test_remove_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        for name in ["personal", "work"] {
+            ConfigManager::with_path(config_path.clone())
+                .with_profile(name)
+                .save(&create_test_config())
+                .unwrap();
+        }
+        let manager = ConfigManager::with_path(config_path);
+
+        assert!(manager.remove_profile("personal").is_err());
+        assert!(manager.remove_profile("missing").is_err());
+        manager.remove_profile("work").unwrap();
+        assert_eq!(manager.profiles().unwrap().0, vec!["personal"]);
+    }
+
+    #[test]
+    fn test_profile_names() {
+        assert_eq!(parse_profile_name("work-2").unwrap(), "work-2");
+        assert!(parse_profile_name("").is_err());
+        assert!(parse_profile_name("../work").is_err());
+        assert!(parse_profile_name("my work").is_err());
+    }
 }
@@ -0,0 +1,623 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    DEFAULT_PREMIUM_REQUEST_LIMIT, ModelTotals, ModelUsage, SkuUsage, UsageStats,
+    model_distribution,
+};
+use crate::percent::{format_percent, format_quantity, round_half_up};
+use crate::projection::{Outlook, Pace, calculate_projection};
+
+/// Layout of the JSON export; bump it when a field changes meaning
+pub const EXPORT_VERSION: u32 = 1;
+
+/// How old the remembered export can be to be offered as a fallback
+const RECENT_EXPORT_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// One line of the exported table (a model or the totals)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportRow {
+    pub model: String,
+    pub requests: f64,
+    pub percentage: f64,
+    pub estimated_cost: f64,
+    /// Requests the plan covered; 0 in exports written before the field
+    #[serde(default)]
+    pub included: f64,
+    /// Requests billed beyond the plan
+    #[serde(default)]
+    pub billed: f64,
+}
+
+/// One SKU of the JSON export's per-SKU grouping
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportSkuRow {
+    pub product: String,
+    pub sku: String,
+    pub requests: f64,
+    pub percentage: f64,
+    pub estimated_cost: f64,
+    pub included: f64,
+    pub billed: f64,
+}
+
+/// The JSON export, which `--from-file` reads back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportDocument {
+    /// 0 for exports written before the field existed
+    #[serde(default)]
+    pub version: u32,
+    pub username: String,
+    pub reset_date: NaiveDate,
+    /// Seconds from the export to the next reset; 0 in exports written before it
+    #[serde(default)]
+    pub seconds_until_reset: i64,
+    /// Premium request allowance; older exports derive it from the totals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<f64>,
+    pub models: Vec<ExportRow>,
+    /// The same usage grouped by SKU; empty in exports written before it
+    #[serde(default)]
+    pub skus: Vec<ExportSkuRow>,
+    pub total: ExportRow,
+    /// Burn rate at export time; missing outside the billing period
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projection: Option<ExportProjection>,
+}
+
+/// Where the burn rate leads by the reset, as exported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportProjection {
+    pub requests_per_day: f64,
+    pub at_reset: f64,
+    /// Day the limit runs out at this rate, when that's before the reset
+    pub runs_out: Option<NaiveDate>,
+    pub limit_reached: bool,
+    pub pace: Pace,
+}
+
+impl ExportDocument {
+    pub fn new(stats: &UsageStats) -> Self {
+        Self::at(stats, Utc::now())
+    }
+
+    fn at(stats: &UsageStats, now: DateTime<Utc>) -> Self {
+        let projection = calculate_projection(stats, now).map(|projection| ExportProjection {
+            requests_per_day: round_half_up(projection.per_day, 2),
+            at_reset: round_half_up(projection.at_reset, 2),
+            runs_out: match projection.outlook {
+                Outlook::RunsOut(date) => Some(date.date_naive()),
+                _ => None,
+            },
+            limit_reached: projection.outlook == Outlook::LimitReached,
+            pace: projection.pace,
+        });
+
+        Self {
+            version: EXPORT_VERSION,
+            username: stats.username.clone(),
+            reset_date: stats.reset_date.date_naive(),
+            seconds_until_reset: stats.until_reset(now).num_seconds(),
+            limit: Some(stats.total_limit),
+            models: model_rows(stats),
+            skus: sku_rows(stats),
+            total: total_row(stats),
+            projection,
+        }
+    }
+
+    /// Rebuilds the stats the export was written from. Only the quantities
+    /// of the billing breakdown are exported: the detail popup shows no amounts.
+    pub fn into_stats(self) -> UsageStats {
+        let limit = self.limit.unwrap_or_else(|| {
+            if self.total.percentage > 0.0 {
+                self.total.requests * 100.0 / self.total.percentage
+            } else {
+                DEFAULT_PREMIUM_REQUEST_LIMIT as f64
+            }
+        });
+
+        let models: Vec<ModelUsage> = self
+            .models
+            .into_iter()
+            .map(|row| ModelUsage {
+                used: row.requests,
+                limit,
+                unmetered: false,
+                percentage: row.percentage,
+                net_used: row.billed,
+                // Su parte del coste por solicitudes, como se exportó
+                cost: row.estimated_cost,
+                totals: row_totals(&row),
+                reported_names: Vec::new(),
+                name: row.model,
+            })
+            .collect();
+        let (top_model, distinct_models) = model_distribution(&models);
+
+        UsageStats {
+            total_used: self.total.requests,
+            total_limit: limit,
+            percentage: self.total.percentage,
+            reset_date: self.reset_date.and_time(Default::default()).and_utc(),
+            // No se exporta: el día del reinicio lo da, salvo en meses cortos
+            billing_cycle_day: self.reset_date.day(),
+            models,
+            top_model,
+            distinct_models,
+            totals: row_totals(&self.total),
+            estimated_cost: self.total.estimated_cost,
+            username: self.username,
+            plan: None,
+            note: None,
+            budget: None,
+            cost_budget: None,
+            skus: self
+                .skus
+                .into_iter()
+                .map(|row| SkuUsage {
+                    product: row.product,
+                    sku: row.sku,
+                    used: row.requests,
+                    percentage: row.percentage,
+                    cost: row.estimated_cost,
+                    totals: ModelTotals {
+                        gross_quantity: row.requests,
+                        discount_quantity: row.included,
+                        net_quantity: row.billed,
+                        ..ModelTotals::default()
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+fn row_totals(row: &ExportRow) -> ModelTotals {
+    ModelTotals {
+        gross_quantity: row.requests,
+        discount_quantity: row.included,
+        net_quantity: row.billed,
+        ..ModelTotals::default()
+    }
+}
+
+const HEADERS: [&str; 4] = ["Model", "Requests", "Percentage", "Estimated cost (USD)"];
+const TOTAL_LABEL: &str = "Total";
+
+/// Per-model rows. The estimated cost is billed at account level, so each
+/// model gets its share by requests.
+pub fn model_rows(stats: &UsageStats) -> Vec<ExportRow> {
+    stats
+        .models
+        .iter()
+        .map(|model| ExportRow {
+            model: model.name.clone(),
+            requests: model.used,
+            percentage: model.percentage,
+            estimated_cost: if stats.total_used > 0.0 {
+                stats.estimated_cost * model.used / stats.total_used
+            } else {
+                0.0
+            },
+            included: model.totals.discount_quantity,
+            billed: model.totals.net_quantity,
+        })
+        .collect()
+}
+
+/// Per-SKU rows, each with the cost billed under the SKU
+pub fn sku_rows(stats: &UsageStats) -> Vec<ExportSkuRow> {
+    stats
+        .skus
+        .iter()
+        .map(|sku| ExportSkuRow {
+            product: sku.product.clone(),
+            sku: sku.sku.clone(),
+            requests: sku.used,
+            percentage: sku.percentage,
+            estimated_cost: sku.cost,
+            included: sku.totals.discount_quantity,
+            billed: sku.totals.net_quantity,
+        })
+        .collect()
+}
+
+pub fn total_row(stats: &UsageStats) -> ExportRow {
+    ExportRow {
+        model: TOTAL_LABEL.to_string(),
+        requests: stats.total_used,
+        percentage: stats.percentage,
+        estimated_cost: stats.estimated_cost,
+        included: stats.totals.discount_quantity,
+        billed: stats.totals.net_quantity,
+    }
+}
+
+fn row_fields(row: &ExportRow) -> [String; 4] {
+    [
+        row.model.clone(),
+        format_quantity(row.requests),
+        format_percent(row.percentage, 1),
+        format!("{:.2}", row.estimated_cost),
+    ]
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub(crate) fn csv_line(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// RFC 4180 CSV: header, one line per model, then the totals
+pub fn to_csv(stats: &UsageStats) -> String {
+    let headers: Vec<String> = HEADERS.iter().map(|h| h.to_string()).collect();
+    let mut csv = csv_line(&headers);
+    csv.push_str("\r\n");
+    for row in model_rows(stats).iter().chain([&total_row(stats)]) {
+        csv.push_str(&csv_line(&row_fields(row)));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// GitHub-flavored Markdown table with a bold totals row
+pub fn to_markdown(stats: &UsageStats) -> String {
+    let escape = |field: &str| field.replace('|', "\\|").replace('\n', " ");
+
+    let mut md = format!("| {} |\n", HEADERS.join(" | "));
+    md.push_str("| --- | ---: | ---: | ---: |\n");
+    for row in model_rows(stats) {
+        let fields = row_fields(&row);
+        md.push_str(&format!(
+            "| {} | {} | {}% | ${} |\n",
+            escape(&fields[0]),
+            fields[1],
+            fields[2],
+            fields[3]
+        ));
+    }
+    let total = row_fields(&total_row(stats));
+    md.push_str(&format!(
+        "| **{}** | **{}** | **{}%** | **${}** |\n",
+        total[0], total[1], total[2], total[3]
+    ));
+    md
+}
+
+pub fn to_json(stats: &UsageStats) -> String {
+    serde_json::to_string_pretty(&ExportDocument::new(stats)).unwrap_or_default()
+}
+
+/// Parses a JSON export back into stats
+pub fn from_json(json: &str) -> Result<UsageStats> {
+    let document: ExportDocument =
+        serde_json::from_str(json).context("Not a copilot-usage JSON export")?;
+    if document.version > EXPORT_VERSION {
+        anyhow::bail!(
+            "Export format version {} is newer than this copilot-usage supports ({})",
+            document.version,
+            EXPORT_VERSION
+        );
+    }
+    Ok(document.into_stats())
+}
+
+pub fn read_file(path: &Path) -> Result<UsageStats> {
+    let json =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    from_json(&json).with_context(|| format!("Failed to load {}", path.display()))
+}
+
+/// File holding the path of the last JSON export
+fn last_export_record() -> Option<PathBuf> {
+    let proj_dirs = ProjectDirs::from("com", "copilot-usage", "copilot-usage")?;
+    Some(proj_dirs.data_dir().join("last-export"))
+}
+
+/// Remembers a JSON export so it can be offered when fetching fails
+pub fn remember_export(path: &Path) -> Result<()> {
+    let record = last_export_record().context("Failed to determine data directory")?;
+    let path = fs::canonicalize(path)?;
+    if let Some(dir) = record.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&record, path.to_string_lossy().as_bytes())?;
+    Ok(())
+}
+
+/// The remembered export, if it's still there and recent
+pub fn recent_export() -> Option<PathBuf> {
+    let path = PathBuf::from(fs::read_to_string(last_export_record()?).ok()?.trim());
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    is_recent(modified, SystemTime::now()).then_some(path)
+}
+
+fn is_recent(modified: SystemTime, now: SystemTime) -> bool {
+    // Un mtime en el futuro cuenta como reciente
+    now.duration_since(modified)
+        .map_or(true, |age| age <= RECENT_EXPORT_MAX_AGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{QuantityMode, TableGrouping};
+    use crate::testing;
+    use chrono::{TimeZone, Utc};
+
+    fn stats(names: &[&str]) -> UsageStats {
+        let models = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| testing::model(name, 10.0 * (i + 1) as f64))
+            .collect();
+        UsageStats {
+            estimated_cost: 2.4,
+            ..testing::stats_with(models)
+        }
+    }
+
+    /// Minimal RFC 4180 reader to check the writer round-trips
+    fn parse_csv(input: &str) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut chars = input.chars().peekable();
+        let mut quoted = false;
+
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => record.push(std::mem::take(&mut field)),
+                (false, '\r') => {}
+                (false, '\n') => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+        records
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let names = [
+            "gpt-4.1",
+            "claude, the \"sonnet\"",
+            "multi\nline",
+            "pipe | model",
+        ];
+        let stats = stats(&names);
+        let records = parse_csv(&to_csv(&stats));
+
+        assert_eq!(records.len(), names.len() + 2);
+        assert_eq!(records[0], HEADERS.map(String::from));
+        for (record, row) in records[1..].iter().zip(model_rows(&stats)) {
+            assert_eq!(record, &row_fields(&row));
+        }
+        assert_eq!(records[1][0], "gpt-4.1");
+        assert_eq!(records[2][0], "claude, the \"sonnet\"");
+        assert_eq!(records[3][0], "multi\nline");
+        assert_eq!(records.last().unwrap(), &row_fields(&total_row(&stats)));
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_costs_add_up_to_total() {
+        let stats = stats(&["a", "b", "c"]);
+        let sum: f64 = model_rows(&stats).iter().map(|r| r.estimated_cost).sum();
+        assert!((sum - stats.estimated_cost).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_markdown_table() {
+        let md = to_markdown(&stats(&["gpt-4.1", "pipe | model"]));
+        let lines: Vec<&str> = md.lines().collect();
+        assert_eq!(
+            lines[0],
+            "| Model | Requests | Percentage | Estimated cost (USD) |"
+        );
+        assert_eq!(lines[1], "| --- | ---: | ---: | ---: |");
+        assert_eq!(lines[2], "| gpt-4.1 | 10 | 3.3% | $0.80 |");
+        assert_eq!(lines[3], "| pipe \\| model | 20 | 6.7% | $1.60 |");
+        assert_eq!(lines[4], "| **Total** | **30** | **10.0%** | **$2.40** |");
+    }
+
+    #[test]
+    fn test_json_export() {
+        let json: serde_json::Value = serde_json::from_str(&to_json(&stats(&["gpt-4.1"]))).unwrap();
+        assert_eq!(json["models"][0]["model"], "gpt-4.1");
+        assert_eq!(json["total"]["requests"], 10.0);
+        assert_eq!(json["reset_date"], "2026-03-01");
+        assert_eq!(json["version"], EXPORT_VERSION);
+        assert_eq!(json["limit"], 300.0);
+        // Marzo ya pasó: sin proyección
+        assert!(json.get("projection").is_none());
+    }
+
+    #[test]
+    fn test_json_seconds_until_reset() {
+        let at = |now| ExportDocument::at(&stats(&["gpt-4.1"]), now).seconds_until_reset;
+        let now = Utc.with_ymd_and_hms(2026, 2, 28, 18, 0, 0).unwrap();
+        assert_eq!(at(now), 6 * 3600);
+        // Un reinicio ya pasado cuenta hasta el siguiente, no en negativo
+        let now = Utc.with_ymd_and_hms(2026, 3, 31, 0, 0, 0).unwrap();
+        assert_eq!(at(now), 86_400);
+    }
+
+    #[test]
+    fn test_json_projection() {
+        // 10 de 28 días de febrero, 30 solicitudes: 3/día, 84 al reinicio
+        let now = Utc.with_ymd_and_hms(2026, 2, 11, 0, 0, 0).unwrap();
+        let document = ExportDocument::at(&stats(&["gpt-4.1", "claude-sonnet-4"]), now);
+        let json = serde_json::to_value(&document).unwrap();
+        assert_eq!(
+            json["projection"],
+            serde_json::json!({
+                "requests_per_day": 3.0,
+                "at_reset": 84.0,
+                "runs_out": null,
+                "limit_reached": false,
+                "pace": "behind",
+            })
+        );
+
+        // Se lee de vuelta, aunque `into_stats` no la use
+        let loaded: ExportDocument =
+            serde_json::from_str(&serde_json::to_string(&document).unwrap()).unwrap();
+        assert_eq!(loaded, document);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let original = stats(&["gpt-4.1", "claude-sonnet-4"]);
+        let loaded = from_json(&to_json(&original)).unwrap();
+
+        assert_eq!(loaded.username, "testuser");
+        assert_eq!(loaded.reset_date, original.reset_date);
+        assert_eq!(loaded.total_used, original.total_used);
+        assert_eq!(loaded.total_limit, 300.0);
+        assert_eq!(loaded.percentage, original.percentage);
+        assert_eq!(loaded.estimated_cost, original.estimated_cost);
+        assert_eq!(loaded.models.len(), 2);
+        assert_eq!(loaded.models[1].name, "claude-sonnet-4");
+        assert_eq!(loaded.models[1].used, 20.0);
+        assert_eq!(loaded.models[1].limit, 300.0);
+        // Exportar lo cargado da el mismo documento
+        let now = Utc::now();
+        assert_eq!(
+            ExportDocument::at(&loaded, now),
+            ExportDocument::at(&original, now)
+        );
+    }
+
+    #[test]
+    fn test_json_billed_quantities() {
+        let mut original = stats(&["gpt-4.1"]);
+        original.models[0].totals = ModelTotals {
+            gross_quantity: 10.0,
+            discount_quantity: 4.0,
+            net_quantity: 6.0,
+            ..ModelTotals::default()
+        };
+        original.totals = original.models[0].totals.clone();
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&original)).unwrap();
+        assert_eq!(json["models"][0]["included"], 4.0);
+        assert_eq!(json["total"]["billed"], 6.0);
+
+        // El modo facturado funciona igual sobre un export
+        let loaded = from_json(&to_json(&original)).unwrap();
+        let billed = loaded.in_mode(QuantityMode::Billed);
+        assert_eq!(billed.total_used, 6.0);
+        assert_eq!(billed.models[0].used, 6.0);
+    }
+
+    #[test]
+    fn test_json_sku_grouping() {
+        let mut original = stats(&["gpt-4.1", "claude-sonnet-4"]);
+        original.skus = vec![SkuUsage {
+            product: "copilot".to_string(),
+            sku: "Copilot Premium Request".to_string(),
+            used: 30.0,
+            percentage: 10.0,
+            cost: 2.4,
+            totals: ModelTotals {
+                gross_quantity: 30.0,
+                discount_quantity: 0.0,
+                net_quantity: 30.0,
+                ..ModelTotals::default()
+            },
+        }];
+
+        // Las dos agrupaciones, una al lado de la otra
+        let json: serde_json::Value = serde_json::from_str(&to_json(&original)).unwrap();
+        assert_eq!(json["models"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            json["skus"],
+            serde_json::json!([{
+                "product": "copilot",
+                "sku": "Copilot Premium Request",
+                "requests": 30.0,
+                "percentage": 10.0,
+                "estimated_cost": 2.4,
+                "included": 0.0,
+                "billed": 30.0,
+            }])
+        );
+
+        let loaded = from_json(&to_json(&original)).unwrap();
+        let by_sku = loaded.grouped(TableGrouping::Sku);
+        assert_eq!(by_sku.models.len(), 1);
+        assert_eq!(by_sku.models[0].name, "Copilot Premium Request");
+        assert_eq!(by_sku.models[0].net_used, 30.0);
+        assert_eq!(ExportDocument::new(&loaded), ExportDocument::new(&original));
+    }
+
+    #[test]
+    fn test_reads_exports_without_version() {
+        // Formato anterior a `version` y `limit`
+        let json = r#"{
+            "username": "testuser",
+            "reset_date": "2026-03-01",
+            "models": [
+                {"model": "gpt-4.1", "requests": 30.0, "percentage": 2.0, "estimated_cost": 0.0}
+            ],
+            "total": {"model": "Total", "requests": 30.0, "percentage": 2.0, "estimated_cost": 0.0}
+        }"#;
+        let stats = from_json(json).unwrap();
+        assert_eq!(stats.total_limit, 1500.0);
+        assert_eq!(stats.models[0].limit, 1500.0);
+        assert!(stats.skus.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_newer_or_foreign_json() {
+        let mut document = ExportDocument::new(&stats(&["gpt-4.1"]));
+        document.version = EXPORT_VERSION + 1;
+        let error = from_json(&serde_json::to_string(&document).unwrap()).unwrap_err();
+        assert!(error.to_string().contains("newer"));
+
+        assert!(from_json(r#"{"timePeriod": {"year": 2026}}"#).is_err());
+    }
+
+    #[test]
+    fn test_recent_export_age() {
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        assert!(is_recent(now - day, now));
+        assert!(!is_recent(now - 8 * day, now));
+        assert!(is_recent(now + day, now));
+    }
+}
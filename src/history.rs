@@ -0,0 +1,439 @@
+use crate::api::calculate_stats_at;
+use crate::cache::profile_file;
+use crate::models::{DEFAULT_PREMIUM_REQUEST_LIMIT, UsageData};
+use crate::period::BillingPeriod;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub mod periods;
+
+use periods::PeriodStore;
+
+/// Default number of days kept in the history file
+pub const DEFAULT_RETENTION_DAYS: u64 = 90;
+
+/// Usage totals captured at a point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub timestamp: DateTime<Utc>,
+    pub year: i32,
+    pub month: Option<u32>,
+    pub total_used: f64,
+    pub models: BTreeMap<String, f64>,
+}
+
+impl Snapshot {
+    fn from_usage(data: &UsageData, timestamp: DateTime<Utc>) -> Self {
+        // Only the totals are stored, so the limit doesn't matter here
+        let stats = calculate_stats_at(data, DEFAULT_PREMIUM_REQUEST_LIMIT, timestamp);
+        Self {
+            timestamp,
+            year: data.time_period.year,
+            month: data.time_period.month,
+            total_used: stats.total_used,
+            models: stats.models.into_iter().map(|m| (m.name, m.used)).collect(),
+        }
+    }
+
+    fn same_numbers(&self, other: &Snapshot) -> bool {
+        self.year == other.year
+            && self.month == other.month
+            && self.total_used == other.total_used
+            && self.models == other.models
+    }
+
+    fn same_period(&self, other: &Snapshot) -> bool {
+        self.year == other.year && self.month == other.month
+    }
+}
+
+/// Usage aggregated for a single day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyUsage {
+    pub date: NaiveDate,
+    /// Requests made during this day
+    pub requests: f64,
+    /// Month-to-date total at the end of this day
+    pub total_used: f64,
+}
+
+/// History file from before profiles existed; it belongs to the default one
+const LEGACY_HISTORY_FILE: &str = "history.jsonl";
+
+/// Append-only JSONL store of usage snapshots under the cache directory,
+/// one per profile like the usage cache
+pub struct History {
+    path: PathBuf,
+    retention_days: u64,
+    /// Month totals kept beyond the retention window
+    periods: Option<PeriodStore>,
+}
+
+impl History {
+    pub fn new(profile: &str, retention_days: u64) -> Result<Self> {
+        let proj_dirs = ProjectDirs::from("com", "copilot-usage", "copilot-usage")
+            .context("Failed to determine cache directory")?;
+
+        let cache_dir = proj_dirs.cache_dir();
+        fs::create_dir_all(cache_dir)?;
+
+        Ok(Self {
+            path: profile_file(cache_dir, LEGACY_HISTORY_FILE, profile),
+            retention_days,
+            periods: Some(PeriodStore::new(profile)?),
+        })
+    }
+
+    /// Keeps `days` of snapshots instead
+    pub fn with_retention(mut self, days: u64) -> Self {
+        self.retention_days = days;
+        self
+    }
+
+    /// Create a History with a custom path (for testing)
+    #[cfg(test)]
+    pub fn with_path(path: PathBuf, retention_days: u64) -> Self {
+        Self {
+            path,
+            retention_days,
+            periods: None,
+        }
+    }
+
+    /// Appends a snapshot of `data` unless the numbers match the last one.
+    /// Returns whether a snapshot was written.
+    pub fn record(&self, data: &UsageData) -> Result<bool> {
+        self.record_at(data, Utc::now())
+    }
+
+    fn record_at(&self, data: &UsageData, now: DateTime<Utc>) -> Result<bool> {
+        let snapshot = Snapshot::from_usage(data, now);
+
+        // Best-effort like the history itself: a failed write only loses
+        // the month comparison
+        if let (Some(store), Some(month)) = (&self.periods, snapshot.month) {
+            let _ = store.record(snapshot.year, month, snapshot.total_used, now);
+        }
+
+        let mut snapshots = self.load()?;
+
+        if snapshots
+            .last()
+            .is_some_and(|last| last.same_numbers(&snapshot))
+        {
+            return Ok(false);
+        }
+
+        let cutoff = now - Duration::days(self.retention_days as i64);
+        let before = snapshots.len();
+        snapshots.retain(|s| s.timestamp >= cutoff);
+
+        if snapshots.len() != before {
+            // Old entries were pruned: rewrite the whole file
+            snapshots.push(snapshot);
+            self.write_all(&snapshots)?;
+        } else {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            writeln!(file, "{}", serde_json::to_string(&snapshot)?)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Loads every snapshot, skipping lines that fail to parse
+    pub fn load(&self) -> Result<Vec<Snapshot>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)?;
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Deletes the snapshots; month totals kept past the retention stay
+    pub fn clear(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// Month totals, oldest first (empty when they aren't tracked)
+    pub fn load_periods(&self) -> Result<Vec<periods::PeriodSummary>> {
+        match &self.periods {
+            Some(store) => store.load(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns one entry per day with data between `from` and `to` (inclusive)
+    pub fn load_range(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyUsage>> {
+        Ok(daily_aggregates(&self.load()?, from, to))
+    }
+
+    /// The days so far of the billing period (resetting on `billing_day`)
+    /// that `now` falls in, with the date it started on
+    pub fn load_current_period(
+        &self,
+        billing_day: u32,
+        now: DateTime<Utc>,
+    ) -> Result<(NaiveDate, Vec<DailyUsage>)> {
+        let start = BillingPeriod::containing(now, billing_day)
+            .start
+            .date_naive();
+        Ok((start, self.load_range(start, now.date_naive())?))
+    }
+
+    fn write_all(&self, snapshots: &[Snapshot]) -> Result<()> {
+        let mut content = String::new();
+        for snapshot in snapshots {
+            content.push_str(&serde_json::to_string(snapshot)?);
+            content.push('\n');
+        }
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+/// Collapses snapshots into per-day figures using the last snapshot of each day.
+/// Totals are month-to-date, so a day's requests are the difference with the
+/// previous day unless the billing period changed in between.
+fn daily_aggregates(snapshots: &[Snapshot], from: NaiveDate, to: NaiveDate) -> Vec<DailyUsage> {
+    let mut last_per_day: BTreeMap<NaiveDate, &Snapshot> = BTreeMap::new();
+    for snapshot in snapshots {
+        let date = snapshot.timestamp.date_naive();
+        match last_per_day.get(&date) {
+            Some(existing) if existing.timestamp > snapshot.timestamp => {}
+            _ => {
+                last_per_day.insert(date, snapshot);
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut previous: Option<&Snapshot> = None;
+
+    for (date, snapshot) in last_per_day {
+        if date >= from && date <= to {
+            let requests = match previous {
+                Some(prev)
+                    if prev.same_period(snapshot) && snapshot.total_used >= prev.total_used =>
+                {
+                    snapshot.total_used - prev.total_used
+                }
+                _ => snapshot.total_used,
+            };
+            result.push(DailyUsage {
+                date,
+                requests,
+                total_used: snapshot.total_used,
+            });
+        }
+        previous = Some(snapshot);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_PROFILE;
+    use crate::models::{TimePeriod, UsageItem};
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn usage(month: u32, quantities: &[(&str, f64)]) -> UsageData {
+        UsageData {
+            time_period: TimePeriod {
+                year: 2026,
+                month: Some(month),
+                day: None,
+            },
+            user: "testuser".to_string(),
+            usage_items: quantities
+                .iter()
+                .map(|(model, qty)| UsageItem {
+                    product: "copilot".to_string(),
+                    sku: "premium".to_string(),
+                    model: model.to_string(),
+                    unit_type: "request".to_string(),
+                    price_per_unit: 0.04,
+                    gross_quantity: *qty,
+                    gross_amount: qty * 0.04,
+                    discount_quantity: *qty,
+                    discount_amount: qty * 0.04,
+                    net_quantity: 0.0,
+                    net_amount: 0.0,
+                    date: None,
+                })
+                .collect(),
+            note: None,
+        }
+    }
+
+    fn at(month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, month, day, hour, 0, 0).unwrap()
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_record_skips_unchanged_numbers() {
+        let dir = TempDir::new().unwrap();
+        let history = History::with_path(dir.path().join("history.jsonl"), 90);
+
+        let data = usage(2, &[("gpt-4.1", 10.0)]);
+        assert!(history.record_at(&data, at(2, 1, 9)).unwrap());
+        assert!(!history.record_at(&data, at(2, 1, 10)).unwrap());
+        assert!(
+            history
+                .record_at(&usage(2, &[("gpt-4.1", 12.0)]), at(2, 1, 11))
+                .unwrap()
+        );
+
+        let snapshots = history.load().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].models["gpt-4.1"], 12.0);
+    }
+
+    #[test]
+    fn test_profiles_keep_separate_history() {
+        let dir = TempDir::new().unwrap();
+        let legacy = dir.path().join(LEGACY_HISTORY_FILE);
+        History::with_path(legacy.clone(), 90)
+            .record_at(&usage(2, &[("gpt-4.1", 10.0)]), at(2, 1, 9))
+            .unwrap();
+
+        // El historial compartido de antes pasa al perfil por defecto
+        let work = History::with_path(profile_file(dir.path(), LEGACY_HISTORY_FILE, "work"), 90);
+        assert!(work.load().unwrap().is_empty());
+        let default = History::with_path(
+            profile_file(dir.path(), LEGACY_HISTORY_FILE, DEFAULT_PROFILE),
+            90,
+        );
+        assert_eq!(default.load().unwrap().len(), 1);
+        assert!(!legacy.exists());
+
+        work.record_at(&usage(2, &[("o3", 3.0)]), at(2, 1, 10))
+            .unwrap();
+        assert_eq!(default.load().unwrap().len(), 1);
+        assert!(dir.path().join("history-work.jsonl").exists());
+    }
+
+    #[test]
+    fn test_load_range_daily_deltas() {
+        let dir = TempDir::new().unwrap();
+        let history = History::with_path(dir.path().join("history.jsonl"), 90);
+
+        history
+            .record_at(&usage(2, &[("a", 3.0)]), at(2, 1, 9))
+            .unwrap();
+        history
+            .record_at(&usage(2, &[("a", 5.0)]), at(2, 1, 18))
+            .unwrap();
+        history
+            .record_at(&usage(2, &[("a", 5.0), ("b", 4.0)]), at(2, 3, 12))
+            .unwrap();
+
+        let days = history.load_range(date(2, 1), date(2, 28)).unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, date(2, 1));
+        assert_eq!(days[0].requests, 5.0);
+        assert_eq!(days[1].date, date(2, 3));
+        assert_eq!(days[1].requests, 4.0);
+        assert_eq!(days[1].total_used, 9.0);
+
+        // Range start after the first day still diffs against the earlier snapshot
+        let days = history.load_range(date(2, 2), date(2, 28)).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].requests, 4.0);
+    }
+
+    #[test]
+    fn test_load_range_period_rollover() {
+        let dir = TempDir::new().unwrap();
+        let history = History::with_path(dir.path().join("history.jsonl"), 90);
+
+        history
+            .record_at(&usage(1, &[("a", 250.0)]), at(1, 31, 20))
+            .unwrap();
+        history
+            .record_at(&usage(2, &[("a", 7.0)]), at(2, 1, 9))
+            .unwrap();
+
+        let days = history.load_range(date(2, 1), date(2, 1)).unwrap();
+        assert_eq!(days[0].requests, 7.0);
+    }
+
+    #[test]
+    fn test_load_current_period_starts_on_the_billing_day() {
+        let dir = TempDir::new().unwrap();
+        let history = History::with_path(dir.path().join("history.jsonl"), 90);
+
+        history
+            .record_at(&usage(2, &[("a", 3.0)]), at(2, 10, 9))
+            .unwrap();
+        history
+            .record_at(&usage(2, &[("a", 8.0)]), at(2, 20, 9))
+            .unwrap();
+
+        let (start, days) = history.load_current_period(15, at(2, 25, 12)).unwrap();
+        assert_eq!(start, date(2, 15));
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].date, date(2, 20));
+
+        let (start, days) = history.load_current_period(1, at(2, 25, 12)).unwrap();
+        assert_eq!(start, date(2, 1));
+        assert_eq!(days.len(), 2);
+    }
+
+    #[test]
+    fn test_record_prunes_old_entries() {
+        let dir = TempDir::new().unwrap();
+        let history = History::with_path(dir.path().join("history.jsonl"), 10);
+
+        history
+            .record_at(&usage(1, &[("a", 1.0)]), at(1, 1, 9))
+            .unwrap();
+        history
+            .record_at(&usage(1, &[("a", 2.0)]), at(1, 20, 9))
+            .unwrap();
+        history
+            .record_at(&usage(1, &[("a", 3.0)]), at(1, 25, 9))
+            .unwrap();
+
+        let snapshots = history.load().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].total_used, 2.0);
+    }
+
+    #[test]
+    fn test_load_ignores_garbage_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let history = History::with_path(path.clone(), 90);
+
+        history
+            .record_at(&usage(2, &[("a", 1.0)]), at(2, 1, 9))
+            .unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{{not json").unwrap();
+
+        assert_eq!(history.load().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,343 @@
+//! Per-month usage totals, kept longer than the snapshot history so months
+//! can be compared with each other
+
+use crate::cache::profile_file;
+use crate::percent::{format_percent, round_half_up};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// How many periods are kept in the store (the TUI shows the last six)
+pub const KEEP_PERIODS: usize = 12;
+
+/// Months shown in the TUI mini chart
+pub const CHART_PERIODS: usize = 6;
+
+/// Store from before profiles existed; it belongs to the default one
+const LEGACY_PERIODS_FILE: &str = "periods.json";
+
+/// Last known total of a billing period (a calendar month)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodSummary {
+    pub year: i32,
+    pub month: u32,
+    pub total_used: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PeriodSummary {
+    fn key(&self) -> (i32, u32) {
+        (self.year, self.month)
+    }
+
+    /// "October"
+    pub fn month_name(&self) -> String {
+        month_name(self.year, self.month)
+    }
+
+    /// Share of `limit` used, rounded like the bar
+    pub fn percent_of(&self, limit: f64) -> String {
+        format_percent(self.total_used / limit * 100.0, 0)
+    }
+}
+
+/// "October" for `(year, month)`
+pub fn month_name(year: i32, month: u32) -> String {
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .map(|date| date.format("%B").to_string())
+        .unwrap_or_else(|| format!("{}-{:02}", year, month))
+}
+
+/// Month before `(year, month)`
+pub fn previous_month((year, month): (i32, u32)) -> (i32, u32) {
+    if month <= 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    }
+}
+
+/// Year and month of `now`
+pub fn current_period(now: DateTime<Utc>) -> (i32, u32) {
+    (now.year(), now.month())
+}
+
+/// Inserts or replaces the summary of its period, keeping the list sorted
+pub fn upsert(periods: &mut Vec<PeriodSummary>, summary: PeriodSummary) {
+    match periods.binary_search_by_key(&summary.key(), PeriodSummary::key) {
+        Ok(i) => periods[i] = summary,
+        Err(i) => periods.insert(i, summary),
+    }
+}
+
+/// Keeps the `KEEP_PERIODS` most recent periods. Months without data
+/// don't count, so gaps never push recorded months out early.
+pub fn prune(periods: &mut Vec<PeriodSummary>) {
+    let excess = periods.len().saturating_sub(KEEP_PERIODS);
+    periods.drain(..excess);
+}
+
+/// Totals for the `count` calendar months ending at `current` (oldest
+/// first), `None` for months the tool wasn't run in
+pub fn last_months(
+    periods: &[PeriodSummary],
+    current: (i32, u32),
+    count: usize,
+) -> Vec<Option<f64>> {
+    let mut months = Vec::with_capacity(count);
+    let mut key = current;
+    for _ in 0..count {
+        months.push(
+            periods
+                .iter()
+                .find(|p| p.key() == key)
+                .map(|p| p.total_used),
+        );
+        key = previous_month(key);
+    }
+    months.reverse();
+    months
+}
+
+/// "October: 281 requests (94%), September: 243 (81%), trending +16%".
+/// Compares the two most recent completed periods before `current`; with a
+/// gap in between the older one is simply the last month on record.
+pub fn trend_sentence(
+    periods: &[PeriodSummary],
+    current: (i32, u32),
+    limit: f64,
+) -> Option<String> {
+    let mut completed = periods.iter().rev().filter(|p| p.key() < current);
+    let latest = completed.next()?;
+    let before = completed.next()?;
+
+    let mut sentence = format!(
+        "{}: {:.0} requests ({}%), {}: {:.0} ({}%)",
+        latest.month_name(),
+        latest.total_used,
+        latest.percent_of(limit),
+        before.month_name(),
+        before.total_used,
+        before.percent_of(limit)
+    );
+    if before.total_used > 0.0 {
+        let change = (latest.total_used - before.total_used) / before.total_used * 100.0;
+        // `{:+.0}` redondea 12.5 a "+12"; aquí, como la barra, "+13"
+        sentence.push_str(&format!(", trending {:+}%", round_half_up(change, 0)));
+    }
+    Some(sentence)
+}
+
+/// JSON file with the period summaries under the data directory
+pub struct PeriodStore {
+    path: PathBuf,
+}
+
+impl PeriodStore {
+    /// The profile's month totals
+    pub fn new(profile: &str) -> Result<Self> {
+        let proj_dirs = ProjectDirs::from("com", "copilot-usage", "copilot-usage")
+            .context("Failed to determine data directory")?;
+
+        let data_dir = proj_dirs.data_dir();
+        fs::create_dir_all(data_dir)?;
+
+        Ok(Self {
+            path: profile_file(data_dir, LEGACY_PERIODS_FILE, profile),
+        })
+    }
+
+    /// Create a PeriodStore with a custom path (for testing)
+    #[cfg(test)]
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Loads the stored periods, oldest first
+    pub fn load(&self) -> Result<Vec<PeriodSummary>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)?;
+        let mut periods: Vec<PeriodSummary> =
+            serde_json::from_str(&content).context("Failed to parse period summaries")?;
+        periods.sort_by_key(PeriodSummary::key);
+        Ok(periods)
+    }
+
+    /// Stores the latest total of a period and prunes old ones
+    pub fn record(&self, year: i32, month: u32, total_used: f64, now: DateTime<Utc>) -> Result<()> {
+        // A corrupted file is replaced rather than blocking new summaries
+        let mut periods = self.load().unwrap_or_default();
+        upsert(
+            &mut periods,
+            PeriodSummary {
+                year,
+                month,
+                total_used,
+                updated_at: now,
+            },
+        );
+        prune(&mut periods);
+        fs::write(&self.path, serde_json::to_string_pretty(&periods)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn summary(year: i32, month: u32, total_used: f64) -> PeriodSummary {
+        PeriodSummary {
+            year,
+            month,
+            total_used,
+            updated_at: Utc.with_ymd_and_hms(year, month, 28, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_upsert_replaces_same_period_and_sorts() {
+        let mut periods = Vec::new();
+        upsert(&mut periods, summary(2026, 3, 10.0));
+        upsert(&mut periods, summary(2025, 12, 50.0));
+        upsert(&mut periods, summary(2026, 3, 40.0));
+
+        let keys: Vec<_> = periods.iter().map(|p| (p.key(), p.total_used)).collect();
+        assert_eq!(keys, vec![((2025, 12), 50.0), ((2026, 3), 40.0)]);
+    }
+
+    #[test]
+    fn test_prune_keeps_most_recent_periods() {
+        let mut periods = Vec::new();
+        for i in 0..20 {
+            let (year, month) = (2024 + i / 12, (i % 12) as u32 + 1);
+            upsert(&mut periods, summary(year, month, i as f64));
+        }
+        prune(&mut periods);
+
+        assert_eq!(periods.len(), KEEP_PERIODS);
+        assert_eq!(periods.first().unwrap().key(), (2024, 9));
+        assert_eq!(periods.last().unwrap().key(), (2025, 8));
+    }
+
+    #[test]
+    fn test_last_months_with_gaps_and_year_rollover() {
+        let periods = vec![
+            summary(2025, 9, 243.0),
+            summary(2025, 10, 281.0),
+            // November and December: not run
+            summary(2026, 1, 120.0),
+            summary(2026, 2, 30.0),
+        ];
+
+        assert_eq!(
+            last_months(&periods, (2026, 2), CHART_PERIODS),
+            vec![
+                Some(243.0),
+                Some(281.0),
+                None,
+                None,
+                Some(120.0),
+                Some(30.0)
+            ]
+        );
+        assert_eq!(last_months(&[], (2026, 1), 2), vec![None, None]);
+    }
+
+    #[test]
+    fn test_trend_sentence_uses_completed_periods() {
+        let periods = vec![
+            summary(2025, 9, 243.0),
+            summary(2025, 10, 281.0),
+            summary(2025, 11, 2.0),
+        ];
+
+        assert_eq!(
+            trend_sentence(&periods, (2025, 11), 300.0).unwrap(),
+            "October: 281 requests (94%), September: 243 (81%), trending +16%"
+        );
+    }
+
+    #[test]
+    fn test_trend_sentence_across_gap() {
+        let periods = vec![summary(2025, 6, 100.0), summary(2025, 10, 80.0)];
+        assert_eq!(
+            trend_sentence(&periods, (2025, 11), 300.0).unwrap(),
+            "October: 80 requests (27%), June: 100 (33%), trending -20%"
+        );
+    }
+
+    #[test]
+    fn test_trend_sentence_rounds_like_the_bar() {
+        // 43.5 / 300 sale 14.4999…: la barra lo muestra como 15%
+        let periods = vec![summary(2025, 9, 43.5), summary(2025, 10, 43.5)];
+        assert!(
+            trend_sentence(&periods, (2025, 11), 300.0)
+                .unwrap()
+                .starts_with("October: 44 requests (15%)")
+        );
+    }
+
+    #[test]
+    fn test_trend_sentence_rounds_halves_away_from_zero() {
+        let up = vec![summary(2025, 9, 200.0), summary(2025, 10, 225.0)];
+        assert!(
+            trend_sentence(&up, (2025, 11), 300.0)
+                .unwrap()
+                .ends_with("trending +13%")
+        );
+        let down = vec![summary(2025, 9, 200.0), summary(2025, 10, 175.0)];
+        assert!(
+            trend_sentence(&down, (2025, 11), 300.0)
+                .unwrap()
+                .ends_with("trending -13%")
+        );
+        let flat = vec![summary(2025, 9, 200.0), summary(2025, 10, 200.0)];
+        assert!(
+            trend_sentence(&flat, (2025, 11), 300.0)
+                .unwrap()
+                .ends_with("trending +0%")
+        );
+        // La columna de `history --periods` redondea igual
+        assert_eq!(summary(2025, 10, 12.5).percent_of(100.0), "13");
+    }
+
+    #[test]
+    fn test_trend_sentence_needs_two_periods() {
+        assert!(trend_sentence(&[summary(2025, 10, 80.0)], (2025, 11), 300.0).is_none());
+
+        let zero_before = vec![summary(2025, 9, 0.0), summary(2025, 10, 80.0)];
+        assert_eq!(
+            trend_sentence(&zero_before, (2025, 11), 300.0).unwrap(),
+            "October: 80 requests (27%), September: 0 (0%)"
+        );
+    }
+
+    #[test]
+    fn test_store_round_trip_and_recovers_from_corruption() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("periods.json");
+        let store = PeriodStore::with_path(path.clone());
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 0, 0, 0).unwrap();
+
+        store.record(2026, 2, 12.0, now).unwrap();
+        store.record(2026, 1, 200.0, now).unwrap();
+        store.record(2026, 2, 15.0, now).unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].total_used, 15.0);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(store.load().is_err());
+        store.record(2026, 2, 20.0, now).unwrap();
+        assert_eq!(store.load().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,850 @@
+//! Catálogo de textos visibles de la interfaz.
+//!
+//! English is compiled in as the reference catalog; other languages only
+//! translate its keys and anything they miss falls back to English. The
+//! language is chosen once at startup from the `language` config key or the
+//! locale environment (`LC_ALL`, `LC_MESSAGES`, `LANG`).
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    /// Code used in the `language` config key
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+        }
+    }
+
+    /// Accepts a bare code or a locale name such as `es_ES.UTF-8`
+    pub fn parse(s: &str) -> Option<Language> {
+        let code = s
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        Language::ALL.into_iter().find(|lang| lang.code() == code)
+    }
+
+    /// The configured language wins; otherwise the first locale variable
+    /// that is set decides, and anything unknown (`C`, `POSIX`…) is English
+    pub fn detect(configured: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Language {
+        if let Some(lang) = configured.and_then(Language::parse) {
+            return lang;
+        }
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(env)
+            .find(|value| !value.is_empty())
+            .and_then(|value| Language::parse(&value))
+            .unwrap_or_default()
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => EN,
+            Language::Spanish => ES,
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(lang: Language) {
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Language::Spanish,
+        _ => Language::English,
+    }
+}
+
+/// Text for `key` in the current language
+pub fn t(key: &str) -> &'static str {
+    translate(language(), key)
+}
+
+/// Like [`t`], replacing each `{}` with the next argument
+pub fn tf(key: &str, args: &[&dyn Display]) -> String {
+    fill(t(key), args)
+}
+
+fn translate(lang: Language, key: &str) -> &'static str {
+    resolve(lang.catalog(), key)
+}
+
+/// Looks `key` up in `catalog`, falling back to English
+fn resolve(catalog: &'static [(&'static str, &'static str)], key: &str) -> &'static str {
+    lookup(catalog, key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or_else(|| {
+            debug_assert!(false, "missing catalog key {:?}", key);
+            ""
+        })
+}
+
+fn lookup(catalog: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, text)| *text)
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+static EN: &[(&str, &str)] = &[
+    // Menú de comandos
+    ("menu.title", " Commands "),
+    ("menu.refresh", "Refresh Data"),
+    ("menu.daily", "Daily Usage"),
+    ("menu.compare", "Compare Months"),
+    ("menu.theme", "Change Theme"),
+    ("menu.reconfigure", "Reconfigure"),
+    ("menu.profile", "Switch Profile"),
+    ("menu.cache", "Cache Status"),
+    ("menu.help", "Help"),
+    ("menu.about", "About"),
+    ("menu.quit", "Quit"),
+    ("menu.section.data", "Data"),
+    ("menu.section.appearance", "Appearance"),
+    ("menu.section.system", "System"),
+    // Barra de ayuda
+    (
+        "helpbar.dashboard",
+        ": Menu • r: Refresh • d: Daily • t: Theme • p: Profile • h: Help • q: Quit",
+    ),
+    (
+        "helpbar.dashboard_models",
+        ": Menu • /: Filter • r: Refresh • d: Daily • t: Theme • p: Profile • ↑↓ Enter: Details • h: Help • q: Quit",
+    ),
+    (
+        "helpbar.menu",
+        "↑↓/jk: Navigate • Enter: Select • Esc: Close • Letter: Quick jump",
+    ),
+    (
+        "helpbar.selector",
+        "↑↓/jk: Navigate • Enter: Select • Esc: Cancel",
+    ),
+    ("helpbar.any_key", "Any key: Close"),
+    ("helpbar.back", "Esc: Back"),
+    ("helpbar.daily", "↑↓/jk: Scroll • Esc: Back"),
+    (
+        "helpbar.filter",
+        "Type to filter models • ↑↓: Select • Enter: Keep • Esc: Clear",
+    ),
+    ("helpbar.loading", "r: Queue another refresh • Esc: Hide"),
+    ("helpbar.confirm", "y: Yes • n: No • Esc: Cancel"),
+    (
+        "helpbar.settings",
+        "Tab/↑↓: Field • Enter: Save • Esc: Discard",
+    ),
+    ("too_small", "Terminal too small (need {}x{}, have {}x{})"),
+    // Avisos y sesión
+    (
+        "notice.disabled",
+        "{} is disabled in config ([menu] disabled)",
+    ),
+    ("notice.read_only", "{} is not available in read-only mode"),
+    (
+        "notice.refresh_failed",
+        "Showing cached data, refresh failed: {}",
+    ),
+    (
+        "notice.refresh_conflict",
+        "Cache TTL and Waybar interval disagree, run copilot-usage doctor",
+    ),
+    ("notice.inconsistent", "Warning: {}"),
+    ("toast.refreshed", "Data updated"),
+    ("toast.copied", "Error details copied to the clipboard"),
+    (
+        "toast.copied_terminal",
+        "Error details sent to the terminal's clipboard (OSC 52)",
+    ),
+    ("toast.copy_failed", "No clipboard available: {}"),
+    ("toast.theme_saved", "Theme saved"),
+    ("toast.theme_save_failed", "Could not save the theme: {}"),
+    (
+        "toast.cache_refetched",
+        "Corrupted cache moved aside and fetched again",
+    ),
+    (
+        "toast.cache_refetch_failed",
+        "Corrupted cache moved aside, fetching it again failed: {}",
+    ),
+    ("session.summary", "open for {}, data refreshed {}×"),
+    ("status.data", "Data: {} ({})"),
+    ("status.just_now", "just now"),
+    ("status.ago", "{} ago"),
+    ("status.api", "API"),
+    ("status.cache", "cache"),
+    ("status.refresh_failed", "Refresh failed"),
+    ("session.last_at", ", last at {}"),
+    ("shutdown.saving", "saving…"),
+    (
+        "shutdown.gave_up",
+        "Gave up waiting for background work; the cache will be refreshed next time.",
+    ),
+    // Diálogos
+    ("confirm.refresh", "Refresh data from API?"),
+    ("confirm.refresh_detail", "Fetches fresh data, skipping the cache"),
+    ("confirm.reconfigure", "Reconfigure settings?"),
+    ("confirm.reconfigure_detail", "Current config will be reset"),
+    // Ajustes
+    ("settings.title", " Settings "),
+    ("settings.token", "GitHub token"),
+    ("settings.token_unchanged", "(unchanged)"),
+    ("settings.theme", "Theme"),
+    ("settings.cache_ttl", "Cache TTL (min)"),
+    ("settings.waybar_format", "Waybar format"),
+    ("settings.saving", "Saving…"),
+    ("settings.saved", "Settings saved"),
+    (
+        "settings.token_overridden",
+        "Settings saved, but {} is set and its token is used instead",
+    ),
+    ("settings.invalid_theme", "Unknown theme '{}'"),
+    (
+        "settings.invalid_ttl",
+        "Cache TTL must be a whole number of minutes, not '{}'",
+    ),
+    ("settings.empty_format", "Waybar format can't be empty"),
+    ("confirm.yes", ": Yes  "),
+    ("confirm.no", ": No"),
+    ("loading.title", " Loading "),
+    ("loading.refresh", "Refreshing data from API..."),
+    (
+        "loading.refresh_queued",
+        "Refreshing data from API...\nanother refresh queued",
+    ),
+    ("loading.cache", "Loading cache info..."),
+    ("loading.hint", "Press Esc to cancel"),
+    ("dialog.close_hint", "Press any key to close"),
+    ("error.title", " Error "),
+    ("error.title_debug", " Error (Debug) "),
+    (
+        "error.hint",
+        "Press 'd' for details, 'y' to copy them, any other key to close",
+    ),
+    (
+        "error.hint_debug",
+        "Press 'd' to hide details, 'y' to copy them, any other key to close",
+    ),
+    (
+        "error.hint_export",
+        "Press 'o' to open your last export, 'd' for details, 'y' to copy, any other key to close",
+    ),
+    ("theme.title", " Select Theme "),
+    ("profile.title", " Switch Profile "),
+    ("profile.empty", " No profiles configured"),
+    ("about.title", " About "),
+    ("about.open_for", "Open for: {}"),
+    ("about.refreshed", "Data refreshed: {}×"),
+    ("about.last_refresh", "Last refresh: {}"),
+    ("about.no_refresh", "Last refresh: none this session"),
+    ("cache.title", " Cache Status "),
+    ("cache.fresh", "Status: Fresh"),
+    ("cache.expired", "Status: Expired"),
+    ("cache.updated", "Last updated: {}"),
+    ("cache.never", "Last updated: Never"),
+    ("cache.ttl", "TTL: {} minutes"),
+    ("cache.calls_reset", "API calls left: {} (resets {})"),
+    ("cache.calls", "API calls left: {}"),
+    ("cache.calls_unknown", "API calls left: unknown"),
+    ("cache.refetch_failed", "refetch failed: {}"),
+    ("cache.refetched", "data refetched"),
+    (
+        "cache.quarantined",
+        "Corrupted cache quarantined to {} ({})",
+    ),
+    // Ayuda
+    ("help.title", " Help "),
+    ("help.shortcuts", "Keyboard Shortcuts"),
+    ("help.global", "Global:"),
+    ("help.menu", "  :             Open command menu"),
+    ("help.filter", "  /             Filter models by name"),
+    (
+        "help.quantity",
+        "  b             Count gross / included / billed",
+    ),
+    (
+        "help.grouping",
+        "  v             Group the table by model / SKU",
+    ),
+    ("help.commands", "Command Menu:"),
+    ("help.in_menus", "In Menus:"),
+    ("help.navigate", "  ↑/↓ or j/k    Navigate"),
+    ("help.page", "  PgUp/PgDn     Scroll a page"),
+    ("help.select", "  Enter         Select item"),
+    ("help.close", "  Esc           Close/Cancel"),
+    ("help.jump", "  Letter        Quick jump to command"),
+    ("help.navigation", "Navigation:"),
+    ("help.about_1", "  The dashboard shows your Copilot usage"),
+    ("help.about_2", "  statistics in real-time."),
+    // Dashboard
+    ("header.title", "GitHub Copilot Usage"),
+    ("header.updated", "{} updated just now"),
+    ("header.read_only", "{} read-only: {}"),
+    (
+        "header.token_expiring",
+        "{} Token expires in {} day(s), on {}: run copilot-usage reconfigure",
+    ),
+    (
+        "header.token_expired",
+        "{} Token expired on {}: run copilot-usage reconfigure",
+    ),
+    ("daily.title", " {} Daily Usage (last {} days) "),
+    ("daily.loading", "loading…"),
+    ("compare.title", " {} This Month vs Last Month "),
+    ("compare.loading", "loading last month…"),
+    ("compare.change", "Change"),
+    ("compare.total", "Total"),
+    ("compare.more", "… and {} more"),
+    ("usage.title", " {} Overall Usage ({}): "),
+    ("usage.mode.gross", "gross"),
+    ("usage.mode.included", "included"),
+    ("usage.mode.billed", "billed"),
+    ("usage.requests", "Requests: "),
+    ("usage.budget", "Budget {}/{}"),
+    ("usage.cost_budget", "Budget: {} / {} ({})"),
+    ("usage.cost", "Overage cost: {}"),
+    ("usage.top", "Top: "),
+    ("usage.models_used", "{} models"),
+    ("usage.over_limit", " OVER LIMIT +{} "),
+    ("usage.month", "{} Month: "),
+    ("usage.elapsed", "{} elapsed"),
+    ("usage.resets_in", "{} Resets in {}"),
+    ("usage.runs_out", "Projected: runs out {}"),
+    ("usage.limit_reached", "Projected: limit reached"),
+    ("usage.on_pace", "On pace: ~{}/{} by reset"),
+    ("usage.pace_ahead", "{} over the even pace"),
+    ("usage.pace_behind", "{} under the even pace"),
+    ("usage.pace_even", "on the even pace"),
+    ("trend.title", " {} Daily Trend: "),
+    ("trend.title_short", " {} Trend: "),
+    ("trend.empty", "no daily history yet"),
+    (
+        "trend.summary",
+        "{} requests over {} days • today {} • peak {}",
+    ),
+    ("models.title", " {} Per-Model Usage: "),
+    (
+        "models.title_filtered",
+        " {} Per-Model Usage (filter: \"{}\") ",
+    ),
+    ("models.title_sku", " {} Per-SKU Usage: "),
+    (
+        "models.title_sku_filtered",
+        " {} Per-SKU Usage (filter: \"{}\") ",
+    ),
+    ("models.empty", "No model usage data available"),
+    ("models.no_match", "No models match the filter"),
+    ("models.model", "Model"),
+    ("models.sku", "SKU"),
+    ("models.progress", "Progress"),
+    ("models.usage", "Usage"),
+    ("models.count", "Count"),
+    ("models.cost", "Cost"),
+    ("models.footer", "Σ {} requests • {} models • {} over {}%"),
+    ("models.footer_sku", "Σ {} requests • {} SKUs • {} over {}%"),
+    ("models.footer_selected", "▸ {}: {}"),
+    ("detail.requests", "Requests"),
+    ("detail.included", "Included"),
+    ("detail.billed", "Billed"),
+    ("detail.price", "Price per unit"),
+    ("detail.gross_cost", "Gross cost"),
+    ("detail.cost", "Cost"),
+    ("detail.share", "Share of total"),
+    ("detail.reported", "Reported as"),
+    // Errores
+    (
+        "err.no_token",
+        "No GitHub token found. Set {} or run 'copilot-usage reconfigure'.",
+    ),
+    (
+        "err.profile_not_configured",
+        "Profile '{}' is not configured",
+    ),
+    (
+        "err.no_username",
+        "Could not determine username from token. Please reconfigure with a valid token.",
+    ),
+    (
+        "err.rate_limited_until",
+        "Rate limited (429), resets at {}.",
+    ),
+    (
+        "err.rate_limited",
+        "Rate limited (429). GitHub API limit reached.\nPlease wait a few minutes before trying again.",
+    ),
+    (
+        "err.unauthorized",
+        "Unauthorized (401). Your token is invalid or expired.\nPlease run 'copilot-usage reconfigure' to set a new token.",
+    ),
+    (
+        "err.token_expires_on",
+        "The token may have expired: its recorded expiry date is {}.",
+    ),
+    (
+        "err.forbidden",
+        "Access Forbidden (403). Your token lacks the 'Plan' permission.\nPlease ensure you have enabled 'Plan' -> Read-only in Account permissions.",
+    ),
+    (
+        "err.not_found",
+        "Not Found (404). This could mean:\n1. You don't have GitHub Copilot Pro on a personal plan\n2. Your Copilot is managed through an organization\n3. The billing API is not available for your account type",
+    ),
+    (
+        "err.server",
+        "GitHub Server Error ({}). GitHub's API is experiencing issues.\nPlease try again later. Response: {}",
+    ),
+    ("err.api", "GitHub API error ({}): {}"),
+];
+
+static ES: &[(&str, &str)] = &[
+    // Menú de comandos
+    ("menu.title", " Comandos "),
+    ("menu.refresh", "Actualizar datos"),
+    ("menu.daily", "Uso diario"),
+    ("menu.compare", "Comparar meses"),
+    ("menu.theme", "Cambiar tema"),
+    ("menu.reconfigure", "Reconfigurar"),
+    ("menu.profile", "Cambiar perfil"),
+    ("menu.cache", "Estado del caché"),
+    ("menu.help", "Ayuda"),
+    ("menu.about", "Acerca de"),
+    ("menu.quit", "Salir"),
+    ("menu.section.data", "Datos"),
+    ("menu.section.appearance", "Apariencia"),
+    ("menu.section.system", "Sistema"),
+    // Barra de ayuda
+    (
+        "helpbar.dashboard",
+        ": Menú • r: Actualizar • d: Diario • t: Tema • p: Perfil • h: Ayuda • q: Salir",
+    ),
+    (
+        "helpbar.dashboard_models",
+        ": Menú • /: Filtrar • r: Actualizar • d: Diario • t: Tema • p: Perfil • ↑↓ Enter: Detalle • h: Ayuda • q: Salir",
+    ),
+    (
+        "helpbar.menu",
+        "↑↓/jk: Navegar • Enter: Elegir • Esc: Cerrar • Letra: Salto rápido",
+    ),
+    (
+        "helpbar.selector",
+        "↑↓/jk: Navegar • Enter: Elegir • Esc: Cancelar",
+    ),
+    ("helpbar.any_key", "Cualquier tecla: Cerrar"),
+    ("helpbar.back", "Esc: Volver"),
+    ("helpbar.daily", "↑↓/jk: Desplazar • Esc: Volver"),
+    (
+        "helpbar.filter",
+        "Escribe para filtrar modelos • ↑↓: Elegir • Enter: Mantener • Esc: Borrar",
+    ),
+    (
+        "helpbar.loading",
+        "r: Encolar otra actualización • Esc: Ocultar",
+    ),
+    ("helpbar.confirm", "y: Sí • n: No • Esc: Cancelar"),
+    (
+        "helpbar.settings",
+        "Tab/↑↓: Campo • Enter: Guardar • Esc: Descartar",
+    ),
+    (
+        "too_small",
+        "Terminal demasiado pequeño (mínimo {}x{}, actual {}x{})",
+    ),
+    // Avisos y sesión
+    (
+        "notice.disabled",
+        "{} está desactivado en la configuración ([menu] disabled)",
+    ),
+    (
+        "notice.read_only",
+        "{} no está disponible en modo de solo lectura",
+    ),
+    (
+        "notice.refresh_failed",
+        "Mostrando datos en caché, falló la actualización: {}",
+    ),
+    (
+        "notice.refresh_conflict",
+        "El TTL del caché y el intervalo de Waybar no cuadran, ejecuta copilot-usage doctor",
+    ),
+    ("notice.inconsistent", "Aviso: {}"),
+    ("toast.refreshed", "Datos actualizados"),
+    (
+        "toast.copied",
+        "Detalles del error copiados al portapapeles",
+    ),
+    (
+        "toast.copied_terminal",
+        "Detalles del error enviados al portapapeles del terminal (OSC 52)",
+    ),
+    ("toast.copy_failed", "No hay portapapeles disponible: {}"),
+    ("toast.theme_saved", "Tema guardado"),
+    ("toast.theme_save_failed", "No se pudo guardar el tema: {}"),
+    (
+        "toast.cache_refetched",
+        "Caché corrupta apartada y descargada de nuevo",
+    ),
+    (
+        "toast.cache_refetch_failed",
+        "Caché corrupta apartada, falló la nueva descarga: {}",
+    ),
+    ("session.summary", "abierto hace {}, datos actualizados {}×"),
+    ("status.data", "Datos: {} ({})"),
+    ("status.just_now", "ahora mismo"),
+    ("status.ago", "hace {}"),
+    ("status.api", "API"),
+    ("status.cache", "caché"),
+    ("status.refresh_failed", "Falló la actualización"),
+    ("session.last_at", ", último a las {}"),
+    ("shutdown.saving", "guardando…"),
+    (
+        "shutdown.gave_up",
+        "Se dejó de esperar el trabajo en segundo plano; el caché se actualizará la próxima vez.",
+    ),
+    // Diálogos
+    ("confirm.refresh", "¿Actualizar datos desde la API?"),
+    ("confirm.refresh_detail", "Pide datos nuevos sin usar el caché"),
+    ("confirm.reconfigure", "¿Reconfigurar ajustes?"),
+    (
+        "confirm.reconfigure_detail",
+        "La configuración actual se restablecerá",
+    ),
+    // Ajustes
+    ("settings.title", " Ajustes "),
+    ("settings.token", "Token de GitHub"),
+    ("settings.token_unchanged", "(sin cambios)"),
+    ("settings.theme", "Tema"),
+    ("settings.cache_ttl", "TTL caché (min)"),
+    ("settings.waybar_format", "Formato Waybar"),
+    ("settings.saving", "Guardando…"),
+    ("settings.saved", "Ajustes guardados"),
+    (
+        "settings.token_overridden",
+        "Ajustes guardados, pero {} está definida y se usa su token",
+    ),
+    ("settings.invalid_theme", "Tema desconocido '{}'"),
+    (
+        "settings.invalid_ttl",
+        "El TTL del caché debe ser un número entero de minutos, no '{}'",
+    ),
+    (
+        "settings.empty_format",
+        "El formato de Waybar no puede estar vacío",
+    ),
+    ("confirm.yes", ": Sí  "),
+    ("confirm.no", ": No"),
+    ("loading.title", " Cargando "),
+    ("loading.refresh", "Actualizando datos desde la API..."),
+    (
+        "loading.refresh_queued",
+        "Actualizando datos desde la API...\notra actualización en cola",
+    ),
+    ("loading.cache", "Cargando información del caché..."),
+    ("loading.hint", "Pulsa Esc para cancelar"),
+    ("dialog.close_hint", "Pulsa cualquier tecla para cerrar"),
+    ("error.title", " Error "),
+    ("error.title_debug", " Error (depuración) "),
+    (
+        "error.hint",
+        "Pulsa 'd' para ver detalles, 'y' para copiarlos, cualquier otra tecla para cerrar",
+    ),
+    (
+        "error.hint_debug",
+        "Pulsa 'd' para ocultar detalles, 'y' para copiarlos, cualquier otra tecla para cerrar",
+    ),
+    (
+        "error.hint_export",
+        "Pulsa 'o' para abrir tu última exportación, 'd' para detalles, 'y' para copiar, otra tecla para cerrar",
+    ),
+    ("theme.title", " Elegir tema "),
+    ("profile.title", " Cambiar perfil "),
+    ("profile.empty", " No hay perfiles configurados"),
+    ("about.title", " Acerca de "),
+    ("about.open_for", "Abierto hace: {}"),
+    ("about.refreshed", "Datos actualizados: {}×"),
+    ("about.last_refresh", "Última actualización: {}"),
+    (
+        "about.no_refresh",
+        "Última actualización: ninguna en esta sesión",
+    ),
+    ("cache.title", " Estado del caché "),
+    ("cache.fresh", "Estado: Vigente"),
+    ("cache.expired", "Estado: Caducado"),
+    ("cache.updated", "Actualizado: {}"),
+    ("cache.never", "Actualizado: Nunca"),
+    ("cache.ttl", "TTL: {} minutos"),
+    (
+        "cache.calls_reset",
+        "Llamadas a la API restantes: {} (se reinician {})",
+    ),
+    ("cache.calls", "Llamadas a la API restantes: {}"),
+    (
+        "cache.calls_unknown",
+        "Llamadas a la API restantes: desconocido",
+    ),
+    ("cache.refetch_failed", "falló la nueva descarga: {}"),
+    ("cache.refetched", "datos descargados de nuevo"),
+    ("cache.quarantined", "Caché corrupto apartado en {} ({})"),
+    // Ayuda
+    ("help.title", " Ayuda "),
+    ("help.shortcuts", "Atajos de teclado"),
+    ("help.global", "Generales:"),
+    ("help.menu", "  :             Abrir el menú de comandos"),
+    ("help.filter", "  /             Filtrar modelos por nombre"),
+    (
+        "help.quantity",
+        "  b             Contar bruto / incluido / facturado",
+    ),
+    (
+        "help.grouping",
+        "  v             Agrupar la tabla por modelo / SKU",
+    ),
+    ("help.commands", "Menú de comandos:"),
+    ("help.in_menus", "En los menús:"),
+    ("help.navigate", "  ↑/↓ o j/k     Navegar"),
+    ("help.page", "  RePág/AvPág   Desplazar una página"),
+    ("help.select", "  Enter         Elegir elemento"),
+    ("help.close", "  Esc           Cerrar/Cancelar"),
+    ("help.jump", "  Letra         Saltar al comando"),
+    ("help.navigation", "Navegación:"),
+    ("help.about_1", "  El panel muestra las estadísticas de uso"),
+    ("help.about_2", "  de Copilot en tiempo real."),
+    // Dashboard
+    ("header.title", "Uso de GitHub Copilot"),
+    ("header.updated", "{} actualizado ahora"),
+    ("header.read_only", "{} solo lectura: {}"),
+    (
+        "header.token_expiring",
+        "{} El token caduca en {} día(s), el {}: ejecuta copilot-usage reconfigure",
+    ),
+    (
+        "header.token_expired",
+        "{} El token caducó el {}: ejecuta copilot-usage reconfigure",
+    ),
+    ("daily.title", " {} Uso diario (últimos {} días) "),
+    ("daily.loading", "cargando…"),
+    ("compare.title", " {} Este mes vs el anterior "),
+    ("compare.loading", "cargando el mes anterior…"),
+    ("compare.change", "Cambio"),
+    ("compare.total", "Total"),
+    ("compare.more", "… y {} más"),
+    ("usage.title", " {} Uso general ({}): "),
+    ("usage.mode.gross", "bruto"),
+    ("usage.mode.included", "incluido"),
+    ("usage.mode.billed", "facturado"),
+    ("usage.requests", "Solicitudes: "),
+    ("usage.budget", "Presupuesto {}/{}"),
+    ("usage.cost_budget", "Presupuesto: {} / {} ({})"),
+    ("usage.cost", "Coste por exceso: {}"),
+    ("usage.top", "Más usado: "),
+    ("usage.models_used", "{} modelos"),
+    ("usage.over_limit", " LÍMITE SUPERADO +{} "),
+    ("usage.month", "{} Mes: "),
+    ("usage.elapsed", "{} transcurrido"),
+    ("usage.resets_in", "{} Reinicio en {}"),
+    ("usage.runs_out", "Proyección: se agota el {}"),
+    ("usage.limit_reached", "Proyección: límite alcanzado"),
+    ("usage.on_pace", "A este ritmo: ~{}/{} al reinicio"),
+    ("usage.pace_ahead", "{} por encima del ritmo uniforme"),
+    ("usage.pace_behind", "{} por debajo del ritmo uniforme"),
+    ("usage.pace_even", "al ritmo uniforme"),
+    ("trend.title", " {} Tendencia diaria: "),
+    ("trend.title_short", " {} Tendencia: "),
+    ("trend.empty", "aún no hay historial diario"),
+    (
+        "trend.summary",
+        "{} solicitudes en {} días • hoy {} • pico {}",
+    ),
+    ("models.title", " {} Uso por modelo: "),
+    (
+        "models.title_filtered",
+        " {} Uso por modelo (filtro: \"{}\") ",
+    ),
+    ("models.title_sku", " {} Uso por SKU: "),
+    (
+        "models.title_sku_filtered",
+        " {} Uso por SKU (filtro: \"{}\") ",
+    ),
+    ("models.empty", "No hay datos de uso por modelo"),
+    ("models.no_match", "Ningún modelo coincide con el filtro"),
+    ("models.model", "Modelo"),
+    ("models.sku", "SKU"),
+    ("models.progress", "Progreso"),
+    ("models.usage", "Uso"),
+    ("models.count", "Cant."),
+    ("models.cost", "Coste"),
+    (
+        "models.footer",
+        "Σ {} solicitudes • {} modelos • {} por encima del {}%",
+    ),
+    (
+        "models.footer_sku",
+        "Σ {} solicitudes • {} SKUs • {} por encima del {}%",
+    ),
+    ("models.footer_selected", "▸ {}: {}"),
+    ("detail.requests", "Solicitudes"),
+    ("detail.included", "Incluidas"),
+    ("detail.billed", "Facturadas"),
+    ("detail.price", "Precio unitario"),
+    ("detail.gross_cost", "Coste bruto"),
+    ("detail.cost", "Coste"),
+    ("detail.share", "Parte del total"),
+    ("detail.reported", "Según GitHub"),
+    // Errores
+    (
+        "err.no_token",
+        "No se encontró un token de GitHub. Define {} o ejecuta 'copilot-usage reconfigure'.",
+    ),
+    (
+        "err.profile_not_configured",
+        "El perfil '{}' no está configurado",
+    ),
+    (
+        "err.no_username",
+        "No se pudo obtener el usuario a partir del token. Reconfigura con un token válido.",
+    ),
+    (
+        "err.rate_limited_until",
+        "Límite de peticiones alcanzado (429), se reinicia a las {}.",
+    ),
+    (
+        "err.rate_limited",
+        "Límite de peticiones alcanzado (429) en la API de GitHub.\nEspera unos minutos antes de volver a intentarlo.",
+    ),
+    (
+        "err.unauthorized",
+        "No autorizado (401). El token no es válido o ha caducado.\nEjecuta 'copilot-usage reconfigure' para configurar uno nuevo.",
+    ),
+    (
+        "err.token_expires_on",
+        "Puede que el token haya caducado: su fecha de caducidad registrada es {}.",
+    ),
+    (
+        "err.forbidden",
+        "Acceso denegado (403). Al token le falta el permiso 'Plan'.\nActiva 'Plan' -> Read-only en los permisos de la cuenta.",
+    ),
+    (
+        "err.not_found",
+        "No encontrado (404). Puede deberse a que:\n1. No tienes GitHub Copilot Pro en un plan personal\n2. Tu Copilot lo gestiona una organización\n3. La API de facturación no está disponible para tu tipo de cuenta",
+    ),
+    (
+        "err.server",
+        "Error del servidor de GitHub ({}). La API de GitHub tiene problemas.\nInténtalo más tarde. Respuesta: {}",
+    ),
+    ("err.api", "Error de la API de GitHub ({}): {}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn keys(catalog: &'static [(&'static str, &'static str)]) -> BTreeSet<&'static str> {
+        let keys: BTreeSet<&str> = catalog.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys.len(), catalog.len(), "duplicated keys");
+        keys
+    }
+
+    #[test]
+    fn test_catalogs_cover_same_keys() {
+        let english = keys(EN);
+        for lang in Language::ALL {
+            let catalog = lang.catalog();
+            assert_eq!(keys(catalog), english, "{:?} catalog", lang);
+            for (key, text) in catalog {
+                assert_eq!(
+                    text.matches("{}").count(),
+                    lookup(EN, key).unwrap().matches("{}").count(),
+                    "placeholders of {:?} in {:?}",
+                    key,
+                    lang
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_key_falls_back_to_english() {
+        static PARTIAL: &[(&str, &str)] = &[("menu.quit", "Salir")];
+        assert_eq!(resolve(PARTIAL, "menu.help"), "Help");
+        assert_eq!(resolve(PARTIAL, "menu.quit"), "Salir");
+        assert_eq!(translate(Language::Spanish, "menu.quit"), "Salir");
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        assert_eq!(
+            fill("{} requests over {} days", &[&12, &"3"]),
+            "12 requests over 3 days"
+        );
+        assert_eq!(fill("no args", &[]), "no args");
+        assert_eq!(fill("{} and {}", &[&1]), "1 and ");
+    }
+
+    #[test]
+    fn test_detect_language() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+
+        assert_eq!(Language::detect(None, env(&[])), Language::English);
+        assert_eq!(
+            Language::detect(None, env(&[("LANG", "es_ES.UTF-8")])),
+            Language::Spanish
+        );
+        assert_eq!(
+            Language::detect(None, env(&[("LANG", "es_AR.UTF-8"), ("LC_ALL", "C")])),
+            Language::English
+        );
+        // LC_ALL vacío no cuenta
+        assert_eq!(
+            Language::detect(None, env(&[("LC_ALL", ""), ("LANG", "es")])),
+            Language::Spanish
+        );
+        assert_eq!(
+            Language::detect(Some("en"), env(&[("LANG", "es_ES.UTF-8")])),
+            Language::English
+        );
+        assert_eq!(Language::detect(Some("es"), env(&[])), Language::Spanish);
+    }
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!(Language::parse("ES"), Some(Language::Spanish));
+        assert_eq!(Language::parse("en-US"), Some(Language::English));
+        assert_eq!(Language::parse("de_DE.UTF-8"), None);
+        assert_eq!(Language::parse("C"), None);
+    }
+}
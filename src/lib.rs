@@ -0,0 +1,87 @@
+//! GitHub Copilot premium request usage: the client, cache and stats behind
+//! the `copilot-usage` binary, for tools that want the numbers without
+//! shelling out to it.
+//!
+//! Fetch the stats of the default profile, reusing the cache while it's
+//! fresh:
+//!
+//! ```no_run
+//! use copilot_usage::percent::{format_percent, format_quantity};
+//! use copilot_usage::{ApiClient, Cache, CacheStatus, ConfigManager, config_stats};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let manager = ConfigManager::new()?;
+//! // Sin config (nunca se configuró) valen los valores por defecto
+//! let config = manager.load()?.unwrap_or_default();
+//! let cache = Cache::new(manager.profile(), config.cache_ttl_minutes)?;
+//!
+//! let data = match cache.status() {
+//!     CacheStatus::Fresh(data) => data,
+//!     _ => {
+//!         let token = manager.require_token(&config)?;
+//!         let client = ApiClient::for_config(token, &config)?;
+//!         let username = match &config.username {
+//!             Some(username) => username.clone(),
+//!             None => client.get_authenticated_user().await?,
+//!         };
+//!         let data = client.fetch_usage(&username).await?;
+//!         cache.set(&data, client.last_rate_limit())?;
+//!         data
+//!     }
+//! };
+//!
+//! let stats = config_stats(&data, &config);
+//! // Redondeado como lo muestra el binario (12.5% -> "13")
+//! println!(
+//!     "{}/{} premium requests ({}%)",
+//!     format_quantity(stats.total_used),
+//!     format_quantity(stats.total_limit),
+//!     format_percent(stats.percentage, 0)
+//! );
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Nothing on this path prompts or panics on a missing config or token:
+//! those come back as errors. The interactive setup lives in
+//! [`ConfigManager::setup_interactive`] and [`cli`].
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod anonymize;
+pub mod api;
+pub mod cache;
+pub mod check;
+pub mod cli;
+pub mod compare;
+pub mod completions;
+pub mod config;
+#[cfg(unix)]
+pub mod daemon;
+pub mod export;
+pub mod history;
+pub mod i18n;
+pub mod logging;
+pub mod metrics;
+pub mod model_names;
+pub mod models;
+pub mod output;
+pub mod ownership;
+pub mod percent;
+pub mod period;
+pub mod projection;
+pub mod redact;
+#[cfg(unix)]
+pub mod signals;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+pub mod themes;
+pub mod ui;
+pub mod usage_range;
+
+pub use api::{ApiClient, calculate_stats, calculate_stats_at, config_stats, config_stats_at};
+pub use cache::{Cache, CachePolicy};
+pub use config::ConfigManager;
+pub use models::{
+    CacheStatus, Config, ModelTotals, ModelUsage, Plan, RateLimitInfo, SkuUsage, UsageData,
+    UsageItem, UsageStats,
+};
@@ -0,0 +1,173 @@
+//! `-v` / `--verbose` logging. The CLI modes log to stderr, and only when
+//! asked; the dashboard logs to a daily file under the cache dir (stderr
+//! would draw over the alternate screen), at `info` even without `-v`, so
+//! `copilot-usage logs` has something to attach to a bug report. Every
+//! line goes through `redact`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use tracing::level_filters::LevelFilter;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+use crate::redact::Redacting;
+
+/// Log files are `copilot-usage.<date>.log`
+const FILE_PREFIX: &str = "copilot-usage";
+const FILE_SUFFIX: &str = "log";
+
+/// Days of log files kept; older ones are deleted as new ones start
+const MAX_LOG_FILES: usize = 7;
+
+/// Where the log goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sink {
+    Stderr,
+    /// The TUI owns the terminal
+    File,
+}
+
+/// `-v` info, `-vv` debug, `-vvv` trace. Without it the CLI stays quiet
+/// (its warnings are already on stderr) and the dashboard logs `info`.
+pub fn level(verbosity: u8, sink: Sink) -> LevelFilter {
+    match (verbosity, sink) {
+        (0, Sink::Stderr) => LevelFilter::OFF,
+        (0 | 1, _) => LevelFilter::INFO,
+        (2, _) => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// `logs/` in the cache dir
+pub fn log_dir() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("com", "copilot-usage", "copilot-usage")
+        .context("Failed to determine cache directory")?;
+    Ok(proj_dirs.cache_dir().join("logs"))
+}
+
+/// Installs the global subscriber; `color` only applies to stderr. Logging
+/// is a diagnostic aid: when the log file can't be opened the run goes on
+/// without it.
+pub fn init(verbosity: u8, sink: Sink, color: bool) {
+    let level = level(verbosity, sink);
+    if level == LevelFilter::OFF {
+        return;
+    }
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false);
+    let _ = match sink {
+        Sink::Stderr => builder
+            .with_ansi(color)
+            .with_writer(Redacting(std::io::stderr))
+            .try_init(),
+        Sink::File => match log_dir().and_then(|dir| appender(&dir)) {
+            Ok(appender) => builder
+                .with_ansi(false)
+                .with_writer(Redacting(appender))
+                .try_init(),
+            Err(_) => return,
+        },
+    };
+}
+
+fn appender(dir: &Path) -> Result<RollingFileAppender> {
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(FILE_PREFIX)
+        .filename_suffix(FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .with_context(|| format!("Failed to open the log in {}", dir.display()))
+}
+
+/// The log files in `dir`, oldest first (their names carry the date)
+fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(&format!("{}.", FILE_PREFIX))
+                        && name.ends_with(&format!(".{}", FILE_SUFFIX))
+                })
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// The last `lines` lines logged in `dir`, reaching into older files when
+/// the newest is shorter. Empty when nothing was logged yet.
+pub fn tail(dir: &Path, lines: usize) -> Result<Vec<String>> {
+    let mut tail = Vec::new();
+    for path in log_files(dir).iter().rev() {
+        if tail.len() >= lines {
+            break;
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let wanted = lines - tail.len();
+        let mut older: Vec<String> = content
+            .lines()
+            .rev()
+            .take(wanted)
+            .map(str::to_string)
+            .collect();
+        older.reverse();
+        older.append(&mut tail);
+        tail = older;
+    }
+    Ok(tail)
+}
+
+/// A token as it may appear in the log: its kind prefix and nothing else
+pub fn redact_token(token: &str) -> String {
+    match token.split_once('_') {
+        Some((prefix, _)) if prefix.len() <= 10 => format!("{}_***", prefix),
+        _ => "***".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(level(0, Sink::Stderr), LevelFilter::OFF);
+        assert_eq!(level(0, Sink::File), LevelFilter::INFO);
+        assert_eq!(level(1, Sink::Stderr), LevelFilter::INFO);
+        assert_eq!(level(2, Sink::File), LevelFilter::DEBUG);
+        assert_eq!(level(5, Sink::Stderr), LevelFilter::TRACE);
+    }
+
+    #[test]
+    fn test_tail_spans_files() {
+        let dir = TempDir::new().unwrap();
+        let write = |name: &str, lines: &[&str]| {
+            std::fs::write(dir.path().join(name), lines.join("\n") + "\n").unwrap();
+        };
+        write("copilot-usage.2026-10-14.log", &["a1", "a2", "a3"]);
+        write("copilot-usage.2026-10-15.log", &["b1", "b2"]);
+        write("unrelated.log", &["x"]);
+
+        assert_eq!(tail(dir.path(), 1).unwrap(), ["b2"]);
+        assert_eq!(tail(dir.path(), 4).unwrap(), ["a2", "a3", "b1", "b2"]);
+        assert_eq!(tail(dir.path(), 50).unwrap().len(), 5);
+        assert!(tail(&dir.path().join("missing"), 50).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_redact_token() {
+        assert_eq!(redact_token("ghp_abc123secret"), "ghp_***");
+        assert_eq!(redact_token("github_pat_11AAA_secret"), "github_***");
+        assert_eq!(redact_token("0123456789abcdef"), "***");
+    }
+}
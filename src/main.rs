@@ -1,15 +1,16 @@
-mod api;
-mod cache;
-mod cli;
-mod config;
-mod models;
-mod themes;
-mod ui;
-mod waybar;
-
-use anyhow::Result;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    cli::run().await
+async fn main() -> ExitCode {
+    match copilot_usage::cli::run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // Como lo imprimiría `Result`, pero sin el token
+            eprintln!(
+                "Error: {}",
+                copilot_usage::redact::redact(&format!("{:?}", e))
+            );
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,354 @@
+//! `copilot-usage metrics`: the usage as OpenMetrics text, printed once or
+//! served on `/metrics` for Prometheus to scrape.
+
+use std::fmt::Write as _;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::models::UsageStats;
+use crate::redact::redact;
+
+/// Content type of the exposition, as Prometheus asks for it
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// A client that doesn't finish its request by then is dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Enough for a request line and the headers of any scraper
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Wait after a failed accept (e.g. out of file descriptors) before the next
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// 503 body: the cause can name URLs or accounts, so it goes to stderr only
+const SCRAPE_FAILED: &str = "Failed to fetch the usage, see the exporter's output\n";
+
+/// The exposition for `stats`, `cache_age` being how old the data is
+/// (zero when it was just fetched)
+pub fn to_openmetrics(stats: &UsageStats, cache_age: Duration) -> String {
+    let mut out = String::new();
+    gauge(
+        &mut out,
+        "copilot_premium_requests_used",
+        "Premium requests used in the billing period.",
+        stats.total_used,
+    );
+    gauge(
+        &mut out,
+        "copilot_premium_requests_limit",
+        "Premium requests included in the plan.",
+        stats.total_limit,
+    );
+    gauge(
+        &mut out,
+        "copilot_usage_percentage",
+        "Premium requests used, in % of the limit.",
+        stats.percentage,
+    );
+    gauge(
+        &mut out,
+        "copilot_estimated_cost_usd",
+        "Estimated cost of the requests beyond the plan, in USD.",
+        stats.estimated_cost,
+    );
+
+    family(
+        &mut out,
+        "copilot_model_requests",
+        "Premium requests used per model.",
+    );
+    for model in &stats.models {
+        let _ = writeln!(
+            out,
+            "copilot_model_requests{{model=\"{}\"}} {}",
+            escape_label(&model.name),
+            number(model.used)
+        );
+    }
+
+    family(
+        &mut out,
+        "copilot_cache_age_seconds",
+        "Age of the usage data, 0 right after a fetch.",
+    );
+    let _ = writeln!(out, "# UNIT copilot_cache_age_seconds seconds");
+    let _ = writeln!(
+        out,
+        "copilot_cache_age_seconds {}",
+        number(cache_age.as_secs_f64().round())
+    );
+
+    out.push_str("# EOF\n");
+    out
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: f64) {
+    family(out, name, help);
+    let _ = writeln!(out, "{} {}", name, number(value));
+}
+
+fn family(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+}
+
+/// OpenMetrics spells the special values `NaN`, `+Inf` and `-Inf`
+fn number(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Label values escape backslashes, double quotes and newlines
+fn escape_label(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Answers `GET /metrics` with whatever `scrape` renders, one connection at
+/// a time: Prometheus scrapes seldom, and two fetches in parallel would only
+/// race for the cache. Runs until the process is stopped: a failed accept
+/// is reported and the next connection awaited.
+pub async fn serve<F, Fut>(listener: TcpListener, mut scrape: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                eprintln!("Failed to accept a connection: {}", e);
+                tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        // Un cliente que se cuelga o corta no para el servidor
+        let _ = handle(stream, &mut scrape).await;
+    }
+}
+
+async fn handle<F, Fut>(mut stream: TcpStream, scrape: &mut F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream)).await??;
+    let mut parts = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let (method, path) = (parts.next(), parts.next());
+    // La query (`/metrics?x=y`) no cambia nada
+    let path = path.map(|path| path.split('?').next().unwrap_or_default());
+
+    let (status, content_type, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) => match scrape().await {
+            Ok(body) => ("200 OK", CONTENT_TYPE, body),
+            Err(e) => {
+                eprintln!("Scrape failed: {}", redact(&format!("{:#}", e)));
+                (
+                    "503 Service Unavailable",
+                    "text/plain; charset=utf-8",
+                    SCRAPE_FAILED.to_string(),
+                )
+            }
+        },
+        (Some("GET"), _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Metrics are served on /metrics\n".to_string(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Only GET is supported\n".to_string(),
+        ),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Reads up to the blank line that ends the headers; a body is never needed
+async fn read_head(stream: &mut TcpStream) -> Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+        if head.len() > MAX_REQUEST_BYTES {
+            anyhow::bail!("Request headers too large");
+        }
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{model, stats_with};
+
+    fn stats() -> UsageStats {
+        UsageStats {
+            estimated_cost: 1.2,
+            username: "octocat".to_string(),
+            ..stats_with(vec![
+                model("gpt-4.1", 84.0),
+                model("claude \"sonnet\"\\4", 42.5),
+            ])
+        }
+    }
+
+    /// Checks the grammar this exporter uses: every sample belongs to the
+    /// family declared right before it, families are declared once, and
+    /// the text ends with `# EOF`
+    fn validate(text: &str) -> Vec<(String, f64)> {
+        assert!(text.ends_with("# EOF\n"), "missing # EOF");
+        let mut families = Vec::new();
+        let mut samples = Vec::new();
+        for line in text.lines().take_while(|line| *line != "# EOF") {
+            if let Some(meta) = line.strip_prefix("# ") {
+                let mut parts = meta.splitn(3, ' ');
+                let (kind, name) = (parts.next().unwrap(), parts.next().unwrap());
+                match kind {
+                    "TYPE" => {
+                        assert_eq!(parts.next(), Some("gauge"), "{}", line);
+                        assert!(!families.contains(&name), "{} declared twice", name);
+                        families.push(name);
+                    }
+                    "HELP" | "UNIT" => {
+                        assert_eq!(families.last(), Some(&name), "{}", line);
+                        assert!(parts.next().is_some_and(|text| !text.is_empty()));
+                    }
+                    _ => panic!("unknown descriptor: {}", line),
+                }
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            let name = series.split('{').next().unwrap();
+            assert_eq!(families.last(), Some(&name), "{}", line);
+            assert!(
+                name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                "{}",
+                name
+            );
+            if let Some(labels) = series.strip_prefix(name) {
+                assert!(labels.is_empty() || labels.starts_with('{') && labels.ends_with('}'));
+            }
+            samples.push((series.to_string(), value.parse::<f64>().unwrap()));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_exposition_format() {
+        let text = to_openmetrics(&stats(), Duration::from_millis(90_400));
+        let samples = validate(&text);
+        let value = |series: &str| {
+            samples
+                .iter()
+                .find(|(s, _)| s == series)
+                .map(|(_, v)| *v)
+                .unwrap_or_else(|| panic!("no {}", series))
+        };
+        assert_eq!(value("copilot_premium_requests_used"), 126.5);
+        assert_eq!(value("copilot_premium_requests_limit"), 300.0);
+        assert_eq!(value("copilot_estimated_cost_usd"), 1.2);
+        assert_eq!(value("copilot_cache_age_seconds"), 90.0);
+        assert_eq!(value("copilot_model_requests{model=\"gpt-4.1\"}"), 84.0);
+        assert_eq!(
+            value("copilot_model_requests{model=\"claude \\\"sonnet\\\"\\\\4\"}"),
+            42.5
+        );
+        assert!(text.contains("# UNIT copilot_cache_age_seconds seconds\n"));
+    }
+
+    #[test]
+    fn test_no_models_still_declares_the_family() {
+        let mut stats = stats();
+        stats.models.clear();
+        let text = to_openmetrics(&stats, Duration::ZERO);
+        validate(&text);
+        assert!(text.contains("# TYPE copilot_model_requests gauge\n"));
+        assert!(!text.contains("copilot_model_requests{"));
+    }
+
+    #[test]
+    fn test_special_values() {
+        assert_eq!(number(f64::NAN), "NaN");
+        assert_eq!(number(f64::INFINITY), "+Inf");
+        assert_eq!(number(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(number(0.0), "0");
+        assert_eq!(escape_label("a\nb"), "a\\nb");
+    }
+
+    #[tokio::test]
+    async fn test_serves_metrics_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let scrapes = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = std::sync::Arc::clone(&scrapes);
+        let server = tokio::spawn(serve(listener, move || {
+            let n = counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            async move {
+                if n == 0 {
+                    Ok(to_openmetrics(&stats(), Duration::ZERO))
+                } else {
+                    anyhow::bail!("API down")
+                }
+            }
+        }));
+
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let response = client.get(format!("{}/metrics", url)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-type"], CONTENT_TYPE);
+        validate(&response.text().await.unwrap());
+
+        // Cada scrape vuelve a pedir los datos; un fallo es un 503 que no
+        // cuenta el motivo
+        let response = client.get(format!("{}/metrics", url)).send().await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(response.text().await.unwrap(), SCRAPE_FAILED);
+
+        let response = client.get(format!("{}/", url)).send().await.unwrap();
+        assert_eq!(response.status(), 404);
+        let response = client
+            .post(format!("{}/metrics", url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 405);
+        assert_eq!(scrapes.load(std::sync::atomic::Ordering::Relaxed), 2);
+        server.abort();
+    }
+}
@@ -0,0 +1,148 @@
+//! Names the usage is grouped under. GitHub reports one model under several
+//! names ("Auto: gpt-4.1", "gpt-4.1-2025-04-14"); each row is summed under
+//! the first of:
+//!
+//! 1. a `[model_aliases]` entry matching the name as reported;
+//! 2. one matching the name without the `Auto: ` prefix and date suffix;
+//! 3. that name itself.
+//!
+//! When several aliases match, the longest regex wins: it's the more
+//! specific one.
+
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Prefix of the models Copilot picked by itself
+const AUTO_PREFIX: &str = "Auto: ";
+
+/// `-2025-04-14` or `-20250414` at the end of a snapshot name
+static DATE_SUFFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"-20\d{2}-?\d{2}-?\d{2}$").unwrap());
+
+/// Built-in rules plus the user's aliases, compiled
+#[derive(Debug, Clone, Default)]
+pub struct ModelNames {
+    /// Longest regex first
+    aliases: Vec<(Regex, String)>,
+}
+
+impl ModelNames {
+    /// The built-in rules alone
+    pub fn builtin() -> Self {
+        Self::default()
+    }
+
+    /// `aliases` maps a regex to the display name; `$1` / `${name}` in the
+    /// name expand to its captures
+    pub fn new(aliases: &BTreeMap<String, String>) -> Result<Self> {
+        let mut compiled = Vec::with_capacity(aliases.len());
+        for (pattern, name) in aliases {
+            let regex =
+                Regex::new(pattern).with_context(|| format!("Invalid regex '{}'", pattern))?;
+            if name.trim().is_empty() {
+                anyhow::bail!("Empty display name for '{}'", pattern);
+            }
+            compiled.push((regex, name.clone()));
+        }
+        // Estable: a igual longitud, el orden alfabético del mapa
+        compiled.sort_by_key(|(regex, _)| std::cmp::Reverse(regex.as_str().len()));
+        Ok(Self { aliases: compiled })
+    }
+
+    /// Name `reported` is grouped under
+    pub fn group(&self, reported: &str) -> String {
+        if let Some(name) = self.alias(reported) {
+            return name;
+        }
+        let normalized = normalize(reported);
+        self.alias(normalized)
+            .unwrap_or_else(|| normalized.to_string())
+    }
+
+    fn alias(&self, name: &str) -> Option<String> {
+        self.aliases.iter().find_map(|(regex, template)| {
+            let captures = regex.captures(name)?;
+            let mut expanded = String::new();
+            captures.expand(template, &mut expanded);
+            Some(expanded)
+        })
+    }
+}
+
+/// `reported` without the `Auto: ` prefix and the date suffix
+pub fn normalize(reported: &str) -> &str {
+    let name = reported.strip_prefix(AUTO_PREFIX).unwrap_or(reported);
+    let name = match DATE_SUFFIX.find(name) {
+        // Un nombre que es solo una fecha se queda como está
+        Some(date) if date.start() > 0 => &name[..date.start()],
+        _ => name,
+    };
+    if name.is_empty() { reported } else { name }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(aliases: &[(&str, &str)]) -> ModelNames {
+        let aliases = aliases
+            .iter()
+            .map(|(pattern, name)| (pattern.to_string(), name.to_string()))
+            .collect();
+        ModelNames::new(&aliases).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let names = ModelNames::builtin();
+        for reported in ["gpt-4.1", "Auto: gpt-4.1", "gpt-4.1-2025-04-14"] {
+            assert_eq!(names.group(reported), "gpt-4.1", "{}", reported);
+        }
+        // Las dos reglas a la vez
+        assert_eq!(
+            names.group("Auto: claude-3-5-sonnet-20241022"),
+            "claude-3-5-sonnet"
+        );
+        // Un número de versión no es una fecha
+        assert_eq!(names.group("o1-2024"), "o1-2024");
+        assert_eq!(names.group("Auto: "), "Auto: ");
+    }
+
+    #[test]
+    fn test_alias_on_the_reported_name_comes_first() {
+        let names = names(&[("^Auto: (.*)", "auto ($1)"), ("^gpt-4\\.1", "GPT-4.1")]);
+        // El alias del nombre tal cual gana a las reglas integradas
+        assert_eq!(names.group("Auto: gpt-4.1"), "auto (gpt-4.1)");
+        // Sin alias para el nombre tal cual, se prueba el normalizado
+        assert_eq!(names.group("gpt-4.1-2025-04-14"), "GPT-4.1");
+        assert_eq!(names.group("o3-2025-04-16"), "o3");
+    }
+
+    #[test]
+    fn test_longest_overlapping_alias_wins() {
+        let names = names(&[
+            ("claude", "Claude"),
+            ("claude-.*-sonnet", "Claude Sonnet"),
+            ("sonnet", "Sonnet"),
+        ]);
+        assert_eq!(names.group("claude-3-7-sonnet"), "Claude Sonnet");
+        assert_eq!(names.group("claude-opus-4"), "Claude");
+        assert_eq!(names.group("sonnet-next"), "Sonnet");
+        // A igual longitud, el primero en orden alfabético
+        let tie = self::names(&[("gpt-4.*", "B"), ("gpt-.*1", "A")]);
+        assert_eq!(tie.group("gpt-4.1"), "A");
+    }
+
+    #[test]
+    fn test_invalid_aliases() {
+        let invalid = |pattern: &str, name: &str| {
+            let aliases = BTreeMap::from([(pattern.to_string(), name.to_string())]);
+            ModelNames::new(&aliases).unwrap_err().to_string()
+        };
+        assert!(invalid("gpt-(", "GPT").contains("'gpt-('"));
+        assert!(invalid("gpt", " ").contains("'gpt'"));
+    }
+}
@@ -1,6 +1,11 @@
-use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::percent::{Zone, zone_between};
+use crate::period::BillingPeriod;
+
 /// Usage data from GitHub API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -8,6 +13,26 @@ pub struct UsageData {
     pub time_period: TimePeriod,
     pub user: String,
     pub usage_items: Vec<UsageItem>,
+    /// Set when the data was synthesized instead of returned by the API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl UsageData {
+    /// Empty usage for the current month, used when GitHub answers with
+    /// 204 or an empty body (e.g. right after enabling Copilot)
+    pub fn empty(user: &str, now: DateTime<Utc>, note: &str) -> Self {
+        Self {
+            time_period: TimePeriod {
+                year: now.year(),
+                month: Some(now.month()),
+                day: None,
+            },
+            user: user.to_string(),
+            usage_items: Vec::new(),
+            note: Some(note.to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,27 +57,306 @@ pub struct UsageItem {
     pub discount_amount: f64,
     pub net_quantity: f64,
     pub net_amount: f64,
+    /// Day slice this row belongs to, when the API mixes in day granularity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+}
+
+impl UsageItem {
+    /// Same quantities at another price per request
+    pub fn reprice(&mut self, price: f64) {
+        self.price_per_unit = price;
+        self.gross_amount = self.gross_quantity * price;
+        self.discount_amount = self.discount_quantity * price;
+        self.net_amount = self.net_quantity * price;
+    }
 }
 
 /// Aggregated usage statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UsageStats {
+    /// Premium requests counted against the plan: unmetered models are left
+    /// out
     pub total_used: f64,
     pub total_limit: f64,
     pub percentage: f64,
     pub reset_date: DateTime<Utc>,
+    /// Day of the month the quota resets on (`reset_date` keeps its time)
+    pub billing_cycle_day: u32,
     pub models: Vec<ModelUsage>,
     pub estimated_cost: f64,
     pub username: String,
+    /// Copilot plan the limit comes from, when known
+    pub plan: Option<Plan>,
+    /// Explanation shown instead of the model table when there is no data
+    pub note: Option<String>,
+    /// Soft budget from the config, in premium requests
+    pub budget: Option<f64>,
+    /// Monthly budget in dollars the estimated cost is held against
+    pub cost_budget: Option<f64>,
+    /// Sum of all billing rows: gross, included (discount) and billed (net)
+    pub totals: ModelTotals,
+    /// The same usage grouped by billing SKU instead of by model
+    pub skus: Vec<SkuUsage>,
+    /// Most used model and its share of all requests, in percent
+    #[serde(default)]
+    pub top_model: Option<(String, f64)>,
+    /// Models with any usage this month
+    #[serde(default)]
+    pub distinct_models: usize,
 }
 
-#[derive(Debug, Clone)]
+/// `UsageStats::top_model` and `distinct_models` of `models`. The share
+/// counts every model, unmetered ones included.
+pub fn model_distribution(models: &[ModelUsage]) -> (Option<(String, f64)>, usize) {
+    let used: Vec<&ModelUsage> = models.iter().filter(|model| model.used > 0.0).collect();
+    let total = used.iter().fold(0.0, |sum, model| sum + model.used);
+    // A igual uso, el primero de la lista
+    let top = used
+        .iter()
+        .rev()
+        .max_by(|a, b| a.used.total_cmp(&b.used))
+        .map(|model| (model.name.clone(), model.used / total * 100.0));
+    (top, used.len())
+}
+
+impl UsageStats {
+    /// Checks that the per-model breakdown adds up to `total_used` (the
+    /// unmetered models are outside it).
+    /// Returns a human readable warning when it doesn't.
+    pub fn consistency_warning(&self) -> Option<String> {
+        let models_sum: f64 = self
+            .models
+            .iter()
+            .filter(|m| !m.unmetered)
+            .map(|m| m.used)
+            .sum();
+        if (models_sum - self.total_used).abs() > 0.001 {
+            Some(format!(
+                "Per-model usage ({:.2}) does not add up to total usage ({:.2})",
+                models_sum, self.total_used
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Past the requests the plan includes: the rest is billed
+    pub fn over_limit(&self) -> bool {
+        self.total_used > self.total_limit
+    }
+
+    /// Requests beyond the limit, 0 within it
+    pub fn overage(&self) -> f64 {
+        (self.total_used - self.total_limit).max(0.0)
+    }
+
+    /// The estimated cost went past the dollar budget
+    pub fn over_budget(&self) -> bool {
+        self.cost_budget
+            .is_some_and(|budget| self.estimated_cost > budget)
+    }
+
+    /// The billing period that ends at `reset_date`
+    pub fn period(&self) -> BillingPeriod {
+        BillingPeriod::ending_at(self.reset_date, self.billing_cycle_day)
+    }
+
+    /// Start of the billing period that ends at `reset_date`
+    pub fn period_start(&self) -> DateTime<Utc> {
+        self.period().start
+    }
+
+    /// Share of the billing period gone by at `now`, 0 to 100
+    pub fn period_elapsed(&self, now: DateTime<Utc>) -> f64 {
+        self.period().progress_percent(now)
+    }
+
+    /// The billing period `now` falls in. A cache from an earlier period
+    /// still has the reset that already happened: the period repeats
+    /// monthly from it.
+    pub fn period_at(&self, now: DateTime<Utc>) -> BillingPeriod {
+        let mut period = self.period();
+        while period.end <= now {
+            period = period.next(self.billing_cycle_day);
+        }
+        period
+    }
+
+    /// The next reset after `now`
+    pub fn next_reset(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.period_at(now).end
+    }
+
+    /// Time left until the next reset, never negative
+    pub fn until_reset(&self, now: DateTime<Utc>) -> chrono::Duration {
+        (self.next_reset(now) - now).max(chrono::Duration::zero())
+    }
+
+    /// The stats counting `mode`'s quantity as usage. Models keep their
+    /// order, so the selection in the table stays on the same row.
+    pub fn in_mode(&self, mode: QuantityMode) -> std::borrow::Cow<'_, UsageStats> {
+        if mode == QuantityMode::Gross {
+            return std::borrow::Cow::Borrowed(self);
+        }
+
+        let mut stats = self.clone();
+        stats.total_used = self.totals.quantity(mode);
+        stats.percentage = stats.total_used / stats.total_limit * 100.0;
+        for model in &mut stats.models {
+            model.used = model.totals.quantity(mode);
+            model.percentage = model.used / model.limit * 100.0;
+        }
+        std::borrow::Cow::Owned(stats)
+    }
+
+    /// The stats with the table rows in `models` grouped by `grouping`:
+    /// per model as calculated, or one row per SKU named after it
+    pub fn grouped(&self, grouping: TableGrouping) -> std::borrow::Cow<'_, UsageStats> {
+        if grouping == TableGrouping::Model {
+            return std::borrow::Cow::Borrowed(self);
+        }
+
+        let mut stats = self.clone();
+        stats.models = self
+            .skus
+            .iter()
+            .map(|sku| ModelUsage {
+                name: sku.sku.clone(),
+                used: sku.used,
+                limit: self.total_limit,
+                unmetered: false,
+                percentage: sku.percentage,
+                net_used: sku.totals.net_quantity,
+                cost: sku.cost,
+                totals: sku.totals.clone(),
+                reported_names: Vec::new(),
+            })
+            .collect();
+        std::borrow::Cow::Owned(stats)
+    }
+}
+
+/// Quantity the dashboard counts: every premium request, the ones the plan
+/// covered, or the ones billed beyond it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuantityMode {
+    #[default]
+    Gross,
+    Included,
+    Billed,
+}
+
+impl QuantityMode {
+    /// Order of the `b` key
+    pub fn next(self) -> Self {
+        match self {
+            QuantityMode::Gross => QuantityMode::Included,
+            QuantityMode::Included => QuantityMode::Billed,
+            QuantityMode::Billed => QuantityMode::Gross,
+        }
+    }
+
+    /// Catalog key of the name shown in the Overall Usage title
+    pub fn label_key(self) -> &'static str {
+        match self {
+            QuantityMode::Gross => "usage.mode.gross",
+            QuantityMode::Included => "usage.mode.included",
+            QuantityMode::Billed => "usage.mode.billed",
+        }
+    }
+}
+
+/// What a row of the usage table stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableGrouping {
+    #[default]
+    Model,
+    Sku,
+}
+
+impl TableGrouping {
+    /// The other grouping, for the `v` key
+    pub fn toggle(self) -> Self {
+        match self {
+            TableGrouping::Model => TableGrouping::Sku,
+            TableGrouping::Sku => TableGrouping::Model,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModelUsage {
     pub name: String,
     pub used: f64,
+    /// Its own limit from `[model_limits]`, or the plan's
     #[allow(dead_code)]
     pub limit: f64,
+    /// `unmetered` in `[model_limits]`: outside the pool, no percentage
+    #[serde(default)]
+    pub unmetered: bool,
     pub percentage: f64,
+    /// Requests billed beyond the plan
+    pub net_used: f64,
+    /// Billed requests at each SKU's own price, in USD
+    pub cost: f64,
+    /// Sum of this model's billing rows, for the detail view
+    pub totals: ModelTotals,
+    /// Names GitHub reported the rows under, when they aren't just `name`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reported_names: Vec<String>,
+}
+
+/// Usage billed under one SKU, across all the models that used it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkuUsage {
+    pub product: String,
+    pub sku: String,
+    pub used: f64,
+    pub percentage: f64,
+    /// Billed requests at the SKU's price, in USD
+    pub cost: f64,
+    pub totals: ModelTotals,
+}
+
+/// Per-model sums of the `UsageItem` fields
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelTotals {
+    pub gross_quantity: f64,
+    pub gross_amount: f64,
+    pub discount_quantity: f64,
+    pub discount_amount: f64,
+    pub net_quantity: f64,
+    pub net_amount: f64,
+}
+
+impl ModelTotals {
+    pub fn add(&mut self, item: &UsageItem) {
+        self.gross_quantity += item.gross_quantity;
+        self.gross_amount += item.gross_amount;
+        self.discount_quantity += item.discount_quantity;
+        self.discount_amount += item.discount_amount;
+        self.net_quantity += item.net_quantity;
+        self.net_amount += item.net_amount;
+    }
+
+    /// Gross, discount or net quantity
+    pub fn quantity(&self, mode: QuantityMode) -> f64 {
+        match mode {
+            QuantityMode::Gross => self.gross_quantity,
+            QuantityMode::Included => self.discount_quantity,
+            QuantityMode::Billed => self.net_quantity,
+        }
+    }
+
+    /// Average price per request across the model's SKUs
+    pub fn price_per_unit(&self) -> f64 {
+        if self.gross_quantity > 0.0 {
+            self.gross_amount / self.gross_quantity
+        } else {
+            0.0
+        }
+    }
 }
 
 /// Application configuration
@@ -64,6 +368,427 @@ pub struct Config {
     pub waybar_format: String,
     #[serde(default)]
     pub username: Option<String>,
+    /// Days of usage snapshots kept in the history file
+    #[serde(default = "default_history_retention_days")]
+    pub history_retention_days: u64,
+    /// Show session time and refresh count in the TUI help bar
+    #[serde(default)]
+    pub show_session_timer: bool,
+    /// Ring the terminal bell when a refresh crosses into the critical zone
+    #[serde(default)]
+    pub alert_bell: bool,
+    /// Monthly premium requests included in the plan
+    #[serde(default = "default_premium_request_limit")]
+    pub premium_request_limit: u32,
+    /// REST API root; GitHub Enterprise Server uses `https://HOST/api/v3`
+    #[serde(default = "default_api_base_url")]
+    pub api_base_url: String,
+    /// Proxy for API requests; unset follows HTTPS_PROXY / HTTP_PROXY / NO_PROXY
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// Extra PEM root certificates, e.g. a corporate proxy's CA
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle_path: Option<std::path::PathBuf>,
+    /// Client ID of the app `copilot-usage login` signs in with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth_client_id: Option<String>,
+    /// Day the token expires, from GitHub or entered in setup; warnings
+    /// start a week before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_expires: Option<NaiveDate>,
+    /// Attempts per API request when GitHub fails transiently
+    #[serde(default = "default_api_max_attempts")]
+    pub api_max_attempts: u32,
+    /// First retry delay in milliseconds, doubled on each further attempt
+    #[serde(default = "default_api_retry_base_delay_ms")]
+    pub api_retry_base_delay_ms: u64,
+    /// Copilot plan detected from the API (or picked during setup)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<Plan>,
+    /// Where `premium_request_limit` comes from, so detection never
+    /// overwrites a custom limit and a failed one waits for `--refresh`
+    #[serde(default, skip_serializing_if = "LimitSource::is_pending")]
+    pub limit_source: LimitSource,
+    /// Request and dollar budgets, and price overrides (`[budget]` table)
+    #[serde(default, skip_serializing_if = "BudgetConfig::is_empty")]
+    pub budget: BudgetConfig,
+    /// Own monthly limit, or `unmetered`, by model name pattern
+    /// (`[model_limits]` table)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub model_limits: BTreeMap<String, ModelLimit>,
+    /// Display name by model name regex, to group the rows GitHub reports
+    /// (`[model_aliases]` table)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub model_aliases: BTreeMap<String, String>,
+    /// `interval` of the Waybar module, to check it against the cache TTL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waybar_interval_seconds: Option<u64>,
+    /// Adds the `copilot-reset-soon` class when fewer days remain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waybar_reset_soon_days: Option<u32>,
+    /// Models listed in the Waybar tooltip; the rest are counted
+    #[serde(default = "default_waybar_tooltip_max_models")]
+    pub waybar_tooltip_max_models: usize,
+    /// Bold headings in the Waybar tooltip (Pango markup)
+    #[serde(default)]
+    pub waybar_markup: bool,
+    /// Day of the month the quota resets on; unset is the 1st
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billing_cycle_day: Option<u32>,
+    /// Included quantity a plan mismatch warning was already shown for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_mismatch_warned: Option<u32>,
+    /// UI language code (`en`, `es`); unset follows the locale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Plain ASCII instead of Nerd Font icons; unset detects it from TERM
+    /// and the locale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ascii_icons: Option<bool>,
+    #[serde(default)]
+    pub menu: MenuConfig,
+    #[serde(default)]
+    pub check: CheckConfig,
+    /// Where the Waybar zone classes change (`waybar_thresholds` table)
+    #[serde(default)]
+    pub waybar_thresholds: WaybarThresholds,
+}
+
+/// Command menu settings (`[menu]` table)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MenuConfig {
+    /// Command ids that can't be run from the menu or their shortcut keys
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+/// Budgets and prices (`[budget]` table). The older `budget = 200` form,
+/// a request budget alone, is still read and written.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BudgetRepr", into = "BudgetRepr")]
+pub struct BudgetConfig {
+    /// Soft monthly budget in premium requests, shown as a second bar
+    pub requests: Option<u32>,
+    /// Monthly budget in dollars for the estimated overage cost
+    pub monthly_budget_usd: Option<f64>,
+    /// Price per request by model name, over the one GitHub reports
+    pub price_overrides: BTreeMap<String, f64>,
+}
+
+impl BudgetConfig {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BudgetRepr {
+    Requests(u32),
+    Table {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        requests: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        monthly_budget_usd: Option<f64>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        price_overrides: BTreeMap<String, f64>,
+    },
+}
+
+impl From<BudgetRepr> for BudgetConfig {
+    fn from(repr: BudgetRepr) -> Self {
+        match repr {
+            BudgetRepr::Requests(requests) => Self {
+                requests: Some(requests),
+                ..Self::default()
+            },
+            BudgetRepr::Table {
+                requests,
+                monthly_budget_usd,
+                price_overrides,
+            } => Self {
+                requests,
+                monthly_budget_usd,
+                price_overrides,
+            },
+        }
+    }
+}
+
+impl From<BudgetConfig> for BudgetRepr {
+    fn from(budget: BudgetConfig) -> Self {
+        // Solo el de peticiones: se queda como `budget = 200`
+        match budget {
+            BudgetConfig {
+                requests: Some(requests),
+                monthly_budget_usd: None,
+                price_overrides,
+            } if price_overrides.is_empty() => BudgetRepr::Requests(requests),
+            BudgetConfig {
+                requests,
+                monthly_budget_usd,
+                price_overrides,
+            } => BudgetRepr::Table {
+                requests,
+                monthly_budget_usd,
+                price_overrides,
+            },
+        }
+    }
+}
+
+/// Entry of `[model_limits]`: `"gpt-4.1" = "unmetered"` or
+/// `"claude-opus-*" = 50`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ModelLimitRepr", into = "ModelLimitRepr")]
+pub enum ModelLimit {
+    /// Premium requests a month for the model alone
+    Requests(u32),
+    /// Doesn't count against the plan
+    Unmetered,
+}
+
+/// Value written for `ModelLimit::Unmetered`
+const UNMETERED: &str = "unmetered";
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ModelLimitRepr {
+    Requests(u32),
+    Keyword(String),
+}
+
+impl TryFrom<ModelLimitRepr> for ModelLimit {
+    type Error = String;
+
+    fn try_from(repr: ModelLimitRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ModelLimitRepr::Requests(requests) => Ok(Self::Requests(requests)),
+            ModelLimitRepr::Keyword(word) if word.eq_ignore_ascii_case(UNMETERED) => {
+                Ok(Self::Unmetered)
+            }
+            ModelLimitRepr::Keyword(word) => Err(format!(
+                "invalid model limit '{}': expected a number of requests or \"{}\"",
+                word, UNMETERED
+            )),
+        }
+    }
+}
+
+impl From<ModelLimit> for ModelLimitRepr {
+    fn from(limit: ModelLimit) -> Self {
+        match limit {
+            ModelLimit::Requests(requests) => Self::Requests(requests),
+            ModelLimit::Unmetered => Self::Keyword(UNMETERED.to_string()),
+        }
+    }
+}
+
+/// Thresholds of `copilot-usage check` (`[check]` table)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckConfig {
+    /// Usage percentage that exits 1 (warning)
+    #[serde(default = "default_check_warn")]
+    pub warn: f64,
+    /// Usage percentage that exits 2 (critical)
+    #[serde(default = "default_check_crit")]
+    pub crit: f64,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            warn: default_check_warn(),
+            crit: default_check_crit(),
+        }
+    }
+}
+
+fn default_check_warn() -> f64 {
+    crate::percent::ZONE_WARNING_START
+}
+
+fn default_check_crit() -> f64 {
+    crate::percent::ZONE_CRITICAL_START
+}
+
+/// Usage percentages the Waybar zone classes start at
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WaybarThresholds {
+    /// `copilot-normal` from here, `copilot-low` below
+    #[serde(default = "default_threshold_low")]
+    pub low: f64,
+    /// `copilot-warning` from here
+    #[serde(default = "default_threshold_warning")]
+    pub warning: f64,
+    /// `copilot-critical` from here
+    #[serde(default = "default_threshold_critical")]
+    pub critical: f64,
+}
+
+impl Default for WaybarThresholds {
+    fn default() -> Self {
+        Self {
+            low: default_threshold_low(),
+            warning: default_threshold_warning(),
+            critical: default_threshold_critical(),
+        }
+    }
+}
+
+impl WaybarThresholds {
+    /// Zone of `percentage` with these bounds
+    pub fn zone(&self, percentage: f64) -> Zone {
+        zone_between(percentage, [self.low, self.warning, self.critical])
+    }
+}
+
+fn default_threshold_low() -> f64 {
+    crate::percent::ZONE_NORMAL_START
+}
+
+fn default_threshold_warning() -> f64 {
+    crate::percent::ZONE_WARNING_START
+}
+
+fn default_threshold_critical() -> f64 {
+    crate::percent::ZONE_CRITICAL_START
+}
+
+/// GitHub Copilot subscription plans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Plan {
+    Free,
+    Pro,
+    ProPlus,
+    Business,
+    Enterprise,
+}
+
+impl Plan {
+    pub const ALL: [Plan; 5] = [
+        Plan::Free,
+        Plan::Pro,
+        Plan::ProPlus,
+        Plan::Business,
+        Plan::Enterprise,
+    ];
+
+    /// Name used in config.toml and on the command line (`pro_plus`)
+    pub fn id(self) -> &'static str {
+        match self {
+            Plan::Free => "free",
+            Plan::Pro => "pro",
+            Plan::ProPlus => "pro_plus",
+            Plan::Business => "business",
+            Plan::Enterprise => "enterprise",
+        }
+    }
+
+    /// Parses a config/CLI id, also accepting the API's names (`pro+`)
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|plan| plan.id() == name)
+            .or_else(|| Self::from_api_name(&name))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Plan::Free => "Copilot Free",
+            Plan::Pro => "Copilot Pro",
+            Plan::ProPlus => "Copilot Pro+",
+            Plan::Business => "Copilot Business",
+            Plan::Enterprise => "Copilot Enterprise",
+        }
+    }
+
+    /// Monthly premium request allowance of the plan
+    pub fn premium_request_limit(self) -> u32 {
+        match self {
+            Plan::Free => 50,
+            Plan::Pro | Plan::Business => 300,
+            Plan::ProPlus => 1500,
+            Plan::Enterprise => 1000,
+        }
+    }
+
+    /// Maps the `copilot_plan` value reported by the GitHub API
+    pub fn from_api_name(name: &str) -> Option<Self> {
+        match name {
+            "free" | "individual_free" => Some(Plan::Free),
+            "individual" | "individual_pro" | "pro" => Some(Plan::Pro),
+            "individual_pro_plus" | "pro_plus" | "pro+" => Some(Plan::ProPlus),
+            "business" => Some(Plan::Business),
+            "enterprise" => Some(Plan::Enterprise),
+            _ => None,
+        }
+    }
+}
+
+/// Origin of the configured premium request limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitSource {
+    /// Not detected yet: the next fetch asks the API for the plan
+    #[default]
+    Pending,
+    /// Follows the detected or chosen plan
+    Plan,
+    /// Typed by the user; detection records the plan but keeps the limit
+    Custom,
+    /// Detection failed; retried only with `--refresh`
+    DetectionFailed,
+}
+
+impl LimitSource {
+    pub fn is_pending(&self) -> bool {
+        *self == LimitSource::Pending
+    }
+}
+
+impl Config {
+    /// Whether the plan should be asked to the API before fetching: once
+    /// when unknown, and again on `--refresh` unless the limit is custom
+    pub fn needs_plan_detection(&self, force_refresh: bool) -> bool {
+        match self.limit_source {
+            LimitSource::Custom => false,
+            LimitSource::Plan | LimitSource::DetectionFailed => force_refresh,
+            LimitSource::Pending => force_refresh || self.plan.is_none(),
+        }
+    }
+}
+
+/// Premium requests per month on Copilot Pro and Business
+pub const DEFAULT_PREMIUM_REQUEST_LIMIT: u32 = 300;
+
+fn default_premium_request_limit() -> u32 {
+    DEFAULT_PREMIUM_REQUEST_LIMIT
+}
+
+fn default_api_base_url() -> String {
+    crate::api::GITHUB_API_URL.to_string()
+}
+
+fn default_api_max_attempts() -> u32 {
+    3
+}
+
+fn default_api_retry_base_delay_ms() -> u64 {
+    500
+}
+
+/// Models listed in the Waybar tooltip before "…and N more"
+pub const DEFAULT_WAYBAR_TOOLTIP_MAX_MODELS: usize = 8;
+
+fn default_waybar_tooltip_max_models() -> usize {
+    DEFAULT_WAYBAR_TOOLTIP_MAX_MODELS
+}
+
+fn default_history_retention_days() -> u64 {
+    crate::history::DEFAULT_RETENTION_DAYS
 }
 
 impl Default for Config {
@@ -74,6 +799,33 @@ impl Default for Config {
             cache_ttl_minutes: 5,
             waybar_format: "{percentage}%".to_string(),
             username: None,
+            history_retention_days: default_history_retention_days(),
+            show_session_timer: false,
+            alert_bell: false,
+            premium_request_limit: DEFAULT_PREMIUM_REQUEST_LIMIT,
+            api_base_url: default_api_base_url(),
+            proxy_url: None,
+            ca_bundle_path: None,
+            oauth_client_id: None,
+            token_expires: None,
+            api_max_attempts: default_api_max_attempts(),
+            api_retry_base_delay_ms: default_api_retry_base_delay_ms(),
+            plan: None,
+            limit_source: LimitSource::Pending,
+            budget: BudgetConfig::default(),
+            model_limits: BTreeMap::new(),
+            model_aliases: BTreeMap::new(),
+            waybar_interval_seconds: None,
+            waybar_reset_soon_days: None,
+            waybar_tooltip_max_models: DEFAULT_WAYBAR_TOOLTIP_MAX_MODELS,
+            waybar_markup: false,
+            billing_cycle_day: None,
+            plan_mismatch_warned: None,
+            language: None,
+            ascii_icons: None,
+            menu: MenuConfig::default(),
+            check: CheckConfig::default(),
+            waybar_thresholds: WaybarThresholds::default(),
         }
     }
 }
@@ -83,6 +835,48 @@ impl Default for Config {
 pub struct CacheEntry {
     pub data: UsageData,
     pub timestamp: DateTime<Utc>,
+    /// Rate-limit state reported with the response that filled the cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitInfo>,
+    #[serde(flatten)]
+    pub validators: CacheValidators,
+}
+
+/// `ETag` and `Last-Modified` of the cached response, sent back as
+/// `If-None-Match` / `If-Modified-Since` so an unchanged month costs a 304
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheValidators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// GitHub API rate-limit state read from response headers
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct RateLimitInfo {
+    /// `x-ratelimit-remaining`
+    pub remaining: Option<u32>,
+    /// `x-ratelimit-reset`
+    pub reset_at: Option<DateTime<Utc>>,
+    /// `Retry-After`, in seconds
+    pub retry_after_secs: Option<u64>,
+}
+
+impl RateLimitInfo {
+    /// When requests are allowed again; `Retry-After` wins over the reset header
+    pub fn resets_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.retry_after_secs {
+            Some(secs) => Some(now + chrono::Duration::seconds(secs as i64)),
+            None => self.reset_at,
+        }
+    }
 }
 
 /// Cache status for robust cache checks
@@ -90,8 +884,8 @@ pub struct CacheEntry {
 pub enum CacheStatus {
     /// Cache is fresh and contains valid data
     Fresh(UsageData),
-    /// Cache exists but has expired
-    Expired,
+    /// Cache has expired but still holds parseable data, shown while refreshing
+    Stale(UsageData),
     /// Cache file doesn't exist or couldn't be read
     Missing,
     /// Cache file is corrupted or invalid
@@ -99,18 +893,51 @@ pub enum CacheStatus {
 }
 
 /// Waybar output format
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WaybarOutput {
     pub text: String,
     pub tooltip: String,
-    #[serde(rename = "class")]
-    pub class: String,
+    /// A single class is written as a string, several as a list
+    #[serde(rename = "class", with = "class_list")]
+    pub class: Vec<String>,
+    /// Rounded usage, for Waybar's `format-icons` ramp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<u32>,
+}
+
+/// Waybar takes `class` as a string or a list of strings
+mod class_list {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    pub fn serialize<S: Serializer>(classes: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+        match classes {
+            [class] => class.serialize(serializer),
+            _ => classes.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<String>, D::Error> {
+        Ok(match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(class) => vec![class],
+            OneOrMany::Many(classes) => classes,
+        })
+    }
 }
 
 /// Available themes
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Theme {
     Dark,
+    Light,
     Nord,
     Monokai,
     Gruvbox,
@@ -122,24 +949,47 @@ pub enum Theme {
 }
 
 impl Theme {
+    /// Every built-in theme, in the order shown by the theme selector
+    pub const ALL: [Theme; 10] = [
+        Theme::Dark,
+        Theme::Light,
+        Theme::Nord,
+        Theme::Monokai,
+        Theme::Gruvbox,
+        Theme::Catppuccin,
+        Theme::OneDark,
+        Theme::TokyoNight,
+        Theme::SolarizedDark,
+        Theme::Kanagawa,
+    ];
+
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
+        // "dracula" falls through to Dark (they were identical)
+        Self::parse(s).unwrap_or(Theme::Dark)
+    }
+
+    /// Like `from_str`, but `None` for names that aren't a theme
+    pub fn parse(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
-            "nord" => Theme::Nord,
-            "monokai" => Theme::Monokai,
-            "gruvbox" => Theme::Gruvbox,
-            "catppuccin" => Theme::Catppuccin,
-            "onedark" | "one_dark" | "one-dark" => Theme::OneDark,
-            "tokyonight" | "tokyo_night" | "tokyo-night" => Theme::TokyoNight,
-            "solarized" | "solarized_dark" | "solarized-dark" => Theme::SolarizedDark,
-            "kanagawa" => Theme::Kanagawa,
-            // "dracula" falls through to Dark (they were identical)
-            _ => Theme::Dark,
+            "dark" => Some(Theme::Dark),
+            "light" => Some(Theme::Light),
+            "nord" => Some(Theme::Nord),
+            "monokai" => Some(Theme::Monokai),
+            "gruvbox" => Some(Theme::Gruvbox),
+            "catppuccin" => Some(Theme::Catppuccin),
+            "onedark" | "one_dark" | "one-dark" => Some(Theme::OneDark),
+            "tokyonight" | "tokyo_night" | "tokyo-night" => Some(Theme::TokyoNight),
+            "solarized" | "solarized_dark" | "solarized-dark" => Some(Theme::SolarizedDark),
+            "kanagawa" => Some(Theme::Kanagawa),
+            _ => None,
         }
     }
 
     pub fn as_str(&self) -> &'static str {
         match self {
             Theme::Dark => "dark",
+            Theme::Light => "light",
             Theme::Nord => "nord",
             Theme::Monokai => "monokai",
             Theme::Gruvbox => "gruvbox",
@@ -0,0 +1,450 @@
+//! Status bar output. Waybar gets its JSON module; polybar, i3blocks and
+//! tmux get the same `waybar_format` text with their own color markup, in
+//! the color Waybar's CSS gives the usage class.
+
+pub mod waybar;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use ratatui::style::Color;
+
+use crate::config::token_expiry_notice;
+use crate::models::{
+    Config, DEFAULT_WAYBAR_TOOLTIP_MAX_MODELS, Theme, UsageStats, WaybarThresholds,
+};
+use crate::percent::format_percent;
+use crate::themes::{self, ThemeColors, to_hex};
+use crate::ui::styles::icons;
+
+/// Status bar the output is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Target {
+    /// JSON module with text, tooltip and CSS classes
+    #[default]
+    Waybar,
+    /// Text with %{F#rrggbb} color tags
+    Polybar,
+    /// Three lines: full text, short text, color
+    I3blocks,
+    /// Text with #[fg=#rrggbb] styling, for status-right
+    Tmux,
+}
+
+/// What the targets take from the config besides the format
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// `waybar_reset_soon_days`, only used by Waybar's classes
+    pub reset_soon_days: Option<u32>,
+    /// Palette the text targets are colored with
+    pub theme: Theme,
+    /// `token_expires`, for Waybar's expiry class and tooltip line
+    pub token_expires: Option<NaiveDate>,
+    /// `waybar_tooltip_max_models`: models listed in the tooltip
+    pub tooltip_max_models: usize,
+    /// `waybar_markup`: bold headings in the tooltip
+    pub markup: bool,
+    /// `waybar_thresholds`: where the zone classes change
+    pub thresholds: WaybarThresholds,
+    /// Fetch time of data shown because refreshing keeps failing (see
+    /// `is_stale`), for Waybar's `copilot-stale` class
+    pub stale_since: Option<DateTime<Utc>>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            reset_soon_days: None,
+            theme: Theme::Dark,
+            token_expires: None,
+            tooltip_max_models: DEFAULT_WAYBAR_TOOLTIP_MAX_MODELS,
+            markup: false,
+            thresholds: WaybarThresholds::default(),
+            stale_since: None,
+        }
+    }
+}
+
+impl Options {
+    /// A status bar has no terminal to ask, so `auto` is dark, as for Waybar
+    pub fn from_config(config: &Config) -> Self {
+        let theme = if config.theme.eq_ignore_ascii_case(themes::AUTO) {
+            Theme::Dark
+        } else {
+            Theme::from_str(&config.theme)
+        };
+        Self {
+            reset_soon_days: config.waybar_reset_soon_days,
+            theme,
+            token_expires: config.token_expires,
+            tooltip_max_models: config.waybar_tooltip_max_models,
+            markup: config.waybar_markup,
+            thresholds: config.waybar_thresholds,
+            stale_since: None,
+        }
+    }
+}
+
+/// Data kept past this many TTLs means refreshing keeps failing
+const STALE_TTLS: u32 = 2;
+
+/// Whether data `age` old, with a cache good for `ttl`, is stale enough to
+/// be marked as such
+pub fn is_stale(age: std::time::Duration, ttl: std::time::Duration) -> bool {
+    age > ttl.saturating_mul(STALE_TTLS)
+}
+
+/// The usage filled into `format`, as `target` prints it
+pub fn generate(stats: &UsageStats, target: Target, format: &str, options: &Options) -> String {
+    generate_at(stats, target, format, options, Utc::now())
+}
+
+fn generate_at(
+    stats: &UsageStats,
+    target: Target,
+    format: &str,
+    options: &Options,
+    now: DateTime<Utc>,
+) -> String {
+    if target == Target::Waybar {
+        let mut output = waybar::output_at(stats, format, options, now);
+        if let Some(notice) = token_expiry_notice(options.token_expires, now.date_naive()) {
+            output.class.push(waybar::CLASS_TOKEN_EXPIRING.to_string());
+            output.tooltip = format!("{}\n\n{}", output.tooltip, waybar::escape_markup(&notice));
+        }
+        return serde_json::to_string(&output).unwrap_or_default();
+    }
+    let text = waybar::format_text(format, stats, now);
+    let short = format!("{}%", format_percent(stats.percentage, 0));
+    let color = class_color(usage_class(stats, options), options.theme).and_then(to_hex);
+    markup(target, &text, &short, color)
+}
+
+/// Template of `copilot-usage prompt` when none is given
+pub fn default_prompt_template() -> String {
+    format!("{} {{percentage}}%", icons().robot)
+}
+
+/// What `copilot-usage prompt` prints and when it hides
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptSegment {
+    /// Text with the `waybar_format` placeholders
+    pub template: String,
+    /// Older data hides the segment
+    pub max_cache_age: Option<std::time::Duration>,
+    /// Wrap it in the ANSI escape of the usage color
+    pub color: bool,
+}
+
+impl PromptSegment {
+    /// The segment for usage fetched `age` ago, or nothing when that's past
+    /// `max_cache_age`
+    pub fn render(
+        &self,
+        stats: &UsageStats,
+        stale: bool,
+        age: std::time::Duration,
+        options: &Options,
+    ) -> Option<String> {
+        if self.max_cache_age.is_some_and(|max_age| age > max_age) {
+            return None;
+        }
+        Some(prompt(stats, &self.template, stale, self.color, options))
+    }
+}
+
+/// Shell prompt segment: `template` filled and, with `color`, wrapped in
+/// the ANSI escape of the usage color. Stale data gets a `~` in front.
+pub fn prompt(
+    stats: &UsageStats,
+    template: &str,
+    stale: bool,
+    color: bool,
+    options: &Options,
+) -> String {
+    prompt_at(stats, template, stale, color, options, Utc::now())
+}
+
+fn prompt_at(
+    stats: &UsageStats,
+    template: &str,
+    stale: bool,
+    color: bool,
+    options: &Options,
+    now: DateTime<Utc>,
+) -> String {
+    let mut text = waybar::format_text(template, stats, now);
+    if stale {
+        text.insert(0, '~');
+    }
+    match class_color(usage_class(stats, options), options.theme) {
+        Some(Color::Rgb(r, g, b)) if color => {
+            format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, text)
+        }
+        _ => text,
+    }
+}
+
+/// Waybar class the usage is colored by: past the limit or the budget wins
+/// over the zone
+fn usage_class(stats: &UsageStats, options: &Options) -> &'static str {
+    if stats.over_limit() {
+        waybar::CLASS_OVER_LIMIT
+    } else if stats.over_budget() {
+        waybar::CLASS_OVER_BUDGET
+    } else {
+        options.thresholds.zone(stats.percentage).css_class()
+    }
+}
+
+/// Warning shown when usage couldn't be fetched; Waybar also gets the error
+/// as tooltip, the others find it on stderr
+pub fn error_output(target: Target, error: &anyhow::Error, options: &Options) -> String {
+    match target {
+        Target::Waybar => waybar::error_output(error, options),
+        _ => warning(target, waybar::CLASS_ERROR, options),
+    }
+}
+
+/// Shown until `copilot-usage` has been set up interactively
+pub fn unconfigured_output(target: Target, options: &Options) -> String {
+    match target {
+        Target::Waybar => waybar::unconfigured_output(options),
+        _ => warning(target, waybar::CLASS_UNCONFIGURED, options),
+    }
+}
+
+fn warning(target: Target, class: &str, options: &Options) -> String {
+    let icon = icons().warning;
+    let color = class_color(class, options.theme).and_then(to_hex);
+    markup(target, icon, icon, color)
+}
+
+/// Color of a Waybar class in `theme`, as `theme css` styles it
+fn class_color(class: &str, theme: Theme) -> Option<Color> {
+    let name = waybar::CLASS_COLORS
+        .iter()
+        .find(|(c, _)| *c == class)
+        .map(|(_, name)| *name)?;
+    ThemeColors::from_theme(theme)
+        .palette()
+        .into_iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, color)| color)
+}
+
+/// `text` with the markup of a text target; without a color it's left plain
+fn markup(target: Target, text: &str, short: &str, color: Option<String>) -> String {
+    match (target, color) {
+        (Target::Polybar, Some(color)) => format!("%{{F{}}}{}%{{F-}}", color, text),
+        // En tmux `#` empieza un formato: el texto lo lleva doblado
+        (Target::Tmux, Some(color)) => {
+            format!("#[fg={}]{}#[fg=default]", color, text.replace('#', "##"))
+        }
+        (Target::Tmux, None) => text.replace('#', "##"),
+        (Target::I3blocks, color) => {
+            format!("{}\n{}\n{}", text, short, color.unwrap_or_default())
+        }
+        (Target::Polybar | Target::Waybar, _) => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WaybarOutput;
+    use crate::testing;
+    use chrono::TimeZone;
+
+    fn stats(percentage: f64) -> UsageStats {
+        UsageStats {
+            percentage,
+            ..testing::stats(percentage * 3.0)
+        }
+    }
+
+    fn render(target: Target, percentage: f64) -> String {
+        let now = Utc.with_ymd_and_hms(2026, 2, 15, 12, 0, 0).unwrap();
+        generate_at(
+            &stats(percentage),
+            target,
+            "{percentage}%",
+            &Options::default(),
+            now,
+        )
+    }
+
+    /// Low, warning and critical usage, with the dark palette's color for each
+    fn levels() -> [(f64, String); 3] {
+        let colors = ThemeColors::dark();
+        let hex = |color| to_hex(color).unwrap();
+        [
+            (30.0, hex(colors.success)),
+            (80.0, hex(colors.warning)),
+            (95.0, hex(colors.error)),
+        ]
+    }
+
+    #[test]
+    fn test_waybar_is_the_json_module() {
+        for (percentage, class) in [
+            (30.0, "copilot-low"),
+            (80.0, "copilot-warning"),
+            (95.0, "copilot-critical"),
+        ] {
+            let out: WaybarOutput =
+                serde_json::from_str(&render(Target::Waybar, percentage)).unwrap();
+            assert_eq!(out.text, format!("{}%", percentage));
+            assert_eq!(out.class, [class]);
+        }
+    }
+
+    #[test]
+    fn test_waybar_warns_before_the_token_expires() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 15, 12, 0, 0).unwrap();
+        let render = |expires: Option<NaiveDate>| -> WaybarOutput {
+            let options = Options {
+                token_expires: expires,
+                ..Options::default()
+            };
+            let out = generate_at(&stats(30.0), Target::Waybar, "{percentage}%", &options, now);
+            serde_json::from_str(&out).unwrap()
+        };
+
+        let soon = render(NaiveDate::from_ymd_opt(2026, 2, 20));
+        assert_eq!(soon.class, ["copilot-low", waybar::CLASS_TOKEN_EXPIRING]);
+        let warning = "Token expires in 5 day(s), on 2026-02-20: run copilot-usage reconfigure";
+        assert!(soon.tooltip.ends_with(warning), "{}", soon.tooltip);
+        let later = render(NaiveDate::from_ymd_opt(2026, 3, 1));
+        assert_eq!(later.class, ["copilot-low"]);
+        assert_eq!(render(None).class, ["copilot-low"]);
+    }
+
+    #[test]
+    fn test_polybar() {
+        for (percentage, color) in levels() {
+            assert_eq!(
+                render(Target::Polybar, percentage),
+                format!("%{{F{}}}{}%%{{F-}}", color, percentage)
+            );
+        }
+    }
+
+    #[test]
+    fn test_i3blocks() {
+        for (percentage, color) in levels() {
+            let out = render(Target::I3blocks, percentage);
+            let lines: Vec<&str> = out.lines().collect();
+            assert_eq!(
+                lines,
+                [
+                    format!("{}%", percentage),
+                    format!("{}%", percentage),
+                    color
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_tmux() {
+        for (percentage, color) in levels() {
+            assert_eq!(
+                render(Target::Tmux, percentage),
+                format!("#[fg={}]{}%#[fg=default]", color, percentage)
+            );
+        }
+        // Un `#` del formato no abre un estilo
+        let now = Utc.with_ymd_and_hms(2026, 2, 15, 12, 0, 0).unwrap();
+        let out = generate_at(
+            &stats(30.0),
+            Target::Tmux,
+            "#{used}",
+            &Options::default(),
+            now,
+        );
+        assert!(out.contains("]##90#["), "{}", out);
+    }
+
+    #[test]
+    fn test_over_limit_is_red() {
+        let red = to_hex(ThemeColors::dark().error).unwrap();
+        assert!(render(Target::Polybar, 120.0).starts_with(&format!("%{{F{}}}", red)));
+        assert!(render(Target::I3blocks, 120.0).ends_with(&format!("\n120%\n{}", red)));
+    }
+
+    #[test]
+    fn test_text_targets_follow_thresholds() {
+        let options = Options {
+            thresholds: WaybarThresholds {
+                low: 10.0,
+                warning: 20.0,
+                critical: 25.0,
+            },
+            ..Options::default()
+        };
+        let now = Utc.with_ymd_and_hms(2026, 2, 15, 12, 0, 0).unwrap();
+        let red = to_hex(ThemeColors::dark().error).unwrap();
+        let out = generate_at(&stats(30.0), Target::Tmux, "{percentage}%", &options, now);
+        assert!(out.starts_with(&format!("#[fg={}]", red)));
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let ttl = std::time::Duration::from_secs(300);
+        assert!(!is_stale(std::time::Duration::from_secs(400), ttl));
+        assert!(!is_stale(std::time::Duration::from_secs(600), ttl));
+        assert!(is_stale(std::time::Duration::from_secs(601), ttl));
+    }
+
+    #[test]
+    fn test_error_outputs() {
+        let error = anyhow::anyhow!("boom");
+        let options = Options::default();
+        let red = to_hex(ThemeColors::dark().error).unwrap();
+        let muted = to_hex(ThemeColors::dark().muted).unwrap();
+        let waybar: WaybarOutput =
+            serde_json::from_str(&error_output(Target::Waybar, &error, &options)).unwrap();
+        assert_eq!(waybar.class, [waybar::CLASS_ERROR]);
+        assert!(
+            error_output(Target::Tmux, &error, &options).starts_with(&format!("#[fg={}]", red))
+        );
+        assert!(unconfigured_output(Target::I3blocks, &options).ends_with(&muted));
+    }
+
+    #[test]
+    fn test_prompt_segment() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 15, 12, 0, 0).unwrap();
+        let options = Options::default();
+        let segment = |percentage, stale, color| {
+            prompt_at(
+                &stats(percentage),
+                "{percentage}%",
+                stale,
+                color,
+                &options,
+                now,
+            )
+        };
+        assert_eq!(segment(30.0, false, false), "30%");
+        assert_eq!(segment(30.0, true, false), "~30%");
+        // Verde de success y rojo de error del tema oscuro
+        assert_eq!(
+            segment(30.0, false, true),
+            "\x1b[38;2;100;255;140m30%\x1b[0m"
+        );
+        assert_eq!(
+            segment(95.0, true, true),
+            "\x1b[38;2;255;100;100m~95%\x1b[0m"
+        );
+        assert!(default_prompt_template().ends_with(" {percentage}%"));
+    }
+
+    #[test]
+    fn test_auto_theme_is_dark() {
+        let config = Config {
+            theme: themes::AUTO.to_string(),
+            ..Config::default()
+        };
+        assert_eq!(Options::from_config(&config).theme, Theme::Dark);
+    }
+}
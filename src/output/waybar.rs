@@ -0,0 +1,857 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::Options;
+use crate::models::{Theme, UsageStats, WaybarOutput};
+use crate::percent::{Zone, format_percent, format_quantity, round_half_up};
+use crate::projection::{Outlook, Projection, calculate_projection};
+use crate::redact::redact;
+use crate::themes::{ThemeColors, to_hex};
+use crate::ui::styles::{format_countdown, format_elapsed, icons};
+
+/// Placeholders understood in `waybar_format`, with a short description.
+/// Literal braces are written `{{` and `}}`.
+pub const PLACEHOLDERS: [(&str, &str); 18] = [
+    ("used", "premium requests used"),
+    ("included", "requests covered by the plan"),
+    ("billed", "requests billed beyond the plan"),
+    ("limit", "monthly premium request limit"),
+    ("remaining", "requests left this month"),
+    ("percentage", "usage percentage, rounded (75)"),
+    ("percentage_precise", "usage percentage, one decimal (74.9)"),
+    ("cost", "estimated cost in USD (1.20)"),
+    ("reset_date", "date the quota resets (Mar 01)"),
+    ("days_until_reset", "days left until the reset"),
+    ("month_elapsed", "billing period gone by, in % (34)"),
+    ("days_left", "whole days left, 0 on the last day"),
+    ("reset_in", "time until the reset (3d 4h, 9h 30m)"),
+    ("time_until_reset", "time until the reset, by hours (2d 14h, 6h)"),
+    ("projected", "usage at the reset at the current pace (212)"),
+    ("runs_out", "date the limit runs out at this pace (Jun 24)"),
+    ("top_model", "most used model"),
+    ("model_count", "models used this month"),
+];
+
+/// Class of the module when usage couldn't be fetched
+pub const CLASS_ERROR: &str = "copilot-error";
+/// Class of the module before the first interactive setup
+pub const CLASS_UNCONFIGURED: &str = "copilot-unconfigured";
+/// Extra class near the end of the billing period (`waybar_reset_soon_days`)
+pub const CLASS_RESET_SOON: &str = "copilot-reset-soon";
+/// Extra class once usage passes the plan's limit and requests are billed
+pub const CLASS_OVER_LIMIT: &str = "copilot-over";
+/// Extra class once the estimated cost passes `[budget] monthly_budget_usd`
+pub const CLASS_OVER_BUDGET: &str = "copilot-over-budget";
+/// Extra class in the last week before the token's `token_expires`
+pub const CLASS_TOKEN_EXPIRING: &str = "copilot-token-expiring";
+/// Extra class when the usage shown is past twice the cache TTL because
+/// refreshing keeps failing
+pub const CLASS_STALE: &str = "copilot-stale";
+
+pub fn generate_output(stats: &UsageStats, format: &str, options: &Options) -> String {
+    serde_json::to_string(&output_at(stats, format, options, Utc::now())).unwrap_or_default()
+}
+
+pub(super) fn output_at(
+    stats: &UsageStats,
+    format: &str,
+    options: &Options,
+    now: DateTime<Utc>,
+) -> WaybarOutput {
+    let text = format_text(format, stats, now);
+    let mut tooltip = format_tooltip(stats, calculate_projection(stats, now), options);
+    let zone = options.thresholds.zone(stats.percentage);
+    let mut class = vec![zone.css_class().to_string()];
+    if stats.over_limit() {
+        class.push(CLASS_OVER_LIMIT.to_string());
+    }
+    if stats.over_budget() {
+        class.push(CLASS_OVER_BUDGET.to_string());
+    }
+    if let Some(days) = options.reset_soon_days
+        && stats.until_reset(now) < Duration::days(i64::from(days))
+    {
+        class.push(CLASS_RESET_SOON.to_string());
+    }
+    if let Some(fetched_at) = options.stale_since {
+        class.push(CLASS_STALE.to_string());
+        tooltip.push_str(&format!(
+            "\n\nStale: refresh failing, data from {} ago",
+            format_elapsed((now - fetched_at).to_std().unwrap_or_default())
+        ));
+    }
+
+    WaybarOutput {
+        text,
+        tooltip,
+        class,
+        percentage: Some(round_half_up(stats.percentage.max(0.0), 0) as u32),
+    }
+}
+
+/// `format` with its placeholders filled, for every output target. Unknown
+/// placeholders are warned about on stderr and left as is.
+pub(super) fn format_text(format: &str, stats: &UsageStats, now: DateTime<Utc>) -> String {
+    let (text, unknown) = substitute(format, stats, now);
+    for name in unknown {
+        eprintln!(
+            "Warning: unknown placeholder {{{}}} in waybar_format, left as is",
+            name
+        );
+    }
+    text
+}
+
+/// Fills the placeholders of `format`. Unknown placeholders and unmatched
+/// braces are kept literally; the names of unknown ones are returned.
+fn substitute(format: &str, stats: &UsageStats, now: DateTime<Utc>) -> (String, Vec<String>) {
+    let mut text = String::with_capacity(format.len());
+    let mut unknown = Vec::new();
+    let mut rest = format;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        text.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            text.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        let closing = tail.starts_with('{').then(|| tail.find('}')).flatten();
+        match closing {
+            Some(end) => {
+                let name = &tail[1..end];
+                match placeholder_value(name, stats, now) {
+                    Some(value) => text.push_str(&value),
+                    None => {
+                        text.push_str(&tail[..=end]);
+                        if !unknown.iter().any(|u| u == name) {
+                            unknown.push(name.to_string());
+                        }
+                    }
+                }
+                rest = &tail[end + 1..];
+            }
+            None => {
+                // Stray brace: keep it
+                text.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    (text, unknown)
+}
+
+fn placeholder_value(name: &str, stats: &UsageStats, now: DateTime<Utc>) -> Option<String> {
+    let value = match name {
+        "used" => format_quantity(stats.total_used),
+        "included" => format_quantity(stats.totals.discount_quantity),
+        "billed" => format_quantity(stats.totals.net_quantity),
+        "limit" => format_quantity(stats.total_limit),
+        "remaining" => format_quantity((stats.total_limit - stats.total_used).max(0.0)),
+        "percentage" => format_percent(stats.percentage, 0),
+        "percentage_precise" => format_percent(stats.percentage, 1),
+        "cost" => format!("{:.2}", stats.estimated_cost),
+        "reset_date" => stats.reset_date.format("%b %d").to_string(),
+        "days_until_reset" => (stats.next_reset(now).date_naive() - now.date_naive())
+            .num_days()
+            .max(0)
+            .to_string(),
+        "month_elapsed" => format_percent(stats.period_elapsed(now), 0),
+        "days_left" => stats.until_reset(now).num_days().to_string(),
+        "reset_in" => format_elapsed(stats.until_reset(now).to_std().unwrap_or_default()),
+        "time_until_reset" => format_countdown(stats.until_reset(now)),
+        "projected" => calculate_projection(stats, now).map_or_else(
+            || "-".to_string(),
+            |projection| format!("{:.0}", projection.at_reset),
+        ),
+        "runs_out" => match calculate_projection(stats, now).map(|projection| projection.outlook) {
+            Some(Outlook::RunsOut(date)) => date.format("%b %d").to_string(),
+            Some(Outlook::LimitReached) => "now".to_string(),
+            _ => "-".to_string(),
+        },
+        "top_model" => stats
+            .top_model
+            .as_ref()
+            .map_or_else(|| "-".to_string(), |(name, _)| name.clone()),
+        "model_count" => stats.distinct_models.to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Tooltip title
+const TITLE: &str = "GitHub Copilot Usage";
+
+/// Warning module for a failed fetch, with the error chain as tooltip
+pub fn error_output(error: &anyhow::Error, options: &Options) -> String {
+    let output = WaybarOutput {
+        text: icons().warning.to_string(),
+        tooltip: format!(
+            "{}\n{}",
+            heading(TITLE, options.markup),
+            escape_markup(&redact(&format!("{:#}", error)))
+        ),
+        class: vec![CLASS_ERROR.to_string()],
+        percentage: None,
+    };
+    serde_json::to_string(&output).unwrap_or_default()
+}
+
+/// Module shown until `copilot-usage` has been set up interactively
+pub fn unconfigured_output(options: &Options) -> String {
+    let output = WaybarOutput {
+        text: icons().warning.to_string(),
+        tooltip: format!(
+            "{}\nNot configured yet: run copilot-usage in a terminal",
+            heading(TITLE, options.markup)
+        ),
+        class: vec![CLASS_UNCONFIGURED.to_string()],
+        percentage: None,
+    };
+    serde_json::to_string(&output).unwrap_or_default()
+}
+
+/// `text` safe inside Pango markup, which Waybar parses tooltips as
+pub(super) fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A tooltip heading, bold with `waybar_markup`
+fn heading(text: &str, markup: bool) -> String {
+    if markup {
+        format!("<b>{}</b>", text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn format_tooltip(stats: &UsageStats, projection: Option<Projection>, options: &Options) -> String {
+    let mut tooltip = format!(
+        "{}\n{} / {} ({}%)\nResets: {}",
+        heading(TITLE, options.markup),
+        stats.total_used,
+        stats.total_limit,
+        format_percent(stats.percentage, 1),
+        stats.reset_date.format("%B %d, %Y at %H:%M UTC")
+    );
+
+    if let Some(projection) = projection {
+        let line = match projection.outlook {
+            Outlook::RunsOut(date) => format!("Projected: runs out {}", date.format("%b %d")),
+            Outlook::LimitReached => "Projected: limit reached".to_string(),
+            Outlook::Lasts => format!(
+                "On pace: ~{:.0}/{:.0} by reset",
+                projection.at_reset, stats.total_limit
+            ),
+        };
+        tooltip.push('\n');
+        tooltip.push_str(&line);
+    }
+
+    if !stats.models.is_empty() {
+        tooltip.push_str("\n\n");
+        tooltip.push_str(&heading("Per-model usage:", options.markup));
+        for model in stats.models.iter().take(options.tooltip_max_models) {
+            tooltip.push_str(&format!(
+                "\n  {}: {:.0} ({}%)",
+                escape_markup(&model.name),
+                model.used,
+                format_percent(model.percentage, 1)
+            ));
+        }
+        let hidden = stats
+            .models
+            .len()
+            .saturating_sub(options.tooltip_max_models);
+        if hidden > 0 {
+            tooltip.push_str(&format!("\n  …and {} more", hidden));
+        }
+    }
+
+    let mut billing = Vec::new();
+    if stats.totals.net_quantity > 0.0 {
+        billing.push(format!(
+            "Billed: {} beyond the {} included",
+            format_quantity(stats.totals.net_quantity),
+            format_quantity(stats.totals.discount_quantity)
+        ));
+    }
+    if stats.estimated_cost > 0.0 {
+        billing.push(format!("Estimated cost: ${:.2}", stats.estimated_cost));
+    }
+    if let Some(budget) = stats.cost_budget {
+        billing.push(format!(
+            "Budget: ${:.2} / ${:.2} ({}%)",
+            stats.estimated_cost,
+            budget,
+            format_percent(stats.estimated_cost / budget * 100.0, 0)
+        ));
+    }
+    if !billing.is_empty() {
+        tooltip.push_str("\n\n");
+        tooltip.push_str(&billing.join("\n"));
+    }
+
+    tooltip
+}
+
+/// Palette color each waybar class is drawn with
+pub(super) const CLASS_COLORS: [(&str, &str); 9] = [
+    (Zone::Low.css_class(), "success"),
+    (Zone::Normal.css_class(), "foreground"),
+    (Zone::Warning.css_class(), "warning"),
+    (Zone::Critical.css_class(), "error"),
+    (CLASS_ERROR, "error"),
+    (CLASS_UNCONFIGURED, "muted"),
+    // Después de las zonas: con las dos clases gana esta
+    (CLASS_OVER_LIMIT, "error"),
+    (CLASS_OVER_BUDGET, "error"),
+    // Los datos viejos se ven apagados aunque estén por encima del límite
+    (CLASS_STALE, "muted"),
+];
+
+/// CSS snippet with the theme palette as custom properties and rules for
+/// every class emitted by `generate_output`
+pub fn theme_css(theme: Theme) -> String {
+    let colors = ThemeColors::from_theme(theme);
+    let mut css = format!("/* copilot-usage theme: {} */\n* {{\n", theme.as_str());
+    for (name, color) in colors.palette() {
+        if let Some(hex) = to_hex(color) {
+            css.push_str(&format!("  --copilot-{}: {};\n", name, hex));
+        }
+    }
+    css.push_str("}\n");
+
+    for (class, name) in CLASS_COLORS {
+        css.push_str(&format!(
+            "\n#custom-copilot.{} {{\n  color: var(--copilot-{});\n}}\n",
+            class, name
+        ));
+    }
+    css
+}
+
+/// The theme palette as a JSON object of `name: "#rrggbb"`
+pub fn theme_json(theme: Theme) -> String {
+    let palette: serde_json::Map<String, serde_json::Value> = ThemeColors::from_theme(theme)
+        .palette()
+        .into_iter()
+        .filter_map(|(name, color)| to_hex(color).map(|hex| (name.to_string(), hex.into())))
+        .collect();
+    serde_json::to_string_pretty(&palette).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ModelUsage, WaybarThresholds, model_distribution};
+    use crate::testing::{self, model};
+    use crate::ui::styles::format_percentage;
+    use chrono::{TimeZone, Utc};
+
+    fn stats(percentage: f64) -> UsageStats {
+        UsageStats {
+            percentage,
+            ..testing::stats(percentage * 3.0)
+        }
+    }
+
+    fn output(percentage: f64) -> WaybarOutput {
+        serde_json::from_str(&generate_output(
+            &stats(percentage),
+            "{percentage}%",
+            &Options::default(),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_css_classes() {
+        assert_eq!(output(95.0).class, ["copilot-critical"]);
+        assert_eq!(output(80.0).class, ["copilot-warning"]);
+        assert_eq!(output(60.0).class, ["copilot-normal"]);
+        assert_eq!(output(30.0).class, ["copilot-low"]);
+    }
+
+    #[test]
+    fn test_over_limit_class() {
+        // Justo en el límite todavía no hay exceso
+        assert_eq!(output(100.0).class, ["copilot-critical"]);
+        assert_eq!(output(100.1).class, ["copilot-critical", CLASS_OVER_LIMIT]);
+        assert_eq!(output(200.0).class, ["copilot-critical", CLASS_OVER_LIMIT]);
+    }
+
+    #[test]
+    fn test_boundaries_agree_with_tui() {
+        for (percentage, text, tui, class) in [
+            (74.95, "75%", "75.0%", "copilot-warning"),
+            (89.95, "90%", "90.0%", "copilot-critical"),
+            (99.95, "100%", "100.0%", "copilot-critical"),
+        ] {
+            let out = output(percentage);
+            assert_eq!(out.text, text);
+            assert_eq!(out.class, [class]);
+            assert!(out.tooltip.contains(&format!("({})", tui)));
+            assert_eq!(format_percentage(percentage), tui);
+        }
+    }
+
+    fn stats_with_models() -> UsageStats {
+        let mut stats = stats(50.0);
+        stats.total_used = 150.5;
+        stats.estimated_cost = 1.2;
+        stats.models = vec![
+            ModelUsage {
+                percentage: 33.5,
+                ..model("claude-sonnet-4", 100.5)
+            },
+            ModelUsage {
+                percentage: 16.7,
+                ..model("gpt-4.1", 50.0)
+            },
+        ];
+        (stats.top_model, stats.distinct_models) = model_distribution(&stats.models);
+        stats
+    }
+
+    fn fill(format: &str) -> String {
+        let now = Utc.with_ymd_and_hms(2026, 2, 20, 12, 0, 0).unwrap();
+        let (text, unknown) = substitute(format, &stats_with_models(), now);
+        assert!(unknown.is_empty(), "unknown placeholders: {:?}", unknown);
+        text
+    }
+
+    #[test]
+    fn test_each_placeholder() {
+        assert_eq!(fill("{used}"), "150.5");
+        assert_eq!(fill("{limit}"), "300");
+        assert_eq!(fill("{remaining}"), "149.5");
+        assert_eq!(fill("{percentage}"), "50");
+        assert_eq!(fill("{percentage_precise}"), "50.0");
+        assert_eq!(fill("{cost}"), "1.20");
+        assert_eq!(fill("{reset_date}"), "Mar 01");
+        assert_eq!(fill("{days_until_reset}"), "9");
+        assert_eq!(fill("{top_model}"), "claude-sonnet-4");
+        assert_eq!(fill("{model_count}"), "2");
+        assert_eq!(fill("{projected}"), "216");
+        assert_eq!(fill("{runs_out}"), "-");
+        assert_eq!(
+            fill(" {used}/{limit} ({percentage}%)"),
+            " 150.5/300 (50%)"
+        );
+    }
+
+    #[test]
+    fn test_billed_placeholders_and_tooltip() {
+        let mut stats = stats_with_models();
+        stats.totals.discount_quantity = 120.0;
+        stats.totals.net_quantity = 30.5;
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+
+        let out = output_at(&stats, "{included}+{billed}", &Options::default(), now);
+        assert_eq!(out.text, "120+30.5");
+        assert!(
+            out.tooltip
+                .ends_with("\n\nBilled: 30.5 beyond the 120 included\nEstimated cost: $1.20")
+        );
+
+        // Sin nada facturado no hay línea
+        stats.totals.net_quantity = 0.0;
+        let out = output_at(&stats, "{billed}", &Options::default(), now);
+        assert_eq!(out.text, "0");
+        assert!(!out.tooltip.contains("Billed"));
+    }
+
+    #[test]
+    fn test_over_budget_class_and_tooltip() {
+        let mut stats = stats_with_models();
+        stats.cost_budget = Some(5.0);
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+
+        let out = output_at(&stats, "", &Options::default(), now);
+        assert_eq!(out.class, ["copilot-normal"]);
+        assert!(out.tooltip.ends_with("\nBudget: $1.20 / $5.00 (24%)"));
+        // Redondea como el porcentaje del texto, no como `{:.0}` (12.5 -> "12")
+        stats.estimated_cost = 0.625;
+        let out = output_at(&stats, "", &Options::default(), now);
+        assert!(
+            out.tooltip.ends_with("\nBudget: $0.62 / $5.00 (13%)"),
+            "{}",
+            out.tooltip
+        );
+
+        stats.estimated_cost = 6.0;
+        let out = output_at(&stats, "", &Options::default(), now);
+        assert_eq!(out.class, ["copilot-normal", CLASS_OVER_BUDGET]);
+        assert!(out.tooltip.ends_with("\nBudget: $6.00 / $5.00 (120%)"));
+    }
+
+    #[test]
+    fn test_tooltip_escapes_markup() {
+        let mut stats = stats_with_models();
+        stats.models[0].name = "models/<weird>&name".to_string();
+        stats.models[1].name = "it's \"quoted\"".to_string();
+        let now = Utc.with_ymd_and_hms(2026, 2, 20, 12, 0, 0).unwrap();
+
+        let tooltip = output_at(&stats, "", &Options::default(), now).tooltip;
+        assert!(tooltip.contains("\n  models/&lt;weird&gt;&amp;name: 100 (33.5%)"));
+        assert!(tooltip.contains("\n  it&apos;s &quot;quoted&quot;: 50 (16.7%)"));
+        assert!(!tooltip.contains(['<', '>', '"', '\'']), "{}", tooltip);
+
+        let error = anyhow::anyhow!("unexpected <html> & \"stuff\"");
+        let out: WaybarOutput =
+            serde_json::from_str(&error_output(&error, &Options::default())).unwrap();
+        assert_eq!(
+            out.tooltip,
+            "GitHub Copilot Usage\nunexpected &lt;html&gt; &amp; &quot;stuff&quot;"
+        );
+    }
+
+    #[test]
+    fn test_tooltip_truncates_models() {
+        let mut stats = stats_with_models();
+        let model = stats.models[1].clone();
+        for n in 0..10 {
+            stats.models.push(ModelUsage {
+                name: format!("model-{}", n),
+                ..model.clone()
+            });
+        }
+        let now = Utc.with_ymd_and_hms(2026, 2, 20, 12, 0, 0).unwrap();
+
+        let tooltip = output_at(&stats, "", &Options::default(), now).tooltip;
+        assert!(tooltip.contains("\n  model-5: 50 (16.7%)\n  …and 4 more"));
+        assert!(!tooltip.contains("model-6"));
+
+        let options = Options {
+            tooltip_max_models: 20,
+            ..Options::default()
+        };
+        let tooltip = output_at(&stats, "", &options, now).tooltip;
+        assert!(tooltip.contains("model-9") && !tooltip.contains("more"));
+    }
+
+    #[test]
+    fn test_tooltip_markup_headings() {
+        let mut stats = stats_with_models();
+        stats.models[0].name = "a<b>".to_string();
+        let now = Utc.with_ymd_and_hms(2026, 2, 20, 12, 0, 0).unwrap();
+        let options = Options {
+            markup: true,
+            ..Options::default()
+        };
+
+        let tooltip = output_at(&stats, "", &options, now).tooltip;
+        assert!(tooltip.starts_with("<b>GitHub Copilot Usage</b>\n"));
+        assert!(tooltip.contains("\n\n<b>Per-model usage:</b>\n  a&lt;b&gt;: 100"));
+        let out: WaybarOutput = serde_json::from_str(&unconfigured_output(&options)).unwrap();
+        assert!(out.tooltip.starts_with("<b>GitHub Copilot Usage</b>\n"));
+    }
+
+    #[test]
+    fn test_placeholder_edge_values() {
+        let mut over = stats(120.0);
+        over.total_used = 360.0;
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+        let (text, _) = substitute("{remaining} {top_model} {days_until_reset}", &over, now);
+        // El reinicio del caché ya pasó: cuenta hasta el de abril
+        assert_eq!(text, "0 - 30");
+    }
+
+    #[test]
+    fn test_billing_period_placeholders() {
+        // stats() resets on Mar 01: the period is Feb 01 - Mar 01 (28 days)
+        let at = |day, hour, minute| {
+            let now = Utc.with_ymd_and_hms(2026, 2, day, hour, minute, 0).unwrap();
+            substitute("{month_elapsed} {days_left} {reset_in}", &stats(50.0), now).0
+        };
+        assert_eq!(at(1, 0, 0), "0 28 28d 0h");
+        assert_eq!(at(15, 0, 0), "50 14 14d 0h");
+        // Último día del periodo, no del mes natural de `now`
+        assert_eq!(at(28, 0, 0), "96 1 1d 0h");
+        assert_eq!(at(28, 14, 30), "99 0 9h 30m");
+        assert_eq!(at(28, 23, 59), "100 0 1m");
+
+        // Ya reseteado (datos de un caché viejo): nada negativo, cuenta
+        // hasta el siguiente reinicio
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+        let (text, _) = substitute("{month_elapsed} {days_left} {reset_in}", &stats(50.0), now);
+        assert_eq!(text, "100 30 30d 0h");
+    }
+
+    #[test]
+    fn test_time_until_reset_placeholder() {
+        let at = |day, hour, minute| {
+            let now = Utc.with_ymd_and_hms(2026, 2, day, hour, minute, 0).unwrap();
+            substitute("{time_until_reset}", &stats(50.0), now).0
+        };
+        assert_eq!(at(26, 9, 30), "2d 14h");
+        assert_eq!(at(28, 17, 15), "6h");
+        assert_eq!(at(28, 23, 20), "40m");
+    }
+
+    #[test]
+    fn test_projection_placeholders_and_tooltip() {
+        let at = |used: f64, day| {
+            let mut stats = stats(used / 3.0);
+            stats.total_used = used;
+            let now = Utc.with_ymd_and_hms(2026, 2, day, 0, 0, 0).unwrap();
+            let out = output_at(&stats, "{projected} {runs_out}", &Options::default(), now);
+            let projection = out.tooltip.lines().nth(3).unwrap_or_default().to_string();
+            (out.text, projection)
+        };
+
+        // 7 de 28 días: 150 solicitudes llevan a 600, se agota el día 15
+        let (text, tooltip) = at(150.0, 8);
+        assert_eq!(text, "600 Feb 15");
+        assert_eq!(tooltip, "Projected: runs out Feb 15");
+
+        let (text, tooltip) = at(70.0, 15);
+        assert_eq!(text, "140 -");
+        assert_eq!(tooltip, "On pace: ~140/300 by reset");
+
+        let (text, tooltip) = at(300.0, 20);
+        assert_eq!(text, "442 now");
+        assert_eq!(tooltip, "Projected: limit reached");
+
+        // Fuera del periodo no hay proyección
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+        let out = output_at(
+            &stats(50.0),
+            "{projected} {runs_out}",
+            &Options::default(),
+            now,
+        );
+        assert_eq!(out.text, "- -");
+        assert!(!out.tooltip.contains("Projected") && !out.tooltip.contains("On pace"));
+    }
+
+    #[test]
+    fn test_configured_thresholds() {
+        let options = Options {
+            thresholds: WaybarThresholds {
+                low: 20.0,
+                warning: 40.0,
+                critical: 60.0,
+            },
+            ..Options::default()
+        };
+        let now = Utc.with_ymd_and_hms(2026, 2, 20, 12, 0, 0).unwrap();
+        let class = |percentage| output_at(&stats(percentage), "", &options, now).class;
+        assert_eq!(class(10.0), ["copilot-low"]);
+        assert_eq!(class(30.0), ["copilot-normal"]);
+        assert_eq!(class(45.0), ["copilot-warning"]);
+        // Redondeado como el texto: 59.5 ya es 60
+        assert_eq!(class(59.5), ["copilot-critical"]);
+        assert_eq!(class(120.0), ["copilot-critical", CLASS_OVER_LIMIT]);
+    }
+
+    #[test]
+    fn test_percentage_field() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 20, 12, 0, 0).unwrap();
+        let percentage = |value| output_at(&stats(value), "", &Options::default(), now).percentage;
+        assert_eq!(percentage(74.5), Some(75));
+        assert_eq!(percentage(130.0), Some(130));
+        let json = serde_json::to_string(&output_at(&stats(30.0), "", &Options::default(), now));
+        assert!(json.unwrap().contains(r#""percentage":30"#));
+
+        let error = anyhow::anyhow!("boom");
+        let out: WaybarOutput =
+            serde_json::from_str(&error_output(&error, &Options::default())).unwrap();
+        assert_eq!(out.percentage, None);
+    }
+
+    #[test]
+    fn test_stale_class_and_tooltip() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 20, 12, 0, 0).unwrap();
+        let options = Options {
+            stale_since: Some(now - Duration::minutes(25)),
+            ..Options::default()
+        };
+        let out = output_at(&stats(80.0), "", &options, now);
+        assert_eq!(out.class, ["copilot-warning", CLASS_STALE]);
+        assert!(
+            out.tooltip
+                .ends_with("\n\nStale: refresh failing, data from 25m ago")
+        );
+
+        let out = output_at(&stats(80.0), "", &Options::default(), now);
+        assert!(!out.tooltip.contains("Stale"));
+    }
+
+    #[test]
+    fn test_reset_soon_class() {
+        let options = |days| Options {
+            reset_soon_days: days,
+            ..Options::default()
+        };
+        let class = |days, day| {
+            let now = Utc.with_ymd_and_hms(2026, 2, day, 12, 0, 0).unwrap();
+            output_at(&stats(80.0), "{percentage}%", &options(days), now).class
+        };
+        assert_eq!(class(None, 28), ["copilot-warning"]);
+        assert_eq!(class(Some(3), 25), ["copilot-warning"]);
+        assert_eq!(class(Some(3), 26), ["copilot-warning", CLASS_RESET_SOON]);
+        assert_eq!(class(Some(3), 28), ["copilot-warning", CLASS_RESET_SOON]);
+
+        // Con una sola clase sigue siendo un string; con dos, una lista
+        let now = Utc.with_ymd_and_hms(2026, 2, 28, 12, 0, 0).unwrap();
+        let json =
+            serde_json::to_string(&output_at(&stats(80.0), "", &options(Some(3)), now)).unwrap();
+        assert!(json.contains(r#""class":["copilot-warning","copilot-reset-soon"]"#));
+        let json =
+            serde_json::to_string(&output_at(&stats(80.0), "", &options(None), now)).unwrap();
+        assert!(json.contains(r#""class":"copilot-warning""#));
+    }
+
+    #[test]
+    fn test_placeholders_table_matches_substitutor() {
+        for (name, _) in PLACEHOLDERS {
+            assert_ne!(fill(&format!("{{{}}}", name)), format!("{{{}}}", name));
+        }
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        assert_eq!(fill("{{percentage}}"), "{percentage}");
+        assert_eq!(fill("{{{percentage}}}"), "{50}");
+        assert_eq!(fill("a }} b {{"), "a } b {");
+        assert_eq!(fill("open { brace"), "open { brace");
+        assert_eq!(fill("close } brace"), "close } brace");
+    }
+
+    #[test]
+    fn test_unknown_placeholder_left_literal() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 20, 12, 0, 0).unwrap();
+        let (text, unknown) = substitute(
+            "{percentage}% {bogus} {bogus} {}",
+            &stats_with_models(),
+            now,
+        );
+        assert_eq!(text, "50% {bogus} {bogus} {}");
+        assert_eq!(unknown, vec!["bogus".to_string(), String::new()]);
+    }
+
+    #[test]
+    fn test_theme_css() {
+        let css = theme_css(Theme::Nord);
+        assert!(css.contains("--copilot-success: #b4d296;"));
+        assert!(css.contains("#custom-copilot.copilot-critical {\n  color: var(--copilot-error);"));
+        for zone in [Zone::Low, Zone::Normal, Zone::Warning, Zone::Critical] {
+            assert!(css.contains(&format!("#custom-copilot.{} {{", zone.css_class())));
+        }
+        assert!(css.contains("#custom-copilot.copilot-error {"));
+        assert!(css.contains("#custom-copilot.copilot-unconfigured {"));
+        assert!(css.contains("#custom-copilot.copilot-over {"));
+        assert!(
+            css.contains("#custom-copilot.copilot-over-budget {\n  color: var(--copilot-error);")
+        );
+        assert!(
+            css.ends_with("#custom-copilot.copilot-stale {\n  color: var(--copilot-muted);\n}\n")
+        );
+    }
+
+    mod errors {
+        use super::*;
+        use crate::api::{ApiClient, RetryPolicy};
+        use std::time::Duration;
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        fn client(base_url: &str) -> ApiClient {
+            ApiClient::new("ghp_test".to_string())
+                .unwrap()
+                .with_base_url(base_url)
+                .with_retry_policy(RetryPolicy {
+                    max_attempts: 1,
+                    base_delay: Duration::from_millis(1),
+                })
+        }
+
+        fn parse(json: &str) -> serde_json::Value {
+            let value: serde_json::Value = serde_json::from_str(json).unwrap();
+            let keys: Vec<&str> = value
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(String::as_str)
+                .collect();
+            assert_eq!(keys.len(), 3);
+            for key in ["text", "tooltip", "class"] {
+                assert!(value[key].is_string(), "missing {}", key);
+            }
+            value
+        }
+
+        #[tokio::test]
+        async fn test_unauthorized_is_error_module() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(401))
+                .mount(&server)
+                .await;
+
+            let err = client(&server.uri())
+                .fetch_usage("testuser")
+                .await
+                .unwrap_err();
+            let out = parse(&error_output(&err, &Options::default()));
+            assert_eq!(out["text"], "⚠");
+            assert_eq!(out["class"], "copilot-error");
+            assert!(
+                out["tooltip"]
+                    .as_str()
+                    .unwrap()
+                    .contains("Unauthorized (401)")
+            );
+        }
+
+        #[tokio::test]
+        async fn test_network_failure_is_error_module() {
+            // Nothing listens on the port of a server that was shut down
+            let uri = MockServer::start().await.uri();
+
+            let err = client(&uri).fetch_usage("testuser").await.unwrap_err();
+            let out = parse(&error_output(&err, &Options::default()));
+            assert_eq!(out["class"], "copilot-error");
+            assert!(out["tooltip"].as_str().unwrap().len() > "GitHub Copilot Usage\n".len());
+        }
+
+        #[test]
+        fn test_missing_config_is_unconfigured_module() {
+            let out = parse(&unconfigured_output(&Options::default()));
+            assert_eq!(out["text"], "⚠");
+            assert_eq!(out["class"], "copilot-unconfigured");
+        }
+    }
+
+    #[test]
+    fn test_theme_json() {
+        let palette: serde_json::Value = serde_json::from_str(&theme_json(Theme::Nord)).unwrap();
+        assert_eq!(palette["success"], "#b4d296");
+        assert_eq!(palette.as_object().unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_error_tooltip_never_shows_the_token() {
+        let error = anyhow::anyhow!("401 for token ghp_A1b2C3d4E5f6G7h8I9j0");
+        let output = error_output(&error, &Options::default());
+        assert!(!output.contains("A1b2C3"), "{}", output);
+        assert!(output.contains("ghp_***"));
+    }
+}
@@ -0,0 +1,235 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+/// True when running with effective uid 0 (root or `sudo`)
+#[cfg(unix)]
+pub fn running_as_root() -> bool {
+    // SAFETY: geteuid has no preconditions and can't fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn running_as_root() -> bool {
+    false
+}
+
+/// Cache and data directories; the config one comes from `ConfigManager`,
+/// which may point elsewhere
+pub fn state_dirs() -> Vec<PathBuf> {
+    ProjectDirs::from("com", "copilot-usage", "copilot-usage")
+        .map(|dirs| {
+            vec![
+                dirs.cache_dir().to_path_buf(),
+                dirs.data_dir().to_path_buf(),
+            ]
+        })
+        .unwrap_or_default()
+}
+
+/// A file or directory owned by someone other than the current user
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignFile {
+    pub path: PathBuf,
+    pub uid: u32,
+}
+
+/// Everything under `dirs` (the dirs included) not owned by `uid`.
+/// `owner_of` reads the owner; tests pass their own.
+pub fn foreign_files(
+    dirs: &[PathBuf],
+    uid: u32,
+    owner_of: impl Fn(&Path) -> Option<u32>,
+) -> Vec<ForeignFile> {
+    let mut found = Vec::new();
+    let mut pending: Vec<PathBuf> = dirs.iter().filter(|dir| dir.exists()).cloned().collect();
+
+    while let Some(path) = pending.pop() {
+        if let Some(owner) = owner_of(&path)
+            && owner != uid
+        {
+            found.push(ForeignFile {
+                path: path.clone(),
+                uid: owner,
+            });
+        }
+        // symlink_metadata: no seguir enlaces fuera de los directorios
+        if fs::symlink_metadata(&path).is_ok_and(|meta| meta.is_dir())
+            && let Ok(entries) = fs::read_dir(&path)
+        {
+            pending.extend(entries.flatten().map(|entry| entry.path()));
+        }
+    }
+
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+}
+
+/// Owner uid from the file's metadata
+#[cfg(unix)]
+pub fn file_owner(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    fs::symlink_metadata(path).ok().map(|meta| meta.uid())
+}
+
+/// Current effective uid
+#[cfg(unix)]
+pub fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and can't fail
+    unsafe { libc::geteuid() }
+}
+
+/// Mode of files holding the token or usage data: the user's only
+#[cfg(unix)]
+const PRIVATE_FILE_MODE: u32 = 0o600;
+
+/// Mode of the config directory
+#[cfg(unix)]
+const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// Opens `path` for writing, truncated, readable by the current user only.
+/// A new file is created with that mode, so its content is never readable
+/// by others, not even briefly.
+#[cfg(unix)]
+pub fn create_private(path: &Path) -> std::io::Result<fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(PRIVATE_FILE_MODE)
+        .open(path)?;
+    // `mode` solo cuenta al crear: un fichero existente conserva el suyo
+    file.set_permissions(fs::Permissions::from_mode(PRIVATE_FILE_MODE))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+pub fn create_private(path: &Path) -> std::io::Result<fs::File> {
+    fs::File::create(path)
+}
+
+/// Makes `dir` accessible by the current user only
+#[cfg(unix)]
+pub fn restrict_dir(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(dir, fs::Permissions::from_mode(PRIVATE_DIR_MODE))
+}
+
+#[cfg(not(unix))]
+pub fn restrict_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Permission bits of `path` when its group or other users can access it
+#[cfg(unix)]
+pub fn exposed_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & 0o077 != 0).then_some(mode)
+}
+
+#[cfg(not(unix))]
+pub fn exposed_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Makes the file at `path` readable by the current user only
+#[cfg(unix)]
+pub fn restrict_file(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(PRIVATE_FILE_MODE))
+}
+
+#[cfg(not(unix))]
+pub fn restrict_file(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// `sudo chown -R alice: <dirs>` giving the directories back to `user`
+pub fn chown_command(user: &str, dirs: &[PathBuf]) -> String {
+    let mut command = format!("sudo chown -R {}:", user);
+    for dir in dirs {
+        command.push(' ');
+        command.push_str(&shell_quote(&dir.to_string_lossy()));
+    }
+    command
+}
+
+fn shell_quote(text: &str) -> String {
+    if text
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._-~".contains(c))
+    {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_files_owned_by_someone_else() {
+        let config = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        fs::write(config.path().join("config.toml"), "").unwrap();
+        fs::write(cache.path().join("usage-default.json"), "{}").unwrap();
+        fs::create_dir(cache.path().join("quarantine")).unwrap();
+        fs::write(cache.path().join("quarantine").join("usage.json"), "").unwrap();
+
+        // Simula lo que deja un `sudo copilot-usage`: el caché de root
+        let root_owned = |path: &Path| {
+            let name = path.file_name()?.to_str()?;
+            Some(if name.starts_with("usage") { 0 } else { 1000 })
+        };
+        let dirs = [config.path().to_path_buf(), cache.path().to_path_buf()];
+        let found = foreign_files(&dirs, 1000, root_owned);
+
+        assert_eq!(
+            found,
+            vec![
+                ForeignFile {
+                    path: cache.path().join("quarantine").join("usage.json"),
+                    uid: 0,
+                },
+                ForeignFile {
+                    path: cache.path().join("usage-default.json"),
+                    uid: 0,
+                },
+            ]
+        );
+        assert!(foreign_files(&dirs, 1000, |_| Some(1000)).is_empty());
+    }
+
+    #[test]
+    fn test_missing_dirs_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("never-created");
+        assert!(foreign_files(&[missing], 1000, |_| Some(0)).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_own_files_are_not_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("config.toml"), "").unwrap();
+        let dirs = [dir.path().to_path_buf()];
+        assert!(foreign_files(&dirs, current_uid(), file_owner).is_empty());
+    }
+
+    #[test]
+    fn test_chown_command() {
+        let dirs = [
+            PathBuf::from("/home/alice/.config/copilot-usage"),
+            PathBuf::from("/home/alice/My Cache/copilot-usage"),
+        ];
+        assert_eq!(
+            chown_command("alice", &dirs),
+            "sudo chown -R alice: /home/alice/.config/copilot-usage '/home/alice/My Cache/copilot-usage'"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+/// Start of the "normal" zone (below is "low")
+pub const ZONE_NORMAL_START: f64 = 50.0;
+/// Start of the "warning" zone
+pub const ZONE_WARNING_START: f64 = 75.0;
+/// Start of the "critical" zone
+pub const ZONE_CRITICAL_START: f64 = 90.0;
+
+/// Usage zone a percentage falls into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    Low,
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl Zone {
+    /// CSS class used in the waybar output
+    pub const fn css_class(self) -> &'static str {
+        match self {
+            Zone::Low => "copilot-low",
+            Zone::Normal => "copilot-normal",
+            Zone::Warning => "copilot-warning",
+            Zone::Critical => "copilot-critical",
+        }
+    }
+}
+
+/// Rounds half away from zero at `precision` decimals (74.95 -> 75.0 at 1,
+/// -2.5 -> -3 at 0). The small epsilon absorbs float error from
+/// `used / limit * 100`.
+pub fn round_half_up(value: f64, precision: usize) -> f64 {
+    let factor = 10f64.powi(precision as i32);
+    let rounded = (value.abs() * factor + 0.5 + 1e-9).floor() / factor;
+    if value < 0.0 {
+        // + 0.0: lo que redondea a cero no se muestra como "-0"
+        -rounded + 0.0
+    } else {
+        rounded
+    }
+}
+
+/// Formats a percentage without the `%` sign (e.g., 74.95, 1 -> "75.0")
+pub fn format_percent(value: f64, precision: usize) -> String {
+    format!("{:.*}", precision, round_half_up(value, precision))
+}
+
+/// Request counts can be fractional (model multipliers): 150 or 12.33
+pub fn format_quantity(value: f64) -> String {
+    format!("{}", (value * 100.0).round() / 100.0)
+}
+
+/// Zone for a percentage, decided on the whole-number value shown by the
+/// most compact surface (waybar) so no surface disagrees at a boundary
+pub fn zone_for(value: f64) -> Zone {
+    zone_between(
+        value,
+        [ZONE_NORMAL_START, ZONE_WARNING_START, ZONE_CRITICAL_START],
+    )
+}
+
+/// `zone_for` with other starts for the normal, warning and critical zones
+pub fn zone_between(value: f64, [normal, warning, critical]: [f64; 3]) -> Zone {
+    let value = round_half_up(value, 0);
+    if value >= critical {
+        Zone::Critical
+    } else if value >= warning {
+        Zone::Warning
+    } else if value >= normal {
+        Zone::Normal
+    } else {
+        Zone::Low
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_half_up() {
+        assert_eq!(format_percent(37.25, 1), "37.3");
+        assert_eq!(format_percent(37.24, 1), "37.2");
+        assert_eq!(format_percent(74.5, 0), "75");
+        assert_eq!(format_percent(74.49, 0), "74");
+        // 224.85 / 300 * 100 is 74.94999... in floating point
+        assert_eq!(format_percent(224.85 / 300.0 * 100.0, 1), "75.0");
+        // Simétrico: las mitades negativas también se alejan del cero
+        assert_eq!(round_half_up(-2.5, 0), -3.0);
+        assert_eq!(round_half_up(-2.4, 0), -2.0);
+        assert_eq!(format_percent(-0.2, 0), "0");
+    }
+
+    #[test]
+    fn test_boundaries() {
+        assert_eq!(format_percent(74.95, 1), "75.0");
+        assert_eq!(format_percent(74.95, 0), "75");
+        assert_eq!(zone_for(74.95), Zone::Warning);
+
+        assert_eq!(format_percent(89.95, 1), "90.0");
+        assert_eq!(format_percent(89.95, 0), "90");
+        assert_eq!(zone_for(89.95), Zone::Critical);
+
+        assert_eq!(format_percent(99.95, 1), "100.0");
+        assert_eq!(format_percent(99.95, 0), "100");
+        assert_eq!(zone_for(99.95), Zone::Critical);
+    }
+
+    #[test]
+    fn test_zone_for() {
+        assert_eq!(zone_for(30.0), Zone::Low);
+        assert_eq!(zone_for(49.5), Zone::Normal);
+        assert_eq!(zone_for(74.4), Zone::Normal);
+        assert_eq!(zone_for(80.0), Zone::Warning);
+        assert_eq!(zone_for(116.7), Zone::Critical);
+    }
+}
@@ -0,0 +1,215 @@
+//! Billing period math, shared by the dashboard, the projection and the
+//! Waybar output. Callers pass the `now` they captured once, so two parts of
+//! a frame can't land on different sides of midnight.
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, TimeZone, Utc};
+
+/// Day the quota resets on when `billing_cycle_day` isn't set
+pub const DEFAULT_BILLING_DAY: u32 = 1;
+
+pub const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// One billing period: premium requests reset at 00:00 UTC on the billing
+/// day. Months shorter than the day reset on their last day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BillingPeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl BillingPeriod {
+    /// The period `now` falls in
+    pub fn containing(now: DateTime<Utc>, day: u32) -> Self {
+        let this_month = reset_in_month(now.year(), now.month(), day);
+        if now < this_month {
+            Self::ending_at(this_month, day)
+        } else {
+            Self::starting_at(this_month, day)
+        }
+    }
+
+    /// The period that ends with the reset at `end`
+    pub fn ending_at(end: DateTime<Utc>, day: u32) -> Self {
+        let (year, month) = shift_month(end, -1);
+        Self {
+            start: reset_in_month(year, month, day),
+            end,
+        }
+    }
+
+    /// The period that starts with the reset at `start`
+    pub fn starting_at(start: DateTime<Utc>, day: u32) -> Self {
+        let (year, month) = shift_month(start, 1);
+        Self {
+            start,
+            end: reset_in_month(year, month, day),
+        }
+    }
+
+    /// The period right after this one
+    pub fn next(&self, day: u32) -> Self {
+        Self::starting_at(self.end, day)
+    }
+
+    /// Share of the period gone by at `now`, 0 to 100
+    pub fn progress_percent(&self, now: DateTime<Utc>) -> f64 {
+        let total = self.days_total();
+        if total <= 0.0 {
+            return 100.0;
+        }
+        (self.days_elapsed(now) / total * 100.0).clamp(0.0, 100.0)
+    }
+
+    /// Days from the start to `now`, fractional and never negative
+    pub fn days_elapsed(&self, now: DateTime<Utc>) -> f64 {
+        days(now - self.start).max(0.0)
+    }
+
+    /// Length of the period in days: 28 to 31
+    pub fn days_total(&self) -> f64 {
+        days(self.end - self.start)
+    }
+}
+
+/// The reset of `year`/`month`: `day` at 00:00 UTC, or the month's last day
+/// when it has fewer days
+fn reset_in_month(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).expect("Invalid month");
+    let last = first
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day());
+    let date = first
+        .with_day(day.clamp(1, last))
+        .expect("Day within the month");
+    Utc.from_utc_datetime(&date.and_time(Default::default()))
+}
+
+fn days(duration: chrono::Duration) -> f64 {
+    duration.num_seconds() as f64 / SECONDS_PER_DAY
+}
+
+/// Year and month `months` away from the month of `date`
+fn shift_month(date: DateTime<Utc>, months: i32) -> (i32, u32) {
+    let index = date.year() * 12 + date.month0() as i32 + months;
+    (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_calendar_month_by_default() {
+        let period = BillingPeriod::containing(at(2026, 2, 11, 9), DEFAULT_BILLING_DAY);
+        assert_eq!(period.start, at(2026, 2, 1, 0));
+        assert_eq!(period.end, at(2026, 3, 1, 0));
+
+        // La medianoche del 1 ya es el ciclo nuevo
+        let period = BillingPeriod::containing(at(2026, 3, 1, 0), DEFAULT_BILLING_DAY);
+        assert_eq!(period.start, at(2026, 3, 1, 0));
+    }
+
+    #[test]
+    fn test_anchor_day_spans_two_months() {
+        let period = BillingPeriod::containing(at(2026, 7, 20, 0), 12);
+        assert_eq!(
+            (period.start, period.end),
+            (at(2026, 7, 12, 0), at(2026, 8, 12, 0))
+        );
+
+        let period = BillingPeriod::containing(at(2026, 7, 11, 23), 12);
+        assert_eq!(
+            (period.start, period.end),
+            (at(2026, 6, 12, 0), at(2026, 7, 12, 0))
+        );
+    }
+
+    #[test]
+    fn test_year_boundary() {
+        let period = BillingPeriod::containing(at(2026, 12, 20, 0), 12);
+        assert_eq!(period.end, at(2027, 1, 12, 0));
+        let period = BillingPeriod::containing(at(2027, 1, 5, 0), 12);
+        assert_eq!(period.start, at(2026, 12, 12, 0));
+        assert_eq!(period.next(12).end, at(2027, 2, 12, 0));
+    }
+
+    #[test]
+    fn test_short_months_reset_on_their_last_day() {
+        // Febrero no tiene 31: reinicia el 28, o el 29 en bisiesto
+        assert_eq!(reset_in_month(2026, 2, 31), at(2026, 2, 28, 0));
+        assert_eq!(reset_in_month(2028, 2, 31), at(2028, 2, 29, 0));
+        assert_eq!(reset_in_month(2026, 4, 31), at(2026, 4, 30, 0));
+
+        let period = BillingPeriod::containing(at(2026, 3, 15, 0), 31);
+        assert_eq!(
+            (period.start, period.end),
+            (at(2026, 2, 28, 0), at(2026, 3, 31, 0))
+        );
+        // Tras un mes corto vuelve al 31, no se queda en el 30
+        let period = BillingPeriod::containing(at(2026, 4, 30, 12), 31);
+        assert_eq!(
+            (period.start, period.end),
+            (at(2026, 4, 30, 0), at(2026, 5, 31, 0))
+        );
+        assert_eq!(
+            BillingPeriod::ending_at(at(2026, 4, 30, 0), 31).start,
+            at(2026, 3, 31, 0)
+        );
+    }
+
+    #[test]
+    fn test_december_rolls_over_to_january() {
+        let period = BillingPeriod::containing(at(2026, 12, 31, 23), DEFAULT_BILLING_DAY);
+        assert_eq!(period.start, at(2026, 12, 1, 0));
+        assert_eq!(period.end, at(2027, 1, 1, 0));
+        assert_eq!(period.days_total(), 31.0);
+
+        let next = period.next(DEFAULT_BILLING_DAY);
+        assert_eq!(
+            (next.start, next.end),
+            (at(2027, 1, 1, 0), at(2027, 2, 1, 0))
+        );
+        assert_eq!(
+            BillingPeriod::ending_at(at(2027, 1, 1, 0), DEFAULT_BILLING_DAY),
+            period
+        );
+    }
+
+    #[test]
+    fn test_leap_february() {
+        let period = BillingPeriod::containing(at(2028, 2, 15, 0), DEFAULT_BILLING_DAY);
+        assert_eq!(period.days_total(), 29.0);
+        assert!((period.progress_percent(at(2028, 2, 15, 12)) - 50.0).abs() < 1e-9);
+
+        let period = BillingPeriod::containing(at(2026, 2, 15, 0), DEFAULT_BILLING_DAY);
+        assert_eq!(period.days_total(), 28.0);
+        assert_eq!(period.progress_percent(at(2026, 2, 15, 0)), 50.0);
+    }
+
+    #[test]
+    fn test_first_and_last_day_of_the_month() {
+        let first = at(2026, 7, 1, 0);
+        let period = BillingPeriod::containing(first, DEFAULT_BILLING_DAY);
+        assert_eq!(period.start, first);
+        assert_eq!(period.days_elapsed(first), 0.0);
+        assert_eq!(period.progress_percent(first), 0.0);
+
+        let last = at(2026, 7, 31, 12);
+        assert_eq!(BillingPeriod::containing(last, DEFAULT_BILLING_DAY), period);
+        assert_eq!(period.days_elapsed(last), 30.5);
+        assert!(period.progress_percent(last) > 98.0);
+    }
+
+    #[test]
+    fn test_progress_is_clamped_outside_the_period() {
+        let period = BillingPeriod::containing(at(2026, 3, 10, 0), DEFAULT_BILLING_DAY);
+        assert_eq!(period.progress_percent(at(2026, 4, 2, 0)), 100.0);
+        assert_eq!(period.progress_percent(at(2026, 2, 2, 0)), 0.0);
+        assert_eq!(period.days_elapsed(at(2026, 2, 2, 0)), 0.0);
+    }
+}
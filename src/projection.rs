@@ -0,0 +1,174 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::UsageStats;
+use crate::period::SECONDS_PER_DAY;
+
+/// Shortest stretch the average is taken over, so a burst on the first
+/// hours of the period isn't extrapolated to the whole month
+const MIN_ELAPSED_DAYS: f64 = 1.0;
+
+/// Distance to the even-burn line, in % of the limit, still counted as on pace
+const PACE_TOLERANCE: f64 = 2.0;
+
+/// How usage compares to spending the limit evenly over the period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pace {
+    /// Spending faster than the even-burn line
+    Ahead,
+    OnPace,
+    Behind,
+}
+
+/// Whether the limit lasts until the reset at the current burn rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outlook {
+    Lasts,
+    RunsOut(DateTime<Utc>),
+    /// Already at or over the limit
+    LimitReached,
+}
+
+/// Where the average burn rate of the period leads by the reset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projection {
+    /// Average premium requests per day so far
+    pub per_day: f64,
+    /// Usage at the reset if the average holds
+    pub at_reset: f64,
+    pub outlook: Outlook,
+    pub pace: Pace,
+    /// Usage minus the even-burn line (limit × share of the period gone by)
+    pub vs_even: f64,
+}
+
+/// Projection of `stats` at `now`. None without a limit or when `now` is
+/// outside the billing period (an old export, a cache from last month).
+pub fn calculate_projection(stats: &UsageStats, now: DateTime<Utc>) -> Option<Projection> {
+    let period = stats.period();
+    if stats.total_limit <= 0.0 || now < period.start || now >= period.end {
+        return None;
+    }
+
+    let period_days = period.days_total();
+    let elapsed_days = period.days_elapsed(now).max(MIN_ELAPSED_DAYS);
+
+    let per_day = stats.total_used / elapsed_days;
+    let at_reset = per_day * period_days;
+
+    let outlook = if stats.total_used >= stats.total_limit {
+        Outlook::LimitReached
+    } else if at_reset > stats.total_limit {
+        let seconds = stats.total_limit / per_day * SECONDS_PER_DAY;
+        Outlook::RunsOut(period.start + Duration::seconds(seconds as i64))
+    } else {
+        Outlook::Lasts
+    };
+
+    let vs_even = stats.total_used - stats.total_limit * period.progress_percent(now) / 100.0;
+    let tolerance = stats.total_limit * PACE_TOLERANCE / 100.0;
+    let pace = if vs_even > tolerance {
+        Pace::Ahead
+    } else if vs_even < -tolerance {
+        Pace::Behind
+    } else {
+        Pace::OnPace
+    };
+
+    Some(Projection {
+        per_day,
+        at_reset,
+        outlook,
+        pace,
+        vs_even,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use chrono::TimeZone;
+
+    /// Junio: 30 días, reinicio el 1 de julio
+    fn stats(used: f64) -> UsageStats {
+        UsageStats {
+            reset_date: Utc.with_ymd_and_hms(2026, 7, 1, 0, 0, 0).unwrap(),
+            ..testing::stats(used)
+        }
+    }
+
+    fn june(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_runs_out_before_reset() {
+        // 10 días, 150 solicitudes: 15/día, se agota el día 20
+        let projection = calculate_projection(&stats(150.0), june(11, 0)).unwrap();
+        assert_eq!(projection.per_day, 15.0);
+        assert_eq!(projection.at_reset, 450.0);
+        assert_eq!(projection.outlook, Outlook::RunsOut(june(21, 0)));
+        assert_eq!(projection.pace, Pace::Ahead);
+        assert_eq!(projection.vs_even.round(), 50.0);
+    }
+
+    #[test]
+    fn test_lasts_until_reset() {
+        // 15 días, 106 solicitudes: ~212 al reinicio
+        let projection = calculate_projection(&stats(106.0), june(16, 0)).unwrap();
+        assert_eq!(projection.at_reset.round(), 212.0);
+        assert_eq!(projection.outlook, Outlook::Lasts);
+        assert_eq!(projection.pace, Pace::Behind);
+
+        // 148 de 150 en la línea uniforme: dentro de la tolerancia
+        let projection = calculate_projection(&stats(148.0), june(16, 0)).unwrap();
+        assert_eq!(projection.pace, Pace::OnPace);
+    }
+
+    #[test]
+    fn test_first_day_averages_over_a_whole_day() {
+        // 30 solicitudes en 6 horas no son 120/día
+        let projection = calculate_projection(&stats(30.0), june(1, 6)).unwrap();
+        assert_eq!(projection.per_day, 30.0);
+        assert_eq!(projection.at_reset, 900.0);
+        assert_eq!(projection.outlook, Outlook::RunsOut(june(11, 0)));
+
+        let projection = calculate_projection(&stats(0.0), june(1, 0)).unwrap();
+        assert_eq!(projection.outlook, Outlook::Lasts);
+        assert_eq!(projection.pace, Pace::OnPace);
+    }
+
+    #[test]
+    fn test_zero_usage() {
+        let projection = calculate_projection(&stats(0.0), june(20, 0)).unwrap();
+        assert_eq!(projection.per_day, 0.0);
+        assert_eq!(projection.at_reset, 0.0);
+        assert_eq!(projection.outlook, Outlook::Lasts);
+        assert_eq!(projection.pace, Pace::Behind);
+    }
+
+    #[test]
+    fn test_over_limit() {
+        let projection = calculate_projection(&stats(320.0), june(20, 0)).unwrap();
+        assert_eq!(projection.outlook, Outlook::LimitReached);
+        assert_eq!(projection.pace, Pace::Ahead);
+
+        let projection = calculate_projection(&stats(300.0), june(1, 12)).unwrap();
+        assert_eq!(projection.outlook, Outlook::LimitReached);
+    }
+
+    #[test]
+    fn test_outside_the_period() {
+        assert!(calculate_projection(&stats(100.0), june(30, 23)).is_some());
+        let july = Utc.with_ymd_and_hms(2026, 7, 2, 0, 0, 0).unwrap();
+        assert!(calculate_projection(&stats(100.0), july).is_none());
+        let may = Utc.with_ymd_and_hms(2026, 5, 31, 0, 0, 0).unwrap();
+        assert!(calculate_projection(&stats(100.0), may).is_none());
+
+        let mut no_limit = stats(100.0);
+        no_limit.total_limit = 0.0;
+        assert!(calculate_projection(&no_limit, june(10, 0)).is_none());
+    }
+}
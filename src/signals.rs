@@ -0,0 +1,48 @@
+//! Signals the long-running modes (`daemon`, `--loop`) answer to, so a bar
+//! keybinding can force a refresh with `pkill -USR1 ...`:
+//!
+//! - SIGUSR1: skip the cache and fetch now
+//! - SIGUSR2: re-read the config (theme, format) and print again
+//! - SIGTERM, SIGINT: clean up and exit 0
+
+use anyhow::Result;
+use tokio::signal::unix::{Signal, SignalKind, signal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wakeup {
+    /// SIGUSR1
+    Refresh,
+    /// SIGUSR2
+    Reload,
+    /// SIGTERM or SIGINT
+    Exit,
+}
+
+pub struct Signals {
+    usr1: Signal,
+    usr2: Signal,
+    term: Signal,
+    int: Signal,
+}
+
+impl Signals {
+    /// Takes the signals over from here on: until then SIGUSR1 and SIGUSR2
+    /// still kill the process
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            usr1: signal(SignalKind::user_defined1())?,
+            usr2: signal(SignalKind::user_defined2())?,
+            term: signal(SignalKind::terminate())?,
+            int: signal(SignalKind::interrupt())?,
+        })
+    }
+
+    pub async fn recv(&mut self) -> Wakeup {
+        tokio::select! {
+            _ = self.usr1.recv() => Wakeup::Refresh,
+            _ = self.usr2.recv() => Wakeup::Reload,
+            _ = self.term.recv() => Wakeup::Exit,
+            _ = self.int.recv() => Wakeup::Exit,
+        }
+    }
+}
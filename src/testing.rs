@@ -0,0 +1,103 @@
+//! Canned `UsageProvider` for tests that drive a refresh without the network,
+//! and the stats most tests start from
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+
+use crate::api::{ProviderFuture, UsageProvider};
+use crate::models::{ModelUsage, UsageData, UsageStats};
+
+/// `name` with `used` requests of a 300-request plan, nothing billed. A test
+/// changes the rest with `ModelUsage { cost: 1.0, ..model("gpt-4.1", 20.0) }`.
+pub fn model(name: &str, used: f64) -> ModelUsage {
+    ModelUsage {
+        name: name.to_string(),
+        used,
+        limit: 300.0,
+        percentage: used / 3.0,
+        ..ModelUsage::default()
+    }
+}
+
+/// `testuser` with `used` of 300 premium requests and no models, in the
+/// period that resets on 2026-03-01
+pub fn stats(used: f64) -> UsageStats {
+    UsageStats {
+        total_used: used,
+        total_limit: 300.0,
+        percentage: used / 3.0,
+        reset_date: Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap(),
+        billing_cycle_day: 1,
+        username: "testuser".to_string(),
+        ..UsageStats::default()
+    }
+}
+
+/// `stats` of `models`, the total being what they used
+pub fn stats_with(models: Vec<ModelUsage>) -> UsageStats {
+    let used = models.iter().map(|model| model.used).sum();
+    UsageStats {
+        models,
+        ..stats(used)
+    }
+}
+
+/// Answers with the same user and usage every time, and counts the fetches
+pub struct MockProvider {
+    user: String,
+    /// Kept as text: anyhow errors can't be cloned for every call
+    usage: std::result::Result<UsageData, String>,
+    fetches: AtomicUsize,
+}
+
+impl MockProvider {
+    /// Serves `usage` for its own user
+    pub fn new(usage: UsageData) -> Self {
+        Self {
+            user: usage.user.clone(),
+            usage: Ok(usage),
+            fetches: AtomicUsize::new(0),
+        }
+    }
+
+    /// Serves a fixture from `tests/fixtures`, e.g. `usage_duplicated_rows.json`
+    pub fn from_fixture(name: &str) -> Result<Self> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name);
+        let json = std::fs::read_to_string(&path)?;
+        Ok(Self::new(serde_json::from_str(&json)?))
+    }
+
+    /// Knows the user, but every usage fetch fails with `message`
+    pub fn failing(user: &str, message: &str) -> Self {
+        Self {
+            user: user.to_string(),
+            usage: Err(message.to_string()),
+            fetches: AtomicUsize::new(0),
+        }
+    }
+
+    /// Usage fetches so far, failed ones included
+    pub fn fetches(&self) -> usize {
+        self.fetches.load(Ordering::Relaxed)
+    }
+}
+
+impl UsageProvider for MockProvider {
+    fn authenticated_user(&self) -> ProviderFuture<'_, String> {
+        Box::pin(async move { Ok(self.user.clone()) })
+    }
+
+    fn fetch_usage<'a>(&'a self, username: &'a str) -> ProviderFuture<'a, UsageData> {
+        self.fetches.fetch_add(1, Ordering::Relaxed);
+        Box::pin(async move {
+            if username != self.user {
+                anyhow::bail!("Unknown user '{}'", username);
+            }
+            self.usage.clone().map_err(|e| anyhow::anyhow!(e))
+        })
+    }
+}
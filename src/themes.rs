@@ -1,5 +1,7 @@
 use crate::models::Theme;
 use ratatui::style::Color;
+use std::sync::OnceLock;
+use std::time::Duration;
 
 pub struct ThemeColors {
     pub foreground: Color,
@@ -11,10 +13,171 @@ pub struct ThemeColors {
     pub bar_empty: Color,
 }
 
+/// Formats an Rgb color as `#rrggbb`. Named/indexed colors have no fixed value.
+pub fn to_hex(color: Color) -> Option<String> {
+    match color {
+        Color::Rgb(r, g, b) => Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        _ => None,
+    }
+}
+
+/// Theme name that follows the terminal background (`--theme auto`)
+pub const AUTO: &str = "auto";
+
+/// How long to wait for the terminal to report its background color
+const BACKGROUND_QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Theme for a `--theme` / config value. `auto` picks light or dark from
+/// the terminal background, falling back to dark when it can't be told.
+pub fn resolve(name: &str) -> Theme {
+    if !name.eq_ignore_ascii_case(AUTO) {
+        return Theme::from_str(name);
+    }
+    // Se consulta una vez: la TUI no puede leer la respuesta en raw mode
+    static DETECTED: OnceLock<bool> = OnceLock::new();
+    let light = *DETECTED.get_or_init(|| {
+        query_background(BACKGROUND_QUERY_TIMEOUT)
+            .or_else(|| {
+                std::env::var("COLORFGBG")
+                    .ok()
+                    .and_then(|value| colorfgbg_is_light(&value))
+            })
+            .unwrap_or(false)
+    });
+    if light { Theme::Light } else { Theme::Dark }
+}
+
+/// `COLORFGBG` ("fg;bg", set by rxvt, Konsole and others): light when the
+/// background is one of the white ANSI colors
+fn colorfgbg_is_light(value: &str) -> Option<bool> {
+    let bg: u8 = value.rsplit(';').next()?.parse().ok()?;
+    Some(matches!(bg, 7 | 15))
+}
+
+/// Parses an OSC 11 reply (`ESC ] 11 ; rgb:RRRR/GGGG/BBBB` + BEL or ST)
+/// into whether the background is light
+fn osc11_is_light(reply: &[u8]) -> Option<bool> {
+    let reply = std::str::from_utf8(reply).ok()?;
+    let rgb = reply.split_once("rgb:")?.1;
+    let rgb = rgb.trim_end_matches(['\x07', '\x1b', '\\']);
+
+    let mut channels = rgb.split('/').map(|hex| {
+        // 1 a 4 dígitos hex por canal, según el terminal
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len().clamp(1, 4))) - 1;
+        Some(value as f64 / max as f64)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(luminance > 0.5)
+}
+
+/// Asks the terminal for its background color (OSC 11). `None` when stdin
+/// or stdout isn't a terminal or no reply arrives within `timeout`.
+#[cfg(unix)]
+fn query_background(timeout: Duration) -> Option<bool> {
+    use std::io::{IsTerminal, Write};
+    use std::time::Instant;
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return None;
+    }
+
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let reply = (|| {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(b"\x1b]11;?\x1b\\").ok()?;
+        stdout.flush().ok()?;
+
+        // poll + read sobre el fd: un read bloqueante se quedaría esperando
+        // para siempre en terminales que no contestan
+        let deadline = Instant::now() + timeout;
+        let mut reply = Vec::new();
+        while !(reply.ends_with(b"\x07") || reply.ends_with(b"\x1b\\")) {
+            let left = deadline.checked_duration_since(Instant::now())?;
+            let mut fd = libc::pollfd {
+                fd: libc::STDIN_FILENO,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: a single valid pollfd for the duration of the call
+            if unsafe { libc::poll(&mut fd, 1, left.as_millis() as libc::c_int) } <= 0 {
+                return None;
+            }
+            let mut buf = [0u8; 64];
+            // SAFETY: reads at most buf.len() bytes into buf
+            let n = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+            if n <= 0 {
+                return None;
+            }
+            reply.extend_from_slice(&buf[..n as usize]);
+        }
+        Some(reply)
+    })();
+    let _ = crossterm::terminal::disable_raw_mode();
+
+    osc11_is_light(&reply?)
+}
+
+#[cfg(not(unix))]
+fn query_background(_timeout: Duration) -> Option<bool> {
+    None
+}
+
+/// `source` of the themes compiled into the binary (`themes list`)
+pub const SOURCE_BUILT_IN: &str = "built-in";
+
+/// One `name<TAB>source` line per theme, for pickers like rofi
+pub fn list_lines() -> String {
+    Theme::ALL
+        .iter()
+        .map(|theme| format!("{}\t{}\n", theme.as_str(), SOURCE_BUILT_IN))
+        .collect()
+}
+
+/// Every theme with its palette as `#rrggbb`. Scripts rely on this shape
+/// (see tests/fixtures/themes_list.json), so only ever add fields.
+pub fn list_json() -> String {
+    let themes: Vec<serde_json::Value> = Theme::ALL
+        .iter()
+        .map(|&theme| {
+            let palette: serde_json::Map<String, serde_json::Value> =
+                ThemeColors::from_theme(theme)
+                    .palette()
+                    .into_iter()
+                    .filter_map(|(name, color)| {
+                        to_hex(color).map(|hex| (name.to_string(), hex.into()))
+                    })
+                    .collect();
+            serde_json::json!({
+                "name": theme.as_str(),
+                "source": SOURCE_BUILT_IN,
+                "palette": palette,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&themes).unwrap_or_default()
+}
+
 impl ThemeColors {
+    /// Palette entries by name, in a stable order (used by CSS/JSON exports)
+    pub fn palette(&self) -> [(&'static str, Color); 7] {
+        [
+            ("foreground", self.foreground),
+            ("success", self.success),
+            ("warning", self.warning),
+            ("error", self.error),
+            ("muted", self.muted),
+            ("border", self.border),
+            ("bar-empty", self.bar_empty),
+        ]
+    }
+
     pub fn from_theme(theme: Theme) -> Self {
         match theme {
             Theme::Dark => Self::dark(),
+            Theme::Light => Self::light(),
             Theme::Nord => Self::nord(),
             Theme::Monokai => Self::monokai(),
             Theme::Gruvbox => Self::gruvbox(),
@@ -38,6 +201,19 @@ impl ThemeColors {
         }
     }
 
+    // Para terminales de fondo claro: texto oscuro y acentos saturados
+    pub fn light() -> Self {
+        Self {
+            foreground: Color::Rgb(56, 58, 66),   // near-black text
+            success: Color::Rgb(40, 140, 60),     // green
+            warning: Color::Rgb(180, 110, 0),     // amber
+            error: Color::Rgb(200, 40, 40),       // red
+            muted: Color::Rgb(120, 125, 140),     // grey
+            border: Color::Rgb(190, 192, 200),    // light grey
+            bar_empty: Color::Rgb(225, 227, 232), // off-white
+        }
+    }
+
     pub fn nord() -> Self {
         Self {
             foreground: Color::Rgb(216, 222, 233),
@@ -139,3 +315,80 @@ impl ThemeColors {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(Color::Rgb(180, 210, 150)).as_deref(), Some("#b4d296"));
+        assert_eq!(to_hex(Color::Rgb(0, 10, 255)).as_deref(), Some("#000aff"));
+        assert_eq!(to_hex(Color::Reset), None);
+    }
+
+    #[test]
+    fn test_every_theme_exports_hex_palette() {
+        for theme in Theme::ALL {
+            for (name, color) in ThemeColors::from_theme(theme).palette() {
+                let hex = to_hex(color)
+                    .unwrap_or_else(|| panic!("{}: {} is not an Rgb color", theme.as_str(), name));
+                assert_eq!(hex.len(), 7);
+                assert!(hex.starts_with('#'));
+            }
+        }
+    }
+
+    #[test]
+    fn test_list_json_matches_golden_file() {
+        assert_eq!(
+            list_json(),
+            include_str!("../tests/fixtures/themes_list.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn test_resolve_named_themes() {
+        assert_eq!(resolve("light"), Theme::Light);
+        assert_eq!(resolve("nord"), Theme::Nord);
+        assert_eq!(resolve("dracula"), Theme::Dark);
+    }
+
+    #[test]
+    fn test_osc11_reply_parsing() {
+        assert_eq!(
+            osc11_is_light(b"\x1b]11;rgb:ffff/ffff/ffff\x1b\\"),
+            Some(true)
+        );
+        assert_eq!(
+            osc11_is_light(b"\x1b]11;rgb:0000/0000/0000\x07"),
+            Some(false)
+        );
+        // Solarized light (fdf6e3) y dark (002b36), con 2 dígitos por canal
+        assert_eq!(osc11_is_light(b"\x1b]11;rgb:fd/f6/e3\x07"), Some(true));
+        assert_eq!(osc11_is_light(b"\x1b]11;rgb:00/2b/36\x07"), Some(false));
+        assert_eq!(osc11_is_light(b"\x1b]11;?\x07"), None);
+        assert_eq!(osc11_is_light(b"\x1b]11;rgb:zz/00/00\x07"), None);
+    }
+
+    #[test]
+    fn test_colorfgbg_heuristic() {
+        assert_eq!(colorfgbg_is_light("0;15"), Some(true));
+        assert_eq!(colorfgbg_is_light("0;default;7"), Some(true));
+        assert_eq!(colorfgbg_is_light("15;0"), Some(false));
+        assert_eq!(colorfgbg_is_light("15;default"), None);
+    }
+
+    #[test]
+    fn test_list_lines_names_round_trip() {
+        let lines = list_lines();
+        assert_eq!(lines.lines().count(), Theme::ALL.len());
+        for line in lines.lines() {
+            let (name, source) = line.split_once('\t').unwrap();
+            assert_eq!(source, SOURCE_BUILT_IN);
+            assert!(Theme::parse(name).is_some(), "{} doesn't parse", name);
+        }
+        assert_eq!(Theme::parse("dracula"), None);
+        assert_eq!(Theme::from_str("dracula"), Theme::Dark);
+    }
+}
@@ -1,53 +1,463 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::{AbortHandle, JoinHandle};
 
-use crate::api::{ApiClient, calculate_stats};
-use crate::cache::Cache;
-use crate::config::ConfigManager;
-use crate::models::{CacheStatus, UsageStats};
-use crate::ui::state::CacheInfo;
+use crate::api::{
+    ApiClient, ConditionalUsage, RateLimitedError, UsageProvider, calculate_stats, config_stats,
+};
+use crate::cache::{Cache, CachePolicy, DayCache, MonthCache};
+use crate::compare;
+use crate::config::{ConfigManager, env_token_var};
+use crate::i18n::{t, tf};
+use crate::models::{CacheStatus, CacheValidators, Config, RateLimitInfo, UsageStats};
+use crate::ui::state::{CacheInfo, SettingsUpdate};
 
 /// Resultado de una operación async
 #[derive(Debug)]
 pub enum AsyncResult {
-    RefreshComplete(Result<UsageStats>),
+    /// Resultado de un refresh del perfil `profile`; `generation` es la
+    /// del `RefreshHandle` que lo lanzó
+    RefreshComplete {
+        profile: String,
+        generation: u64,
+        result: Result<UsageStats>,
+    },
+    /// Requests of one day of the daily view
+    DayLoaded {
+        profile: String,
+        date: NaiveDate,
+        result: Result<f64>,
+    },
+    /// Usage of the month the compare view holds against this one
+    PreviousMonthLoaded {
+        profile: String,
+        period: (i32, u32),
+        result: Result<UsageStats>,
+    },
     CacheInfoReady(CacheInfo),
     ThemeSaved(Result<()>),
+    /// The settings form was written to the config
+    SettingsSaved(Result<SavedSettings>),
+}
+
+/// What `spawn_save_settings` wrote
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSettings {
+    pub update: SettingsUpdate,
+    /// Environment variable whose token still wins over the one saved
+    pub token_overridden_by: Option<&'static str>,
+}
+
+/// What a refresh fetched, before it's cached and turned into stats
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    pub usage: ConditionalUsage,
+    pub rate_limit: Option<RateLimitInfo>,
+}
+
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<Fetched>> + Send + 'a>>;
+
+/// Where refreshes get the usage from: the GitHub API, or canned data when
+/// a test drives the TUI
+pub trait UsageSource: Send + Sync {
+    /// `validators` come from the cached response; a source may answer
+    /// `NotModified` when they still match
+    fn fetch<'a>(
+        &'a self,
+        context: &'a AppContext,
+        validators: &'a CacheValidators,
+    ) -> FetchFuture<'a>;
+}
+
+/// The active profile with its config and API client, built once and shared
+/// by every background task. Switching profile builds a new one; so does
+/// the next TUI run after a reconfigure.
+#[derive(Clone)]
+pub struct AppContext {
+    pub config_manager: ConfigManager,
+    /// Errors are kept as text and raised by the task that needs them, so a
+    /// missing token doesn't stop the cache info or a read-only session
+    config: std::result::Result<Arc<Config>, String>,
+    api_client: std::result::Result<Arc<ApiClient>, String>,
+    /// --max-age / --cache-ttl of this run, kept across profile switches
+    cache_policy: CachePolicy,
+}
+
+impl AppContext {
+    /// Loads the profile of `config_manager` and builds its client
+    pub fn load(config_manager: ConfigManager) -> Self {
+        let config = config_manager
+            .load()
+            .and_then(|config| {
+                config
+                    .with_context(|| tf("err.profile_not_configured", &[&config_manager.profile()]))
+            })
+            .map(Arc::new)
+            .map_err(|e| format!("{:#}", e));
+        let api_client = match &config {
+            Ok(config) => {
+                Self::build_client(&config_manager, config).map_err(|e| format!("{:#}", e))
+            }
+            Err(e) => Err(e.clone()),
+        };
+
+        Self {
+            config_manager,
+            config,
+            api_client,
+            cache_policy: CachePolicy::Ttl,
+        }
+    }
+
+    pub fn with_cache_policy(mut self, cache_policy: CachePolicy) -> Self {
+        self.cache_policy = cache_policy;
+        self
+    }
+
+    fn build_client(config_manager: &ConfigManager, config: &Config) -> Result<Arc<ApiClient>> {
+        let client = ApiClient::for_config(config_manager.require_token(config)?, config)?;
+        Ok(Arc::new(client))
+    }
+
+    pub fn profile(&self) -> &str {
+        self.config_manager.profile()
+    }
+
+    pub fn config(&self) -> Result<&Config> {
+        self.config
+            .as_deref()
+            .map_err(|e| anyhow::anyhow!(e.clone()))
+    }
+
+    pub fn api_client(&self) -> Result<Arc<ApiClient>> {
+        self.api_client.clone().map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+/// The GitHub billing API, with the profile's token
+pub struct ApiSource;
+
+impl UsageSource for ApiSource {
+    fn fetch<'a>(
+        &'a self,
+        context: &'a AppContext,
+        validators: &'a CacheValidators,
+    ) -> FetchFuture<'a> {
+        Box::pin(async move {
+            let api_client = context.api_client()?;
+            fetch_from(api_client.as_ref(), validators).await
+        })
+    }
+}
+
+/// The same provider for every profile, whatever its token
+struct ProviderSource<P>(P);
+
+impl<P: UsageProvider> UsageSource for ProviderSource<P> {
+    fn fetch<'a>(&'a self, _: &'a AppContext, validators: &'a CacheValidators) -> FetchFuture<'a> {
+        Box::pin(fetch_from(&self.0, validators))
+    }
+}
+
+/// Usage of the user the provider is authenticated as
+async fn fetch_from(provider: &dyn UsageProvider, validators: &CacheValidators) -> Result<Fetched> {
+    let username = match provider.authenticated_user().await {
+        Ok(user) => user,
+        Err(e) if e.is::<RateLimitedError>() => return Err(e),
+        Err(_) => {
+            // Si falla, devolver error - la TUI lo manejará mostrando error dialog
+            anyhow::bail!(t("err.no_username"));
+        }
+    };
+
+    let usage = provider
+        .fetch_usage_conditional(&username, validators)
+        .await?;
+    Ok(Fetched {
+        usage,
+        rate_limit: provider.last_rate_limit(),
+    })
+}
+
+/// A running refresh: its generation tags the result, so one from a
+/// cancelled or superseded refresh is recognized and dropped
+#[derive(Debug)]
+pub struct RefreshHandle {
+    pub generation: u64,
+    /// Switching profile already saved the new default; cancelling it would
+    /// leave the old profile's data under the new name
+    pub profile_switch: bool,
+    abort: AbortHandle,
+}
+
+impl RefreshHandle {
+    /// Stops the task at its next await; the cache is written synchronously
+    /// after the fetch, so it's never left half-written
+    pub fn abort(&self) {
+        tracing::info!(generation = self.generation, "refresh cancelled");
+        self.abort.abort();
+    }
 }
 
 /// Maneja operaciones asíncronas en background
 pub struct AsyncHandler {
     sender: Sender<AsyncResult>,
     receiver: Receiver<AsyncResult>,
+    /// Perfil activo (respeta `--profile` y los cambios de perfil)
+    context: Mutex<AppContext>,
+    /// Tareas en curso; pueden estar escribiendo el cache o la config
+    pending: Mutex<Vec<JoinHandle<()>>>,
+    /// Last generation handed to a refresh
+    generation: AtomicU64,
+    source: Arc<dyn UsageSource>,
 }
 
 impl AsyncHandler {
-    pub fn new() -> Self {
+    pub fn new(context: AppContext) -> Self {
         let (sender, receiver) = mpsc::channel();
-        Self { sender, receiver }
+        Self {
+            sender,
+            receiver,
+            context: Mutex::new(context),
+            pending: Mutex::new(Vec::new()),
+            generation: AtomicU64::new(0),
+            source: Arc::new(ApiSource),
+        }
+    }
+
+    /// Refreshes from `source` instead of the GitHub API
+    pub fn with_source(mut self, source: impl UsageSource + 'static) -> Self {
+        self.source = Arc::new(source);
+        self
+    }
+
+    /// Refreshes from `provider` instead of the profile's API client
+    pub fn with_provider(self, provider: impl UsageProvider + 'static) -> Self {
+        self.with_source(ProviderSource(provider))
+    }
+
+    /// Contexto del perfil activo
+    pub fn context(&self) -> AppContext {
+        self.context
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Loads the active profile again, after its config changed: a new
+    /// token gets a new client
+    pub fn reload_context(&self) {
+        let mut current = self.context.lock().unwrap_or_else(|e| e.into_inner());
+        *current = AppContext::load(current.config_manager.clone())
+            .with_cache_policy(current.cache_policy);
+    }
+
+    /// Spawn que queda registrado para esperarlo al salir; `name` lo
+    /// identifica en el log
+    fn spawn_tracked(
+        &self,
+        name: &'static str,
+        task: impl Future<Output = ()> + Send + 'static,
+    ) -> AbortHandle {
+        tracing::debug!(task = name, "task started");
+        let handle = tokio::spawn(async move {
+            let started = std::time::Instant::now();
+            task.await;
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            tracing::debug!(task = name, elapsed_ms, "task finished");
+        });
+        let abort = handle.abort_handle();
+        // Los terminados se quitan en `try_recv`, una vez entregado su resultado
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(handle);
+        abort
+    }
+
+    /// Spawns `refresh` under a new generation; it resolves to the profile
+    /// it fetched and the result
+    fn spawn_refresh_task(
+        &self,
+        profile_switch: bool,
+        refresh: impl Future<Output = (String, Result<UsageStats>)> + Send + 'static,
+    ) -> RefreshHandle {
+        let sender = self.sender.clone();
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let abort = self.spawn_tracked("refresh", async move {
+            let (profile, result) = refresh.await;
+            match &result {
+                Ok(_) => tracing::info!(generation, %profile, "refresh done"),
+                Err(e) => {
+                    let error = format!("{:#}", e);
+                    tracing::warn!(generation, %profile, %error, "refresh failed");
+                }
+            }
+            let _ = sender.send(AsyncResult::RefreshComplete {
+                profile,
+                generation,
+                result,
+            });
+        });
+        RefreshHandle {
+            generation,
+            profile_switch,
+            abort,
+        }
+    }
+
+    /// Waits up to `cap` for spawned tasks so a refresh that just finished
+    /// gets its cache write to disk before the process exits. `on_slow` runs
+    /// once if they're still busy after `notice_after`. Returns false when
+    /// the cap ran out first.
+    pub async fn finish_pending(
+        &self,
+        cap: Duration,
+        notice_after: Duration,
+        on_slow: impl FnOnce(),
+    ) -> bool {
+        let handles = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        let mut all_done = std::pin::pin!(tokio::time::timeout(cap, async {
+            for handle in handles {
+                let _ = handle.await;
+            }
+        }));
+
+        match tokio::time::timeout(notice_after, &mut all_done).await {
+            Ok(finished) => finished.is_ok(),
+            Err(_) => {
+                on_slow();
+                all_done.await.is_ok()
+            }
+        }
     }
 
     /// Intenta recibir resultado sin bloquear
     pub fn try_recv(&self) -> Option<AsyncResult> {
-        self.receiver.try_recv().ok()
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        // Una tarea manda su resultado antes de terminar: con el canal vacío,
+        // las que ya habían terminado no tienen nada más que entregar
+        let finished: Vec<bool> = pending.iter().map(JoinHandle::is_finished).collect();
+        let result = self.receiver.try_recv().ok();
+        if result.is_none() {
+            let mut finished = finished.into_iter();
+            pending.retain(|_| !finished.next().unwrap_or(false));
+        }
+        result
+    }
+
+    /// A spawned task is still running, or its result hasn't been taken
+    /// with `try_recv` yet. The event loop polls often meanwhile: results
+    /// come over a channel `event::poll` can't see.
+    pub fn is_busy(&self) -> bool {
+        !self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
     }
 
     /// Spawn task para refrescar datos
-    pub fn spawn_refresh(&self) {
+    pub fn spawn_refresh(&self) -> RefreshHandle {
+        let context = self.context();
+        let source = Arc::clone(&self.source);
+
+        self.spawn_refresh_task(false, async move {
+            let result = Self::do_refresh(&context, source.as_ref()).await;
+            (context.profile().to_string(), result)
+        })
+    }
+
+    /// Cambia de perfil: lo guarda como default y refresca sus datos.
+    /// Los refresh del perfil anterior que sigan en curso se descartan.
+    pub fn spawn_switch_profile(&self, profile: String) -> RefreshHandle {
+        let context = {
+            let mut current = self.context.lock().unwrap_or_else(|e| e.into_inner());
+            let config_manager = current
+                .config_manager
+                .clone()
+                .with_profile(profile.as_str());
+            *current = AppContext::load(config_manager).with_cache_policy(current.cache_policy);
+            current.clone()
+        };
+        let source = Arc::clone(&self.source);
+
+        self.spawn_refresh_task(true, async move {
+            let result = match context.config_manager.set_default_profile(&profile) {
+                Ok(()) => Self::do_refresh(&context, source.as_ref()).await,
+                Err(e) => Err(e),
+            };
+            (profile, result)
+        })
+    }
+
+    /// Spawn task para la vista diaria: un resultado por día, en orden,
+    /// para que las filas se vayan llenando
+    pub fn spawn_daily_usage(&self, dates: Vec<NaiveDate>) {
+        if dates.is_empty() {
+            return;
+        }
         let sender = self.sender.clone();
+        let context = self.context();
+
+        self.spawn_tracked("daily_usage", async move {
+            let profile = context.profile().to_string();
+            let send = |date, result| {
+                let _ = sender.send(AsyncResult::DayLoaded {
+                    profile: profile.clone(),
+                    date,
+                    result,
+                });
+            };
+
+            let fetcher = match DailyFetcher::new(&context).await {
+                Ok(fetcher) => fetcher,
+                Err(e) => {
+                    // Sin cliente no hay ningún día: todas las filas muestran el error
+                    let reason = format!("{:#}", e);
+                    for date in dates {
+                        send(date, Err(anyhow::anyhow!(reason.clone())));
+                    }
+                    return;
+                }
+            };
 
-        tokio::spawn(async move {
-            let result = Self::do_refresh().await;
-            let _ = sender.send(AsyncResult::RefreshComplete(result));
+            for date in dates {
+                send(date, fetcher.requests(date).await);
+            }
+        });
+    }
+
+    /// Spawn task para la vista de comparación: el mes anterior entero
+    pub fn spawn_previous_month(&self, period: (i32, u32)) {
+        let sender = self.sender.clone();
+        let context = self.context();
+
+        self.spawn_tracked("previous_month", async move {
+            let result = Self::do_previous_month(&context, period).await;
+            let _ = sender.send(AsyncResult::PreviousMonthLoaded {
+                profile: context.profile().to_string(),
+                period,
+                result,
+            });
         });
     }
 
     /// Spawn task para obtener info del cache
     pub fn spawn_cache_info(&self) {
         let sender = self.sender.clone();
+        let context = self.context();
+        let source = Arc::clone(&self.source);
 
-        tokio::spawn(async move {
-            let info = Self::do_cache_info().await;
+        self.spawn_tracked("cache_info", async move {
+            let info = Self::do_cache_info(&context, source.as_ref()).await;
             let _ = sender.send(AsyncResult::CacheInfoReady(info));
         });
     }
@@ -55,51 +465,66 @@ impl AsyncHandler {
     /// Spawn task para guardar tema en config (background, no bloquea UI)
     pub fn spawn_save_theme(&self, theme_name: String) {
         let sender = self.sender.clone();
+        let config_manager = self.context().config_manager;
 
-        tokio::spawn(async move {
-            let result = Self::do_save_theme(&theme_name).await;
+        self.spawn_tracked("save_theme", async move {
+            let result = Self::do_save_theme(&config_manager, &theme_name).await;
             let _ = sender.send(AsyncResult::ThemeSaved(result));
         });
     }
 
-    /// Implementación real del refresh
-    async fn do_refresh() -> Result<UsageStats> {
-        let config_manager = ConfigManager::new()?;
-        let config = config_manager.load()?.unwrap();
-        let cache = Cache::new(config.cache_ttl_minutes)?;
+    /// Spawn task para guardar el formulario de ajustes
+    pub fn spawn_save_settings(&self, update: SettingsUpdate) {
+        let sender = self.sender.clone();
+        let config_manager = self.context().config_manager;
 
-        // Invalidar cache
-        cache.invalidate()?;
+        self.spawn_tracked("save_settings", async move {
+            let result = Self::do_save_settings(&config_manager, update);
+            let _ = sender.send(AsyncResult::SettingsSaved(result));
+        });
+    }
 
-        // Fetch desde API
-        let api_client = ApiClient::new(config.token.clone())?;
+    /// Implementación real del refresh
+    async fn do_refresh(context: &AppContext, source: &dyn UsageSource) -> Result<UsageStats> {
+        let config = context.config()?;
+        let cache = Cache::new(context.profile(), config.cache_ttl_minutes)?
+            .with_history_retention(config.history_retention_days);
 
-        let username = match api_client.get_authenticated_user().await {
-            Ok(user) => user,
-            Err(_) => {
-                // Si falla, devolver error - la TUI lo manejará mostrando error dialog
-                anyhow::bail!(
-                    "Could not determine username from token. Please reconfigure with a valid token."
-                );
-            }
-        };
+        // Un archivo corrupto se aparta para inspección. Lo anterior queda para
+        // la petición condicional y sigue sirviendo si falla: store lo
+        // reemplaza de forma atómica
+        cache.quarantine_if_corrupted()?;
+        let previous = cache.entry().ok().flatten();
 
-        let usage_data = api_client.fetch_usage(&username).await?;
-        cache.set(&usage_data)?;
+        // Fetch desde API
+        let validators = previous
+            .as_ref()
+            .map(|entry| entry.validators.clone())
+            .unwrap_or_default();
+        let fetched = source.fetch(context, &validators).await?;
+        let usage = cache.store(fetched.usage, previous, fetched.rate_limit)?;
 
-        Ok(calculate_stats(&usage_data))
+        Ok(config_stats(&usage, config))
     }
 
     /// Implementación real de cache info
-    async fn do_cache_info() -> CacheInfo {
-        let config_manager = ConfigManager::new().ok();
-
-        if let Some(config_manager) = config_manager
-            && let Ok(Some(config)) = config_manager.load()
-        {
-            let cache = Cache::new(config.cache_ttl_minutes).ok();
+    async fn do_cache_info(context: &AppContext, source: &dyn UsageSource) -> CacheInfo {
+        if let Ok(config) = context.config() {
+            let cache = Cache::new(context.profile(), config.cache_ttl_minutes)
+                .ok()
+                .map(|cache| context.cache_policy.apply(cache));
 
             if let Some(cache) = cache {
+                // Un cache corrupto se aparta y se vuelve a pedir a la API una vez
+                let quarantined = cache.quarantine_if_corrupted().ok().flatten();
+                let refetch_error = match quarantined {
+                    Some(_) => Self::do_refresh(context, source)
+                        .await
+                        .err()
+                        .map(|e| format!("{:#}", e)),
+                    None => None,
+                };
+
                 let last_updated = cache
                     .last_updated()
                     .ok()
@@ -107,11 +532,15 @@ impl AsyncHandler {
                     .map(|ts| ts.format("%Y-%m-%d %H:%M:%S").to_string());
 
                 let is_fresh = matches!(cache.status(), CacheStatus::Fresh(_));
+                let rate_limit = cache.rate_limit();
 
                 return CacheInfo {
                     last_updated,
                     is_fresh,
                     ttl_minutes: config.cache_ttl_minutes,
+                    quarantined: quarantined.map(|path| path.display().to_string()),
+                    refetch_error,
+                    rate_limit,
                 };
             }
         }
@@ -121,12 +550,59 @@ impl AsyncHandler {
             last_updated: None,
             is_fresh: false,
             ttl_minutes: 5,
+            quarantined: None,
+            refetch_error: None,
+            rate_limit: None,
         }
     }
 
+    /// A cached month doesn't need the client nor the username
+    async fn do_previous_month(context: &AppContext, period: (i32, u32)) -> Result<UsageStats> {
+        let config = context.config()?;
+        let cache = MonthCache::new(context.profile())?;
+        if let Some(data) = cache.get(period.0, period.1) {
+            return Ok(config_stats(&data, config));
+        }
+        let api_client = context.api_client()?;
+        let username = authenticated_username(&api_client).await?;
+        let data = compare::month_usage(&api_client, &cache, &username, period).await?;
+        Ok(config_stats(&data, config))
+    }
+
+    /// Keeps everything the form doesn't edit; an unconfigured profile
+    /// starts from the defaults
+    /// A keyring token wins over config.toml, so a new token goes to the
+    /// keyring; without one it goes to config.toml and the keyring's old
+    /// token is dropped
+    fn do_save_settings(
+        config_manager: &ConfigManager,
+        update: SettingsUpdate,
+    ) -> Result<SavedSettings> {
+        let mut config = config_manager.load()?.unwrap_or_default();
+        update.apply(&mut config);
+        if let Some(token) = &update.token {
+            match config_manager.store_token_in_keyring(token) {
+                Ok(()) => config.token.clear(),
+                Err(e) => {
+                    tracing::warn!(error = %e, "keyring unavailable, saving the token to config.toml");
+                    let deleted = config_manager.delete_keyring_token(config_manager.profile());
+                    if config_manager.keyring_token().is_some() {
+                        deleted.context("The OS keyring still holds the old token")?;
+                    }
+                }
+            }
+        }
+        config_manager.save(&config)?;
+
+        let token_overridden_by = update.token.as_ref().and(env_token_var());
+        Ok(SavedSettings {
+            update,
+            token_overridden_by,
+        })
+    }
+
     /// Implementación real de guardar tema
-    async fn do_save_theme(theme_name: &str) -> Result<()> {
-        let config_manager = ConfigManager::new()?;
+    async fn do_save_theme(config_manager: &ConfigManager, theme_name: &str) -> Result<()> {
         if let Some(mut config) = config_manager.load()? {
             config.theme = theme_name.to_string();
             config_manager.save(&config)?;
@@ -134,3 +610,327 @@ impl AsyncHandler {
         Ok(())
     }
 }
+
+/// Pide días sueltos a la API, pasando antes por el caché de días
+struct DailyFetcher {
+    api_client: Arc<ApiClient>,
+    username: String,
+    limit: u32,
+    cache: DayCache,
+}
+
+impl DailyFetcher {
+    async fn new(context: &AppContext) -> Result<Self> {
+        let config = context.config()?;
+        let cache = context
+            .cache_policy
+            .apply_days(DayCache::new(context.profile(), config.cache_ttl_minutes)?);
+        let api_client = context.api_client()?;
+        let username = authenticated_username(&api_client).await?;
+
+        Ok(Self {
+            api_client,
+            username,
+            limit: config.premium_request_limit,
+            cache,
+        })
+    }
+
+    async fn requests(&self, date: NaiveDate) -> Result<f64> {
+        if let Some(requests) = self.cache.get(date) {
+            return Ok(requests);
+        }
+        let data = self
+            .api_client
+            .fetch_usage_for_day(&self.username, date)
+            .await?;
+        let requests = calculate_stats(&data, self.limit).total_used;
+        // El caché de días es una ayuda: si no se puede escribir, se sigue
+        let _ = self.cache.set(date, requests);
+        Ok(requests)
+    }
+}
+
+/// The token's user; the usage endpoints need it in the path
+async fn authenticated_username(api_client: &ApiClient) -> Result<String> {
+    match api_client.get_authenticated_user().await {
+        Ok(user) => Ok(user),
+        Err(e) if e.is::<RateLimitedError>() => Err(e),
+        Err(_) => anyhow::bail!(t("err.no_username")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TimePeriod, UsageData};
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    /// Keyring tokens stay in memory: tests never touch the real keyring
+    fn handler(dir: &TempDir) -> AsyncHandler {
+        AsyncHandler::new(AppContext::load(
+            ConfigManager::with_path(dir.path().join("config.toml")).with_memory_keyring(),
+        ))
+    }
+
+    fn usage() -> UsageData {
+        UsageData {
+            time_period: TimePeriod {
+                year: 2026,
+                month: Some(2),
+                day: None,
+            },
+            user: "testuser".to_string(),
+            usage_items: vec![],
+            note: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_slow_cache_write_is_flushed_before_return() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("usage-default.json");
+        let handler = handler(&dir);
+
+        let path = cache_path.clone();
+        handler.spawn_tracked("test", async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Cache::with_path(path, 5).set(&usage(), None).unwrap();
+        });
+
+        let noticed = Cell::new(false);
+        let flushed = handler
+            .finish_pending(Duration::from_secs(2), Duration::from_millis(50), || {
+                noticed.set(true)
+            })
+            .await;
+
+        assert!(flushed);
+        assert!(noticed.get());
+        assert!(matches!(
+            Cache::with_path(cache_path, 5).status(),
+            CacheStatus::Fresh(_)
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_flush_gives_up_after_cap() {
+        let dir = TempDir::new().unwrap();
+        let handler = handler(&dir);
+        handler.spawn_tracked("test", tokio::time::sleep(Duration::from_secs(30)));
+
+        let started = std::time::Instant::now();
+        let flushed = handler
+            .finish_pending(Duration::from_millis(100), Duration::from_millis(50), || {})
+            .await;
+
+        assert!(!flushed);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_busy_until_the_result_is_taken() {
+        let dir = TempDir::new().unwrap();
+        let handler = handler(&dir);
+        assert!(!handler.is_busy());
+
+        // Sin config no hay nada que guardar, pero el resultado llega igual
+        handler.spawn_save_theme("nord".to_string());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        // Terminada, con el resultado aún en el canal
+        assert!(handler.is_busy());
+
+        assert!(matches!(
+            handler.try_recv(),
+            Some(AsyncResult::ThemeSaved(_))
+        ));
+        assert!(handler.try_recv().is_none());
+        assert!(!handler.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_nothing_pending_returns_quietly() {
+        let dir = TempDir::new().unwrap();
+        let flushed = handler(&dir)
+            .finish_pending(Duration::from_secs(2), Duration::ZERO, || {
+                panic!("no notice without pending work")
+            })
+            .await;
+        assert!(flushed);
+    }
+
+    fn stats() -> UsageStats {
+        calculate_stats(&usage(), 300)
+    }
+
+    /// Recibe hasta que llega algo o pasa `wait`
+    async fn recv_within(handler: &AsyncHandler, wait: Duration) -> Option<AsyncResult> {
+        let started = std::time::Instant::now();
+        while started.elapsed() < wait {
+            if let Some(result) = handler.try_recv() {
+                return Some(result);
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        None
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancelled_refresh_never_reports() {
+        let dir = TempDir::new().unwrap();
+        let handler = handler(&dir);
+        let mut app = crate::ui::state::AppStateManager::new();
+
+        assert!(app.request_refresh());
+        app.refresh_task = Some(handler.spawn_refresh_task(false, async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            ("default".to_string(), Ok(stats()))
+        }));
+
+        app.cancel_refresh().unwrap().abort();
+        assert!(!app.refresh_in_flight);
+        assert!(
+            recv_within(&handler, Duration::from_millis(500))
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_superseded_result_is_not_current() {
+        let dir = TempDir::new().unwrap();
+        let handler = handler(&dir);
+        let mut app = crate::ui::state::AppStateManager::new();
+
+        // Cancelado justo cuando ya había terminado: su resultado llega igual
+        let first =
+            handler.spawn_refresh_task(false, async { ("default".to_string(), Ok(stats())) });
+        let late = recv_within(&handler, Duration::from_secs(2)).await.unwrap();
+        let second = handler.spawn_refresh_task(false, std::future::pending());
+        assert!(second.generation > first.generation);
+        app.refresh_task = Some(second);
+
+        let AsyncResult::RefreshComplete { generation, .. } = late else {
+            panic!("expected a refresh result");
+        };
+        assert_eq!(generation, first.generation);
+        assert!(!app.is_current_refresh(generation));
+        assert!(app.is_current_refresh(first.generation + 1));
+
+        // Tras cancelar, ninguno es el actual
+        app.cancel_refresh().unwrap().abort();
+        assert!(!app.is_current_refresh(first.generation + 1));
+    }
+
+    #[tokio::test]
+    async fn test_profile_switch_continues_in_background() {
+        let dir = TempDir::new().unwrap();
+        let handler = handler(&dir);
+        let mut app = crate::ui::state::AppStateManager::new();
+
+        app.refresh_in_flight = true;
+        let switch = handler.spawn_refresh_task(true, std::future::pending());
+        let generation = switch.generation;
+        app.refresh_task = Some(switch);
+
+        assert!(app.cancel_refresh().is_none());
+        assert!(app.background_refresh);
+        assert!(app.refresh_in_flight);
+        assert!(app.is_current_refresh(generation));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_saved_settings_reach_the_context() {
+        let dir = TempDir::new().unwrap();
+        let handler = handler(&dir);
+        let update = SettingsUpdate {
+            token: Some("ghp_from_form".to_string()),
+            theme: "nord".to_string(),
+            cache_ttl_minutes: 30,
+            waybar_format: "{used}".to_string(),
+        };
+        handler.spawn_save_settings(update.clone());
+
+        let result = recv_within(&handler, Duration::from_secs(2)).await;
+        assert!(
+            matches!(result, Some(AsyncResult::SettingsSaved(Ok(ref saved))) if saved.update == update)
+        );
+
+        // Antes de recargar, el contexto sigue sin perfil configurado
+        assert!(handler.context().config().is_err());
+        handler.reload_context();
+        let context = handler.context();
+        let config = context.config().unwrap();
+        // El token va al keyring, no al archivo
+        assert_eq!(config.token, "");
+        assert_eq!(
+            context.config_manager.keyring_token().as_deref(),
+            Some("ghp_from_form")
+        );
+        assert_eq!(config.theme, "nord");
+        assert_eq!(config.cache_ttl_minutes, 30);
+        assert!(context.api_client().is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_saved_token_replaces_the_keyring_one() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user"))
+            .and(header("authorization", "Bearer ghp_new"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "login": "testuser"
+            })))
+            .mount(&server)
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let handler = handler(&dir);
+        let config_manager = handler.context().config_manager.clone();
+        config_manager
+            .save(&Config {
+                api_base_url: server.uri(),
+                ..Config::default()
+            })
+            .unwrap();
+        config_manager.store_token_in_keyring("ghp_old").unwrap();
+
+        handler.spawn_save_settings(SettingsUpdate {
+            token: Some("ghp_new".to_string()),
+            theme: "dark".to_string(),
+            cache_ttl_minutes: 5,
+            waybar_format: "{used}".to_string(),
+        });
+        let result = recv_within(&handler, Duration::from_secs(2)).await;
+        assert!(matches!(result, Some(AsyncResult::SettingsSaved(Ok(_)))));
+
+        // El siguiente refresh llama a la API con el token nuevo
+        handler.reload_context();
+        let client = handler.context().api_client().unwrap();
+        assert_eq!(client.get_authenticated_user().await.unwrap(), "testuser");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_switch_swaps_the_context_and_reports_errors() {
+        let dir = TempDir::new().unwrap();
+        let handler = handler(&dir);
+        assert_eq!(handler.context().profile(), "default");
+        assert!(handler.context().api_client().is_err());
+
+        // Perfil sin configurar: error por el canal, nada de panics
+        handler.spawn_switch_profile("work".to_string());
+        assert_eq!(handler.context().profile(), "work");
+        let error = handler.context().config().unwrap_err().to_string();
+        assert!(error.contains("work"));
+
+        let result = recv_within(&handler, Duration::from_secs(2)).await;
+        assert!(matches!(
+            result,
+            Some(AsyncResult::RefreshComplete { profile, result: Err(_), .. }) if profile == "work"
+        ));
+    }
+}
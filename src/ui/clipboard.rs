@@ -0,0 +1,131 @@
+//! Copying text out of the TUI. The system clipboard goes through arboard;
+//! over SSH that would be the remote machine's, so OSC 52 asks the terminal
+//! to set its own instead. The terminal doesn't answer OSC 52: it's sent
+//! when it has a fair chance of working, never confirmed.
+
+use std::io::{self, Write};
+
+use anyhow::{Result, bail};
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+/// Where the copied text went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Copied {
+    Clipboard,
+    /// Handed to the terminal through OSC 52
+    Terminal,
+}
+
+/// How to copy, given the environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    /// The system clipboard, then OSC 52 if that fails and the terminal
+    /// may take it
+    System { osc52_fallback: bool },
+    Osc52,
+    /// Over SSH on a terminal without OSC 52
+    Unavailable,
+}
+
+/// Keeps the system clipboard open: on X11 and Wayland this process serves
+/// what it copied, and it's gone once the handle is dropped
+#[derive(Default)]
+pub struct Clipboard {
+    system: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub fn copy(&mut self, text: &str) -> Result<Copied> {
+        match route(|name| std::env::var(name).ok()) {
+            Route::System { osc52_fallback } => match self.copy_to_system(text) {
+                Ok(()) => Ok(Copied::Clipboard),
+                Err(e) if osc52_fallback => {
+                    tracing::debug!(error = %e, "system clipboard unavailable, using OSC 52");
+                    write_osc52(text)
+                }
+                Err(e) => Err(e),
+            },
+            Route::Osc52 => write_osc52(text),
+            Route::Unavailable => bail!("the terminal can't receive it over SSH"),
+        }
+    }
+
+    fn copy_to_system(&mut self, text: &str) -> Result<()> {
+        let clipboard = match &mut self.system {
+            Some(clipboard) => clipboard,
+            None => self.system.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        Ok(())
+    }
+}
+
+fn route(env: impl Fn(&str) -> Option<String>) -> Route {
+    let over_ssh = env("SSH_CONNECTION").is_some() || env("SSH_TTY").is_some();
+    // La consola de Linux y TERM=dumb no entienden OSC 52
+    let osc52 = env("TERM").is_some_and(|term| !matches!(term.as_str(), "" | "dumb" | "linux"));
+    match (over_ssh, osc52) {
+        (false, osc52_fallback) => Route::System { osc52_fallback },
+        (true, true) => Route::Osc52,
+        (true, false) => Route::Unavailable,
+    }
+}
+
+/// Escape sequence that puts `text` on the terminal's clipboard
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+/// On the terminal the TUI draws to, between frames
+fn write_osc52(text: &str) -> Result<Copied> {
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52(text).as_bytes())?;
+    stdout.flush()?;
+    Ok(Copied::Terminal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        assert_eq!(
+            route(env(&[("TERM", "xterm-kitty")])),
+            Route::System {
+                osc52_fallback: true
+            }
+        );
+        assert_eq!(
+            route(env(&[("TERM", "linux")])),
+            Route::System {
+                osc52_fallback: false
+            }
+        );
+        // Por SSH el portapapeles del sistema es el de la otra máquina
+        let ssh = [("SSH_CONNECTION", "10.0.0.2 50000 10.0.0.1 22")];
+        assert_eq!(
+            route(env(&[ssh[0], ("TERM", "tmux-256color")])),
+            Route::Osc52
+        );
+        assert_eq!(route(env(&ssh)), Route::Unavailable);
+        assert_eq!(
+            route(env(&[("SSH_TTY", "/dev/pts/1"), ("TERM", "dumb")])),
+            Route::Unavailable
+        );
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52("copilot"), "\x1b]52;c;Y29waWxvdA==\x07");
+        assert_eq!(osc52(""), "\x1b]52;c;\x07");
+    }
+}
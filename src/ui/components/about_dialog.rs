@@ -0,0 +1,75 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::i18n::{t, tf};
+use crate::themes::ThemeColors;
+use crate::ui::layout::{POPUP_WIDTH, centered_rect};
+use crate::ui::state::AppStateManager;
+use crate::ui::styles::format_elapsed;
+
+/// Renderiza el diálogo "About" con versión e información de la sesión
+pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) -> Rect {
+    let area = centered_rect(POPUP_WIDTH, 40, f.area());
+
+    let block = Block::default()
+        .title(t("about.title"))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.border));
+
+    let inner = block.inner(area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Nombre + versión
+            Constraint::Length(1), // Espaciado
+            Constraint::Length(1), // Sesión abierta
+            Constraint::Length(1), // Refrescos
+            Constraint::Length(1), // Último refresco
+            Constraint::Min(1),    // Espaciado
+            Constraint::Length(1), // Hint
+        ])
+        .vertical_margin(1)
+        .horizontal_margin(2)
+        .split(inner);
+
+    let title = Paragraph::new(format!("copilot-usage v{}", env!("CARGO_PKG_VERSION"))).style(
+        Style::default()
+            .fg(colors.foreground)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let open_for = Paragraph::new(tf(
+        "about.open_for",
+        &[&format_elapsed(app.session_started.elapsed())],
+    ))
+    .style(Style::default().fg(colors.foreground));
+
+    let refreshes = Paragraph::new(tf("about.refreshed", &[&app.refresh_count]))
+        .style(Style::default().fg(colors.foreground));
+
+    let last_refresh_text = match app.last_refresh_at {
+        Some(time) => tf("about.last_refresh", &[&time.format("%H:%M")]),
+        None => t("about.no_refresh").to_string(),
+    };
+    let last_refresh = Paragraph::new(last_refresh_text).style(Style::default().fg(colors.muted));
+
+    let hint = Paragraph::new(t("dialog.close_hint"))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(colors.muted));
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(title, layout[0]);
+    f.render_widget(open_for, layout[2]);
+    f.render_widget(refreshes, layout[3]);
+    f.render_widget(last_refresh, layout[4]);
+    f.render_widget(hint, layout[6]);
+
+    area
+}
@@ -1,22 +1,24 @@
+use chrono::Local;
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Style,
-    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 
+use crate::i18n::{t, tf};
 use crate::themes::ThemeColors;
-use crate::ui::layout::POPUP_WIDTH;
+use crate::ui::layout::{centered_rect_fixed_height, POPUP_WIDTH};
 use crate::ui::state::CacheInfo;
 
 /// Renderiza un diálogo con información del cache
-pub fn render(f: &mut Frame, colors: &ThemeColors, info: &CacheInfo) {
+pub fn render(f: &mut Frame, colors: &ThemeColors, info: &CacheInfo) -> Rect {
     // Usar altura fija en lugar de porcentaje para evitar problemas en pantallas pequeñas
     let height = 12;
     let area = centered_rect_fixed_height(POPUP_WIDTH, height, f.area());
 
     let block = Block::default()
-        .title(" Cache Status ")
+        .title(t("cache.title"))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(colors.border));
@@ -29,7 +31,8 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, info: &CacheInfo) {
             Constraint::Length(1), // Status
             Constraint::Length(1), // Last updated
             Constraint::Length(1), // TTL
-            Constraint::Length(2), // Espaciado
+            Constraint::Length(1), // Rate limit
+            Constraint::Length(2), // Cache en cuarentena
             Constraint::Length(1), // Hint
         ])
         .vertical_margin(1)
@@ -38,27 +41,49 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, info: &CacheInfo) {
 
     // Status con color
     let (status_text, status_color) = if info.is_fresh {
-        ("Status: Fresh", colors.success)
+        (t("cache.fresh"), colors.success)
     } else {
-        ("Status: Expired", colors.warning)
+        (t("cache.expired"), colors.warning)
     };
 
     let status = Paragraph::new(status_text).style(Style::default().fg(status_color));
 
     // Last updated
     let last_updated_text = match &info.last_updated {
-        Some(time) => format!("Last updated: {}", time),
-        None => "Last updated: Never".to_string(),
+        Some(time) => tf("cache.updated", &[time]),
+        None => t("cache.never").to_string(),
     };
     let last_updated =
         Paragraph::new(last_updated_text).style(Style::default().fg(colors.foreground));
 
     // TTL
-    let ttl = Paragraph::new(format!("TTL: {} minutes", info.ttl_minutes))
+    let ttl = Paragraph::new(tf("cache.ttl", &[&info.ttl_minutes]))
         .style(Style::default().fg(colors.muted));
 
+    // Llamadas restantes a la API según la última respuesta
+    let rate_limit_text = match info.rate_limit.and_then(|r| r.remaining.map(|n| (n, r.reset_at))) {
+        Some((remaining, Some(reset))) => tf(
+            "cache.calls_reset",
+            &[&remaining, &reset.with_timezone(&Local).format("%H:%M")],
+        ),
+        Some((remaining, None)) => tf("cache.calls", &[&remaining]),
+        None => t("cache.calls_unknown").to_string(),
+    };
+    let rate_limit = Paragraph::new(rate_limit_text).style(Style::default().fg(colors.muted));
+
+    // Cache corrupto apartado y resultado del refetch
+    let quarantine = info.quarantined.as_ref().map(|path| {
+        let outcome = match &info.refetch_error {
+            Some(error) => tf("cache.refetch_failed", &[error]),
+            None => t("cache.refetched").to_string(),
+        };
+        Paragraph::new(tf("cache.quarantined", &[path, &outcome]))
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(colors.warning))
+    });
+
     // Hint
-    let hint = Paragraph::new("Press any key to close")
+    let hint = Paragraph::new(t("dialog.close_hint"))
         .alignment(Alignment::Center)
         .style(Style::default().fg(colors.muted));
 
@@ -67,35 +92,16 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, info: &CacheInfo) {
     f.render_widget(block, area);
 
     // Safety check: ensure layout has enough chunks (in case vertical_margin reduces space too much)
-    if layout.len() >= 5 {
+    if layout.len() >= 6 {
         f.render_widget(status, layout[0]);
         f.render_widget(last_updated, layout[1]);
         f.render_widget(ttl, layout[2]);
-        f.render_widget(hint, layout[4]);
+        f.render_widget(rate_limit, layout[3]);
+        if let Some(quarantine) = quarantine {
+            f.render_widget(quarantine, layout[4]);
+        }
+        f.render_widget(hint, layout[5]);
     }
-}
-
-/// Helper local para centrar con altura fija
-fn centered_rect_fixed_height(
-    percent_x: u16,
-    height: u16,
-    r: ratatui::layout::Rect,
-) -> ratatui::layout::Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Fill(1),
-            Constraint::Length(height),
-            Constraint::Fill(1),
-        ])
-        .split(r);
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+    area
 }
@@ -5,19 +5,21 @@ use ratatui::{
     Frame,
 };
 
+use crate::i18n::t;
 use crate::themes::ThemeColors;
 use crate::ui::layout::{centered_rect, POPUP_HEIGHT, POPUP_WIDTH};
-use crate::ui::state::AppStateManager;
+use crate::ui::state::{AppStateManager, ClickAreas, ListArea, MenuRow};
+use crate::ui::styles::{icons, pad_to_width};
 
 const MIN_VISIBLE_FOR_SCROLL: usize = 5;
 
-pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) {
+pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) -> ClickAreas {
     let area = centered_rect(POPUP_WIDTH, POPUP_HEIGHT, f.area());
 
     f.render_widget(Clear, area);
 
     let block = Block::default()
-        .title(" Commands ")
+        .title(t("menu.title"))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(colors.border))
         .title_style(
@@ -29,32 +31,51 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) {
     let inner = block.inner(area);
     f.render_widget(block, area);
 
-    let total_commands = app.commands.len();
+    // Las cabeceras de sección ocupan filas: el scroll va por filas
+    let rows = app.menu_rows();
+    let total_rows = rows.len();
 
-    let (start, end) = if total_commands > MIN_VISIBLE_FOR_SCROLL {
+    let (start, end) = if total_rows > MIN_VISIBLE_FOR_SCROLL {
         let visible_rows = inner.height as usize;
-        if visible_rows >= total_commands {
-            (0, total_commands)
+        if visible_rows >= total_rows {
+            (0, total_rows)
         } else {
             let start = app.command_scroll_offset;
-            (start, (start + visible_rows).min(total_commands))
+            (start, (start + visible_rows).min(total_rows))
         }
     } else {
-        (0, total_commands)
+        (0, total_rows)
     };
 
-    let items: Vec<ListItem> = app.commands[start..end]
+    let items: Vec<ListItem> = rows[start..end]
         .iter()
-        .enumerate()
-        .map(|(i, cmd)| {
-            let actual_index = start + i;
+        .map(|row| {
+            let actual_index = match *row {
+                MenuRow::Command(index) => index,
+                MenuRow::Header(section) => {
+                    let header = Span::styled(
+                        section.label(),
+                        Style::default()
+                            .fg(colors.muted)
+                            .add_modifier(Modifier::BOLD),
+                    );
+                    return ListItem::new(Line::from(header));
+                }
+            };
+            let cmd = &app.commands[actual_index];
             let shortcut_char = cmd.shortcut.map_or(' ', |s| s.to_ascii_uppercase());
-            let label = format!("[{}] {}", shortcut_char, cmd.label);
-            let padding = " ".repeat(inner.width as usize - label.len());
-            let text = format!("{}{}", label, padding);
+            let disabled = app.is_command_disabled(cmd.id);
+            let label = if disabled {
+                format!("  [{}] {} {}", shortcut_char, cmd.label, icons().lock)
+            } else {
+                format!("  [{}] {}", shortcut_char, cmd.label)
+            };
+            let text = pad_to_width(&label, inner.width as usize);
 
             // Style for the text (foreground color)
-            let text_style = if actual_index == app.selected_command {
+            let text_style = if disabled {
+                Style::default().fg(colors.muted)
+            } else if actual_index == app.selected_command {
                 Style::default()
                     .fg(colors.success)
                     .add_modifier(Modifier::BOLD)
@@ -77,4 +98,15 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) {
 
     let list = List::new(items).highlight_symbol("");
     f.render_widget(list, inner);
+
+    ClickAreas {
+        popup: Some(area),
+        list: Some(ListArea {
+            area: inner,
+            first: start,
+            len: end - start,
+            total: total_rows,
+        }),
+        ..ClickAreas::default()
+    }
 }
@@ -0,0 +1,283 @@
+use chrono::{DateTime, Utc};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::compare::{self, Comparison};
+use crate::history::periods;
+use crate::i18n::{t, tf};
+use crate::models::UsageStats;
+use crate::themes::ThemeColors;
+use crate::ui::layout::centered_rect_fixed_height;
+use crate::ui::state::{AppStateManager, MonthLoad};
+use crate::ui::styles::{
+    error_style, header_style, icons, muted_style, pad_to_width, success_style, truncate_middle,
+    warning_style,
+};
+
+/// Ancho del popup, en % del terminal
+const COMPARE_WIDTH: u16 = 60;
+
+/// Model rows shown; the rest are summed up in one line
+const COMPARE_MODEL_ROWS: usize = 8;
+
+/// Each number column, right-aligned
+const COLUMN_WIDTH: usize = 10;
+
+/// Este mes contra el anterior: totales y una fila por modelo, con el
+/// cambio coloreado según suba o baje
+pub fn render(
+    f: &mut Frame,
+    colors: &ThemeColors,
+    stats: &UsageStats,
+    app: &AppStateManager,
+    now: DateTime<Utc>,
+) -> Rect {
+    // Lo que dejan bordes, márgenes y las tres columnas es para el nombre
+    let width = centered_rect_fixed_height(COMPARE_WIDTH, 0, f.area()).width as usize;
+    let name_width = width.saturating_sub(6 + 3 * (COLUMN_WIDTH + 1));
+
+    let previous = app.previous_month.as_ref();
+    let lines = match previous.map(|previous| (previous.period, &previous.usage)) {
+        Some(((previous_year, previous_month), MonthLoad::Loaded(previous))) => {
+            let (year, month) = periods::current_period(now);
+            let months = (
+                periods::month_name(year, month),
+                periods::month_name(previous_year, previous_month),
+            );
+            let comparison = Comparison::new(stats, previous);
+            comparison_lines(&comparison, months, name_width, colors)
+        }
+        Some((_, MonthLoad::Failed(reason))) => vec![Line::from(Span::styled(
+            format!("⚠ {}", reason),
+            error_style(),
+        ))],
+        Some((_, MonthLoad::Loading)) | None => vec![Line::from(Span::styled(
+            format!("{} {}", app.get_spinner_char(), t("compare.loading")),
+            muted_style(colors),
+        ))],
+    };
+
+    // Líneas + hint + bordes y márgenes
+    let height = lines.len() as u16 + 5;
+    let area = centered_rect_fixed_height(COMPARE_WIDTH, height, f.area());
+
+    let block = Block::default()
+        .title(tf("compare.title", &[&icons().trend]))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.border))
+        .title_style(
+            Style::default()
+                .fg(colors.foreground)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let inner = block.inner(area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Comparación
+            Constraint::Length(1), // Hint
+        ])
+        .vertical_margin(1)
+        .horizontal_margin(2)
+        .split(inner);
+
+    let hint = Paragraph::new(t("helpbar.back"))
+        .alignment(Alignment::Center)
+        .style(muted_style(colors));
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(Paragraph::new(lines), layout[0]);
+    f.render_widget(hint, layout[1]);
+
+    area
+}
+
+/// Header, total, a blank line and the models
+fn comparison_lines(
+    comparison: &Comparison,
+    (this_month, last_month): (String, String),
+    name_width: usize,
+    colors: &ThemeColors,
+) -> Vec<Line<'static>> {
+    let header = format!(
+        "{:name_width$} {:>width$} {:>width$} {:>width$}",
+        "",
+        this_month,
+        last_month,
+        t("compare.change"),
+        name_width = name_width,
+        width = COLUMN_WIDTH
+    );
+    let mut lines = vec![
+        Line::from(Span::styled(header, header_style(colors))),
+        row(
+            t("compare.total"),
+            comparison.current_total,
+            comparison.previous_total,
+            comparison.change(),
+            name_width,
+            colors,
+        ),
+    ];
+    if !comparison.models.is_empty() {
+        lines.push(Line::default());
+    }
+    for model in comparison.models.iter().take(COMPARE_MODEL_ROWS) {
+        lines.push(row(
+            &model.name,
+            model.current,
+            model.previous,
+            model.change(),
+            name_width,
+            colors,
+        ));
+    }
+    let hidden = comparison.models.len().saturating_sub(COMPARE_MODEL_ROWS);
+    if hidden > 0 {
+        lines.push(Line::from(Span::styled(
+            tf("compare.more", &[&hidden]),
+            muted_style(colors),
+        )));
+    }
+    lines
+}
+
+fn row(
+    name: &str,
+    current: f64,
+    previous: f64,
+    change: Option<f64>,
+    name_width: usize,
+    colors: &ThemeColors,
+) -> Line<'static> {
+    // Más solicitudes que el mes pasado en naranja, menos en verde
+    let change_style = match change {
+        Some(change) if change > 0.0 => warning_style(),
+        Some(change) if change < 0.0 => success_style(colors),
+        _ => muted_style(colors),
+    };
+    Line::from(vec![
+        Span::styled(
+            pad_to_width(&truncate_middle(name, name_width), name_width),
+            Style::default().fg(colors.foreground),
+        ),
+        Span::styled(
+            format!(
+                " {:>width$.0} {:>width$.0}",
+                current,
+                previous,
+                width = COLUMN_WIDTH
+            ),
+            Style::default().fg(colors.foreground),
+        ),
+        Span::styled(
+            format!(
+                " {:>width$}",
+                compare::format_change(change),
+                width = COLUMN_WIDTH
+            ),
+            change_style,
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::calculate_stats;
+    use crate::models::{TimePeriod, UsageData, UsageItem};
+    use chrono::TimeZone;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn stats(models: &[(&str, f64)]) -> UsageStats {
+        let usage_items = models
+            .iter()
+            .map(|&(model, quantity)| UsageItem {
+                product: "copilot".to_string(),
+                sku: "premium".to_string(),
+                model: model.to_string(),
+                unit_type: "request".to_string(),
+                price_per_unit: 0.04,
+                gross_quantity: quantity,
+                gross_amount: quantity * 0.04,
+                discount_quantity: quantity,
+                discount_amount: quantity * 0.04,
+                net_quantity: 0.0,
+                net_amount: 0.0,
+                date: None,
+            })
+            .collect();
+        let data = UsageData {
+            time_period: TimePeriod {
+                year: 2026,
+                month: Some(10),
+                day: None,
+            },
+            user: "testuser".to_string(),
+            usage_items,
+            note: None,
+        };
+        calculate_stats(&data, 300)
+    }
+
+    fn rows(current: &UsageStats, app: &AppStateManager) -> Vec<String> {
+        let colors = ThemeColors::dark();
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal
+            .draw(|f| {
+                render(f, &colors, current, app, now);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..30)
+            .map(|y| (0..100).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    fn find<'a>(rows: &'a [String], needle: &str) -> &'a String {
+        rows.iter()
+            .find(|row| row.contains(needle))
+            .unwrap_or_else(|| panic!("missing {:?}", needle))
+    }
+
+    #[test]
+    fn test_shows_totals_and_model_changes() {
+        let mut app = AppStateManager::new();
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let period = app.open_compare_view(today).unwrap();
+        let previous = stats(&[("claude-sonnet-4", 60.0), ("o3", 20.0)]);
+        app.set_previous_month(period, MonthLoad::Loaded(Box::new(previous)));
+
+        let current = stats(&[("claude-sonnet-4", 90.0), ("gpt-4.1", 30.0)]);
+        let rows = rows(&current, &app);
+        assert!(find(&rows, "October").contains("September"));
+        assert!(find(&rows, "Total").contains("▲ 50.0%"));
+        assert!(find(&rows, "claude-sonnet-4").contains("▲ 50.0%"));
+        // Sin uso el mes pasado no hay porcentaje
+        assert!(find(&rows, "gpt-4.1").contains("n/a"));
+        assert!(find(&rows, "o3").contains("▼ 100.0%"));
+    }
+
+    #[test]
+    fn test_loading_and_failure() {
+        let mut app = AppStateManager::new();
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let period = app.open_compare_view(today).unwrap();
+        let current = stats(&[]);
+        find(&rows(&current, &app), "loading last month");
+
+        app.set_previous_month(period, MonthLoad::Failed("GitHub API error".into()));
+        find(&rows(&current, &app), "⚠ GitHub API error");
+    }
+}
@@ -0,0 +1,209 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::i18n::{t, tf};
+use crate::themes::ThemeColors;
+use crate::ui::layout::centered_rect_fixed_height;
+use crate::ui::state::{AppStateManager, DAILY_DAYS, DAILY_VISIBLE_ROWS, DayLoad, DayRow};
+use crate::ui::styles::{
+    BAR_BRAILLE_EMPTY, BAR_BRAILLE_FILLED, calculate_filled_cells, error_style, icons, muted_style,
+    success_style_bold,
+};
+
+/// Ancho del popup, en % del terminal
+const DAILY_WIDTH: u16 = 60;
+
+/// "Mon Mar 02"
+const DATE_WIDTH: usize = 10;
+
+/// Requests column, right-aligned
+const COUNT_WIDTH: usize = 6;
+
+/// Tabla de solicitudes por día, con una mini barra por fila relativa al
+/// día con más uso
+pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) -> Rect {
+    // Filas + hint + bordes y márgenes
+    let height = DAILY_VISIBLE_ROWS as u16 + 5;
+    let area = centered_rect_fixed_height(DAILY_WIDTH, height, f.area());
+
+    let block = Block::default()
+        .title(tf("daily.title", &[&icons().trend, &DAILY_DAYS]))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.border))
+        .title_style(
+            Style::default()
+                .fg(colors.foreground)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let inner = block.inner(area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Días
+            Constraint::Length(1), // Hint
+        ])
+        .vertical_margin(1)
+        .horizontal_margin(2)
+        .split(inner);
+
+    let max = app
+        .daily_rows
+        .iter()
+        .filter_map(|row| match row.requests {
+            DayLoad::Loaded(requests) => Some(requests),
+            _ => None,
+        })
+        .fold(0.0, f64::max);
+    let bar_width = (layout[0].width as usize).saturating_sub(DATE_WIDTH + COUNT_WIDTH + 2);
+
+    let lines: Vec<Line> = app
+        .daily_rows
+        .iter()
+        .skip(app.daily_scroll_offset)
+        .take(DAILY_VISIBLE_ROWS)
+        .map(|row| day_line(row, max, bar_width, app.get_spinner_char(), colors))
+        .collect();
+
+    let hint = Paragraph::new(t("helpbar.daily"))
+        .alignment(Alignment::Center)
+        .style(muted_style(colors));
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(Paragraph::new(lines), layout[0]);
+    f.render_widget(hint, layout[1]);
+
+    area
+}
+
+fn day_line(
+    row: &DayRow,
+    max: f64,
+    bar_width: usize,
+    spinner: char,
+    colors: &ThemeColors,
+) -> Line<'static> {
+    let date = Span::styled(
+        format!(
+            "{:<width$} ",
+            row.date.format("%a %b %d"),
+            width = DATE_WIDTH
+        ),
+        Style::default().fg(colors.foreground),
+    );
+
+    match &row.requests {
+        DayLoad::Loading => Line::from(vec![
+            date,
+            Span::styled(
+                format!("{} {}", spinner, t("daily.loading")),
+                muted_style(colors),
+            ),
+        ]),
+        DayLoad::Failed(reason) => Line::from(vec![
+            date,
+            Span::styled(format!("⚠ {}", reason), error_style()),
+        ]),
+        DayLoad::Loaded(requests) => {
+            let percentage = if max > 0.0 {
+                requests / max * 100.0
+            } else {
+                0.0
+            };
+            let filled = calculate_filled_cells(percentage, bar_width);
+            Line::from(vec![
+                date,
+                Span::styled(
+                    BAR_BRAILLE_FILLED.repeat(filled),
+                    success_style_bold(colors),
+                ),
+                Span::styled(
+                    BAR_BRAILLE_EMPTY.repeat(bar_width - filled),
+                    muted_style(colors),
+                ),
+                Span::styled(
+                    format!(" {:>width$.0}", requests, width = COUNT_WIDTH),
+                    Style::default().fg(colors.foreground),
+                ),
+            ])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn rows(app: &AppStateManager) -> Vec<String> {
+        let colors = ThemeColors::dark();
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal
+            .draw(|f| {
+                render(f, &colors, app);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..30)
+            .map(|y| (0..100).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_rows_show_requests_and_state() {
+        let mut app = AppStateManager::new();
+        let today = NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        let dates = app.open_daily_view(today);
+        app.set_day(dates[0], DayLoad::Loaded(12.0));
+        app.set_day(dates[1], DayLoad::Loaded(6.0));
+        app.set_day(dates[2], DayLoad::Failed("GitHub API error".to_string()));
+        let rows = rows(&app);
+
+        let find = |needle: &str| {
+            rows.iter()
+                .find(|row| row.contains(needle))
+                .unwrap_or_else(|| panic!("missing {:?}", needle))
+        };
+        find("Daily Usage (last 30 days)");
+
+        // El día con más uso llena la barra; la mitad, media barra
+        let full = find("Fri Mar 20");
+        let half = find("Thu Mar 19");
+        let count = |row: &str| row.matches(BAR_BRAILLE_FILLED).count();
+        // Sin el borde derecho del popup
+        let requests = |row: &str| {
+            let row = row.trim_end().trim_end_matches('│').trim_end();
+            row.rsplit(' ').next().unwrap().to_string()
+        };
+        assert_eq!(requests(full), "12");
+        assert_eq!(requests(half), "6");
+        assert_eq!(count(half), count(full) / 2);
+
+        assert!(find("Wed Mar 18").contains("⚠ GitHub API error"));
+        assert!(find("Tue Mar 17").contains("loading…"));
+        // Solo se ven las filas que caben
+        assert!(!rows.iter().any(|row| row.contains("Mar 10")));
+    }
+
+    #[test]
+    fn test_scroll_moves_the_window() {
+        let mut app = AppStateManager::new();
+        app.open_daily_view(NaiveDate::from_ymd_opt(2026, 3, 20).unwrap());
+        app.scroll_daily_down();
+        app.scroll_daily_down();
+        let rows = rows(&app);
+        assert!(!rows.iter().any(|row| row.contains("Mar 19")));
+        assert!(rows.iter().any(|row| row.contains("Mar 18")));
+        assert!(rows.iter().any(|row| row.contains("Mar 09")));
+    }
+}
@@ -1,15 +1,16 @@
 use ratatui::{
     Frame,
-    layout::Alignment,
+    layout::{Alignment, Rect},
     style::{Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, Paragraph},
 };
 
+use crate::i18n::t;
 use crate::themes::ThemeColors;
 use crate::ui::layout::centered_rect;
 
-pub fn render_confirm(f: &mut Frame, colors: &ThemeColors, title: &str, message: &str) {
+pub fn render_confirm(f: &mut Frame, colors: &ThemeColors, title: &str, message: &str) -> Rect {
     let area = centered_rect(50, 20, f.area());
 
     f.render_widget(Clear, area);
@@ -37,17 +38,19 @@ pub fn render_confirm(f: &mut Frame, colors: &ThemeColors, title: &str, message:
                     .fg(colors.success)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::styled(": Yes  ", Style::default().fg(colors.muted)),
+            Span::styled(t("confirm.yes"), Style::default().fg(colors.muted)),
             Span::styled(
                 "n",
                 Style::default()
                     .fg(colors.error)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::styled(": No", Style::default().fg(colors.muted)),
+            Span::styled(t("confirm.no"), Style::default().fg(colors.muted)),
         ]),
     ]))
     .alignment(Alignment::Center);
 
     f.render_widget(text, inner);
+
+    area
 }
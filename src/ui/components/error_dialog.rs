@@ -1,10 +1,11 @@
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Style,
     widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
 };
 
+use crate::i18n::t;
 use crate::themes::ThemeColors;
 use crate::ui::layout::centered_rect;
 
@@ -15,13 +16,14 @@ pub fn render(
     message: &str,
     debug_message: &str,
     show_debug: bool,
-) {
+    has_export: bool,
+) -> Rect {
     let area = centered_rect(70, 60, f.area());
 
     let title = if show_debug {
-        " Error (Debug) "
+        t("error.title_debug")
     } else {
-        " Error "
+        t("error.title")
     };
 
     let block = Block::default()
@@ -52,9 +54,11 @@ pub fn render(
 
     // Hint - mostrar opción de debug si no está activo
     let hint_text = if show_debug {
-        "Press 'd' to hide details, any other key to close"
+        t("error.hint_debug")
+    } else if has_export {
+        t("error.hint_export")
     } else {
-        "Press 'd' for details, any other key to close"
+        t("error.hint")
     };
 
     let hint = Paragraph::new(hint_text)
@@ -66,4 +70,6 @@ pub fn render(
     f.render_widget(block, area);
     f.render_widget(error, layout[0]);
     f.render_widget(hint, layout[2]);
+
+    area
 }
@@ -1,30 +1,47 @@
-use chrono::Local;
+use chrono::{DateTime, Local, Utc};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
     Frame,
 };
 
+use crate::config::token_expiry_notice;
+use crate::i18n::{t, tf};
 use crate::models::{Theme, UsageStats};
 use crate::themes::ThemeColors;
-use crate::ui::styles::{ICON_CALENDAR, ICON_RESET, ICON_ROBOT, ICON_THEME, ICON_USER};
+use crate::ui::state::AppStateManager;
+use crate::ui::styles::{countdown_style, format_countdown, icons, truncate_end};
 
-pub fn render(f: &mut Frame, area: Rect, stats: &UsageStats, colors: &ThemeColors, theme: Theme) {
+/// Cells for the username, so a long one leaves room for the rest
+const USERNAME_WIDTH: usize = 12;
+
+pub fn render(
+    f: &mut Frame,
+    area: Rect,
+    stats: &UsageStats,
+    colors: &ThemeColors,
+    theme: Theme,
+    app: &AppStateManager,
+    now: DateTime<Utc>,
+) {
     // Check for compact mode
     let is_compact = area.width < 60;
 
-    // Truncar username a 10 caracteres + ".." si es necesario
-    let display_username = if stats.username.len() > 10 {
-        format!("{}..", &stats.username[..10])
-    } else {
-        stats.username.clone()
+    // Username en 12 celdas como mucho, ".." incluido
+    let display_username = truncate_end(&stats.username, USERNAME_WIDTH, "..");
+
+    // Plan name next to the username when known
+    let display_username = match stats.plan {
+        Some(plan) => format!("{} ({})", display_username, plan.name()),
+        None => display_username,
     };
 
     // Theme name capitalized
     let theme_name = match theme {
         Theme::Dark => "Dark",
+        Theme::Light => "Light",
         Theme::Nord => "Nord",
         Theme::Monokai => "Monokai",
         Theme::Gruvbox => "Gruvbox",
@@ -38,10 +55,63 @@ pub fn render(f: &mut Frame, area: Rect, stats: &UsageStats, colors: &ThemeColor
     if is_compact {
         render_compact(f, area, stats, colors, theme_name, display_username);
     } else {
-        render_full(f, area, stats, colors, theme_name, display_username);
+        render_full(f, area, stats, colors, theme_name, display_username, now);
+    }
+
+    if let Some(notice) = token_expiry_notice(app.token_expires, now.date_naive()) {
+        render_banner(f, area, notice, colors.warning);
+    }
+
+    // Sin refresh no hay "actualizado" que mostrar
+    if let Some(name) = app.read_only.as_deref() {
+        render_indicator(
+            f,
+            area,
+            tf("header.read_only", &[&icons().lock, &name]),
+            colors.warning,
+        );
+    } else if app.updated_just_now() {
+        render_indicator(
+            f,
+            area,
+            tf("header.updated", &[&icons().reset]),
+            colors.success,
+        );
     }
 }
 
+/// Small tag at the right of the title row
+fn render_indicator(f: &mut Frame, area: Rect, text: String, color: Color) {
+    let title_row = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .horizontal_margin(1)
+        .split(area)[0];
+
+    let indicator = Paragraph::new(Line::from(Span::styled(text, Style::default().fg(color))))
+        .alignment(Alignment::Right);
+    f.render_widget(indicator, title_row);
+}
+
+/// Warning in the spacer row under the title
+fn render_banner(f: &mut Frame, area: Rect, text: String, color: Color) {
+    let banner_row = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .horizontal_margin(1)
+        .split(area)[1];
+
+    let banner = Paragraph::new(Line::from(Span::styled(
+        truncate_end(&text, usize::from(banner_row.width), ".."),
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    )));
+    f.render_widget(banner, banner_row);
+}
+
 fn render_compact(
     f: &mut Frame,
     area: Rect,
@@ -63,7 +133,7 @@ fn render_compact(
 
     // Title row (left aligned)
     let title = Paragraph::new(Line::from(vec![Span::styled(
-        format!("{} GitHub Copilot Usage", ICON_ROBOT),
+        format!("{} {}", icons().robot, t("header.title")),
         Style::default()
             .fg(colors.foreground)
             .add_modifier(Modifier::BOLD),
@@ -78,7 +148,7 @@ fn render_compact(
 
     // Left side: only username
     let user_info = Paragraph::new(Line::from(vec![Span::styled(
-        format!("{} {}", ICON_USER, display_username),
+        format!("{} {}", icons().user, display_username),
         Style::default().fg(colors.muted),
     )]));
     f.render_widget(user_info, info_cols[0]);
@@ -86,15 +156,15 @@ fn render_compact(
     // Right side: theme name + color dots (right aligned)
     let theme_info = Paragraph::new(Line::from(vec![
         Span::styled(
-            format!("{} {} ", ICON_THEME, theme_name),
+            format!("{} {} ", icons().theme, theme_name),
             Style::default().fg(colors.foreground),
         ),
-        Span::styled("●", Style::default().fg(colors.foreground)),
-        Span::styled("●", Style::default().fg(colors.success)),
-        Span::styled("●", Style::default().fg(colors.warning)),
-        Span::styled("●", Style::default().fg(colors.error)),
-        Span::styled("●", Style::default().fg(colors.muted)),
-        Span::styled("●", Style::default().fg(colors.border)),
+        Span::styled(icons().dot, Style::default().fg(colors.foreground)),
+        Span::styled(icons().dot, Style::default().fg(colors.success)),
+        Span::styled(icons().dot, Style::default().fg(colors.warning)),
+        Span::styled(icons().dot, Style::default().fg(colors.error)),
+        Span::styled(icons().dot, Style::default().fg(colors.muted)),
+        Span::styled(icons().dot, Style::default().fg(colors.border)),
     ]))
     .alignment(Alignment::Right);
     f.render_widget(theme_info, info_cols[1]);
@@ -107,6 +177,7 @@ fn render_full(
     colors: &ThemeColors,
     theme_name: &str,
     display_username: String,
+    now: DateTime<Utc>,
 ) {
     // Split area into rows: title, spacer, and info line
     let rows = Layout::default()
@@ -121,13 +192,15 @@ fn render_full(
 
     // Title row (left aligned)
     let title = Paragraph::new(Line::from(vec![Span::styled(
-        format!("{} GitHub Copilot Usage", ICON_ROBOT),
+        format!("{} {}", icons().robot, t("header.title")),
         Style::default()
             .fg(colors.foreground)
             .add_modifier(Modifier::BOLD),
     )]));
     f.render_widget(title, rows[0]);
 
+    let until_reset = stats.until_reset(now);
+
     // Info row: split into left (date/user) and right (theme)
     let info_cols = Layout::default()
         .direction(Direction::Horizontal)
@@ -137,21 +210,24 @@ fn render_full(
     // Left side: date, reset, username
     let date_info = Paragraph::new(Line::from(vec![
         Span::styled(
-            format!("{} {}", ICON_CALENDAR, Local::now().format("%d %B %Y")),
+            format!(
+                "{} {}",
+                icons().calendar,
+                now.with_timezone(&Local).format("%d %B %Y")
+            ),
             Style::default().fg(colors.muted),
         ),
-        Span::styled(" • ", Style::default().fg(colors.muted)),
+        Span::styled(icons().separator, Style::default().fg(colors.muted)),
         Span::styled(
-            format!(
-                "{} Resets: {}",
-                ICON_RESET,
-                stats.reset_date.format("%b %d")
+            tf(
+                "usage.resets_in",
+                &[&icons().reset, &format_countdown(until_reset)],
             ),
-            Style::default().fg(colors.muted),
+            countdown_style(until_reset, colors),
         ),
-        Span::styled(" • ", Style::default().fg(colors.muted)),
+        Span::styled(icons().separator, Style::default().fg(colors.muted)),
         Span::styled(
-            format!("{} {}", ICON_USER, display_username),
+            format!("{} {}", icons().user, display_username),
             Style::default().fg(colors.muted),
         ),
     ]));
@@ -160,15 +236,15 @@ fn render_full(
     // Right side: theme name + color dots (right aligned)
     let theme_info = Paragraph::new(Line::from(vec![
         Span::styled(
-            format!("{} {} ", ICON_THEME, theme_name),
+            format!("{} {} ", icons().theme, theme_name),
             Style::default().fg(colors.foreground),
         ),
-        Span::styled("●", Style::default().fg(colors.foreground)),
-        Span::styled("●", Style::default().fg(colors.success)),
-        Span::styled("●", Style::default().fg(colors.warning)),
-        Span::styled("●", Style::default().fg(colors.error)),
-        Span::styled("●", Style::default().fg(colors.muted)),
-        Span::styled("●", Style::default().fg(colors.border)),
+        Span::styled(icons().dot, Style::default().fg(colors.foreground)),
+        Span::styled(icons().dot, Style::default().fg(colors.success)),
+        Span::styled(icons().dot, Style::default().fg(colors.warning)),
+        Span::styled(icons().dot, Style::default().fg(colors.error)),
+        Span::styled(icons().dot, Style::default().fg(colors.muted)),
+        Span::styled(icons().dot, Style::default().fg(colors.border)),
     ]))
     .alignment(Alignment::Right);
     f.render_widget(theme_info, info_cols[1]);
@@ -1,20 +1,30 @@
 use ratatui::{
     Frame,
+    layout::Margin,
     style::{Modifier, Style},
-    text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, Paragraph},
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
 };
 
+use crate::i18n::t;
 use crate::themes::ThemeColors;
+use crate::ui::events::DASHBOARD_COMMAND_KEYS;
 use crate::ui::layout::centered_rect;
+use crate::ui::state::{AppStateManager, ClickAreas, ListArea};
+use crate::ui::styles::icons;
 
-pub fn render(f: &mut Frame, colors: &ThemeColors) {
+/// Columna de las teclas, como la de los textos fijos de la ayuda
+const KEY_WIDTH: usize = 14;
+
+/// Ayuda desplazable; las teclas de comandos salen del menú, así que no se
+/// quedan atrás cuando se añade uno
+pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) -> ClickAreas {
     let area = centered_rect(70, 70, f.area());
 
     f.render_widget(Clear, area);
 
     let block = Block::default()
-        .title(" Help ")
+        .title(t("help.title"))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(colors.border))
         .title_style(
@@ -26,41 +36,165 @@ pub fn render(f: &mut Frame, colors: &ThemeColors) {
     let inner = block.inner(area);
     f.render_widget(block, area);
 
-    let help_text = vec![
-        Line::from(vec![Span::styled(
-            "Keyboard Shortcuts",
+    let lines = help_lines(app, colors);
+    let total = lines.len();
+    let visible = (inner.height as usize).min(total);
+    // El terminal pudo crecer desde el último desplazamiento
+    let first = app.help_scroll_offset.min(total - visible);
+
+    let help = Paragraph::new(lines).scroll((first as u16, 0));
+    f.render_widget(help, inner);
+
+    if total > visible {
+        let mut state = ScrollbarState::new(total - visible)
+            .position(first)
+            .viewport_content_length(visible);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .style(Style::default().fg(colors.muted));
+        f.render_stateful_widget(scrollbar, area.inner(Margin::new(0, 1)), &mut state);
+    }
+
+    ClickAreas {
+        popup: Some(area),
+        list: Some(ListArea {
+            area: inner,
+            first,
+            len: visible,
+            total,
+        }),
+        ..ClickAreas::default()
+    }
+}
+
+fn help_lines(app: &AppStateManager, colors: &ThemeColors) -> Vec<Line<'static>> {
+    let section = |key| Line::styled(t(key), Style::default().fg(colors.success));
+
+    let mut lines = vec![
+        Line::styled(
+            t("help.shortcuts"),
             Style::default()
                 .fg(colors.foreground)
                 .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Global:",
-            Style::default().fg(colors.success),
-        )]),
-        Line::from("  / or :        Open command menu"),
-        Line::from("  r             Refresh data from API"),
-        Line::from("  t             Change theme"),
-        Line::from("  h             Show this help"),
-        Line::from("  q             Quit application"),
+        ),
         Line::from(""),
-        Line::from(vec![Span::styled(
-            "In Menus:",
-            Style::default().fg(colors.success),
-        )]),
-        Line::from("  ↑/↓ or j/k    Navigate"),
-        Line::from("  Enter         Select item"),
-        Line::from("  Esc           Close/Cancel"),
-        Line::from("  Letter        Quick jump to command"),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Navigation:",
-            Style::default().fg(colors.success),
-        )]),
-        Line::from("  The dashboard shows your Copilot usage"),
-        Line::from("  statistics in real-time."),
+        section("help.global"),
+        Line::from(t("help.menu")),
+        Line::from(t("help.filter")),
     ];
+    for (key, id) in DASHBOARD_COMMAND_KEYS {
+        if let Some(cmd) = app.commands.iter().find(|cmd| cmd.id == id) {
+            lines.push(command_line(
+                app,
+                colors,
+                key.to_string(),
+                cmd.id,
+                cmd.label,
+            ));
+        }
+    }
+    lines.push(Line::from(t("help.quantity")));
+    lines.push(Line::from(t("help.grouping")));
 
-    let help = Paragraph::new(Text::from(help_text));
-    f.render_widget(help, inner);
+    lines.push(Line::from(""));
+    lines.push(section("help.commands"));
+    for cmd in &app.commands {
+        let key = cmd
+            .shortcut
+            .map_or_else(String::new, |s| s.to_ascii_uppercase().to_string());
+        lines.push(command_line(app, colors, key, cmd.id, cmd.label));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(section("help.in_menus"));
+    for key in [
+        "help.navigate",
+        "help.page",
+        "help.select",
+        "help.close",
+        "help.jump",
+    ] {
+        lines.push(Line::from(t(key)));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(section("help.navigation"));
+    lines.push(Line::from(t("help.about_1")));
+    lines.push(Line::from(t("help.about_2")));
+    lines
+}
+
+/// Tecla y nombre del comando; uno desactivado sale atenuado y con candado
+fn command_line(
+    app: &AppStateManager,
+    colors: &ThemeColors,
+    key: String,
+    id: &str,
+    label: &str,
+) -> Line<'static> {
+    let text = format!("  {:<width$}{}", key, label, width = KEY_WIDTH);
+    if app.is_command_disabled(id) {
+        Line::styled(
+            format!("{} {}", text, icons().lock),
+            Style::default().fg(colors.muted),
+        )
+    } else {
+        Line::from(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn draw(app: &AppStateManager, height: u16) -> (Vec<String>, ClickAreas) {
+        let colors = ThemeColors::dark();
+        let mut terminal = Terminal::new(TestBackend::new(80, height)).unwrap();
+        let mut areas = ClickAreas::default();
+        terminal.draw(|f| areas = render(f, &colors, app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let rows = (0..height)
+            .map(|y| (0..80).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+        (rows, areas)
+    }
+
+    #[test]
+    fn test_lists_every_command_with_its_keys() {
+        let mut app = AppStateManager::new();
+        app.disabled_commands = vec!["reconfigure".to_string()];
+        let (rows, areas) = draw(&app, 80);
+        let screen = rows.concat();
+
+        assert!(screen.contains(&format!("  r             {}", t("menu.refresh"))));
+        assert!(screen.contains(&format!("  q             {}", t("menu.quit"))));
+        for cmd in &app.commands {
+            assert!(screen.contains(cmd.label), "missing {}", cmd.id);
+        }
+        assert!(screen.contains(&format!("{} {}", t("menu.reconfigure"), icons().lock)));
+
+        // Todo cabe: no hay barra ni nada que desplazar
+        let list = areas.list.unwrap();
+        assert_eq!(list.len, list.total);
+        assert!(!screen.contains('█'));
+    }
+
+    #[test]
+    fn test_scrolls_with_a_scrollbar_when_it_overflows() {
+        let mut app = AppStateManager::new();
+        let (rows, areas) = draw(&app, 20);
+        let list = areas.list.unwrap();
+        assert!(list.len < list.total);
+        assert!(rows.concat().contains(t("help.shortcuts")));
+        assert!(rows.iter().any(|row| row.contains('█')));
+
+        // Más allá del final se queda en la última página
+        app.help_scroll_offset = 1000;
+        let (rows, areas) = draw(&app, 20);
+        let list = areas.list.unwrap();
+        assert_eq!(list.first, list.total - list.len);
+        assert!(rows.concat().contains(t("help.about_2").trim()));
+        assert!(!rows.concat().contains(t("help.shortcuts")));
+    }
 }
@@ -1,20 +1,21 @@
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Style,
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
 
+use crate::i18n::t;
 use crate::themes::ThemeColors;
 use crate::ui::layout::centered_rect;
 
 /// Renderiza un diálogo de carga con spinner animado
-pub fn render(f: &mut Frame, colors: &ThemeColors, spinner_char: char, message: &str) {
+pub fn render(f: &mut Frame, colors: &ThemeColors, spinner_char: char, message: &str) -> Rect {
     let area = centered_rect(40, 25, f.area());
 
     // Block con bordes redondeados
     let block = Block::default()
-        .title(" Loading ")
+        .title(t("loading.title"))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(colors.border));
@@ -45,7 +46,7 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, spinner_char: char, message:
         .style(Style::default().fg(colors.success));
 
     // Hint de cancelación
-    let hint = Paragraph::new("Press Esc to cancel")
+    let hint = Paragraph::new(t("loading.hint"))
         .alignment(Alignment::Center)
         .style(Style::default().fg(colors.muted));
 
@@ -55,4 +56,6 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, spinner_char: char, message:
     f.render_widget(msg, layout[0]);
     f.render_widget(spinner, layout[1]);
     f.render_widget(hint, layout[3]);
+
+    area
 }
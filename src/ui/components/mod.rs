@@ -1,10 +1,19 @@
+pub mod about_dialog;
 pub mod cache_info_dialog;
 pub mod command_menu;
+pub mod compare_view;
+pub mod daily_view;
 pub mod dialogs;
 pub mod error_dialog;
 pub mod header;
 pub mod help_dialog;
 pub mod loading_dialog;
+pub mod model_detail;
 pub mod model_table;
+pub mod profile_selector;
+pub mod settings_form;
+pub mod text_input;
 pub mod theme_selector;
+pub mod toast;
+pub mod trend;
 pub mod usage_overall;
@@ -0,0 +1,284 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::i18n::t;
+use crate::models::{ModelUsage, UsageStats};
+use crate::themes::ThemeColors;
+use crate::ui::layout::{POPUP_WIDTH, centered_rect_fixed_height};
+use crate::ui::state::AppStateManager;
+use crate::ui::styles::{format_percentage, split_by_width, truncate_middle};
+use unicode_width::UnicodeWidthStr;
+
+const DETAIL_LINES: usize = 9;
+
+/// Column the figures start at
+const LABEL_WIDTH: usize = 16;
+
+/// Renderiza el detalle de facturación del modelo seleccionado en la tabla
+pub fn render(
+    f: &mut Frame,
+    colors: &ThemeColors,
+    stats: &UsageStats,
+    app: &AppStateManager,
+) -> Option<Rect> {
+    // Tras un refresh el índice puede quedar fuera de la lista nueva
+    let models = app.filtered_models(&stats.models);
+    let model = app.selected_model.and_then(|i| models.get(i).copied())?;
+
+    // Un nombre que no cabe en el título se muestra entero sobre las cifras
+    let width = centered_rect_fixed_height(POPUP_WIDTH, 0, f.area()).width;
+    let title_width = width.saturating_sub(4) as usize;
+    let name_lines = if model.name.width() > title_width {
+        split_by_width(&model.name, width.saturating_sub(6) as usize)
+    } else {
+        Vec::new()
+    };
+
+    // Nombre + cifras + hint + bordes y márgenes
+    let name_height = if name_lines.is_empty() {
+        0
+    } else {
+        name_lines.len() + 1
+    };
+    let reported = reported_lines(model, width.saturating_sub(6) as usize, colors);
+    let height = (DETAIL_LINES + name_height + reported.len()) as u16 + 5;
+    let area = centered_rect_fixed_height(POPUP_WIDTH, height, f.area());
+
+    let block = Block::default()
+        .title(format!(" {} ", truncate_middle(&model.name, title_width)))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.border))
+        .title_style(
+            Style::default()
+                .fg(colors.foreground)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let inner = block.inner(area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Cifras
+            Constraint::Length(1), // Hint
+        ])
+        .vertical_margin(1)
+        .horizontal_margin(2)
+        .split(inner);
+
+    let mut lines: Vec<Line> = name_lines
+        .into_iter()
+        .map(|line| Line::styled(line, Style::default().fg(colors.foreground)))
+        .collect();
+    if !lines.is_empty() {
+        lines.push(Line::default());
+    }
+    lines.extend(detail_lines(model, stats.total_used, colors));
+    lines.extend(reported);
+    let details = Paragraph::new(lines);
+
+    let hint = Paragraph::new(t("helpbar.back"))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(colors.muted));
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(details, layout[0]);
+    f.render_widget(hint, layout[1]);
+
+    Some(area)
+}
+
+fn detail_lines(
+    model: &ModelUsage,
+    total_used: f64,
+    colors: &ThemeColors,
+) -> [Line<'static>; DETAIL_LINES] {
+    let totals = &model.totals;
+    let share = if total_used > 0.0 {
+        model.used / total_used * 100.0
+    } else {
+        0.0
+    };
+
+    let row = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(
+                format!("{:<width$}", label, width = LABEL_WIDTH),
+                Style::default().fg(colors.muted),
+            ),
+            Span::styled(value, Style::default().fg(colors.foreground)),
+        ])
+    };
+
+    [
+        row(t("detail.requests"), format!("{:.1}", totals.gross_quantity)),
+        row(
+            t("detail.included"),
+            format!(
+                "{:.1} (-${:.2})",
+                totals.discount_quantity, totals.discount_amount
+            ),
+        ),
+        row(t("detail.billed"), format!("{:.1}", model.net_used)),
+        Line::default(),
+        row(t("detail.price"), format!("${:.2}", totals.price_per_unit())),
+        row(t("detail.gross_cost"), format!("${:.2}", totals.gross_amount)),
+        row(t("detail.cost"), format!("${:.2}", model.cost)),
+        Line::default(),
+        row(t("detail.share"), format_percentage(share)),
+    ]
+}
+
+/// The names a grouped row was summed from, one per line under the figures
+fn reported_lines(model: &ModelUsage, width: usize, colors: &ThemeColors) -> Vec<Line<'static>> {
+    if model.reported_names.is_empty() {
+        return Vec::new();
+    }
+    let name_width = width.saturating_sub(LABEL_WIDTH);
+    let mut lines = vec![Line::default()];
+    for (i, name) in model.reported_names.iter().enumerate() {
+        let label = if i == 0 { t("detail.reported") } else { "" };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:<width$}", label, width = LABEL_WIDTH),
+                Style::default().fg(colors.muted),
+            ),
+            Span::styled(
+                truncate_middle(name, name_width),
+                Style::default().fg(colors.foreground),
+            ),
+        ]));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModelTotals;
+    use crate::testing;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    const LONG_NAME: &str = "claude-3-7-sonnet-thinking-extended-preview-20250115-private";
+
+    fn stats() -> UsageStats {
+        let totals = ModelTotals {
+            gross_quantity: 75.0,
+            gross_amount: 3.0,
+            discount_quantity: 50.0,
+            discount_amount: 2.0,
+            net_quantity: 25.0,
+            net_amount: 1.0,
+        };
+        UsageStats {
+            estimated_cost: 1.0,
+            ..testing::stats_with(vec![
+                ModelUsage {
+                    percentage: 25.0,
+                    net_used: 25.0,
+                    cost: 1.0,
+                    totals,
+                    ..testing::model("claude-sonnet-4", 75.0)
+                },
+                testing::model("gpt-4.1", 25.0),
+            ])
+        }
+    }
+
+    fn rows(app: &AppStateManager) -> Vec<String> {
+        rows_with(app, &stats())
+    }
+
+    fn rows_with(app: &AppStateManager, stats: &UsageStats) -> Vec<String> {
+        let colors = ThemeColors::dark();
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal
+            .draw(|f| {
+                render(f, &colors, stats, app);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..30)
+            .map(|y| (0..100).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_shows_selected_model_breakdown() {
+        let mut app = AppStateManager::new();
+        app.selected_model = Some(0);
+        let rows = rows(&app);
+
+        let find = |needle: &str| {
+            rows.iter()
+                .find(|row| row.contains(needle))
+                .unwrap_or_else(|| panic!("missing {:?}", needle))
+        };
+        find(" claude-sonnet-4 ");
+        assert!(find("Requests").contains("75.0"));
+        assert!(find("Included").contains("50.0 (-$2.00)"));
+        assert!(find("Billed").contains("25.0"));
+        assert!(find("Price per unit").contains("$0.04"));
+        assert!(find("Cost ").contains("$1.00"));
+        assert!(find("Share of total").contains("75.0%"));
+    }
+
+    #[test]
+    fn test_stale_selection_renders_nothing() {
+        let mut app = AppStateManager::new();
+        app.selected_model = Some(5);
+        assert!(rows(&app).iter().all(|row| row.trim().is_empty()));
+    }
+
+    #[test]
+    fn test_lists_the_reported_names_of_a_group() {
+        let mut app = AppStateManager::new();
+        app.selected_model = Some(1);
+        assert!(!rows(&app).iter().any(|row| row.contains("Reported as")));
+
+        let mut stats = stats();
+        stats.models[1].reported_names = vec![
+            "Auto: gpt-4.1".to_string(),
+            "gpt-4.1".to_string(),
+            "gpt-4.1-2025-04-14".to_string(),
+        ];
+        let rows = rows_with(&app, &stats);
+        let at = rows
+            .iter()
+            .position(|row| row.contains("Reported as"))
+            .unwrap();
+        assert!(rows[at].contains("Auto: gpt-4.1"));
+        assert!(rows[at + 1].contains("gpt-4.1 "));
+        assert!(rows[at + 2].contains("gpt-4.1-2025-04-14"));
+        // El hint sigue debajo, dentro del popup
+        assert!(rows[at + 3..].iter().any(|row| row.contains("Esc")));
+    }
+
+    #[test]
+    fn test_long_name_shows_in_full() {
+        let mut stats = stats();
+        stats.models[0].name = LONG_NAME.to_string();
+        let mut app = AppStateManager::new();
+        app.selected_model = Some(0);
+        let rows = rows_with(&app, &stats);
+
+        // Título recortado por el medio; el nombre completo, en trozos
+        let title = rows.iter().find(|row| row.contains('╭')).unwrap();
+        assert!(title.contains(" claude-3-7-sonnet-") && title.contains("…-20250115-private "));
+        let text: String = rows
+            .iter()
+            .filter(|row| !row.contains('╭'))
+            .map(|row| row.trim().trim_matches('│').trim())
+            .collect();
+        assert!(text.contains(LONG_NAME));
+        assert!(rows.iter().any(|row| row.contains("Share of total")));
+    }
+}
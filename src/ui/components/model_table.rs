@@ -1,30 +1,57 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Style,
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table},
+    widgets::{
+        Block, BorderType, Borders, Cell, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table, Wrap,
+    },
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
-use crate::models::UsageStats;
+use crate::i18n::{t, tf};
+use crate::models::{ModelUsage, TableGrouping, UsageStats};
+use crate::percent::format_quantity;
 use crate::themes::ThemeColors;
-use crate::ui::state::AppStateManager;
+use crate::ui::state::{AppState, AppStateManager, ListArea};
 use crate::ui::styles::{
     calculate_filled_cells, calculate_responsive_bar_width, calculate_zone_boundaries,
-    error_style_bold, format_count, format_percentage, header_style, muted_style,
-    success_style_bold, usage_style, warning_style_bold, with_horizontal_margin, BAR_EMPTY,
-    BAR_FILLED, ICON_LIST,
+    error_style_bold, format_count, format_percentage, header_style, icons, muted_style,
+    success_style_bold, truncate_middle, usage_style, warning_style, warning_style_bold,
+    with_horizontal_margin,
+    BAR_EMPTY, BAR_FILLED, CELL_WIDTH, MIN_BAR_CELLS,
 };
 
+/// Models above this share of the limit are counted in the footer
+const FOOTER_PERCENT_THRESHOLD: u8 = 10;
+
+/// Inner height below which the footer gives its row back to the table
+/// (header + 3 rows + footer)
+const FOOTER_MIN_HEIGHT: u16 = 5;
+
+/// Usage ("100.0%" centrado), Count and Cost ("$123.45") columns
+const USAGE_WIDTH: u16 = 8;
+const COUNT_WIDTH: u16 = 7;
+const COST_WIDTH: u16 = 8;
+const COLUMN_SPACING: u16 = 2;
+
+/// Share of the row the Model column may grow to for long model ids
+const MODEL_MAX_SHARE: u16 = 40;
+
+/// Returns the rows drawn, so scrolling pages by what's really on screen
 pub fn render(
     f: &mut Frame,
     area: Rect,
     stats: &UsageStats,
     colors: &ThemeColors,
     app: &AppStateManager,
-) {
-    let has_scroll = stats.models.len() > 8;
-    let title = build_title(has_scroll, app.model_scroll_offset, stats.models.len(), 8);
+) -> Option<ListArea> {
+    let models = app.filtered_models(&stats.models);
+    let filtering = app.state == AppState::FilterModels || !app.model_filter.is_empty();
+    let filter = filtering.then_some(app.model_filter.as_str());
+
+    let title = build_title(app.table_grouping, filter);
 
     let block = Block::default()
         .title(title)
@@ -37,28 +64,103 @@ pub fn render(
     f.render_widget(block, area);
 
     if stats.models.is_empty() {
-        render_empty_state(f, inner, colors);
-        return;
+        render_empty_state(f, inner, stats.note.as_deref(), colors);
+        return None;
+    }
+    if models.is_empty() {
+        render_empty_state(f, inner, Some(t("models.no_match")), colors);
+        return None;
+    }
+
+    if inner.height < FOOTER_MIN_HEIGHT {
+        return Some(render_table(f, inner, &models, colors, app));
     }
 
-    render_table(f, inner, stats, colors, app);
+    // El pie queda fuera de la zona con scroll
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    let rows = render_table(f, chunks[0], &models, colors, app);
+
+    let selected = app.selected_model.and_then(|i| models.get(i).copied());
+    let footer_area = with_horizontal_margin(chunks[1])[0];
+    let footer_text = fit_footer(&models, selected, app.table_grouping, footer_area.width);
+    let footer = Paragraph::new(footer_text).style(muted_style(colors));
+    f.render_widget(footer, footer_area);
+    Some(rows)
 }
 
-fn build_title(has_scroll: bool, scroll: usize, total: usize, visible: usize) -> String {
-    let mut title = format!(" {} Per-Model Usage: ", ICON_LIST);
-    if has_scroll {
-        if scroll > 0 {
-            title.insert_str(0, "↑ ");
-        }
-        if scroll + visible < total {
-            title.push_str(" ↓");
-        }
+/// The footer, or only the highlighted model when both don't fit: it's
+/// where a truncated name can be read in full
+fn fit_footer(
+    models: &[&ModelUsage],
+    selected: Option<&ModelUsage>,
+    grouping: TableGrouping,
+    width: u16,
+) -> String {
+    let text = footer_text(models, selected, grouping);
+    match selected {
+        Some(model) if text.width() > width as usize => selected_text(model),
+        _ => text,
     }
-    title
 }
 
-fn render_empty_state(f: &mut Frame, area: Rect, colors: &ThemeColors) {
-    let no_data = Paragraph::new("No model usage data available")
+/// Totals of the listed rows, plus the highlighted one
+fn footer_text(
+    models: &[&ModelUsage],
+    selected: Option<&ModelUsage>,
+    grouping: TableGrouping,
+) -> String {
+    // `sum()` de f64 vacío da -0
+    let requests = models.iter().fold(0.0, |sum, model| sum + model.used);
+    let over = models
+        .iter()
+        .filter(|model| model.percentage > FOOTER_PERCENT_THRESHOLD as f64)
+        .count();
+
+    let key = match grouping {
+        TableGrouping::Model => "models.footer",
+        TableGrouping::Sku => "models.footer_sku",
+    };
+    let mut text = tf(
+        key,
+        &[
+            &format_quantity(requests),
+            &models.len(),
+            &over,
+            &FOOTER_PERCENT_THRESHOLD,
+        ],
+    );
+    if let Some(model) = selected {
+        text.push_str(" • ");
+        text.push_str(&selected_text(model));
+    }
+    text
+}
+
+fn selected_text(model: &ModelUsage) -> String {
+    tf(
+        "models.footer_selected",
+        &[&model.name, &format_quantity(model.used)],
+    )
+}
+
+fn build_title(grouping: TableGrouping, filter: Option<&str>) -> String {
+    let (key, filtered_key) = match grouping {
+        TableGrouping::Model => ("models.title", "models.title_filtered"),
+        TableGrouping::Sku => ("models.title_sku", "models.title_sku_filtered"),
+    };
+    match filter {
+        Some(filter) => tf(filtered_key, &[&icons().list, &filter]),
+        None => tf(key, &[&icons().list]),
+    }
+}
+
+fn render_empty_state(f: &mut Frame, area: Rect, message: Option<&str>, colors: &ThemeColors) {
+    let message = message.unwrap_or(t("models.empty"));
+    let no_data = Paragraph::new(message)
+        .wrap(Wrap { trim: true })
         .alignment(Alignment::Center)
         .style(muted_style(colors));
     f.render_widget(no_data, area);
@@ -67,90 +169,227 @@ fn render_empty_state(f: &mut Frame, area: Rect, colors: &ThemeColors) {
 fn render_table(
     f: &mut Frame,
     area: Rect,
-    stats: &UsageStats,
+    models: &[&ModelUsage],
     colors: &ThemeColors,
     app: &AppStateManager,
-) {
+) -> ListArea {
     let visible_count = (area.height as usize).saturating_sub(1);
-    let scroll = app
+    let mut scroll = app
         .model_scroll_offset
-        .min(stats.models.len().saturating_sub(visible_count));
+        .min(models.len().saturating_sub(visible_count));
+    // El terminal pudo encoger desde la última selección: sigue a la vista
+    if let Some(selected) = app.selected_model
+        && visible_count > 0
+        && selected >= scroll + visible_count
+    {
+        scroll = selected + 1 - visible_count;
+    }
 
-    let visible_models: Vec<_> = stats
-        .models
+    let visible_models: Vec<_> = models
         .iter()
         .skip(scroll)
         .take(visible_count)
+        .copied()
         .collect();
 
+    // Fila resaltada, relativa a las visibles
+    let highlighted = app.selected_model.and_then(|i| i.checked_sub(scroll));
+
+    let header = match app.table_grouping {
+        TableGrouping::Model => t("models.model"),
+        TableGrouping::Sku => t("models.sku"),
+    };
+    // Con todos los modelos, no solo los visibles: la columna no salta al hacer scroll
+    let longest = models
+        .iter()
+        .map(|model| model.name.width())
+        .chain([header.width()])
+        .max()
+        .unwrap_or(0);
+    let name_column = NameColumn { header, longest };
+    // Solo si algo se facturó; igual que el ancho, con todos los modelos
+    let with_cost = models.iter().any(|model| model.cost > 0.0);
+
     // Check if we should use compact mode (hide progress bar when terminal is small)
     let is_compact = area.width < 60;
 
     if is_compact {
-        render_compact_table(f, area, &visible_models, colors);
+        render_compact_table(f, area, &visible_models, header, highlighted, colors);
     } else {
-        render_full_table(f, area, &visible_models, colors);
+        render_full_table(
+            f,
+            area,
+            &visible_models,
+            &name_column,
+            highlighted,
+            with_cost,
+            colors,
+        );
+    }
+
+    if models.len() > visible_count {
+        // Sobre el borde derecho del bloque, a la altura de las filas
+        let track = Rect::new(area.right(), area.y + 1, 1, visible_count as u16);
+        let mut state = ScrollbarState::new(models.len() - visible_count)
+            .position(scroll)
+            .viewport_content_length(visible_count);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(muted_style(colors));
+        f.render_stateful_widget(scrollbar, track, &mut state);
+    }
+
+    // La primera fila es la cabecera
+    ListArea {
+        area: Rect::new(area.x, area.y + 1, area.width, visible_count as u16),
+        first: scroll,
+        len: visible_models.len(),
+        total: models.len(),
+    }
+}
+
+/// Header of the first column ("Model" or "SKU") and its widest entry
+struct NameColumn {
+    header: &'static str,
+    longest: usize,
+}
+
+/// Usage, Count and, with billed models, Cost, plus the spacing around the
+/// full table's Progress column
+fn fixed_columns_width(with_cost: bool) -> u16 {
+    let cost = if with_cost {
+        COST_WIDTH + COLUMN_SPACING
+    } else {
+        0
+    };
+    USAGE_WIDTH + COUNT_WIDTH + 3 * COLUMN_SPACING + cost
+}
+
+/// Model column of the full table: as wide as the longest name, up to
+/// `MODEL_MAX_SHARE` of the row, always leaving room for the shortest bar
+fn model_column_width(longest: usize, width: u16, with_cost: bool) -> u16 {
+    let rest = width.saturating_sub(fixed_columns_width(with_cost));
+    let min_bar = (MIN_BAR_CELLS * CELL_WIDTH) as u16;
+    let cap = (width * MODEL_MAX_SHARE / 100).min(rest.saturating_sub(min_bar));
+    (longest as u16).min(cap)
+}
+
+fn name_cell(model: &ModelUsage, width: u16, colors: &ThemeColors) -> Cell<'static> {
+    Cell::from(Span::styled(
+        truncate_middle(&model.name, width as usize),
+        Style::default().fg(colors.foreground),
+    ))
+}
+
+/// "$2.40" in the warning color for models with billed requests, a dash
+/// for the ones the plan covered
+fn cost_cell(model: &ModelUsage, colors: &ThemeColors) -> Cell<'static> {
+    if model.cost > 0.0 {
+        let cost = format!("${:.2}", model.cost);
+        Cell::from(Span::styled(
+            format!("{:>width$}", cost, width = COST_WIDTH as usize),
+            warning_style(),
+        ))
+    } else {
+        Cell::from(Span::styled(
+            format!("{:>width$}", "-", width = COST_WIDTH as usize),
+            muted_style(colors),
+        ))
+    }
+}
+
+/// An unmetered model has no share of a limit to show
+fn usage_text(model: &ModelUsage) -> String {
+    if model.unmetered {
+        icons().unmetered.to_string()
+    } else {
+        format_percentage(model.percentage)
+    }
+}
+
+fn row_style(row: usize, highlighted: Option<usize>, colors: &ThemeColors) -> Style {
+    if highlighted == Some(row) {
+        Style::default().bg(colors.bar_empty)
+    } else {
+        Style::default()
     }
 }
 
 fn render_compact_table(
     f: &mut Frame,
     area: Rect,
-    visible_models: &[&crate::models::ModelUsage],
+    visible_models: &[&ModelUsage],
+    name_header: &str,
+    highlighted: Option<usize>,
     colors: &ThemeColors,
 ) {
+    let layout = with_horizontal_margin(area);
+    // Sin barra, el nombre se queda con todo lo que sobra
+    let name_width = layout[0]
+        .width
+        .saturating_sub(USAGE_WIDTH + COUNT_WIDTH + 2 * COLUMN_SPACING);
+
     let rows: Vec<Row> = visible_models
         .iter()
-        .map(|model| {
-            let percentage_str = format_percentage(model.percentage);
+        .enumerate()
+        .map(|(row, model)| {
+            let percentage_str = usage_text(model);
             let usage_str = format_count(model.used);
-            let display_name = model.name.strip_prefix("Auto: ").unwrap_or(&model.name);
 
             Row::new(vec![
-                Cell::from(Span::styled(
-                    display_name.to_string(),
-                    Style::default().fg(colors.foreground),
-                )),
+                name_cell(model, name_width, colors),
                 Cell::from(Span::styled(
                     format!("{:^8}", percentage_str),
                     usage_style(model.percentage, colors),
                 )),
                 Cell::from(Span::styled(usage_str, muted_style(colors))),
             ])
+            .style(row_style(row, highlighted, colors))
         })
         .collect();
 
     let table = Table::new(
         rows,
         [
-            Constraint::Percentage(65), // Model (wider when no progress bar)
-            Constraint::Percentage(20), // Usage
-            Constraint::Length(7),      // Count
+            Constraint::Fill(1),             // Model (wider when no progress bar)
+            Constraint::Length(USAGE_WIDTH), // Usage
+            Constraint::Length(COUNT_WIDTH), // Count
         ],
     )
-    .header(Row::new(vec!["Model", "Usage", "Count"]).style(header_style(colors)))
-    .column_spacing(2);
+    .header(
+        Row::new(vec![name_header, t("models.usage"), t("models.count")])
+            .style(header_style(colors)),
+    )
+    .column_spacing(COLUMN_SPACING);
 
-    let layout = with_horizontal_margin(area);
     f.render_widget(table, layout[0]);
 }
 
 fn render_full_table(
     f: &mut Frame,
     area: Rect,
-    visible_models: &[&crate::models::ModelUsage],
+    visible_models: &[&ModelUsage],
+    name_column: &NameColumn,
+    highlighted: Option<usize>,
+    with_cost: bool,
     colors: &ThemeColors,
 ) {
-    // Calculate responsive bar width
-    let progress_col_width = ((area.width as f32 * 0.56) as u16).saturating_sub(4);
+    let layout = with_horizontal_margin(area);
+    let name_width = model_column_width(name_column.longest, layout[0].width, with_cost);
+
+    // Calculate responsive bar width from what the other columns leave
+    let progress_col_width = layout[0]
+        .width
+        .saturating_sub(name_width + fixed_columns_width(with_cost));
     let bar_width = calculate_responsive_bar_width(progress_col_width);
 
     let rows: Vec<Row> = visible_models
         .iter()
-        .map(|model| {
-            let percentage_str = format_percentage(model.percentage);
+        .enumerate()
+        .map(|(row, model)| {
+            let percentage_str = usage_text(model);
             let usage_str = format_count(model.used);
-            let display_name = model.name.strip_prefix("Auto: ").unwrap_or(&model.name);
 
             // Build responsive segmented progress bar
             let filled = calculate_filled_cells(model.percentage, bar_width);
@@ -201,33 +440,350 @@ fn render_full_table(
                 ));
             }
 
-            Row::new(vec![
-                Cell::from(Span::styled(
-                    display_name.to_string(),
-                    Style::default().fg(colors.foreground),
-                )),
+            let mut cells = vec![
+                name_cell(model, name_width, colors),
                 Cell::from(Line::from(bar_spans)),
                 Cell::from(Span::styled(
                     format!("{:^8}", percentage_str),
                     usage_style(model.percentage, colors),
                 )),
                 Cell::from(Span::styled(usage_str, muted_style(colors))),
-            ])
+            ];
+            if with_cost {
+                cells.push(cost_cell(model, colors));
+            }
+            Row::new(cells).style(row_style(row, highlighted, colors))
         })
         .collect();
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Percentage(25), // Model
-            Constraint::Percentage(58), // Progress
-            Constraint::Percentage(10), // Usage
-            Constraint::Length(7),      // Count (fixed width)
-        ],
-    )
-    .header(Row::new(vec!["Model", "Progress", "Usage", "Count"]).style(header_style(colors)))
-    .column_spacing(2);
+    let mut widths = vec![
+        Constraint::Length(name_width),  // Model (adaptive)
+        Constraint::Fill(1),             // Progress
+        Constraint::Length(USAGE_WIDTH), // Usage
+        Constraint::Length(COUNT_WIDTH), // Count (fixed width)
+    ];
+    let mut header = vec![
+        name_column.header,
+        t("models.progress"),
+        t("models.usage"),
+        t("models.count"),
+    ];
+    if with_cost {
+        widths.push(Constraint::Length(COST_WIDTH));
+        header.push(t("models.cost"));
+    }
+
+    let table = Table::new(rows, widths)
+        .header(Row::new(header).style(header_style(colors)))
+        .column_spacing(COLUMN_SPACING);
 
-    let layout = with_horizontal_margin(area);
     f.render_widget(table, layout[0]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, model};
+
+    #[test]
+    fn test_footer_text() {
+        let models = [
+            model("gpt-4.1", 150.0),
+            model("claude-sonnet-4", 60.0),
+            model("o3-mini", 4.0),
+        ];
+        let listed: Vec<&ModelUsage> = models.iter().collect();
+
+        assert_eq!(
+            footer_text(&listed, None, TableGrouping::Model),
+            "Σ 214 requests • 3 models • 2 over 10%"
+        );
+        assert_eq!(
+            footer_text(&listed, Some(&models[1]), TableGrouping::Model),
+            "Σ 214 requests • 3 models • 2 over 10% • ▸ claude-sonnet-4: 60"
+        );
+        // Exactamente el umbral no cuenta
+        assert_eq!(
+            footer_text(&[&model("a", 30.0)], None, TableGrouping::Model),
+            "Σ 30 requests • 1 models • 0 over 10%"
+        );
+        assert_eq!(
+            footer_text(&[], None, TableGrouping::Model),
+            "Σ 0 requests • 0 models • 0 over 10%"
+        );
+        assert_eq!(
+            footer_text(&listed, None, TableGrouping::Sku),
+            "Σ 214 requests • 3 SKUs • 2 over 10%"
+        );
+    }
+
+    #[test]
+    fn test_footer_collapses_when_short() {
+        use ratatui::{Terminal, backend::TestBackend};
+
+        let models = vec![model("gpt-4.1", 150.0), model("o3-mini", 4.0)];
+        let stats = UsageStats {
+            models,
+            ..testing::stats(154.0)
+        };
+        let colors = ThemeColors::dark();
+        let app = AppStateManager::new();
+
+        let has_footer = |height: u16| {
+            let mut terminal = Terminal::new(TestBackend::new(80, height)).unwrap();
+            terminal
+                .draw(|f| {
+                    render(f, f.area(), &stats, &colors, &app);
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            (0..height).any(|y| {
+                let row: String = (0..80).map(|x| buffer[(x, y)].symbol()).collect();
+                row.contains("Σ 154 requests")
+            })
+        };
+
+        // Los bordes ocupan dos filas
+        assert!(has_footer(FOOTER_MIN_HEIGHT + 2));
+        assert!(!has_footer(FOOTER_MIN_HEIGHT + 1));
+    }
+
+    #[test]
+    fn test_title_shows_filter() {
+        assert_eq!(
+            build_title(TableGrouping::Model, Some("clau")),
+            format!(" {} Per-Model Usage (filter: \"clau\") ", icons().list)
+        );
+        assert_eq!(
+            build_title(TableGrouping::Model, None),
+            format!(" {} Per-Model Usage: ", icons().list)
+        );
+    }
+
+    const LONG_NAME: &str = "claude-3-7-sonnet-thinking-extended-preview-20250115-private";
+
+    fn render_rows(width: u16, app: &AppStateManager) -> Vec<String> {
+        render_rows_with(
+            width,
+            app,
+            vec![model(LONG_NAME, 150.0), model("gpt-4.1", 4.0)],
+        )
+    }
+
+    fn render_rows_with(width: u16, app: &AppStateManager, models: Vec<ModelUsage>) -> Vec<String> {
+        draw(width, 10, app, models).0
+    }
+
+    /// Filas de la pantalla y las de la tabla que se dibujaron
+    fn draw(
+        width: u16,
+        height: u16,
+        app: &AppStateManager,
+        models: Vec<ModelUsage>,
+    ) -> (Vec<String>, Option<ListArea>) {
+        use ratatui::{Terminal, backend::TestBackend};
+
+        let stats = UsageStats {
+            models,
+            ..testing::stats(154.0)
+        };
+        let colors = ThemeColors::dark();
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut table = None;
+        terminal
+            .draw(|f| table = render(f, f.area(), &stats, &colors, app))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows = (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+        (rows, table)
+    }
+
+    /// Columna donde empieza alguno de `needles` en las filas del nombre
+    /// largo y de gpt-4.1
+    fn aligned_at(rows: &[String], needles: &[char]) -> (usize, usize) {
+        let long = rows.iter().find(|row| row.contains("claude-3")).unwrap();
+        let short = rows.iter().find(|row| row.contains("gpt-4.1")).unwrap();
+        let column = |row: &String| row.chars().position(|c| needles.contains(&c)).unwrap();
+        (column(long), column(short))
+    }
+
+    #[test]
+    fn test_long_name_is_truncated_in_the_middle() {
+        assert_eq!(LONG_NAME.len(), 60);
+        let app = AppStateManager::new();
+
+        for width in [80, 45] {
+            let rows = render_rows(width, &app);
+            let long = rows.iter().find(|row| row.contains("claude-3")).unwrap();
+            // Principio y final en la misma fila: no se parte en dos líneas
+            assert!(long.contains('…') && long.contains("private"), "{}", long);
+            assert!(!rows.iter().any(|row| row.contains(LONG_NAME)));
+
+            let (long, short) = aligned_at(&rows, &['%']);
+            assert_eq!(long, short, "usage misaligned at width {}", width);
+        }
+
+        // Las barras empiezan en la misma columna y la del modelo largo
+        // conserva su ancho mínimo
+        let rows = render_rows(80, &app);
+        let (long, short) = aligned_at(&rows, &['■', '·']);
+        assert_eq!(long, short);
+        let long = rows.iter().find(|row| row.contains("claude-3")).unwrap();
+        assert!(long.matches(['■', '·']).count() >= MIN_BAR_CELLS);
+    }
+
+    #[test]
+    fn test_cost_column_only_with_billed_models() {
+        let app = AppStateManager::new();
+        let rows = render_rows(80, &app);
+        assert!(!rows.iter().any(|row| row.contains("Cost")));
+
+        let mut billed = model("gpt-4.1", 4.0);
+        billed.cost = 2.4;
+        let rows = render_rows_with(80, &app, vec![model(LONG_NAME, 150.0), billed]);
+        assert!(rows.iter().any(|row| row.contains("Count    Cost")));
+        let gpt = rows.iter().find(|row| row.contains("gpt-4.1")).unwrap();
+        assert!(gpt.ends_with("   $2.40 │"), "{}", gpt);
+        let long = rows.iter().find(|row| row.contains("claude-3")).unwrap();
+        assert!(long.ends_with("       - │"), "{}", long);
+        // El % sigue alineado con la columna extra
+        let (long, short) = aligned_at(&rows, &['%']);
+        assert_eq!(long, short);
+    }
+
+    #[test]
+    fn test_unmetered_model_shows_infinity() {
+        let app = AppStateManager::new();
+        let mut unmetered = model("gpt-4.1", 120.0);
+        unmetered.unmetered = true;
+        unmetered.percentage = 0.0;
+        let rows = render_rows_with(80, &app, vec![unmetered, model("o3", 30.0)]);
+        let gpt = rows.iter().find(|row| row.contains("gpt-4.1")).unwrap();
+        assert!(gpt.contains("∞"), "{}", gpt);
+        assert!(!gpt.contains('%'), "{}", gpt);
+        let o3 = rows.iter().find(|row| row.contains("o3")).unwrap();
+        assert!(o3.contains("10.0%"), "{}", o3);
+    }
+
+    #[test]
+    fn test_model_column_adapts_to_names() {
+        // Nombres cortos: la columna no ocupa más de lo necesario
+        assert_eq!(model_column_width(15, 78, false), 15);
+        // Largos: crece hasta el tope
+        assert_eq!(model_column_width(60, 78, false), 31);
+        assert_eq!(model_column_width(60, 200, false), 60);
+        assert_eq!(model_column_width(100, 200, false), 80);
+        // Estrecho: la barra conserva su mínimo
+        assert_eq!(model_column_width(60, 58, false), 17);
+        // La columna de coste sale del nombre
+        assert_eq!(model_column_width(60, 78, true), 27);
+    }
+
+    #[test]
+    fn test_sku_grouping_labels() {
+        let mut app = AppStateManager::new();
+        app.table_grouping = TableGrouping::Sku;
+        let rows = render_rows_with(80, &app, vec![model("Copilot Premium Request", 150.0)]);
+
+        assert!(rows[0].contains("Per-SKU Usage"));
+        assert!(rows[1].trim_start_matches(['│', ' ']).starts_with("SKU "));
+        assert!(rows[2].contains("Copilot Premium Request"));
+        assert!(
+            rows.iter()
+                .any(|row| row.contains("Σ 150 requests • 1 SKUs"))
+        );
+    }
+
+    #[test]
+    fn test_selected_long_name_shows_in_full() {
+        let mut app = AppStateManager::new();
+        app.selected_model = Some(0);
+        let rows = render_rows(80, &app);
+        assert!(rows.iter().any(|row| row.contains(&format!("▸ {}: 150", LONG_NAME))));
+
+        // Sin sitio para todo, el pie deja solo el modelo seleccionado
+        let long = model(LONG_NAME, 150.0);
+        let listed = [&long];
+        assert_eq!(
+            fit_footer(&listed, Some(&long), TableGrouping::Model, 70),
+            format!("▸ {}: 150", LONG_NAME)
+        );
+        assert!(
+            fit_footer(&listed, Some(&long), TableGrouping::Model, 200)
+                .starts_with("Σ 150 requests")
+        );
+        assert!(fit_footer(&listed, None, TableGrouping::Model, 20).starts_with("Σ 150 requests"));
+    }
+
+    #[test]
+    fn test_scrollbar_only_when_rows_overflow() {
+        // Las filas que no caben mueven la barra del borde derecho
+        let thumb_column = |rows: &[String]| {
+            rows.iter()
+                .filter_map(|row| row.chars().last())
+                .filter(|c| *c == '█')
+                .count()
+        };
+        let mut app = AppStateManager::new();
+        let many: Vec<ModelUsage> = (0..12)
+            .map(|i| model(&format!("model-{}", i), 12.0 - i as f64))
+            .collect();
+        let rows = render_rows_with(80, &app, many.clone());
+        assert!(thumb_column(&rows) > 0);
+        assert!(!rows[0].contains('↓'));
+
+        // Al final de la lista la barra llega abajo del todo
+        app.model_scroll_offset = 100;
+        let rows = render_rows_with(80, &app, many);
+        assert!(rows.iter().any(|row| row.contains("model-11")));
+        assert_eq!(rows[7].chars().last(), Some('█'));
+
+        let rows = render_rows_with(80, &app, vec![model("gpt-4.1", 4.0)]);
+        assert_eq!(thumb_column(&rows), 0);
+    }
+
+    #[test]
+    fn test_scrolling_pages_by_the_rows_drawn() {
+        let models: Vec<ModelUsage> = (0..40)
+            .map(|i| model(&format!("model-{}", i), 40.0 - i as f64))
+            .collect();
+        // Bordes, cabecera y pie: 4 filas que no son de modelos
+        for height in [10, 20, 30] {
+            let mut app = AppStateManager::new();
+            let (_, table) = draw(80, height, &app, models.clone());
+            app.click_areas.table = table;
+            let visible = height as usize - 4;
+            assert_eq!(app.visible_model_rows(), visible, "height {}", height);
+
+            // El final de la tabla se alcanza y no se pasa
+            for _ in 0..100 {
+                app.select_next_model(models.len(), app.visible_model_rows());
+            }
+            assert_eq!(app.model_scroll_offset, models.len() - visible);
+            let (rows, table) = draw(80, height, &app, models.clone());
+            assert_eq!(table.unwrap().first, models.len() - visible);
+            assert!(rows[height as usize - 3].contains("model-39"));
+
+            // Un refresh con menos modelos vuelve a dejar la última fila abajo
+            app.clamp_model_view(visible + 2, app.visible_model_rows());
+            assert_eq!(app.model_scroll_offset, 2);
+            assert_eq!(app.selected_model, Some(visible + 1));
+        }
+    }
+
+    #[test]
+    fn test_highlight_stays_visible_on_a_shorter_terminal() {
+        let models: Vec<ModelUsage> = (0..40)
+            .map(|i| model(&format!("model-{}", i), 40.0 - i as f64))
+            .collect();
+        let mut app = AppStateManager::new();
+        app.selected_model = Some(20);
+        app.model_scroll_offset = 5;
+
+        let (rows, table) = draw(80, 10, &app, models);
+        assert_eq!(table.unwrap().first, 15);
+        assert!(rows[7].contains("model-20"));
+    }
+}
@@ -0,0 +1,132 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use crate::i18n::t;
+use crate::themes::ThemeColors;
+use crate::ui::layout::{centered_rect, POPUP_HEIGHT, POPUP_WIDTH};
+use crate::ui::state::{AppStateManager, ClickAreas, ListArea};
+
+const MIN_VISIBLE_FOR_SCROLL: usize = 5;
+
+/// Marker next to the profile whose data is on screen
+const ACTIVE_MARKER: &str = "●";
+
+pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) -> ClickAreas {
+    let area = centered_rect(POPUP_WIDTH, POPUP_HEIGHT, f.area());
+
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(t("profile.title"))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.border))
+        .title_style(
+            Style::default()
+                .fg(colors.foreground)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if app.profiles.is_empty() {
+        let empty = Paragraph::new(t("profile.empty"))
+            .style(Style::default().fg(colors.muted));
+        f.render_widget(empty, inner);
+        return ClickAreas::popup(area);
+    }
+
+    let total_profiles = app.profiles.len();
+
+    let (start, end) = if total_profiles > MIN_VISIBLE_FOR_SCROLL {
+        let visible_rows = inner.height as usize;
+        if visible_rows >= total_profiles {
+            (0, total_profiles)
+        } else {
+            let start = app.profile_scroll_offset;
+            (start, (start + visible_rows).min(total_profiles))
+        }
+    } else {
+        (0, total_profiles)
+    };
+
+    let items: Vec<ListItem> = app.profiles[start..end]
+        .iter()
+        .enumerate()
+        .map(|(i, profile)| {
+            let actual_index = start + i;
+            let selected = actual_index == app.selected_profile;
+
+            let name_style = if selected {
+                Style::default()
+                    .fg(colors.success)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(colors.foreground)
+            };
+
+            let mut spans = vec![Span::styled(format!(" {}", profile), name_style)];
+            if *profile == app.active_profile {
+                spans.push(Span::styled(
+                    format!(" {}", ACTIVE_MARKER),
+                    Style::default().fg(colors.success),
+                ));
+            }
+
+            let item_style = if selected {
+                Style::default().bg(colors.bar_empty)
+            } else {
+                Style::default()
+            };
+
+            ListItem::new(Line::from(spans)).style(item_style)
+        })
+        .collect();
+
+    let list = List::new(items).highlight_symbol("");
+    f.render_widget(list, inner);
+
+    ClickAreas {
+        popup: Some(area),
+        list: Some(ListArea {
+            area: inner,
+            first: start,
+            len: end - start,
+            total: total_profiles,
+        }),
+        ..ClickAreas::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    #[test]
+    fn test_marks_active_profile() {
+        let mut app = AppStateManager::new();
+        app.set_profiles(vec!["personal".to_string(), "work".to_string()], "work");
+        let colors = ThemeColors::dark();
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal
+            .draw(|f| {
+                render(f, &colors, &app);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..24)
+            .map(|y| (0..80).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+        let personal = rows.iter().find(|row| row.contains(" personal")).unwrap();
+        let work = rows.iter().find(|row| row.contains(" work")).unwrap();
+        assert!(!personal.contains(ACTIVE_MARKER));
+        assert!(work.contains(&format!("work {}", ACTIVE_MARKER)));
+    }
+}
@@ -0,0 +1,163 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::i18n::t;
+use crate::themes::ThemeColors;
+use crate::ui::layout::centered_rect_fixed_height;
+use crate::ui::state::{AppStateManager, SettingsField, SettingsForm};
+use crate::ui::styles::{error_style, muted_style};
+
+/// Ancho del popup, en % del terminal
+const SETTINGS_WIDTH: u16 = 60;
+
+/// Labels column, as wide as the longest label ("Cache TTL (min)")
+const LABEL_WIDTH: usize = 15;
+
+/// Formulario de ajustes: una fila por campo, el foco marcado con un cursor
+pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) -> Rect {
+    let form = &app.settings;
+    // Campos + estado + hint, bordes y márgenes
+    let height = SettingsField::ALL.len() as u16 + 6;
+    let area = centered_rect_fixed_height(SETTINGS_WIDTH, height, f.area());
+
+    let block = Block::default()
+        .title(t("settings.title"))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.border))
+        .title_style(
+            Style::default()
+                .fg(colors.foreground)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let inner = block.inner(area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(SettingsField::ALL.len() as u16), // Campos
+            Constraint::Length(1),                               // Error o guardando
+            Constraint::Min(1),                                  // Hint
+        ])
+        .vertical_margin(1)
+        .horizontal_margin(2)
+        .split(inner);
+
+    let value_width = (layout[0].width as usize).saturating_sub(LABEL_WIDTH + 1);
+    let lines: Vec<Line> = SettingsField::ALL
+        .iter()
+        .map(|&field| field_line(form, field, value_width, colors))
+        .collect();
+
+    let status = match (&form.error, form.saving) {
+        (Some(error), _) => Line::styled(format!("⚠ {}", error), error_style()),
+        (None, true) => Line::styled(t("settings.saving"), muted_style(colors)),
+        (None, false) => Line::default(),
+    };
+
+    let hint = Paragraph::new(t("helpbar.settings"))
+        .alignment(Alignment::Center)
+        .style(muted_style(colors));
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(Paragraph::new(lines), layout[0]);
+    f.render_widget(Paragraph::new(status), layout[1]);
+    f.render_widget(hint, layout[2]);
+
+    area
+}
+
+fn field_line(
+    form: &SettingsForm,
+    field: SettingsField,
+    width: usize,
+    colors: &ThemeColors,
+) -> Line<'static> {
+    let focused = form.focused_field() == field;
+    let label_style = if focused {
+        Style::default()
+            .fg(colors.foreground)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        muted_style(colors)
+    };
+    let label = Span::styled(
+        format!("{:<width$} ", field.label(), width = LABEL_WIDTH),
+        label_style,
+    );
+
+    let input = form.input(field);
+    // El token va enmascarado; vacío se conserva el actual
+    if field == SettingsField::Token && input.value().is_empty() && !focused {
+        let unchanged = Span::styled(t("settings.token_unchanged"), muted_style(colors));
+        return Line::from(vec![label, unchanged]);
+    }
+
+    let mut line = input.line(width, focused, colors);
+    line.spans.insert(0, label);
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Config;
+    use crate::ui::components::text_input::{CURSOR, Edit};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn rows(app: &AppStateManager) -> Vec<String> {
+        let colors = ThemeColors::dark();
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal
+            .draw(|f| {
+                render(f, &colors, app);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..30)
+            .map(|y| (0..100).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    fn find<'a>(rows: &'a [String], needle: &str) -> &'a str {
+        rows.iter()
+            .find(|row| row.contains(needle))
+            .unwrap_or_else(|| panic!("missing {:?}", needle))
+    }
+
+    #[test]
+    fn test_fields_and_masked_token() {
+        let mut app = AppStateManager::new();
+        app.open_settings(SettingsForm::new(&Config::default()));
+        app.settings.move_focus(true);
+
+        let screen = rows(&app);
+        find(&screen, "Settings");
+        assert!(find(&screen, "GitHub token").contains("unchanged"));
+        assert!(find(&screen, "Theme").contains(&format!("dark{}", CURSOR)));
+        // El cursor se mueve dentro del valor
+        app.settings.edit(Edit::Left);
+        assert!(find(&rows(&app), "Theme").contains(&format!("dar{}k", CURSOR)));
+        assert!(find(&screen, "Cache TTL").contains(" 5"));
+        assert!(find(&screen, "Waybar format").contains("{percentage}%"));
+
+        // Lo escrito en el token no se ve
+        app.settings.move_focus(false);
+        for edit in Edit::pasted("ghp_secret") {
+            app.settings.edit(edit);
+        }
+        app.settings.error = Some("Cache TTL must be a number".to_string());
+        let screen = rows(&app);
+        assert!(!screen.iter().any(|row| row.contains("secret")));
+        assert!(find(&screen, "GitHub token").contains(&"•".repeat(10)));
+        find(&screen, "⚠ Cache TTL must be a number");
+    }
+}
@@ -0,0 +1,400 @@
+//! One-line text input: cursor movement, editing, pasting, optional masking
+//! for tokens, a validation message and horizontal scrolling when the value
+//! is wider than the box.
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+use unicode_width::UnicodeWidthChar;
+
+use crate::themes::ThemeColors;
+use crate::ui::styles::error_style;
+
+/// Shown instead of each character of a masked value
+pub const MASK: char = '•';
+
+/// Drawn between the characters where the cursor is
+pub const CURSOR: &str = "▏";
+
+/// Checks the value after every edit; the message is shown with the input
+pub type Validator = fn(&str) -> Result<(), String>;
+
+/// An edit, from a key or a pasted character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    Insert(char),
+    /// Deletes before the cursor
+    Backspace,
+    /// Deletes under the cursor
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+}
+
+impl Edit {
+    /// The edit a key means in a text field, if any
+    pub fn from_key(code: KeyCode) -> Option<Self> {
+        match code {
+            KeyCode::Char(c) => Some(Edit::Insert(c)),
+            KeyCode::Backspace => Some(Edit::Backspace),
+            KeyCode::Delete => Some(Edit::Delete),
+            KeyCode::Left => Some(Edit::Left),
+            KeyCode::Right => Some(Edit::Right),
+            KeyCode::Home => Some(Edit::Home),
+            KeyCode::End => Some(Edit::End),
+            _ => None,
+        }
+    }
+
+    /// The characters of a bracketed paste that go into a one-line input:
+    /// line breaks and other control characters are dropped (a copied token
+    /// often ends with a newline)
+    pub fn pasted(text: &str) -> impl Iterator<Item = Edit> + '_ {
+        text.chars().filter(|c| !c.is_control()).map(Edit::Insert)
+    }
+}
+
+/// The value being edited and where the cursor is
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    value: String,
+    /// Position of the cursor, in characters
+    cursor: usize,
+    masked: bool,
+    validator: Option<Validator>,
+    /// The validator's message for the current value
+    error: Option<String>,
+}
+
+/// The validator is left out: two inputs are equal when they show the same
+impl PartialEq for TextInput {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+            && self.cursor == other.cursor
+            && self.masked == other.masked
+            && self.error == other.error
+    }
+}
+
+impl TextInput {
+    /// An input holding `value`, with the cursor at its end
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.chars().count(),
+            value,
+            ..Self::default()
+        }
+    }
+
+    /// Shows `•` for every character; the value is kept as typed
+    pub fn with_mask(mut self) -> Self {
+        self.masked = true;
+        self
+    }
+
+    /// Runs `validator` after each edit. The value it starts with isn't
+    /// checked: an empty field isn't an error before anything is typed.
+    pub fn with_validator(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_masked(&self) -> bool {
+        self.masked
+    }
+
+    /// Why the validator rejected the value
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn apply(&mut self, edit: Edit) {
+        let len = self.value.chars().count();
+        match edit {
+            Edit::Insert(c) => {
+                let at = self.byte_index(self.cursor);
+                self.value.insert(at, c);
+                self.cursor += 1;
+            }
+            Edit::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.value.remove(self.byte_index(self.cursor));
+            }
+            Edit::Delete if self.cursor < len => {
+                self.value.remove(self.byte_index(self.cursor));
+            }
+            Edit::Left => self.cursor = self.cursor.saturating_sub(1),
+            Edit::Right => self.cursor = (self.cursor + 1).min(len),
+            Edit::Home => self.cursor = 0,
+            Edit::End => self.cursor = len,
+            Edit::Backspace | Edit::Delete => {}
+        }
+        if matches!(edit, Edit::Insert(_) | Edit::Backspace | Edit::Delete) {
+            self.validate();
+        }
+    }
+
+    /// Inserts `text` at the cursor, as `Edit::pasted` filters it
+    pub fn paste(&mut self, text: &str) {
+        for edit in Edit::pasted(text) {
+            self.apply(edit);
+        }
+    }
+
+    fn validate(&mut self) {
+        self.error = self
+            .validator
+            .and_then(|validator| validator(&self.value).err());
+    }
+
+    fn byte_index(&self, chars: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(chars)
+            .map_or(self.value.len(), |(index, _)| index)
+    }
+
+    /// The part of the value that fits in `width` columns, masked if asked,
+    /// and the column of the cursor in it. Scrolls just enough to keep the
+    /// cursor in view, with a column left for it after the last character.
+    pub fn visible(&self, width: usize) -> (String, usize) {
+        let shown: Vec<(char, usize)> = self
+            .value
+            .chars()
+            .map(|c| {
+                let c = if self.masked { MASK } else { c };
+                (c, c.width().unwrap_or(0))
+            })
+            .collect();
+
+        let mut start = 0;
+        let mut column: usize = shown[..self.cursor].iter().map(|(_, w)| w).sum();
+        while column >= width && start < self.cursor {
+            column -= shown[start].1;
+            start += 1;
+        }
+
+        let mut used = 0;
+        let text = shown[start..]
+            .iter()
+            .take_while(|(_, w)| {
+                used += w;
+                used <= width
+            })
+            .map(|(c, _)| c)
+            .collect();
+        (text, column)
+    }
+
+    /// The visible part as a line, with `CURSOR` at its place when focused.
+    /// The cursor takes a column of its own: with the cursor inside a value
+    /// that fills the width, the last character falls off the edge.
+    pub fn line(&self, width: usize, focused: bool, colors: &ThemeColors) -> Line<'static> {
+        let style = Style::default().fg(colors.foreground);
+        if !focused {
+            return Line::styled(self.visible(width).0, style);
+        }
+        let (text, column) = self.visible(width);
+        // La columna del cursor cae siempre entre dos caracteres
+        let mut split = text.len();
+        let mut used = 0;
+        for (index, c) in text.char_indices() {
+            if used >= column {
+                split = index;
+                break;
+            }
+            used += c.width().unwrap_or(0);
+        }
+        let (before, after) = text.split_at(split);
+        Line::from(vec![
+            Span::styled(before.to_string(), style),
+            Span::styled(CURSOR, Style::default().fg(colors.success)),
+            Span::styled(after.to_string(), style),
+        ])
+    }
+}
+
+/// The input in a bordered box titled `title`, three rows high. A rejected
+/// value turns the border red and shows the message on the bottom border.
+pub fn render(
+    f: &mut Frame,
+    area: Rect,
+    colors: &ThemeColors,
+    input: &TextInput,
+    title: &str,
+    focused: bool,
+) {
+    let border_style = match (input.error(), focused) {
+        (Some(_), _) => error_style(),
+        (None, true) => Style::default().fg(colors.success),
+        (None, false) => Style::default().fg(colors.border),
+    };
+    let mut block = Block::default()
+        .title(title.to_string())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(border_style)
+        .title_style(
+            Style::default()
+                .fg(colors.foreground)
+                .add_modifier(Modifier::BOLD),
+        );
+    if let Some(error) = input.error() {
+        block = block.title_bottom(Line::styled(format!(" ⚠ {} ", error), error_style()));
+    }
+
+    let inner = block.inner(area);
+    let line = input.line(inner.width as usize, focused, colors);
+    f.render_widget(Paragraph::new(line).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::default();
+        for c in text.chars() {
+            input.apply(Edit::Insert(c));
+        }
+        input
+    }
+
+    #[test]
+    fn test_insert_and_delete_at_the_cursor() {
+        let mut input = typed("gh_123");
+        assert_eq!(input.cursor(), 6);
+
+        input.apply(Edit::Home);
+        input.apply(Edit::Right);
+        input.apply(Edit::Right);
+        input.apply(Edit::Insert('p'));
+        assert_eq!(input.value(), "ghp_123");
+        assert_eq!(input.cursor(), 3);
+
+        input.apply(Edit::Delete);
+        assert_eq!(input.value(), "ghp123");
+        input.apply(Edit::Backspace);
+        assert_eq!(input.value(), "gh123");
+        assert_eq!(input.cursor(), 2);
+
+        input.apply(Edit::End);
+        input.apply(Edit::Backspace);
+        assert_eq!(input.value(), "gh12");
+    }
+
+    #[test]
+    fn test_edges_do_nothing() {
+        let mut input = TextInput::new("ab");
+        input.apply(Edit::Delete);
+        input.apply(Edit::Right);
+        assert_eq!((input.value(), input.cursor()), ("ab", 2));
+
+        input.apply(Edit::Home);
+        input.apply(Edit::Backspace);
+        input.apply(Edit::Left);
+        assert_eq!((input.value(), input.cursor()), ("ab", 0));
+    }
+
+    #[test]
+    fn test_multibyte_characters() {
+        let mut input = TextInput::new("añb");
+        input.apply(Edit::Left);
+        input.apply(Edit::Backspace);
+        assert_eq!(input.value(), "ab");
+        input.apply(Edit::Insert('€'));
+        assert_eq!(input.value(), "a€b");
+        assert_eq!(input.cursor(), 2);
+    }
+
+    #[test]
+    fn test_paste_drops_line_breaks() {
+        let mut input = TextInput::new("[]");
+        input.apply(Edit::Left);
+        input.paste("ghp_abc\r\n");
+        assert_eq!(input.value(), "[ghp_abc]");
+        assert_eq!(input.cursor(), 8);
+    }
+
+    #[test]
+    fn test_validator_runs_on_edits() {
+        fn digits(value: &str) -> Result<(), String> {
+            if value.chars().all(|c| c.is_ascii_digit()) {
+                Ok(())
+            } else {
+                Err(format!("'{}' is not a number", value))
+            }
+        }
+        let mut input = TextInput::new("x5").with_validator(digits);
+        assert_eq!(input.error(), None);
+
+        input.apply(Edit::Insert('0'));
+        assert_eq!(input.error(), Some("'x50' is not a number"));
+        // Mover el cursor no cambia el valor
+        input.apply(Edit::Home);
+        assert!(input.error().is_some());
+        input.apply(Edit::Delete);
+        assert_eq!(input.error(), None);
+    }
+
+    #[test]
+    fn test_masked_value_is_kept() {
+        let input = TextInput::new("ghp_secret").with_mask();
+        assert_eq!(input.value(), "ghp_secret");
+        assert_eq!(input.visible(20), ("•".repeat(10), 10));
+    }
+
+    #[test]
+    fn test_scrolls_to_keep_the_cursor_in_view() {
+        let mut input = TextInput::new("abcdefghij");
+        // Al final: lo último escrito, con sitio para el cursor
+        assert_eq!(input.visible(5), ("ghij".to_string(), 4));
+
+        input.apply(Edit::Home);
+        assert_eq!(input.visible(5), ("abcde".to_string(), 0));
+
+        for _ in 0..6 {
+            input.apply(Edit::Right);
+        }
+        assert_eq!(input.visible(5), ("cdefg".to_string(), 4));
+        assert_eq!(TextInput::new("abc").visible(0), (String::new(), 0));
+    }
+
+    #[test]
+    fn test_scrolls_by_display_width() {
+        // Cada carácter ocupa dos columnas
+        let input = TextInput::new("日本語です");
+        assert_eq!(input.visible(7), ("語です".to_string(), 6));
+    }
+
+    #[test]
+    fn test_cursor_line() {
+        let colors = ThemeColors::dark();
+        let mut input = TextInput::new("token").with_mask();
+        input.apply(Edit::Left);
+        let text = |line: Line| {
+            line.spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect::<String>()
+        };
+        assert_eq!(text(input.line(10, true, &colors)), "••••▏•");
+        assert_eq!(text(input.line(10, false, &colors)), "•••••");
+    }
+}
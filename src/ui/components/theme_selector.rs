@@ -5,10 +5,12 @@ use ratatui::{
     Frame,
 };
 
+use crate::i18n::t;
 use crate::models::Theme;
 use crate::themes::ThemeColors;
 use crate::ui::layout::{centered_rect, POPUP_HEIGHT, POPUP_WIDTH};
-use crate::ui::state::AppStateManager;
+use crate::ui::state::{AppStateManager, ClickAreas, ListArea};
+use crate::ui::styles::{pad_to_width, truncate_end};
 
 const MIN_VISIBLE_FOR_SCROLL: usize = 5;
 
@@ -26,13 +28,13 @@ fn theme_color_dots(theme_name: &str) -> Vec<Span<'static>> {
     ]
 }
 
-pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) {
+pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) -> ClickAreas {
     let area = centered_rect(POPUP_WIDTH, POPUP_HEIGHT, f.area());
 
     f.render_widget(Clear, area);
 
     let block = Block::default()
-        .title(" Select Theme ")
+        .title(t("theme.title"))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(colors.border))
         .title_style(
@@ -73,12 +75,10 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) {
             let mut spans: Vec<Span> = Vec::new();
 
             // Theme name (padded to fixed width)
-            let display_name = if theme_name.len() > available_name_width {
-                &theme_name[..available_name_width]
-            } else {
-                theme_name
-            };
-            let padding = available_name_width.saturating_sub(display_name.len());
+            let display_name = pad_to_width(
+                &truncate_end(theme_name, available_name_width, "…"),
+                available_name_width,
+            );
 
             let name_style = if actual_index == app.selected_theme {
                 Style::default()
@@ -88,10 +88,7 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) {
                 Style::default().fg(colors.foreground)
             };
 
-            spans.push(Span::styled(
-                format!(" {}{} ", display_name, " ".repeat(padding)),
-                name_style,
-            ));
+            spans.push(Span::styled(format!(" {} ", display_name), name_style));
 
             // Add color dots for this theme
             spans.extend(theme_color_dots(theme_name));
@@ -111,4 +108,15 @@ pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) {
 
     let list = List::new(items).highlight_symbol("");
     f.render_widget(list, inner);
+
+    ClickAreas {
+        popup: Some(area),
+        list: Some(ListArea {
+            area: inner,
+            first: start,
+            len: end - start,
+            total: total_themes,
+        }),
+        ..ClickAreas::default()
+    }
 }
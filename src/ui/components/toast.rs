@@ -0,0 +1,153 @@
+//! Toasts stacked in the top-right corner, newest on top. Nothing here
+//! animates: a toast is drawn dimmed for its last second and then left out.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::themes::ThemeColors;
+use crate::ui::state::{AppStateManager, Toast, ToastSeverity};
+use crate::ui::styles::{icons, truncate_end};
+
+/// Widest a toast gets, borders included
+const TOAST_MAX_WIDTH: u16 = 48;
+
+/// Borders and a space on each side of the message
+const TOAST_CHROME: u16 = 4;
+
+pub fn render(f: &mut Frame, colors: &ThemeColors, app: &AppStateManager) {
+    let area = f.area();
+    // Un margen de una celda con el borde de la terminal
+    let max_width = TOAST_MAX_WIDTH.min(area.width.saturating_sub(2));
+    if max_width <= TOAST_CHROME {
+        return;
+    }
+
+    let mut y = area.y + 1;
+    for toast in app.active_toasts() {
+        if y + 3 > area.bottom() {
+            break;
+        }
+        let text = truncate_end(
+            &message(toast),
+            (max_width - TOAST_CHROME) as usize,
+            "…",
+        );
+        let width = text.width() as u16 + TOAST_CHROME;
+        let toast_area = Rect::new(area.right() - width - 1, y, width, 3);
+        render_toast(f, toast_area, colors, toast, text);
+        y += 3;
+    }
+}
+
+fn message(toast: &Toast) -> String {
+    match toast.severity {
+        ToastSeverity::Info => toast.message.clone(),
+        ToastSeverity::Warning | ToastSeverity::Error => {
+            format!("{} {}", icons().warning, toast.message)
+        }
+    }
+}
+
+fn render_toast(f: &mut Frame, area: Rect, colors: &ThemeColors, toast: &Toast, text: String) {
+    let color = match toast.severity {
+        ToastSeverity::Info => colors.success,
+        ToastSeverity::Warning => colors.warning,
+        ToastSeverity::Error => colors.error,
+    };
+    let (border, text_style) = if toast.is_fading() {
+        let dimmed = Style::default()
+            .fg(colors.muted)
+            .add_modifier(Modifier::DIM);
+        (dimmed, dimmed)
+    } else {
+        (
+            Style::default().fg(color),
+            Style::default().fg(colors.foreground),
+        )
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(border);
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(format!(" {} ", text))
+            .style(text_style)
+            .block(block),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::state::{TOAST_DURATION, TOAST_FADE};
+    use ratatui::{Terminal, backend::TestBackend};
+    use std::time::Instant;
+
+    fn rows(app: &AppStateManager, width: u16, height: u16) -> Vec<String> {
+        let colors = ThemeColors::dark();
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| render(f, &colors, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_stacks_newest_on_top_right() {
+        let mut app = AppStateManager::new();
+        app.toast_info("Theme saved");
+        app.toast_error("Could not save the theme");
+
+        let rows = rows(&app, 60, 10);
+        assert!(rows[2].ends_with("Could not save the theme │ "));
+        assert!(rows[5].ends_with("│ Theme saved │ "));
+        assert!(rows[7].trim().is_empty());
+    }
+
+    #[test]
+    fn test_long_messages_are_cut() {
+        let mut app = AppStateManager::new();
+        app.toast_info("x".repeat(100));
+        let rows = rows(&app, 30, 5);
+        assert!(rows[2].ends_with("x… │ "));
+        assert!(rows[2].starts_with(" │ x"));
+    }
+
+    #[test]
+    fn test_only_what_fits_is_drawn() {
+        let mut app = AppStateManager::new();
+        app.toast_info("one");
+        app.toast_info("two");
+        // Solo cabe uno debajo de la fila de margen
+        let rows = rows(&app, 30, 5);
+        assert!(rows[2].contains("two"));
+        assert!(!rows.iter().any(|row| row.contains("one")));
+    }
+
+    #[test]
+    fn test_fading_toast_is_dimmed() {
+        let mut app = AppStateManager::new();
+        app.toast_info("saved");
+        app.toasts[0].created_at = Instant::now() - (TOAST_DURATION - TOAST_FADE / 2);
+
+        let colors = ThemeColors::dark();
+        let mut terminal = Terminal::new(TestBackend::new(30, 5)).unwrap();
+        terminal.draw(|f| render(f, &colors, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let cell = (0..30)
+            .map(|x| &buffer[(x, 2)])
+            .find(|cell| cell.symbol() == "s")
+            .unwrap();
+        assert_eq!(cell.fg, colors.muted);
+        assert!(cell.modifier.contains(Modifier::DIM));
+    }
+}
@@ -0,0 +1,333 @@
+use chrono::NaiveDate;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph, Sparkline, SparklineBar},
+};
+
+use crate::history::DailyUsage;
+use crate::history::periods::{CHART_PERIODS, PeriodSummary, last_months, trend_sentence};
+use crate::i18n::{t, tf};
+use crate::models::UsageStats;
+use crate::themes::ThemeColors;
+use crate::ui::styles::{get_usage_color, header_style, icons, muted_style};
+
+/// Height of the trend block when there is room for the sparkline
+pub const TREND_HEIGHT: u16 = 5;
+
+/// Height of the trend row when collapsed to a summary line
+pub const TREND_COLLAPSED_HEIGHT: u16 = 1;
+
+/// Width of the month mini chart: one column per month plus padding
+const MONTHS_WIDTH: u16 = CHART_PERIODS as u16 + 2;
+
+/// Below this inner width only the daily sparkline is drawn
+const MONTHS_MIN_INNER_WIDTH: u16 = 40;
+
+/// Data behind the trend block
+#[derive(Debug, Clone, Default)]
+pub struct TrendData {
+    /// Requests per day this month
+    pub daily: Vec<u64>,
+    /// Stored month totals, oldest first
+    pub periods: Vec<PeriodSummary>,
+    /// Year and month of today
+    pub current_period: (i32, u32),
+}
+
+/// Builds one value per day from `month_start` to `today`, using zero for
+/// days without recorded usage
+pub fn daily_series(days: &[DailyUsage], month_start: NaiveDate, today: NaiveDate) -> Vec<u64> {
+    month_start
+        .iter_days()
+        .take_while(|date| *date <= today)
+        .map(|date| {
+            days.iter()
+                .find(|d| d.date == date)
+                .map_or(0, |d| d.requests.round() as u64)
+        })
+        .collect()
+}
+
+pub fn render(
+    f: &mut Frame,
+    area: Rect,
+    trend: &TrendData,
+    stats: &UsageStats,
+    colors: &ThemeColors,
+) {
+    let daily = &trend.daily;
+    if area.height < TREND_HEIGHT {
+        render_summary(f, area, daily, colors);
+        return;
+    }
+
+    let months = last_months(&trend.periods, trend.current_period, CHART_PERIODS);
+    let show_months = months.iter().any(Option::is_some);
+
+    let mut block = Block::default()
+        .title(tf("trend.title", &[&icons().trend]))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.border))
+        .title_style(header_style(colors));
+    if let Some(sentence) = trend_sentence(&trend.periods, trend.current_period, stats.total_limit)
+    {
+        block = block.title_bottom(Line::styled(format!(" {} ", sentence), muted_style(colors)));
+    }
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let (daily_area, months_area) = if show_months && inner.width >= MONTHS_MIN_INNER_WIDTH {
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(MONTHS_WIDTH)])
+            .split(inner);
+        (cols[0], Some(cols[1]))
+    } else {
+        (inner, None)
+    };
+
+    // One column per day: keep the most recent days when the month doesn't fit
+    let width = daily_area.width.saturating_sub(2) as usize;
+    let visible = &daily[daily.len().saturating_sub(width)..];
+    let last_color = get_usage_color(stats.percentage, colors);
+
+    let bars: Vec<SparklineBar> = visible
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let bar = SparklineBar::from(*value);
+            if i + 1 == visible.len() {
+                bar.style(Style::default().fg(last_color))
+            } else {
+                bar
+            }
+        })
+        .collect();
+
+    let sparkline = Sparkline::default().data(bars).style(muted_style(colors));
+
+    let sparkline_area = Rect {
+        x: daily_area.x + 1,
+        width: daily_area.width.saturating_sub(2),
+        ..daily_area
+    };
+    f.render_widget(sparkline, sparkline_area);
+
+    if let Some(months_area) = months_area {
+        render_months(f, months_area, &months, last_color, colors);
+    }
+}
+
+/// Tiny chart of the last months, the current one colored like the usage.
+/// Months the tool wasn't run in show as a dot.
+fn render_months(
+    f: &mut Frame,
+    area: Rect,
+    months: &[Option<f64>],
+    current_color: ratatui::style::Color,
+    colors: &ThemeColors,
+) {
+    let bars: Vec<SparklineBar> = months
+        .iter()
+        .enumerate()
+        .map(|(i, total)| {
+            let bar = SparklineBar::from(total.map(|t| t.round() as u64));
+            if i + 1 == months.len() {
+                bar.style(Style::default().fg(current_color))
+            } else {
+                bar
+            }
+        })
+        .collect();
+
+    let chart = Sparkline::default()
+        .data(bars)
+        .style(muted_style(colors))
+        .absent_value_symbol("·")
+        .absent_value_style(muted_style(colors));
+
+    let chart_area = Rect {
+        x: area.x + 1,
+        width: area.width.saturating_sub(2),
+        ..area
+    };
+    f.render_widget(chart, chart_area);
+}
+
+/// Single-line fallback used when there is no vertical room for the sparkline
+fn render_summary(f: &mut Frame, area: Rect, daily: &[u64], colors: &ThemeColors) {
+    let line = Line::from(vec![
+        Span::styled(
+            tf("trend.title_short", &[&icons().trend]),
+            header_style(colors),
+        ),
+        Span::styled(summary_text(daily), muted_style(colors)),
+    ]);
+    f.render_widget(Paragraph::new(line), area);
+}
+
+fn summary_text(daily: &[u64]) -> String {
+    if daily.iter().all(|v| *v == 0) {
+        return t("trend.empty").to_string();
+    }
+
+    let total: u64 = daily.iter().sum();
+    let today = daily.last().copied().unwrap_or(0);
+    let peak = daily.iter().max().copied().unwrap_or(0);
+    tf("trend.summary", &[&total, &daily.len(), &today, &peak])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use chrono::{TimeZone, Utc};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    fn stats(percentage: f64) -> UsageStats {
+        UsageStats {
+            percentage,
+            ..testing::stats(percentage * 3.0)
+        }
+    }
+
+    fn render_to_lines(width: u16, height: u16, daily: &[u64]) -> Vec<String> {
+        let trend = TrendData {
+            daily: daily.to_vec(),
+            ..TrendData::default()
+        };
+        render_trend(width, height, &trend)
+    }
+
+    fn render_trend(width: u16, height: u16, trend: &TrendData) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let colors = ThemeColors::dark();
+        terminal
+            .draw(|f| render(f, f.area(), trend, &stats(40.0), &colors))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_daily_series_fills_missing_days() {
+        let days = vec![
+            DailyUsage {
+                date: date(2),
+                requests: 4.0,
+                total_used: 4.0,
+            },
+            DailyUsage {
+                date: date(4),
+                requests: 2.0,
+                total_used: 6.0,
+            },
+        ];
+
+        assert_eq!(daily_series(&days, date(1), date(5)), vec![0, 4, 0, 2, 0]);
+    }
+
+    #[test]
+    fn test_summary_text() {
+        assert_eq!(summary_text(&[0, 0]), "no daily history yet");
+        assert_eq!(
+            summary_text(&[3, 0, 8, 2]),
+            "13 requests over 4 days • today 2 • peak 8"
+        );
+    }
+
+    #[test]
+    fn test_render_sparkline_snapshot() {
+        let lines = render_to_lines(12, TREND_HEIGHT, &[0, 8, 4, 8]);
+        assert_eq!(
+            lines,
+            vec![
+                format!("╭ {} Daily T╮", icons().trend),
+                "│  █ █     │".to_string(),
+                "│  █▄█     │".to_string(),
+                "│  ███     │".to_string(),
+                "╰──────────╯".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_collapses_when_short() {
+        let lines = render_to_lines(60, TREND_COLLAPSED_HEIGHT, &[3, 0, 8, 2]);
+        assert_eq!(
+            lines[0].trim_end(),
+            format!(
+                " {} Trend: 13 requests over 4 days • today 2 • peak 8",
+                icons().trend
+            )
+        );
+    }
+
+    #[test]
+    fn test_last_bar_uses_zone_color() {
+        let mut terminal = Terminal::new(TestBackend::new(12, TREND_HEIGHT)).unwrap();
+        let colors = ThemeColors::dark();
+        let trend = TrendData {
+            daily: vec![1, 2, 8],
+            ..TrendData::default()
+        };
+        terminal
+            .draw(|f| render(f, f.area(), &trend, &stats(95.0), &colors))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(4, 3)].fg, get_usage_color(95.0, &colors));
+        assert_eq!(buffer[(3, 3)].fg, colors.muted);
+    }
+
+    #[test]
+    fn test_render_months_chart_and_sentence() {
+        let period = |month: u32, total_used: f64| PeriodSummary {
+            year: 2025,
+            month,
+            total_used,
+            updated_at: Utc.with_ymd_and_hms(2025, month, 20, 0, 0, 0).unwrap(),
+        };
+        let trend = TrendData {
+            daily: vec![0, 8],
+            // July: not run
+            periods: vec![
+                period(6, 150.0),
+                period(8, 300.0),
+                period(9, 243.0),
+                period(10, 281.0),
+                period(11, 75.0),
+            ],
+            current_period: (2025, 11),
+        };
+        let lines = render_trend(70, TREND_HEIGHT, &trend);
+
+        // Months chart: Jun..Nov in the last columns before the border
+        let months: Vec<String> = lines[1..4]
+            .iter()
+            .map(|line| line.chars().skip(62).take(6).collect())
+            .collect();
+        assert_eq!(months, vec![" ·█▃▆ ", "▄·███ ", "█·███▆"]);
+
+        assert_eq!(
+            lines[4],
+            "╰ October: 281 requests (94%), September: 243 (81%), trending +16% ──╯"
+        );
+    }
+}
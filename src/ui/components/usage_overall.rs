@@ -1,4 +1,4 @@
-use chrono::{Datelike, Utc};
+use chrono::{DateTime, Utc};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
@@ -7,19 +7,122 @@ use ratatui::{
     Frame,
 };
 
-use crate::models::UsageStats;
+use crate::i18n::{t, tf};
+use crate::models::{QuantityMode, UsageStats};
+use crate::percent::{format_percent, format_quantity};
+use crate::projection::{Outlook, Pace, Projection};
 use crate::themes::ThemeColors;
 use crate::ui::styles::{
-    calculate_filled_cells, calculate_zone_boundaries, error_style_bold, header_style, muted_style,
-    success_style_bold, usage_style, warning_style_bold, BAR_BRAILLE_EMPTY, BAR_BRAILLE_FILLED,
-    ICON_CALENDAR, ICON_RESET, ICON_USAGE,
+    calculate_filled_cells, calculate_zone_boundaries, countdown_style, error_style,
+    error_style_bold, format_countdown, icons,
+    format_percentage, header_style, muted_style, warning_style,
+    success_style_bold, truncate_middle, usage_style, warning_style_bold, BAR_BRAILLE_EMPTY,
+    BAR_BRAILLE_FILLED,
 };
+use unicode_width::UnicodeWidthStr;
 
-pub fn render(f: &mut Frame, area: Rect, stats: &UsageStats, colors: &ThemeColors) {
-    let is_compact = area.width < 60;
+/// Below this width the block switches to compact mode
+const COMPACT_WIDTH: u16 = 60;
+
+/// Borders + padding, requests label and bar, spacer, month label and bar
+pub const OVERALL_HEIGHT: u16 = 8;
+
+/// Budget bar: thin line under the requests bar
+const BUDGET_FILLED: &str = "━";
+const BUDGET_EMPTY: &str = "─";
+/// Budget position marked on the requests bar
+const BUDGET_TICK: &str = "│";
+
+/// Height of the block: one extra row for the budget line in full mode,
+/// one for the projection, the cost rows and the top model
+pub fn height(width: u16, stats: &UsageStats, has_projection: bool) -> u16 {
+    let budget_rows = u16::from(stats.budget.is_some() && width >= COMPACT_WIDTH);
+    compact_height(stats, has_projection) + budget_rows
+}
+
+/// Height in compact mode, which the dashboard also falls back to when
+/// it's short on rows
+pub fn compact_height(stats: &UsageStats, has_projection: bool) -> u16 {
+    OVERALL_HEIGHT + u16::from(has_projection) + cost_height(stats) + distribution_height(stats)
+}
+
+/// The top model line, once anything was used
+fn distribution_height(stats: &UsageStats) -> u16 {
+    u16::from(stats.top_model.is_some())
+}
+
+/// "Top: claude-sonnet-4 (41%) • 5 models", the name shortened to fit
+/// `width`
+fn distribution_line(stats: &UsageStats, width: usize, colors: &ThemeColors) -> Line<'static> {
+    let Some((name, share)) = &stats.top_model else {
+        return Line::default();
+    };
+    let label = t("usage.top");
+    let share = format!(" ({}%)", format_percent(*share, 0));
+    let count = tf("usage.models_used", &[&stats.distinct_models]);
+    let separator = icons().separator;
+    let name_width =
+        width.saturating_sub(label.width() + share.width() + separator.width() + count.width());
+    Line::from(vec![
+        Span::styled(label, muted_style(colors)),
+        Span::styled(
+            truncate_middle(name, name_width),
+            Style::default()
+                .fg(colors.foreground)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(share, muted_style(colors)),
+        Span::styled(separator, muted_style(colors)),
+        Span::styled(count, muted_style(colors)),
+    ])
+}
+
+/// The overage cost once something was billed, the dollar budget when set
+fn cost_height(stats: &UsageStats) -> u16 {
+    u16::from(stats.estimated_cost > 0.0) + u16::from(stats.cost_budget.is_some())
+}
+
+/// "Overage cost: $2.40" and "Budget: $3.20 / $5.00 (64%)"
+fn cost_lines(stats: &UsageStats, colors: &ThemeColors) -> Paragraph<'static> {
+    let mut lines = Vec::new();
+    if stats.estimated_cost > 0.0 {
+        lines.push(Line::styled(
+            tf("usage.cost", &[&format!("${:.2}", stats.estimated_cost)]),
+            warning_style(),
+        ));
+    }
+    if let Some(budget) = stats.cost_budget {
+        let percentage = budget_percentage(stats.estimated_cost, budget);
+        lines.push(Line::styled(
+            tf(
+                "usage.cost_budget",
+                &[
+                    &format!("${:.2}", stats.estimated_cost),
+                    &format!("${:.2}", budget),
+                    &format!("{}%", format_percent(percentage, 0)),
+                ],
+            ),
+            usage_style(percentage, colors),
+        ));
+    }
+    Paragraph::new(lines)
+}
+
+pub fn render(
+    f: &mut Frame,
+    area: Rect,
+    stats: &UsageStats,
+    mode: QuantityMode,
+    projection: Option<&Projection>,
+    colors: &ThemeColors,
+    now: DateTime<Utc>,
+) {
+    // Sin sitio para la barra del presupuesto, va como sufijo
+    let is_compact =
+        area.width < COMPACT_WIDTH || area.height < height(area.width, stats, projection.is_some());
 
     let block = Block::default()
-        .title(format!(" {} Overall Usage: ", ICON_USAGE))
+        .title(tf("usage.title", &[&icons().usage, &t(mode.label_key())]))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(colors.border))
@@ -29,169 +132,349 @@ pub fn render(f: &mut Frame, area: Rect, stats: &UsageStats, colors: &ThemeColor
     f.render_widget(block, area);
 
     if is_compact {
-        render_compact(f, inner, stats, colors);
+        render_compact(f, inner, stats, projection, colors, now);
     } else {
-        render_full(f, inner, stats, colors);
+        render_full(f, inner, stats, projection, colors, now);
     }
 }
 
-fn render_compact(f: &mut Frame, area: Rect, stats: &UsageStats, colors: &ThemeColors) {
+fn render_compact(
+    f: &mut Frame,
+    area: Rect,
+    stats: &UsageStats,
+    projection: Option<&Projection>,
+    colors: &ThemeColors,
+    now: DateTime<Utc>,
+) {
+    let projection_rows = u16::from(projection.is_some());
+    let cost_rows = cost_height(stats);
+    let top_rows = distribution_height(stats);
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(1), // Top Padding
-            Constraint::Length(1), // Requests Label
-            Constraint::Length(1), // Requests Bar
-            Constraint::Length(1), // Spacer
-            Constraint::Length(1), // Reset Label (compact: only reset, no month text)
-            Constraint::Length(1), // Month Bar (still visible!)
+            Constraint::Length(1),               // Top Padding
+            Constraint::Length(1),               // Requests Label
+            Constraint::Length(1),               // Requests Bar
+            Constraint::Length(projection_rows), // Projection
+            Constraint::Length(cost_rows),       // Overage cost and dollar budget
+            Constraint::Length(top_rows),        // Top model and model count
+            Constraint::Length(1),               // Spacer
+            Constraint::Length(1),               // Reset Label (compact: only reset, no month text)
+            Constraint::Length(1),               // Month Bar (still visible!)
         ])
         .horizontal_margin(1)
         .split(area);
 
-    // 1. Requests Label
-    let usage_text = Paragraph::new(Line::from(vec![
-        Span::styled(format!("Requests: "), muted_style(colors)),
-        Span::styled(
-            format!("{:.0}/{:.0}", stats.total_used, stats.total_limit),
-            usage_style(stats.percentage, colors).add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(format!(" ({:.1}%)", stats.percentage), muted_style(colors)),
-    ]));
-    f.render_widget(usage_text, layout[1]);
+    // 1. Requests Label (compact: budget as a suffix instead of its own bar)
+    let mut label = requests_label(stats, colors);
+    if let Some(budget) = stats.budget {
+        label.push(Span::styled(icons().separator, muted_style(colors)));
+        label.push(Span::styled(
+            budget_label(stats.total_used, budget),
+            usage_style(budget_percentage(stats.total_used, budget), colors),
+        ));
+    }
+    f.render_widget(Paragraph::new(Line::from(label)), layout[1]);
 
     // 2. Requests Bar
     render_requests_bar(f, layout[2], stats, colors);
 
-    // 3. Compact: Only show Reset (no month elapsed text)
-    let reset_text = Paragraph::new(Line::from(vec![Span::styled(
-        format!(
-            "{} Resets: {}",
-            ICON_RESET,
-            stats.reset_date.format("%b %d")
-        ),
-        muted_style(colors),
-    )]));
-    f.render_widget(reset_text, layout[4]);
+    // 3. Projection (compact: without the pace)
+    if let Some(projection) = projection {
+        let line = Line::from(projection_spans(stats, projection, false, colors));
+        f.render_widget(Paragraph::new(line), layout[3]);
+    }
+
+    // 4. Overage cost and dollar budget
+    f.render_widget(cost_lines(stats, colors), layout[4]);
+
+    // 5. Top model and model count
+    let line = distribution_line(stats, layout[5].width as usize, colors);
+    f.render_widget(Paragraph::new(line), layout[5]);
 
-    // 4. Month Bar (still visible in compact mode!)
-    render_month_bar(f, layout[5], colors);
+    // 6. Compact: Only show Reset (no month elapsed text)
+    let reset_text = Paragraph::new(Line::from(vec![reset_span(stats, colors, now)]));
+    f.render_widget(reset_text, layout[7]);
+
+    // 7. Month Bar (still visible in compact mode!)
+    render_month_bar(f, layout[8], stats, colors, now);
 }
 
-fn render_full(f: &mut Frame, area: Rect, stats: &UsageStats, colors: &ThemeColors) {
+fn render_full(
+    f: &mut Frame,
+    area: Rect,
+    stats: &UsageStats,
+    projection: Option<&Projection>,
+    colors: &ThemeColors,
+    now: DateTime<Utc>,
+) {
+    let budget_rows = if stats.budget.is_some() { 1 } else { 0 };
+    let projection_rows = u16::from(projection.is_some());
+    let cost_rows = cost_height(stats);
+    let top_rows = distribution_height(stats);
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(1), // Top Padding
-            Constraint::Length(1), // Requests Label
-            Constraint::Length(1), // Requests Bar
-            Constraint::Length(1), // Spacer (1 line for breathing room)
-            Constraint::Length(1), // Month Label + Reset
-            Constraint::Length(1), // Month Bar
+            Constraint::Length(1),               // Top Padding
+            Constraint::Length(1),               // Requests Label
+            Constraint::Length(1),               // Requests Bar
+            Constraint::Length(budget_rows),     // Budget Bar (only with a budget)
+            Constraint::Length(projection_rows), // Projection (only within the period)
+            Constraint::Length(cost_rows),       // Overage cost and dollar budget
+            Constraint::Length(top_rows),        // Top model and model count
+            Constraint::Length(1),               // Spacer (1 line for breathing room)
+            Constraint::Length(1),               // Month Label + Reset
+            Constraint::Length(1),               // Month Bar
         ])
         .horizontal_margin(1)
         .split(area);
 
     // 1. Requests Label
-    let usage_text = Paragraph::new(Line::from(vec![
-        Span::styled(format!("Requests: "), muted_style(colors)),
-        Span::styled(
-            format!("{:.0}/{:.0}", stats.total_used, stats.total_limit),
-            usage_style(stats.percentage, colors).add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(format!(" ({:.1}%)", stats.percentage), muted_style(colors)),
-    ]));
+    let usage_text = Paragraph::new(Line::from(requests_label(stats, colors)));
     f.render_widget(usage_text, layout[1]);
 
     // 2. Requests Bar
     render_requests_bar(f, layout[2], stats, colors);
 
-    // 3. Spacer (Empty)
+    // 3. Budget Bar
+    if let Some(budget) = stats.budget {
+        render_budget_bar(f, layout[3], stats.total_used, budget, colors);
+    }
+
+    // 4. Projection
+    if let Some(projection) = projection {
+        let line = Line::from(projection_spans(stats, projection, true, colors));
+        f.render_widget(Paragraph::new(line), layout[4]);
+    }
+
+    // 5. Overage cost and dollar budget
+    f.render_widget(cost_lines(stats, colors), layout[5]);
 
-    // 4. Month Label + Reset
-    let days_in_month = days_in_current_month();
-    let current_day = Utc::now().day();
-    let month_progress = (current_day as f64 / days_in_month as f64) * 100.0;
+    // 6. Top model and model count
+    let line = distribution_line(stats, layout[6].width as usize, colors);
+    f.render_widget(Paragraph::new(line), layout[6]);
+
+    // 7. Spacer (Empty)
+
+    // 8. Month Label + Reset
+    let month_progress = stats.period_at(now).progress_percent(now);
 
     let month_text = Paragraph::new(Line::from(vec![
-        Span::styled(format!("{} Month: ", ICON_CALENDAR), muted_style(colors)),
-        Span::styled(
-            format!("{:.1}% elapsed", month_progress),
-            muted_style(colors),
-        ),
-        Span::styled(" • ", muted_style(colors)),
+        Span::styled(tf("usage.month", &[&icons().calendar]), muted_style(colors)),
         Span::styled(
-            format!(
-                "{} Resets: {}",
-                ICON_RESET,
-                stats.reset_date.format("%b %d")
-            ),
+            tf("usage.elapsed", &[&format_percentage(month_progress)]),
             muted_style(colors),
         ),
+        Span::styled(icons().separator, muted_style(colors)),
+        reset_span(stats, colors, now),
     ]));
-    f.render_widget(month_text, layout[4]);
+    f.render_widget(month_text, layout[8]);
 
-    // 5. Month Bar
-    render_month_bar(f, layout[5], colors);
+    // 9. Month Bar
+    render_month_bar(f, layout[9], stats, colors, now);
 }
 
-/// Helper function to render the requests progress bar
-fn render_requests_bar(f: &mut Frame, area: Rect, stats: &UsageStats, colors: &ThemeColors) {
-    let bar_width = area.width as usize;
-    let filled_len = calculate_filled_cells(stats.percentage, bar_width);
-    let (zone_success_end, zone_warning_end) = calculate_zone_boundaries(bar_width);
+/// "↻ Resets in 2d 14h", green in the last day
+fn reset_span(stats: &UsageStats, colors: &ThemeColors, now: DateTime<Utc>) -> Span<'static> {
+    let until_reset = stats.until_reset(now);
+    Span::styled(
+        tf(
+            "usage.resets_in",
+            &[&icons().reset, &format_countdown(until_reset)],
+        ),
+        countdown_style(until_reset, colors),
+    )
+}
 
-    let mut bar_spans: Vec<Span> = Vec::new();
+/// "Projected: runs out Jun 24" or "On pace: ~212/300 by reset", plus the
+/// distance to the even-burn line when `with_pace`
+fn projection_spans<'a>(
+    stats: &UsageStats,
+    projection: &Projection,
+    with_pace: bool,
+    colors: &ThemeColors,
+) -> Vec<Span<'a>> {
+    let outlook = match projection.outlook {
+        Outlook::RunsOut(date) => Span::styled(
+            tf("usage.runs_out", &[&date.format("%b %d")]),
+            warning_style(),
+        ),
+        Outlook::LimitReached => Span::styled(t("usage.limit_reached"), error_style()),
+        Outlook::Lasts => Span::styled(
+            tf(
+                "usage.on_pace",
+                &[
+                    &format!("{:.0}", projection.at_reset),
+                    &format!("{:.0}", stats.total_limit),
+                ],
+            ),
+            muted_style(colors),
+        ),
+    };
+
+    let mut spans = vec![outlook];
+    // Con el límite agotado la distancia a la línea uniforme no aporta nada
+    if with_pace && projection.outlook != Outlook::LimitReached {
+        let distance = format!("{:.0}", projection.vs_even.abs());
+        let pace = match projection.pace {
+            Pace::Ahead => tf("usage.pace_ahead", &[&distance]),
+            Pace::Behind => tf("usage.pace_behind", &[&distance]),
+            Pace::OnPace => t("usage.pace_even").to_string(),
+        };
+        spans.push(Span::styled(icons().separator, muted_style(colors)));
+        spans.push(Span::styled(pace, muted_style(colors)));
+    }
+    spans
+}
 
-    let success_chars = filled_len.min(zone_success_end);
-    if success_chars > 0 {
-        bar_spans.push(Span::styled(
-            BAR_BRAILLE_FILLED.repeat(success_chars),
-            success_style_bold(colors),
+/// "Requests: 150/300 (50.0%)", with an "OVER LIMIT +50" badge past the
+/// limit
+fn requests_label<'a>(stats: &UsageStats, colors: &ThemeColors) -> Vec<Span<'a>> {
+    let mut spans = vec![
+        Span::styled(t("usage.requests"), muted_style(colors)),
+        Span::styled(
+            format!("{:.0}/{:.0}", stats.total_used, stats.total_limit),
+            usage_style(stats.percentage, colors).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(format!(" ({})", format_percentage(stats.percentage)), muted_style(colors)),
+    ];
+    if stats.over_limit() {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            tf("usage.over_limit", &[&format_quantity(stats.overage())]),
+            error_style_bold().add_modifier(Modifier::REVERSED),
         ));
     }
+    spans
+}
 
-    if filled_len > zone_success_end {
-        let warning_chars = filled_len
-            .min(zone_warning_end)
-            .saturating_sub(zone_success_end);
-        if warning_chars > 0 {
-            bar_spans.push(Span::styled(
-                BAR_BRAILLE_FILLED.repeat(warning_chars),
-                warning_style_bold(),
-            ));
+/// "Budget 150/200"
+fn budget_label(used: f64, budget: f64) -> String {
+    tf(
+        "usage.budget",
+        &[&format!("{:.0}", used), &format!("{:.0}", budget)],
+    )
+}
+
+fn budget_percentage(used: f64, budget: f64) -> f64 {
+    (used / budget) * 100.0
+}
+
+/// Helper function to render the requests progress bar, with a tick at the
+/// budget when one is set below the limit. Past the limit or the dollar
+/// budget the whole filled part turns red.
+fn render_requests_bar(f: &mut Frame, area: Rect, stats: &UsageStats, colors: &ThemeColors) {
+    let bar_width = area.width as usize;
+    let mut cells = zone_bar_cells(
+        stats.percentage,
+        bar_width,
+        BAR_BRAILLE_FILLED,
+        BAR_BRAILLE_EMPTY,
+        colors,
+    );
+    if stats.over_limit() || stats.over_budget() {
+        let filled = calculate_filled_cells(stats.percentage, bar_width);
+        for cell in &mut cells[..filled] {
+            cell.1 = error_style_bold();
         }
     }
 
-    if filled_len > zone_warning_end {
-        let error_chars = filled_len.saturating_sub(zone_warning_end);
-        if error_chars > 0 {
-            bar_spans.push(Span::styled(
-                BAR_BRAILLE_FILLED.repeat(error_chars),
-                error_style_bold(),
-            ));
+    if let Some(budget) = stats.budget.filter(|b| *b < stats.total_limit) {
+        let tick = calculate_filled_cells(budget_percentage(budget, stats.total_limit), bar_width);
+        if let Some(cell) = cells.get_mut(tick) {
+            *cell = (
+                BUDGET_TICK,
+                Style::default()
+                    .fg(colors.foreground)
+                    .add_modifier(Modifier::BOLD),
+            );
         }
     }
 
-    let empty_len = bar_width.saturating_sub(filled_len);
-    if empty_len > 0 {
-        bar_spans.push(Span::styled(
-            BAR_BRAILLE_EMPTY.repeat(empty_len),
-            muted_style(colors),
-        ));
+    f.render_widget(Paragraph::new(cells_to_line(cells)), area);
+}
+
+/// Thin bar of the budget consumption followed by "Budget X/Y", colored by
+/// its own zones
+fn render_budget_bar(f: &mut Frame, area: Rect, used: f64, budget: f64, colors: &ThemeColors) {
+    let percentage = budget_percentage(used, budget);
+    let label = format!(" {}", budget_label(used, budget));
+    let bar_width = (area.width as usize).saturating_sub(label.chars().count());
+
+    let mut line = cells_to_line(zone_bar_cells(
+        percentage,
+        bar_width,
+        BUDGET_FILLED,
+        BUDGET_EMPTY,
+        colors,
+    ));
+    line.spans
+        .push(Span::styled(label, usage_style(percentage, colors)));
+
+    f.render_widget(Paragraph::new(line), area);
+}
+
+/// One glyph per cell, colored by the zone the cell falls in:
+/// success up to 75%, warning up to 90%, error beyond
+fn zone_bar_cells(
+    percentage: f64,
+    width: usize,
+    filled: &'static str,
+    empty: &'static str,
+    colors: &ThemeColors,
+) -> Vec<(&'static str, Style)> {
+    let filled_len = calculate_filled_cells(percentage, width);
+    let (zone_success_end, zone_warning_end) = calculate_zone_boundaries(width);
+
+    (0..width)
+        .map(|i| {
+            if i >= filled_len {
+                (empty, muted_style(colors))
+            } else if i < zone_success_end {
+                (filled, success_style_bold(colors))
+            } else if i < zone_warning_end {
+                (filled, warning_style_bold())
+            } else {
+                (filled, error_style_bold())
+            }
+        })
+        .collect()
+}
+
+/// Joins runs of equally styled cells into spans
+fn cells_to_line(cells: Vec<(&'static str, Style)>) -> Line<'static> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_style: Option<Style> = None;
+
+    for (glyph, style) in cells {
+        if run_style != Some(style) {
+            if let Some(previous) = run_style {
+                spans.push(Span::styled(std::mem::take(&mut run), previous));
+            }
+            run_style = Some(style);
+        }
+        run.push_str(glyph);
+    }
+    if let Some(style) = run_style {
+        spans.push(Span::styled(run, style));
     }
 
-    let bar_text = Paragraph::new(Line::from(bar_spans));
-    f.render_widget(bar_text, area);
+    Line::from(spans)
 }
 
-/// Helper function to render the month progress bar (dots with pipe indicator)
-fn render_month_bar(f: &mut Frame, area: Rect, colors: &ThemeColors) {
-    let days_in_month = days_in_current_month();
-    let current_day = Utc::now().day();
+/// Helper function to render the billing period progress bar (dots with
+/// pipe indicator)
+fn render_month_bar(
+    f: &mut Frame,
+    area: Rect,
+    stats: &UsageStats,
+    colors: &ThemeColors,
+    now: DateTime<Utc>,
+) {
+    let elapsed = stats.period_at(now).progress_percent(now);
     let bar_width = area.width as usize;
-    let pipe_pos =
-        ((current_day as f64 / days_in_month as f64) * (bar_width as f64 - 1.0)) as usize;
+    let pipe_pos = ((elapsed / 100.0) * (bar_width as f64 - 1.0)) as usize;
 
     let mut month_spans: Vec<Span> = Vec::new();
 
@@ -203,7 +486,7 @@ fn render_month_bar(f: &mut Frame, area: Rect, colors: &ThemeColors) {
 
     month_spans.push(Span::styled("|", success_style_bold(colors)));
 
-    if pipe_pos < bar_width - 1 {
+    if pipe_pos + 1 < bar_width {
         month_spans.push(Span::styled(
             ".".repeat(bar_width.saturating_sub(pipe_pos + 1)),
             Style::default().fg(colors.bar_empty),
@@ -215,7 +498,7 @@ fn render_month_bar(f: &mut Frame, area: Rect, colors: &ThemeColors) {
 }
 
 /// Get color for a month progress dot based on its position
-/// Colors indicate proximity to reset date (end of month)
+/// Colors indicate proximity to reset date (end of the billing period)
 fn get_month_dot_color(position_percent: f64, colors: &ThemeColors) -> ratatui::style::Color {
     if position_percent >= 95.0 {
         colors.success // Green - Reset imminent! New requests coming!
@@ -228,15 +511,424 @@ fn get_month_dot_color(position_percent: f64, colors: &ThemeColors) -> ratatui::
     }
 }
 
-fn days_in_current_month() -> u32 {
-    let now = Utc::now();
-    let next_month = if now.month() == 12 {
-        now.with_month(1)
-            .unwrap()
-            .with_year(now.year() + 1)
-            .unwrap()
-    } else {
-        now.with_month(now.month() + 1).unwrap()
-    };
-    (next_month - now).num_days() as u32 + now.day()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::ui::styles::ERROR_COLOR;
+    use chrono::TimeZone;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn stats(budget: Option<f64>) -> UsageStats {
+        UsageStats {
+            reset_date: Utc::now(),
+            budget,
+            ..testing::stats(150.0)
+        }
+    }
+
+    /// Rendered rows inside the block borders and margin
+    fn render_rows(width: u16, budget: Option<f64>) -> Vec<String> {
+        render_with_projection(width, budget, None)
+    }
+
+    fn render_with_projection(
+        width: u16,
+        budget: Option<f64>,
+        projection: Option<Projection>,
+    ) -> Vec<String> {
+        let height = height(width, &stats(budget), projection.is_some());
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let colors = ThemeColors::dark();
+        terminal
+            .draw(|f| {
+                render(
+                    f,
+                    f.area(),
+                    &stats(budget),
+                    QuantityMode::Gross,
+                    projection.as_ref(),
+                    &colors,
+                    Utc::now(),
+                )
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| {
+                (2..width - 2)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    fn bar(filled: usize, before_tick: usize, after_tick: Option<usize>) -> String {
+        let mut bar = BAR_BRAILLE_FILLED.repeat(filled) + &BAR_BRAILLE_EMPTY.repeat(before_tick);
+        if let Some(after) = after_tick {
+            bar.push_str(BUDGET_TICK);
+            bar.push_str(&BAR_BRAILLE_EMPTY.repeat(after));
+        }
+        bar
+    }
+
+    fn budget_line(filled: usize, empty: usize) -> String {
+        format!(
+            "{}{} Budget 150/200",
+            BUDGET_FILLED.repeat(filled),
+            BUDGET_EMPTY.repeat(empty)
+        )
+    }
+
+    fn projection(outlook: Outlook, pace: Pace, vs_even: f64) -> Projection {
+        Projection {
+            per_day: 7.06,
+            at_reset: 211.8,
+            outlook,
+            pace,
+            vs_even,
+        }
+    }
+
+    #[test]
+    fn test_title_names_the_mode() {
+        let colors = ThemeColors::dark();
+        let mut terminal = Terminal::new(TestBackend::new(80, OVERALL_HEIGHT)).unwrap();
+        for (mode, title) in [
+            (QuantityMode::Gross, "Overall Usage (gross)"),
+            (QuantityMode::Billed, "Overall Usage (billed)"),
+        ] {
+            terminal
+                .draw(|f| render(f, f.area(), &stats(None), mode, None, &colors, Utc::now()))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            let top: String = (0..80).map(|x| buffer[(x, 0)].symbol()).collect();
+            assert!(top.contains(title), "{}", top);
+        }
+    }
+
+    #[test]
+    fn test_cost_line_only_when_billed() {
+        let colors = ThemeColors::dark();
+        let mut billed = stats(None);
+        billed.estimated_cost = 2.4;
+
+        for width in [80, 50] {
+            let height = height(width, &billed, false);
+            assert_eq!(height, OVERALL_HEIGHT + 1);
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|f| {
+                    render(
+                        f,
+                        f.area(),
+                        &billed,
+                        QuantityMode::Gross,
+                        None,
+                        &colors,
+                        Utc::now(),
+                    )
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            let row: String = (2..width - 2).map(|x| buffer[(x, 4)].symbol()).collect();
+            assert_eq!(row.trim_end(), "Overage cost: $2.40");
+            assert_eq!(buffer[(2, 4)].fg, crate::ui::styles::WARNING_COLOR);
+        }
+
+        assert!(!render_rows(80, None).concat().contains("Overage"));
+    }
+
+    #[test]
+    fn test_reset_countdown_turns_green_on_the_last_day() {
+        let colors = ThemeColors::dark();
+        let now = Utc.with_ymd_and_hms(2026, 2, 28, 18, 0, 0).unwrap();
+        let mut stats = stats(None);
+        stats.reset_date = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(80, OVERALL_HEIGHT)).unwrap();
+        let mut draw = |now| {
+            terminal
+                .draw(|f| render(f, f.area(), &stats, QuantityMode::Gross, None, &colors, now))
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            let row: String = (0..80).map(|x| buffer[(x, 5)].symbol()).collect();
+            let start = row.find("Resets in").unwrap();
+            let x = row[..start].chars().count() as u16;
+            (
+                row[start..]
+                    .trim_end()
+                    .trim_end_matches('│')
+                    .trim_end()
+                    .to_string(),
+                buffer[(x, 5)].fg,
+            )
+        };
+
+        assert_eq!(draw(now), ("Resets in 6h".to_string(), colors.success));
+        let (text, fg) = draw(now - chrono::Duration::days(2));
+        assert_eq!(text, "Resets in 2d 6h");
+        assert_eq!(fg, colors.muted);
+        // Un caché del mes pasado cuenta hasta el próximo reinicio
+        let (text, _) = draw(now + chrono::Duration::days(1));
+        assert_eq!(text, "Resets in 30d 6h");
+    }
+
+    #[test]
+    fn test_month_bar_follows_the_billing_cycle() {
+        let colors = ThemeColors::dark();
+        // Reinicia el 12: el periodo va del 12 de febrero al 12 de marzo
+        let mut stats = stats(None);
+        stats.billing_cycle_day = 12;
+        stats.reset_date = Utc.with_ymd_and_hms(2026, 3, 12, 0, 0, 0).unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 2, 26, 0, 0, 0).unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(80, OVERALL_HEIGHT)).unwrap();
+        terminal
+            .draw(|f| render(f, f.area(), &stats, QuantityMode::Gross, None, &colors, now))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y| (2..78).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert!(row(5).contains("Month: 50.0% elapsed"), "{}", row(5));
+        // 76 columnas: la marca a mitad de la barra
+        assert_eq!(row(6).find('|'), Some(37));
+    }
+
+    #[test]
+    fn test_height_grows_only_in_full_mode_with_budget() {
+        let budget = stats(Some(200.0));
+        assert_eq!(height(80, &stats(None), false), OVERALL_HEIGHT);
+        assert_eq!(height(80, &budget, false), OVERALL_HEIGHT + 1);
+        assert_eq!(height(50, &budget, false), OVERALL_HEIGHT);
+        assert_eq!(height(80, &budget, true), OVERALL_HEIGHT + 2);
+        assert_eq!(height(50, &budget, true), OVERALL_HEIGHT + 1);
+
+        let mut billed = stats(None);
+        billed.estimated_cost = 2.4;
+        assert_eq!(height(50, &billed, false), OVERALL_HEIGHT + 1);
+        billed.cost_budget = Some(5.0);
+        assert_eq!(height(50, &billed, false), OVERALL_HEIGHT + 2);
+    }
+
+    #[test]
+    fn test_over_limit_badge_and_bar() {
+        let colors = ThemeColors::dark();
+        let width = 80;
+        let mut terminal = Terminal::new(TestBackend::new(width, OVERALL_HEIGHT)).unwrap();
+        let mut render_at = |used: f64| {
+            let mut stats = stats(None);
+            stats.total_used = used;
+            stats.percentage = used / 3.0;
+            let mode = QuantityMode::Gross;
+            terminal
+                .draw(|f| render(f, f.area(), &stats, mode, None, &colors, Utc::now()))
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            let label: String = (2..width - 2).map(|x| buffer[(x, 2)].symbol()).collect();
+            let bar: Vec<_> = (3..width - 3).map(|x| buffer[(x, 3)].clone()).collect();
+            (label.trim_end().to_string(), bar)
+        };
+
+        // En el límite exacto: barra llena por zonas, sin insignia
+        let (label, bar) = render_at(300.0);
+        assert_eq!(label, "Requests: 300/300 (100.0%)");
+        assert!(bar.iter().all(|cell| cell.symbol() == BAR_BRAILLE_FILLED));
+        assert_eq!(bar[0].fg, colors.success);
+
+        for (used, badge) in [(300.3, "+0.3"), (600.0, "+300")] {
+            let (label, bar) = render_at(used);
+            assert!(label.ends_with(&format!("OVER LIMIT {}", badge)));
+            // Toda la barra en rojo: llena no significa "todo bien"
+            assert!(bar.iter().all(|cell| cell.symbol() == BAR_BRAILLE_FILLED));
+            assert!(bar.iter().all(|cell| cell.fg == ERROR_COLOR));
+        }
+    }
+
+    #[test]
+    fn test_dollar_budget_line_and_over_budget_bar() {
+        let colors = ThemeColors::dark();
+        let mut stats = stats(None);
+        stats.estimated_cost = 3.2;
+        stats.cost_budget = Some(5.0);
+
+        let width = 80;
+        let height = height(width, &stats, false);
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| {
+                render(
+                    f,
+                    f.area(),
+                    &stats,
+                    QuantityMode::Gross,
+                    None,
+                    &colors,
+                    Utc::now(),
+                )
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y| {
+            (2..width - 2)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        };
+        assert_eq!(row(4).trim_end(), "Overage cost: $3.20");
+        assert_eq!(row(5).trim_end(), "Budget: $3.20 / $5.00 (64%)");
+        // Mitades hacia arriba, como el resto de porcentajes (no "12%")
+        stats.estimated_cost = 0.625;
+        terminal
+            .draw(|f| {
+                render(
+                    f,
+                    f.area(),
+                    &stats,
+                    QuantityMode::Gross,
+                    None,
+                    &colors,
+                    Utc::now(),
+                )
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row: String = (2..width - 2).map(|x| buffer[(x, 5)].symbol()).collect();
+        assert_eq!(row.trim_end(), "Budget: $0.62 / $5.00 (13%)");
+        // Dentro del presupuesto la barra sigue sus zonas
+        assert_eq!(buffer[(2, 3)].fg, colors.success);
+
+        stats.estimated_cost = 6.0;
+        terminal
+            .draw(|f| {
+                render(
+                    f,
+                    f.area(),
+                    &stats,
+                    QuantityMode::Gross,
+                    None,
+                    &colors,
+                    Utc::now(),
+                )
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row: String = (2..width - 2).map(|x| buffer[(x, 5)].symbol()).collect();
+        assert_eq!(row.trim_end(), "Budget: $6.00 / $5.00 (120%)");
+        assert_eq!(buffer[(2, 5)].fg, ERROR_COLOR);
+        assert_eq!(buffer[(2, 3)].fg, ERROR_COLOR);
+    }
+
+    #[test]
+    fn test_projection_line() {
+        let lasts = projection(Outlook::Lasts, Pace::Behind, -43.6);
+        let rows = render_with_projection(80, None, Some(lasts));
+        assert_eq!(rows.len(), 9);
+        assert_eq!(
+            rows[4].trim_end(),
+            "On pace: ~212/300 by reset • 44 under the even pace"
+        );
+        assert_eq!(rows[5].trim(), "");
+
+        let date = Utc.with_ymd_and_hms(2026, 6, 24, 10, 0, 0).unwrap();
+        let runs_out = projection(Outlook::RunsOut(date), Pace::Ahead, 12.0);
+        let rows = render_with_projection(80, Some(200.0), Some(runs_out));
+        assert_eq!(
+            rows[5].trim_end(),
+            "Projected: runs out Jun 24 • 12 over the even pace"
+        );
+
+        // Compacto: sin la distancia al ritmo uniforme
+        let rows = render_with_projection(50, None, Some(runs_out));
+        assert_eq!(rows[4].trim_end(), "Projected: runs out Jun 24");
+
+        let reached = projection(Outlook::LimitReached, Pace::Ahead, 150.0);
+        let rows = render_with_projection(80, None, Some(reached));
+        assert_eq!(rows[4].trim_end(), "Projected: limit reached");
+    }
+
+    #[test]
+    fn test_snapshot_without_budget() {
+        let rows = render_rows(80, None);
+        assert_eq!(rows.len(), 8);
+        assert_eq!(rows[2].trim_end(), "Requests: 150/300 (50.0%)");
+        assert_eq!(rows[3], bar(38, 38, None));
+        assert_eq!(rows[4].trim(), "");
+    }
+
+    #[test]
+    fn test_snapshot_with_budget_80() {
+        let rows = render_rows(80, Some(200.0));
+        assert_eq!(rows.len(), 9);
+        assert_eq!(rows[2].trim_end(), "Requests: 150/300 (50.0%)");
+        assert_eq!(rows[3], bar(38, 12, Some(25)));
+        assert_eq!(rows[4], budget_line(45, 16));
+        assert_eq!(rows[5].trim(), "");
+    }
+
+    #[test]
+    fn test_snapshot_with_budget_120() {
+        let rows = render_rows(120, Some(200.0));
+        assert_eq!(rows[3], bar(58, 19, Some(38)));
+        assert_eq!(rows[4], budget_line(75, 26));
+    }
+
+    #[test]
+    fn test_snapshot_compact_budget_is_a_suffix() {
+        let rows = render_rows(50, Some(200.0));
+        assert_eq!(rows.len(), 8);
+        assert_eq!(
+            rows[2].trim_end(),
+            "Requests: 150/300 (50.0%) • Budget 150/200"
+        );
+        assert_eq!(rows[3], bar(23, 7, Some(15)));
+        assert_eq!(rows[4].trim(), "");
+
+        let without = render_rows(50, None);
+        assert_eq!(without[2].trim_end(), "Requests: 150/300 (50.0%)");
+        assert_eq!(without[3], bar(23, 23, None));
+    }
+
+    #[test]
+    fn test_top_model_line() {
+        let colors = ThemeColors::dark();
+        let mut stats = stats(None);
+        stats.top_model = Some(("claude-3-7-sonnet-thinking".to_string(), 41.3));
+        stats.distinct_models = 5;
+        assert_eq!(height(80, &stats, false), OVERALL_HEIGHT + 1);
+        assert_eq!(compact_height(&stats, false), OVERALL_HEIGHT + 1);
+
+        for (width, expected) in [
+            (80, "Top: claude-3-7-sonnet-thinking (41%) • 5 models"),
+            // Compacto: el nombre se recorta por el medio para caber
+            (40, "Top: claude-…inking (41%) • 5 models"),
+        ] {
+            let height = height(width, &stats, false);
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|f| {
+                    render(
+                        f,
+                        f.area(),
+                        &stats,
+                        QuantityMode::Gross,
+                        None,
+                        &colors,
+                        Utc::now(),
+                    )
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            let row: String = (2..width - 2).map(|x| buffer[(x, 4)].symbol()).collect();
+            assert_eq!(row.trim_end(), expected);
+            // El reinicio sigue en su sitio, debajo del espaciador
+            let reset: String = (2..width - 2).map(|x| buffer[(x, 6)].symbol()).collect();
+            assert!(reset.contains("Resets in"), "{}", reset);
+        }
+    }
+
+    #[test]
+    fn test_no_tick_when_budget_reaches_limit() {
+        let rows = render_rows(80, Some(300.0));
+        assert!(!rows[3].contains(BUDGET_TICK));
+    }
 }
@@ -1,227 +1,568 @@
-use crossterm::event::{Event, KeyCode, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
 
-use crate::models::Theme;
-
-use super::async_handler::AsyncHandler;
+use super::components::text_input::Edit;
+use super::reducer::AppEvent;
 use super::state::{AppState, AppStateManager};
 
-/// Procesa eventos de teclado y actualiza el estado de la aplicación
+/// Teclas directas del dashboard y el comando del menú al que equivalen;
+/// la ayuda las lista desde aquí
+pub const DASHBOARD_COMMAND_KEYS: [(char, &str); 7] = [
+    ('r', "refresh"),
+    ('d', "daily"),
+    ('m', "compare"),
+    ('p', "profile"),
+    ('t', "theme"),
+    ('h', "help"),
+    ('q', "quit"),
+];
+
+/// Traduce teclado y ratón al `AppEvent` que significan en la vista actual;
+/// el estado lo cambia `reducer::reduce`
 pub struct EventHandler;
 
 impl EventHandler {
-    pub fn handle_event(
-        app: &mut AppStateManager,
-        event: Event,
-        total_models: usize,
-        async_handler: &AsyncHandler,
-    ) -> bool {
-        if let Event::Key(key) = event
-            && key.kind == KeyEventKind::Press
-        {
-            return Self::handle_key_press(app, key.code, total_models, async_handler);
+    /// Like `app_event`, but a bracketed paste is every pasted character
+    /// typed in turn, in the views that take text
+    pub fn app_events(app: &AppStateManager, event: Event) -> Vec<AppEvent> {
+        match event {
+            Event::Paste(text) => Self::paste_events(app, &text),
+            event => Self::app_event(app, event).into_iter().collect(),
         }
-        false
     }
 
-    fn handle_key_press(
-        app: &mut AppStateManager,
-        code: KeyCode,
-        total_models: usize,
-        async_handler: &AsyncHandler,
-    ) -> bool {
+    fn paste_events(app: &AppStateManager, text: &str) -> Vec<AppEvent> {
+        let edits = Edit::pasted(text);
         match app.state {
-            AppState::Dashboard => Self::handle_dashboard(app, code, total_models),
-            AppState::CommandMenu => Self::handle_command_menu(app, code, async_handler),
-            AppState::ThemeSelector => Self::handle_theme_selector(app, code),
-            AppState::ConfirmRefresh => Self::handle_confirm_refresh(app, code, async_handler),
-            AppState::ConfirmReconfigure => Self::handle_confirm_reconfigure(app, code),
-            AppState::ShowHelp => Self::handle_help(app, code),
-            AppState::LoadingRefresh | AppState::LoadingCache => Self::handle_loading(app, code),
-            AppState::ShowCacheInfo(_) => Self::handle_cache_info(app, code),
-            AppState::ShowError { .. } => Self::handle_error(app, code),
+            AppState::Settings if !app.settings.saving => {
+                edits.map(AppEvent::SettingsEdit).collect()
+            }
+            AppState::FilterModels => edits
+                .filter_map(|edit| match edit {
+                    Edit::Insert(c) => Some(AppEvent::FilterInput(c)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
         }
     }
 
-    fn handle_dashboard(app: &mut AppStateManager, code: KeyCode, total_models: usize) -> bool {
-        match code {
-            KeyCode::Char('/') | KeyCode::Char(':') => {
-                app.state = AppState::CommandMenu;
-            }
-            KeyCode::Char('q') => {
-                app.action_taken = Some("quit".to_string());
-                return true;
-            }
-            KeyCode::Char('r') => {
-                app.state = AppState::ConfirmRefresh;
-            }
-            KeyCode::Char('t') => {
-                app.state = AppState::ThemeSelector;
-            }
-            KeyCode::Char('h') => {
-                app.state = AppState::ShowHelp;
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                app.scroll_models_down(total_models, 8);
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                app.scroll_models_up();
-            }
-            _ => {}
+    pub fn app_event(app: &AppStateManager, event: Event) -> Option<AppEvent> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => Self::key_event(app, key.code),
+            Event::Mouse(mouse) => Self::mouse_event(app, mouse),
+            _ => None,
         }
-        false
     }
 
-    fn handle_command_menu(
-        app: &mut AppStateManager,
-        code: KeyCode,
-        async_handler: &AsyncHandler,
-    ) -> bool {
-        match code {
-            KeyCode::Esc => {
-                app.state = AppState::Dashboard;
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                app.next_command();
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                app.previous_command();
-            }
-            KeyCode::Enter => {
-                return Self::execute_selected_command(app, async_handler);
-            }
-            KeyCode::Char(c) => {
-                // Atajo rápido por letra
-                if let Some(pos) = app
-                    .commands
-                    .iter()
-                    .position(|cmd| cmd.shortcut.is_some_and(|s| s == c.to_ascii_lowercase()))
-                {
-                    app.selected_command = pos;
-                    return Self::execute_selected_command(app, async_handler);
+    /// La rueda mueve la selección, un click en una fila la elige y un
+    /// click fuera del popup equivale a Esc
+    fn mouse_event(app: &AppStateManager, mouse: MouseEvent) -> Option<AppEvent> {
+        // Cualquier tecla cierra algunos diálogos: la rueda solo va a las listas
+        let scrollable = matches!(
+            app.state,
+            AppState::Dashboard
+                | AppState::FilterModels
+                | AppState::DailyView
+                | AppState::CommandMenu
+                | AppState::ThemeSelector
+                | AppState::ProfileSelector
+                | AppState::ShowHelp
+        );
+
+        // Las áreas son del último frame, que puede no tener aún el popup cerrado
+        let has_popup = !matches!(app.state, AppState::Dashboard | AppState::FilterModels);
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown if scrollable => Some(AppEvent::Scroll(1)),
+            MouseEventKind::ScrollUp if scrollable => Some(AppEvent::Scroll(-1)),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(index) = app.click_areas.list_item_at(mouse.column, mouse.row) {
+                    match app.state {
+                        // El menú cuenta filas: una cabecera no hace nada
+                        AppState::CommandMenu => {
+                            app.command_at_row(index).map(AppEvent::SelectCommand)
+                        }
+                        AppState::ThemeSelector => Some(AppEvent::SelectTheme(index)),
+                        AppState::ProfileSelector => Some(AppEvent::SelectProfile(index)),
+                        _ => None,
+                    }
+                } else if has_popup && app.click_areas.is_outside_popup(mouse.column, mouse.row) {
+                    Self::key_event(app, KeyCode::Esc)
+                } else {
+                    None
                 }
             }
-            _ => {}
+            _ => None,
         }
-        false
     }
 
-    fn handle_theme_selector(app: &mut AppStateManager, code: KeyCode) -> bool {
+    pub fn key_event(app: &AppStateManager, code: KeyCode) -> Option<AppEvent> {
+        match app.state {
+            AppState::Dashboard => Self::dashboard_key(app, code),
+            AppState::CommandMenu => Self::command_menu_key(app, code),
+            AppState::ThemeSelector | AppState::ProfileSelector => match code {
+                KeyCode::Esc => Some(AppEvent::Back),
+                KeyCode::Enter => Some(AppEvent::Activate),
+                _ => Self::navigation_key(code),
+            },
+            AppState::ModelDetail => match code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => Some(AppEvent::Back),
+                _ => None,
+            },
+            AppState::DailyView => match code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('d') => Some(AppEvent::Back),
+                _ => Self::navigation_key(code),
+            },
+            AppState::CompareView => match code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('m') => Some(AppEvent::Back),
+                _ => None,
+            },
+            AppState::FilterModels => match code {
+                KeyCode::Esc => Some(AppEvent::Back),
+                KeyCode::Enter => Some(AppEvent::Activate),
+                KeyCode::Backspace => Some(AppEvent::FilterBackspace),
+                KeyCode::Down => Some(AppEvent::Scroll(1)),
+                KeyCode::Up => Some(AppEvent::Scroll(-1)),
+                // Las teclas de atajo se escriben
+                KeyCode::Char(c) => Some(AppEvent::FilterInput(c)),
+                _ => None,
+            },
+            AppState::ConfirmRefresh => match code {
+                KeyCode::Char('y') | KeyCode::Enter => Some(AppEvent::ConfirmRefresh),
+                KeyCode::Char('n') | KeyCode::Esc => Some(AppEvent::Back),
+                _ => None,
+            },
+            AppState::ConfirmReconfigure => match code {
+                KeyCode::Char('y') | KeyCode::Enter => Some(AppEvent::ConfirmReconfigure),
+                KeyCode::Char('n') | KeyCode::Esc => Some(AppEvent::Back),
+                _ => None,
+            },
+            // Mientras se guarda no se edita
+            AppState::Settings if app.settings.saving => match code {
+                KeyCode::Esc => Some(AppEvent::Back),
+                _ => None,
+            },
+            AppState::Settings => match code {
+                KeyCode::Esc => Some(AppEvent::Back),
+                KeyCode::Enter => Some(AppEvent::Activate),
+                KeyCode::Tab | KeyCode::Down => Some(AppEvent::Scroll(1)),
+                KeyCode::BackTab | KeyCode::Up => Some(AppEvent::Scroll(-1)),
+                _ => Edit::from_key(code).map(AppEvent::SettingsEdit),
+            },
+            AppState::ShowHelp => Self::help_key(app, code),
+            AppState::LoadingRefresh | AppState::LoadingCache => match code {
+                KeyCode::Esc => Some(AppEvent::Back),
+                // Ya hay un refresh en curso: queda en cola
+                KeyCode::Char('r') if app.state == AppState::LoadingRefresh => {
+                    Some(AppEvent::QueueRefresh)
+                }
+                _ => None,
+            },
+            AppState::ShowError { .. } => match code {
+                KeyCode::Char('o') if app.fallback_export.is_some() => Some(AppEvent::OpenExport),
+                KeyCode::Char('d') => Some(AppEvent::ToggleDebug),
+                KeyCode::Char('y' | 'c') => Some(AppEvent::CopyError),
+                _ => Some(AppEvent::Back),
+            },
+            // Cualquier tecla los cierra
+            AppState::ShowAbout | AppState::ShowCacheInfo(_) => Some(AppEvent::Back),
+        }
+    }
+
+    fn navigation_key(code: KeyCode) -> Option<AppEvent> {
         match code {
-            KeyCode::Esc => {
-                app.state = AppState::Dashboard;
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                app.next_theme();
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                app.previous_theme();
-            }
-            KeyCode::Enter => {
-                // Apply theme change in-place (don't exit TUI)
-                let theme_name = app.themes[app.selected_theme];
-                let new_theme = Theme::from_str(theme_name);
-                app.pending_theme_change = Some(new_theme);
-                app.state = AppState::Dashboard;
-            }
-            _ => {}
+            KeyCode::Down | KeyCode::Char('j') => Some(AppEvent::Scroll(1)),
+            KeyCode::Up | KeyCode::Char('k') => Some(AppEvent::Scroll(-1)),
+            _ => None,
         }
-        false
     }
 
-    fn handle_confirm_refresh(
-        app: &mut AppStateManager,
-        code: KeyCode,
-        async_handler: &AsyncHandler,
-    ) -> bool {
+    /// La ayuda se desplaza por líneas o por páginas del último frame
+    fn help_key(app: &AppStateManager, code: KeyCode) -> Option<AppEvent> {
+        let page = app.click_areas.list.map_or(1, |list| list.len.max(1)) as isize;
         match code {
-            KeyCode::Char('y') | KeyCode::Enter => {
-                app.state = AppState::LoadingRefresh;
-                async_handler.spawn_refresh();
-            }
-            KeyCode::Char('n') | KeyCode::Esc => {
-                app.state = AppState::Dashboard;
-            }
-            _ => {}
+            KeyCode::Esc | KeyCode::Char('q') => Some(AppEvent::Back),
+            KeyCode::PageDown => Some(AppEvent::Scroll(page)),
+            KeyCode::PageUp => Some(AppEvent::Scroll(-page)),
+            _ => Self::navigation_key(code),
         }
-        false
     }
 
-    fn handle_confirm_reconfigure(app: &mut AppStateManager, code: KeyCode) -> bool {
+    /// Comando del menú al que equivale una tecla directa del dashboard
+    fn dashboard_command_id(code: KeyCode) -> Option<&'static str> {
+        let KeyCode::Char(c) = code else {
+            return None;
+        };
+        DASHBOARD_COMMAND_KEYS
+            .iter()
+            .find(|(key, _)| *key == c)
+            .map(|(_, id)| *id)
+    }
+
+    fn dashboard_key(app: &AppStateManager, code: KeyCode) -> Option<AppEvent> {
+        if let Some(id) = Self::dashboard_command_id(code) {
+            return Some(AppEvent::RunCommand(id));
+        }
         match code {
-            KeyCode::Char('y') | KeyCode::Enter => {
-                app.action_taken = Some("reconfigure".to_string());
-                return true;
-            }
-            KeyCode::Char('n') | KeyCode::Esc => {
-                app.state = AppState::Dashboard;
-            }
-            _ => {}
+            KeyCode::Char(':') => Some(AppEvent::OpenMenu),
+            KeyCode::Char('/') => Some(AppEvent::OpenFilter),
+            KeyCode::Char('b') => Some(AppEvent::CycleQuantity),
+            KeyCode::Char('v') => Some(AppEvent::ToggleGrouping),
+            KeyCode::PageDown => Some(AppEvent::ScrollPage(1)),
+            KeyCode::PageUp => Some(AppEvent::ScrollPage(-1)),
+            KeyCode::Home => Some(AppEvent::ScrollToStart),
+            KeyCode::End => Some(AppEvent::ScrollToEnd),
+            KeyCode::Esc if !app.model_filter.is_empty() => Some(AppEvent::Back),
+            KeyCode::Enter => Some(AppEvent::Activate),
+            _ => Self::navigation_key(code),
         }
-        false
     }
 
-    fn handle_help(app: &mut AppStateManager, code: KeyCode) -> bool {
+    fn command_menu_key(app: &AppStateManager, code: KeyCode) -> Option<AppEvent> {
         match code {
-            KeyCode::Esc | KeyCode::Char('q') => {
-                app.state = AppState::Dashboard;
+            KeyCode::Esc => Some(AppEvent::Back),
+            KeyCode::Enter => Some(AppEvent::Activate),
+            KeyCode::Down | KeyCode::Up | KeyCode::Char('j') | KeyCode::Char('k') => {
+                Self::navigation_key(code)
             }
-            _ => {}
+            // Atajo rápido por letra
+            KeyCode::Char(c) => app
+                .commands
+                .iter()
+                .position(|cmd| cmd.shortcut.is_some_and(|s| s == c.to_ascii_lowercase()))
+                .map(AppEvent::SelectCommand),
+            _ => None,
         }
-        false
     }
+}
 
-    fn execute_selected_command(app: &mut AppStateManager, async_handler: &AsyncHandler) -> bool {
-        match app.get_selected_command_id() {
-            "refresh" => app.state = AppState::ConfirmRefresh,
-            "theme" => app.state = AppState::ThemeSelector,
-            "reconfigure" => app.state = AppState::ConfirmReconfigure,
-            "cache" => {
-                app.state = AppState::LoadingCache;
-                async_handler.spawn_cache_info();
-            }
-            "help" => app.state = AppState::ShowHelp,
-            "quit" => {
-                app.action_taken = Some("quit".to_string());
-                return true;
-            }
-            _ => {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Theme;
+    use crate::ui::reducer::{Effect, reduce};
+    use crate::ui::state::{ClickAreas, ListArea};
+    use chrono::NaiveDate;
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use ratatui::layout::Rect;
+
+    /// Lo que hace el loop principal: evento de la terminal → `AppEvent` → reduce
+    fn dispatch(app: &mut AppStateManager, event: Event) -> Vec<Effect> {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        EventHandler::app_event(app, event)
+            .map(|event| reduce(app, event, 3, today))
+            .unwrap_or_default()
+    }
+
+    fn press(app: &mut AppStateManager, code: KeyCode) -> Vec<Effect> {
+        dispatch(app, Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    fn mouse(
+        app: &mut AppStateManager,
+        kind: MouseEventKind,
+        column: u16,
+        row: u16,
+    ) -> Vec<Effect> {
+        let event = Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        });
+        dispatch(app, event)
+    }
+
+    /// Popup en (10,5) de 20x10 con la lista desde la fila 6
+    fn list_areas(first: usize, len: usize) -> ClickAreas {
+        ClickAreas {
+            popup: Some(Rect::new(10, 5, 20, 10)),
+            list: Some(ListArea {
+                area: Rect::new(11, 6, 18, 8),
+                first,
+                len,
+                total: first + len,
+            }),
+            ..ClickAreas::default()
         }
-        false
     }
 
-    fn handle_loading(app: &mut AppStateManager, code: KeyCode) -> bool {
-        if matches!(code, KeyCode::Esc) {
-            app.state = AppState::Dashboard;
+    #[test]
+    fn test_paste_is_typed_in_text_views() {
+        let mut app = AppStateManager::new();
+        let paste = |app: &AppStateManager| {
+            EventHandler::app_events(app, Event::Paste("ghp_x\n".to_string()))
+        };
+        // En el dashboard pegar no es pulsar atajos
+        assert!(paste(&app).is_empty());
+
+        app.state = AppState::Settings;
+        let typed: Vec<AppEvent> = "ghp_x"
+            .chars()
+            .map(|c| AppEvent::SettingsEdit(Edit::Insert(c)))
+            .collect();
+        assert_eq!(paste(&app), typed);
+
+        app.state = AppState::FilterModels;
+        assert_eq!(paste(&app).len(), 5);
+    }
+
+    #[test]
+    fn test_key_matrix() {
+        use AppEvent as E;
+        use AppState as S;
+        use KeyCode as K;
+        let edit = |edit| Some(E::SettingsEdit(edit));
+
+        let error = S::ShowError {
+            message: String::new(),
+            debug_message: String::new(),
+            show_debug: false,
+            endpoint: None,
+        };
+        let cases = [
+            (S::Dashboard, K::Char('q'), Some(E::RunCommand("quit"))),
+            (S::Dashboard, K::Char('r'), Some(E::RunCommand("refresh"))),
+            (S::Dashboard, K::Char('d'), Some(E::RunCommand("daily"))),
+            (S::Dashboard, K::Char('m'), Some(E::RunCommand("compare"))),
+            (S::Dashboard, K::Char(':'), Some(E::OpenMenu)),
+            (S::Dashboard, K::Char('/'), Some(E::OpenFilter)),
+            (S::Dashboard, K::Char('b'), Some(E::CycleQuantity)),
+            (S::FilterModels, K::Char('b'), Some(E::FilterInput('b'))),
+            (S::Dashboard, K::Char('v'), Some(E::ToggleGrouping)),
+            (S::Dashboard, K::Char('j'), Some(E::Scroll(1))),
+            (S::Dashboard, K::Up, Some(E::Scroll(-1))),
+            (S::Dashboard, K::PageDown, Some(E::ScrollPage(1))),
+            (S::Dashboard, K::PageUp, Some(E::ScrollPage(-1))),
+            (S::Dashboard, K::Home, Some(E::ScrollToStart)),
+            (S::Dashboard, K::End, Some(E::ScrollToEnd)),
+            (S::Dashboard, K::Enter, Some(E::Activate)),
+            // Sin filtro aplicado, Esc no hace nada
+            (S::Dashboard, K::Esc, None),
+            (S::CommandMenu, K::Char('k'), Some(E::Scroll(-1))),
+            (S::CommandMenu, K::Char('R'), Some(E::SelectCommand(0))),
+            (S::CommandMenu, K::Char('z'), None),
+            (S::ThemeSelector, K::Enter, Some(E::Activate)),
+            (S::ProfileSelector, K::Esc, Some(E::Back)),
+            (S::ModelDetail, K::Char('q'), Some(E::Back)),
+            (S::ModelDetail, K::Char('j'), None),
+            (S::DailyView, K::Char('d'), Some(E::Back)),
+            (S::DailyView, K::Down, Some(E::Scroll(1))),
+            (S::CompareView, K::Char('m'), Some(E::Back)),
+            (S::CompareView, K::Char('j'), None),
+            // En modo filtro las teclas de atajo se escriben
+            (S::FilterModels, K::Char('q'), Some(E::FilterInput('q'))),
+            (S::FilterModels, K::Char('j'), Some(E::FilterInput('j'))),
+            (S::FilterModels, K::Backspace, Some(E::FilterBackspace)),
+            (S::ConfirmRefresh, K::Char('y'), Some(E::ConfirmRefresh)),
+            (S::ConfirmRefresh, K::Char('n'), Some(E::Back)),
+            (S::ConfirmReconfigure, K::Enter, Some(E::ConfirmReconfigure)),
+            // El formulario de ajustes también recibe las letras
+            (S::Settings, K::Char('q'), edit(Edit::Insert('q'))),
+            (S::Settings, K::Tab, Some(E::Scroll(1))),
+            (S::Settings, K::BackTab, Some(E::Scroll(-1))),
+            (S::Settings, K::Backspace, edit(Edit::Backspace)),
+            (S::Settings, K::Home, edit(Edit::Home)),
+            (S::Settings, K::Enter, Some(E::Activate)),
+            (S::ShowHelp, K::Char('x'), None),
+            (S::ShowHelp, K::Char('j'), Some(E::Scroll(1))),
+            (S::ShowHelp, K::Up, Some(E::Scroll(-1))),
+            // Sin frame dibujado la página es de una línea
+            (S::ShowHelp, K::PageDown, Some(E::Scroll(1))),
+            (S::ShowHelp, K::Esc, Some(E::Back)),
+            (S::ShowAbout, K::Char('x'), Some(E::Back)),
+            (S::LoadingRefresh, K::Char('r'), Some(E::QueueRefresh)),
+            (S::LoadingCache, K::Char('r'), None),
+            (S::LoadingCache, K::Esc, Some(E::Back)),
+            (error.clone(), K::Char('d'), Some(E::ToggleDebug)),
+            (error.clone(), K::Char('y'), Some(E::CopyError)),
+            (error.clone(), K::Char('c'), Some(E::CopyError)),
+            // Sin exportación a la que volver, 'o' cierra como cualquier tecla
+            (error, K::Char('o'), Some(E::Back)),
+        ];
+
+        let mut app = AppStateManager::new();
+        for (state, code, expected) in cases {
+            app.state = state.clone();
+            assert_eq!(
+                EventHandler::key_event(&app, code),
+                expected,
+                "{:?} in {:?}",
+                code,
+                state
+            );
         }
-        false
     }
 
-    fn handle_cache_info(app: &mut AppStateManager, _code: KeyCode) -> bool {
-        app.state = AppState::Dashboard;
-        false
+    #[test]
+    fn test_wheel_moves_selection() {
+        let mut app = AppStateManager::new();
+        mouse(&mut app, MouseEventKind::ScrollDown, 0, 0);
+        mouse(&mut app, MouseEventKind::ScrollDown, 0, 0);
+        assert_eq!(app.selected_model, Some(1));
+
+        app.state = AppState::CommandMenu;
+        mouse(&mut app, MouseEventKind::ScrollDown, 0, 0);
+        assert_eq!(app.selected_command, 1);
+        mouse(&mut app, MouseEventKind::ScrollUp, 0, 0);
+        mouse(&mut app, MouseEventKind::ScrollUp, 0, 0);
+        assert_eq!(app.selected_command, app.commands.len() - 1);
+
+        // La rueda no cierra diálogos que se cierran con cualquier tecla
+        app.state = AppState::ShowAbout;
+        mouse(&mut app, MouseEventKind::ScrollDown, 0, 0);
+        assert_eq!(app.state, AppState::ShowAbout);
     }
 
-    fn handle_error(app: &mut AppStateManager, code: KeyCode) -> bool {
-        match code {
-            KeyCode::Char('d') => {
-                // Toggle debug view
-                if let AppState::ShowError {
-                    message,
-                    debug_message,
-                    show_debug,
-                } = &app.state
-                {
-                    app.state = AppState::ShowError {
-                        message: message.clone(),
-                        debug_message: debug_message.clone(),
-                        show_debug: !show_debug,
-                    };
-                }
-            }
-            _ => {
-                app.state = AppState::Dashboard;
-            }
+    #[test]
+    fn test_click_activates_row() {
+        let mut app = AppStateManager::new();
+        app.state = AppState::ThemeSelector;
+        app.click_areas = list_areas(2, 5);
+
+        let effects = mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 15, 7);
+        assert_eq!(app.selected_theme, 3);
+        let expected = Theme::from_str(app.themes[3]);
+        assert!(matches!(effects[..], [Effect::SaveTheme(theme)] if theme == expected));
+        assert_eq!(app.state, AppState::Dashboard);
+
+        // La primera fila del menú es la cabecera de una sección
+        app.state = AppState::CommandMenu;
+        app.click_areas = list_areas(0, 2);
+        let left = MouseEventKind::Down(MouseButton::Left);
+        assert!(mouse(&mut app, left, 15, 6).is_empty());
+        assert_eq!(app.state, AppState::CommandMenu);
+        assert_eq!(app.selected_command, 0);
+
+        // "quit" es el último comando del menú
+        app.click_areas = list_areas(app.menu_rows().len() - 1, 1);
+        let effects = mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 15, 6);
+        assert!(matches!(effects[..], [Effect::Quit]));
+        assert_eq!(app.action_taken.as_deref(), Some("quit"));
+    }
+
+    #[test]
+    fn test_click_outside_popup_dismisses_it() {
+        let mut app = AppStateManager::new();
+        app.state = AppState::CommandMenu;
+        app.click_areas = list_areas(0, 3);
+
+        // Dentro del popup pero bajo la lista: no hace nada
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 15, 12);
+        assert_eq!(app.state, AppState::CommandMenu);
+
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 2, 2);
+        assert_eq!(app.state, AppState::Dashboard);
+
+        app.state = AppState::ShowHelp;
+        app.click_areas = ClickAreas::popup(Rect::new(10, 5, 20, 10));
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 40, 2);
+        assert_eq!(app.state, AppState::Dashboard);
+
+        // Áreas de un frame anterior no tocan el filtro del dashboard
+        app.push_filter_char('g');
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 40, 2);
+        assert_eq!(app.model_filter, "g");
+    }
+
+    #[test]
+    fn test_help_pages_by_the_rows_on_screen() {
+        let mut app = AppStateManager::new();
+        press(&mut app, KeyCode::Char('h'));
+        assert_eq!(app.state, AppState::ShowHelp);
+
+        // 8 filas a la vista de 20
+        app.click_areas = ClickAreas {
+            popup: Some(Rect::new(10, 5, 20, 10)),
+            list: Some(ListArea {
+                area: Rect::new(11, 6, 18, 8),
+                first: 0,
+                len: 8,
+                total: 20,
+            }),
+            ..ClickAreas::default()
+        };
+        press(&mut app, KeyCode::PageDown);
+        assert_eq!(app.help_scroll_offset, 8);
+        press(&mut app, KeyCode::PageDown);
+        assert_eq!(app.help_scroll_offset, 12);
+        mouse(&mut app, MouseEventKind::ScrollUp, 15, 8);
+        assert_eq!(app.help_scroll_offset, 11);
+        press(&mut app, KeyCode::PageUp);
+        assert_eq!(app.help_scroll_offset, 3);
+
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.state, AppState::Dashboard);
+    }
+
+    #[test]
+    fn test_slash_filters_and_colon_opens_menu() {
+        let mut app = AppStateManager::new();
+        press(&mut app, KeyCode::Char(':'));
+        assert_eq!(app.state, AppState::CommandMenu);
+
+        app.state = AppState::Dashboard;
+        press(&mut app, KeyCode::Char('/'));
+        assert_eq!(app.state, AppState::FilterModels);
+
+        // En modo filtro las teclas de atajo se escriben
+        for c in ['q', 'r'] {
+            assert!(press(&mut app, KeyCode::Char(c)).is_empty());
         }
-        false
+        assert_eq!(app.model_filter, "qr");
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state, AppState::Dashboard);
+        assert_eq!(app.model_filter, "q");
+
+        // Esc en el dashboard borra el filtro que quedó aplicado
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.model_filter, "");
+
+        app.state = AppState::FilterModels;
+        app.push_filter_char('o');
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.state, AppState::Dashboard);
+        assert_eq!(app.model_filter, "");
+    }
+
+    #[test]
+    fn test_d_opens_daily_view() {
+        let mut app = AppStateManager::new();
+        let effects = press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.state, AppState::DailyView);
+        assert!(
+            matches!(&effects[..], [Effect::FetchDays(days)] if days.len() == app.daily_rows.len())
+        );
+
+        // La rueda desplaza la tabla y un click fuera la cierra
+        mouse(&mut app, MouseEventKind::ScrollDown, 0, 0);
+        assert_eq!(app.daily_scroll_offset, 1);
+        app.click_areas = ClickAreas::popup(Rect::new(10, 5, 20, 10));
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 2, 2);
+        assert_eq!(app.state, AppState::Dashboard);
+
+        // En modo solo lectura no hay API a la que pedir los días
+        let mut app = AppStateManager::new();
+        app.set_read_only("usage.json".to_string());
+        assert!(press(&mut app, KeyCode::Char('d')).is_empty());
+        assert_eq!(app.state, AppState::Dashboard);
+    }
+
+    #[test]
+    fn test_esc_cancels_the_refresh() {
+        let mut app = AppStateManager::new();
+        press(&mut app, KeyCode::Char('r'));
+        let effects = press(&mut app, KeyCode::Char('y'));
+        assert!(matches!(effects[..], [Effect::SpawnRefresh]));
+        assert_eq!(app.state, AppState::LoadingRefresh);
+
+        press(&mut app, KeyCode::Char('r'));
+        assert!(app.refresh_pending);
+
+        // Sin tarea registrada no hay nada que abortar, pero nada queda en cola
+        assert!(press(&mut app, KeyCode::Esc).is_empty());
+        assert_eq!(app.state, AppState::Dashboard);
+        assert!(!app.refresh_in_flight && !app.refresh_pending);
     }
 }
@@ -1,9 +1,30 @@
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 
+use crate::models::UsageStats;
+use crate::ui::components::trend::{TREND_COLLAPSED_HEIGHT, TREND_HEIGHT};
+use crate::ui::components::usage_overall;
+
 /// Tamaño de diálogos popup (menus, cache, etc.)
 pub const POPUP_WIDTH: u16 = 40;
 pub const POPUP_HEIGHT: u16 = 30;
 
+/// Tamaño mínimo del terminal para el dashboard. Por debajo de 60
+/// columnas ya se usan los layouts compactos.
+pub const MIN_WIDTH: u16 = 50;
+pub const MIN_HEIGHT: u16 = 20;
+
+/// Header block: title, spacing and the date line
+const HEADER_HEIGHT: u16 = 3;
+
+/// Model block kept before the trend and the overall block give up rows:
+/// borders, column header, three rows and the footer
+const TABLE_MIN_HEIGHT: u16 = 7;
+
+/// Whether the dashboard can be laid out in `area` without overlapping
+pub fn fits_dashboard(area: Rect) -> bool {
+    area.width >= MIN_WIDTH && area.height >= MIN_HEIGHT
+}
+
 /// Crea un rectángulo centrado con porcentajes dados
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -25,21 +46,67 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+/// Crea un rectángulo centrado con altura fija, para diálogos con
+/// contenido conocido que no debe recortarse en pantallas pequeñas
+pub fn centered_rect_fixed_height(percent_x: u16, height: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(height),
+            Constraint::Fill(1),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
 /// Layout principal del dashboard
-pub fn dashboard_layout(area: Rect, model_count: usize) -> Vec<Rect> {
+///
+/// Chunks: 0 header, 2 overall usage, 3 trend, 5 model table. The model
+/// block takes what's left and scrolls; when that's under
+/// `TABLE_MIN_HEIGHT`, the trend collapses first and then the overall
+/// block goes compact.
+pub fn dashboard_layout(area: Rect, stats: &UsageStats, has_projection: bool) -> Vec<Rect> {
     // Calculate required height for models:
-    // Header (1) + Borders (2) + Rows (model_count) + Bottom Padding (1)
-    // We add a safety minimum of 4
-    let model_height = ((model_count as u16) + 3).max(4);
+    // Header (1) + Borders (2) + Rows (model_count) + Footer (1)
+    let model_height = (stats.models.len() as u16) + 4;
+    let table_height = model_height.min(TABLE_MIN_HEIGHT);
+    let fits =
+        |overall: u16, trend: u16| area.height >= HEADER_HEIGHT + overall + trend + table_height;
+
+    // The budgets, the projection and the cost add a line each to the overall block
+    let full_overall = usage_overall::height(area.width, stats, has_projection);
+
+    // The trend collapses to a summary line when the sparkline doesn't fit
+    // next to the minimum model table
+    let trend_height = if fits(full_overall, TREND_HEIGHT) {
+        TREND_HEIGHT
+    } else {
+        TREND_COLLAPSED_HEIGHT
+    };
+    let overall_height = if fits(full_overall, trend_height) {
+        full_overall
+    } else {
+        usage_overall::compact_height(stats, has_projection)
+    };
 
     Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),            // Header
-            Constraint::Length(0),            // Spacing (Reduced to 0)
-            Constraint::Length(8),            // Overall usage
-            Constraint::Length(0),            // Spacing (Reduced to 0)
-            Constraint::Length(model_height), // Model usage (Fixed height)
+            Constraint::Length(HEADER_HEIGHT),  // Header
+            Constraint::Length(0),              // Spacing (Reduced to 0)
+            Constraint::Length(overall_height), // Overall usage
+            Constraint::Length(trend_height),   // Daily trend
+            Constraint::Length(0),              // Spacing (Reduced to 0)
+            Constraint::Max(model_height),      // Model usage (the rest, scrolls)
         ])
         .split(area)
         .to_vec()
@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+use crate::themes::ThemeColors;
+
+const OVERLAY_WIDTH: u16 = 24;
+const OVERLAY_HEIGHT: u16 = 5;
+
+/// Frame timings shown by the debug overlay (F12 / `--debug-overlay`)
+pub struct FrameStats {
+    pub visible: bool,
+    /// Time spent inside the last `terminal.draw`
+    pub last_draw: Duration,
+    /// How long the last event poll overran its timeout
+    pub poll_lag: Duration,
+    /// Draw instants within the last second
+    frames: VecDeque<Instant>,
+}
+
+impl FrameStats {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            last_draw: Duration::ZERO,
+            poll_lag: Duration::ZERO,
+            frames: VecDeque::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn record_poll(&mut self, waited: Duration, timeout: Duration) {
+        self.poll_lag = waited.saturating_sub(timeout);
+    }
+
+    pub fn record_frame(&mut self, draw_time: Duration) {
+        let now = Instant::now();
+        self.last_draw = draw_time;
+        self.frames.push_back(now);
+        while self
+            .frames
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > Duration::from_secs(1))
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Frames drawn during the last second
+    pub fn fps(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// Draws the overlay in the top-right corner, over whatever is there
+pub fn render(f: &mut Frame, stats: &FrameStats, colors: &ThemeColors) {
+    if !stats.visible {
+        return;
+    }
+
+    let screen = f.area();
+    let width = OVERLAY_WIDTH.min(screen.width);
+    let area = Rect {
+        x: screen.x + screen.width - width,
+        y: screen.y,
+        width,
+        height: OVERLAY_HEIGHT.min(screen.height),
+    };
+
+    let lines = vec![
+        Line::from(format!(
+            "draw: {:.2} ms",
+            stats.last_draw.as_secs_f64() * 1000.0
+        )),
+        Line::from(format!(
+            "poll lag: {:.2} ms",
+            stats.poll_lag.as_secs_f64() * 1000.0
+        )),
+        Line::from(format!("fps: {}", stats.fps())),
+    ];
+
+    let overlay = Paragraph::new(lines)
+        .style(Style::default().fg(colors.foreground))
+        .block(
+            Block::default()
+                .title(" Frame ")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(colors.warning)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(overlay, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    #[test]
+    fn test_fps_counts_last_second() {
+        let mut stats = FrameStats::new(true);
+        stats
+            .frames
+            .push_back(Instant::now() - Duration::from_secs(2));
+        stats.record_frame(Duration::from_millis(3));
+        stats.record_frame(Duration::from_millis(4));
+
+        assert_eq!(stats.fps(), 2);
+        assert_eq!(stats.last_draw, Duration::from_millis(4));
+    }
+
+    #[test]
+    fn test_renders_only_in_top_right_corner() {
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        let colors = ThemeColors::dark();
+        terminal
+            .draw(|f| render(f, &FrameStats::new(true), &colors))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(40 - OVERLAY_WIDTH, 0)].symbol(), "╭");
+        for y in 0..10 {
+            for x in 0..40 {
+                let inside = x >= 40 - OVERLAY_WIDTH && y < OVERLAY_HEIGHT;
+                if !inside {
+                    assert_eq!(buffer[(x, y)].symbol(), " ");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,534 @@
+use chrono::NaiveDate;
+
+use crate::models::Theme;
+
+use super::async_handler::RefreshHandle;
+use super::components::text_input::Edit;
+use super::state::{AppState, AppStateManager, SettingsUpdate};
+
+/// What the user asked for, independent of the key or click that did it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppEvent {
+    OpenMenu,
+    OpenFilter,
+    /// A menu command, from the menu or its dashboard shortcut
+    RunCommand(&'static str),
+    /// Menu entry `n`, picked by its letter or a click
+    SelectCommand(usize),
+    SelectTheme(usize),
+    SelectProfile(usize),
+    /// Moves the selection or the view of the current list by `n` rows
+    Scroll(isize),
+    /// Same, by `n` pages of the model table
+    ScrollPage(isize),
+    /// First or last row of the model table
+    ScrollToStart,
+    ScrollToEnd,
+    /// Enter on the current view
+    Activate,
+    /// Esc: closes the popup, clears the filter or cancels the refresh
+    Back,
+    ConfirmRefresh,
+    ConfirmReconfigure,
+    /// Another refresh once the running one finishes
+    QueueRefresh,
+    FilterInput(char),
+    FilterBackspace,
+    /// Typed or pasted into the focused field of the settings form
+    SettingsEdit(Edit),
+    /// Shows or hides the details of the error dialog
+    ToggleDebug,
+    /// Opens the fallback export offered by the error dialog
+    OpenExport,
+    /// Copies the error dialog's report for a bug report
+    CopyError,
+    /// Gross → included → billed in the dashboard
+    CycleQuantity,
+    /// Per-model ↔ per-SKU rows in the usage table
+    ToggleGrouping,
+}
+
+/// Work left to the event loop after applying an event
+#[derive(Debug)]
+pub enum Effect {
+    SpawnRefresh,
+    SpawnCacheInfo,
+    /// Stops the refresh cancelled with Esc
+    AbortRefresh(RefreshHandle),
+    /// Fetches the data of the profile chosen in the selector
+    SwitchProfile(String),
+    /// Days of the daily view still to be fetched
+    FetchDays(Vec<NaiveDate>),
+    /// Year and month the compare view still needs
+    FetchPreviousMonth((i32, u32)),
+    /// Applies the theme in place and saves it to the config
+    SaveTheme(Theme),
+    /// Fills the settings form from the config and shows it
+    OpenSettings,
+    SaveSettings(SettingsUpdate),
+    /// Puts the text on the clipboard
+    Copy(String),
+    /// Leaves the TUI; `action_taken` says what for
+    Quit,
+}
+
+/// Applies `event` to `app` and returns the effects for the event loop.
+/// No I/O here: `today` and `total_models` come from the caller.
+pub fn reduce(
+    app: &mut AppStateManager,
+    event: AppEvent,
+    total_models: usize,
+    today: NaiveDate,
+) -> Vec<Effect> {
+    match event {
+        AppEvent::OpenMenu => app.state = AppState::CommandMenu,
+        AppEvent::OpenFilter => app.state = AppState::FilterModels,
+        AppEvent::RunCommand(id) => return run_command(app, id, today),
+        AppEvent::SelectCommand(index) => {
+            app.selected_command = index;
+            return run_command(app, app.get_selected_command_id(), today);
+        }
+        AppEvent::SelectTheme(index) => {
+            app.selected_theme = index;
+            return apply_selected_theme(app);
+        }
+        AppEvent::SelectProfile(index) => {
+            app.selected_profile = index;
+            return switch_to_selected_profile(app);
+        }
+        AppEvent::Scroll(rows) => scroll(app, rows, total_models),
+        AppEvent::ScrollPage(pages) => {
+            scroll(app, pages * app.visible_model_rows() as isize, total_models)
+        }
+        AppEvent::ScrollToStart => app.select_first_model(total_models),
+        AppEvent::ScrollToEnd => app.select_last_model(total_models, app.visible_model_rows()),
+        AppEvent::Activate => return activate(app, total_models, today),
+        AppEvent::Back => return back(app),
+        AppEvent::ConfirmRefresh => {
+            app.state = AppState::LoadingRefresh;
+            if app.request_refresh() {
+                return vec![Effect::SpawnRefresh];
+            }
+        }
+        AppEvent::ConfirmReconfigure => return quit(app, "reconfigure".to_string()),
+        AppEvent::QueueRefresh => {
+            // Ya hay un refresh en curso: queda en cola
+            if !app.is_command_disabled("refresh") {
+                app.request_refresh();
+            }
+        }
+        AppEvent::FilterInput(c) => app.push_filter_char(c),
+        AppEvent::FilterBackspace => app.pop_filter_char(),
+        AppEvent::SettingsEdit(edit) => app.settings.edit(edit),
+        AppEvent::ToggleDebug => {
+            if let AppState::ShowError { show_debug, .. } = &mut app.state {
+                *show_debug = !*show_debug;
+            }
+        }
+        AppEvent::CopyError => {
+            if let Some(report) = app.error_report() {
+                return vec![Effect::Copy(report)];
+            }
+        }
+        AppEvent::OpenExport => {
+            // La CLI abre la exportación en modo de solo lectura
+            if let Some(path) = &app.fallback_export {
+                let action = format!("export:{}", path.display());
+                return quit(app, action);
+            }
+        }
+        AppEvent::CycleQuantity => app.quantity_mode = app.quantity_mode.next(),
+        AppEvent::ToggleGrouping => app.toggle_grouping(),
+    }
+    Vec::new()
+}
+
+fn quit(app: &mut AppStateManager, action: String) -> Vec<Effect> {
+    app.action_taken = Some(action);
+    vec![Effect::Quit]
+}
+
+fn run_command(app: &mut AppStateManager, id: &'static str, today: NaiveDate) -> Vec<Effect> {
+    if app.is_command_disabled(id) {
+        app.notify_disabled(id);
+        return Vec::new();
+    }
+
+    match id {
+        "refresh" => app.state = AppState::ConfirmRefresh,
+        "daily" => {
+            // Solo los días que falten
+            let dates = app.open_daily_view(today);
+            if !dates.is_empty() {
+                return vec![Effect::FetchDays(dates)];
+            }
+        }
+        "compare" => {
+            if let Some(period) = app.open_compare_view(today) {
+                return vec![Effect::FetchPreviousMonth(period)];
+            }
+        }
+        "theme" => app.state = AppState::ThemeSelector,
+        "reconfigure" if app.inline_settings => return vec![Effect::OpenSettings],
+        "reconfigure" => app.state = AppState::ConfirmReconfigure,
+        "profile" => app.state = AppState::ProfileSelector,
+        "cache" => {
+            app.state = AppState::LoadingCache;
+            return vec![Effect::SpawnCacheInfo];
+        }
+        "help" => app.open_help(),
+        "about" => app.state = AppState::ShowAbout,
+        "quit" => return quit(app, "quit".to_string()),
+        _ => {}
+    }
+    Vec::new()
+}
+
+fn apply_selected_theme(app: &mut AppStateManager) -> Vec<Effect> {
+    // Se aplica sin salir de la TUI
+    let theme = Theme::from_str(app.themes[app.selected_theme]);
+    app.state = AppState::Dashboard;
+    vec![Effect::SaveTheme(theme)]
+}
+
+fn switch_to_selected_profile(app: &mut AppStateManager) -> Vec<Effect> {
+    match app.choose_selected_profile() {
+        Some(profile) => {
+            app.state = AppState::LoadingRefresh;
+            vec![Effect::SwitchProfile(profile)]
+        }
+        None => {
+            app.state = AppState::Dashboard;
+            Vec::new()
+        }
+    }
+}
+
+fn scroll(app: &mut AppStateManager, rows: isize, total_models: usize) {
+    for _ in 0..rows.unsigned_abs() {
+        let down = rows > 0;
+        match app.state {
+            AppState::Dashboard | AppState::FilterModels if down => {
+                app.select_next_model(total_models, app.visible_model_rows())
+            }
+            AppState::Dashboard | AppState::FilterModels => app.select_previous_model(),
+            AppState::CommandMenu if down => app.next_command(),
+            AppState::CommandMenu => app.previous_command(),
+            AppState::ThemeSelector if down => app.next_theme(),
+            AppState::ThemeSelector => app.previous_theme(),
+            AppState::ProfileSelector if down => app.next_profile(),
+            AppState::ProfileSelector => app.previous_profile(),
+            AppState::DailyView if down => app.scroll_daily_down(),
+            AppState::DailyView => app.scroll_daily_up(),
+            AppState::ShowHelp if down => app.scroll_help_down(),
+            AppState::ShowHelp => app.scroll_help_up(),
+            AppState::Settings => app.settings.move_focus(down),
+            _ => {}
+        }
+    }
+}
+
+fn activate(app: &mut AppStateManager, total_models: usize, today: NaiveDate) -> Vec<Effect> {
+    match app.state {
+        AppState::Dashboard => {
+            app.open_model_detail(total_models);
+        }
+        AppState::CommandMenu => return run_command(app, app.get_selected_command_id(), today),
+        AppState::ThemeSelector => return apply_selected_theme(app),
+        AppState::ProfileSelector => return switch_to_selected_profile(app),
+        AppState::FilterModels => app.state = AppState::Dashboard,
+        AppState::Settings => return save_settings(app),
+        _ => {}
+    }
+    Vec::new()
+}
+
+fn save_settings(app: &mut AppStateManager) -> Vec<Effect> {
+    if app.settings.saving {
+        return Vec::new();
+    }
+    match app.settings.validate() {
+        Ok(update) => {
+            app.settings.saving = true;
+            vec![Effect::SaveSettings(update)]
+        }
+        Err(error) => {
+            app.settings.error = Some(error);
+            Vec::new()
+        }
+    }
+}
+
+fn back(app: &mut AppStateManager) -> Vec<Effect> {
+    let mut effects = Vec::new();
+    match app.state {
+        AppState::Dashboard => app.clear_model_filter(),
+        AppState::FilterModels => {
+            app.clear_model_filter();
+            app.state = AppState::Dashboard;
+        }
+        AppState::LoadingRefresh => {
+            effects.extend(app.cancel_refresh().map(Effect::AbortRefresh));
+            app.state = AppState::Dashboard;
+        }
+        _ => app.state = AppState::Dashboard,
+    }
+    effects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Config, QuantityMode, TableGrouping};
+    use crate::ui::state::SettingsForm;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 20).unwrap()
+    }
+
+    /// Aplica los eventos en orden y devuelve todos los efectos
+    fn replay(app: &mut AppStateManager, events: &[AppEvent]) -> Vec<Effect> {
+        events
+            .iter()
+            .flat_map(|event| reduce(app, *event, 3, today()))
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_flow() {
+        let mut app = AppStateManager::new();
+        replay(
+            &mut app,
+            &[
+                AppEvent::OpenFilter,
+                AppEvent::FilterInput('q'),
+                AppEvent::FilterInput('r'),
+                AppEvent::FilterBackspace,
+                AppEvent::Activate,
+            ],
+        );
+        assert_eq!(app.state, AppState::Dashboard);
+        assert_eq!(app.model_filter, "q");
+
+        // Esc en el dashboard borra el filtro que quedó aplicado
+        replay(&mut app, &[AppEvent::Back]);
+        assert_eq!(app.model_filter, "");
+
+        replay(
+            &mut app,
+            &[
+                AppEvent::OpenFilter,
+                AppEvent::FilterInput('o'),
+                AppEvent::Back,
+            ],
+        );
+        assert_eq!(app.state, AppState::Dashboard);
+        assert_eq!(app.model_filter, "");
+    }
+
+    #[test]
+    fn test_quantity_mode_cycles() {
+        let mut app = AppStateManager::new();
+        assert_eq!(app.quantity_mode, QuantityMode::Gross);
+        replay(
+            &mut app,
+            &[AppEvent::CycleQuantity, AppEvent::CycleQuantity],
+        );
+        assert_eq!(app.quantity_mode, QuantityMode::Billed);
+        assert!(replay(&mut app, &[AppEvent::CycleQuantity]).is_empty());
+        assert_eq!(app.quantity_mode, QuantityMode::Gross);
+        assert_eq!(app.state, AppState::Dashboard);
+    }
+
+    #[test]
+    fn test_grouping_toggle_resets_the_selection() {
+        let mut app = AppStateManager::new();
+        replay(&mut app, &[AppEvent::Scroll(1), AppEvent::Scroll(1)]);
+        assert_eq!(app.selected_model, Some(1));
+
+        assert!(replay(&mut app, &[AppEvent::ToggleGrouping]).is_empty());
+        assert_eq!(app.table_grouping, TableGrouping::Sku);
+        assert_eq!(app.selected_model, None);
+        replay(&mut app, &[AppEvent::ToggleGrouping]);
+        assert_eq!(app.table_grouping, TableGrouping::Model);
+    }
+
+    #[test]
+    fn test_menu_runs_commands() {
+        let mut app = AppStateManager::new();
+        let effects = replay(&mut app, &[AppEvent::OpenMenu, AppEvent::Activate]);
+        assert!(effects.is_empty());
+        assert_eq!(app.state, AppState::ConfirmRefresh);
+
+        let effects = replay(&mut app, &[AppEvent::ConfirmRefresh]);
+        assert!(matches!(effects[..], [Effect::SpawnRefresh]));
+        assert_eq!(app.state, AppState::LoadingRefresh);
+
+        // Uno en curso: el siguiente queda en cola, sin lanzar otro
+        assert!(replay(&mut app, &[AppEvent::QueueRefresh]).is_empty());
+        assert!(app.refresh_pending);
+
+        let cache = app.commands.iter().position(|cmd| cmd.id == "cache");
+        let effects = replay(&mut app, &[AppEvent::SelectCommand(cache.unwrap())]);
+        assert!(matches!(effects[..], [Effect::SpawnCacheInfo]));
+        assert_eq!(app.state, AppState::LoadingCache);
+
+        let effects = replay(&mut app, &[AppEvent::RunCommand("quit")]);
+        assert!(matches!(effects[..], [Effect::Quit]));
+        assert_eq!(app.action_taken.as_deref(), Some("quit"));
+    }
+
+    #[test]
+    fn test_disabled_command_only_notifies() {
+        let mut app = AppStateManager::new();
+        app.disabled_commands = vec!["refresh".to_string()];
+
+        assert!(replay(&mut app, &[AppEvent::RunCommand("refresh")]).is_empty());
+        assert_eq!(app.state, AppState::Dashboard);
+        assert!(app.active_notice().is_some());
+
+        app.state = AppState::LoadingRefresh;
+        replay(&mut app, &[AppEvent::QueueRefresh]);
+        assert!(!app.refresh_pending);
+    }
+
+    #[test]
+    fn test_theme_and_profile_selection() {
+        let mut app = AppStateManager::new();
+        let effects = replay(&mut app, &[AppEvent::SelectTheme(3)]);
+        let expected = Theme::from_str(app.themes[3]);
+        assert!(matches!(effects[..], [Effect::SaveTheme(theme)] if theme == expected));
+        assert_eq!(app.state, AppState::Dashboard);
+
+        app.set_profiles(vec!["personal".to_string(), "work".to_string()], "work");
+        app.state = AppState::ProfileSelector;
+        // El perfil que ya está en pantalla solo cierra el selector
+        assert!(replay(&mut app, &[AppEvent::Activate]).is_empty());
+        assert_eq!(app.state, AppState::Dashboard);
+
+        let effects = replay(&mut app, &[AppEvent::SelectProfile(0)]);
+        assert!(matches!(&effects[..], [Effect::SwitchProfile(p)] if p == "personal"));
+        assert_eq!(app.state, AppState::LoadingRefresh);
+    }
+
+    #[test]
+    fn test_daily_view_fetches_missing_days() {
+        let mut app = AppStateManager::new();
+        let effects = replay(&mut app, &[AppEvent::RunCommand("daily")]);
+        assert_eq!(app.state, AppState::DailyView);
+        assert!(
+            matches!(&effects[..], [Effect::FetchDays(days)] if days.len() == app.daily_rows.len())
+        );
+
+        replay(&mut app, &[AppEvent::Scroll(1), AppEvent::Scroll(1)]);
+        assert_eq!(app.daily_scroll_offset, 2);
+        replay(&mut app, &[AppEvent::Back]);
+        assert_eq!(app.state, AppState::Dashboard);
+
+        // Reabrirla mientras se cargan no los pide otra vez
+        assert!(replay(&mut app, &[AppEvent::RunCommand("daily")]).is_empty());
+    }
+
+    #[test]
+    fn test_compare_view_fetches_last_month() {
+        let mut app = AppStateManager::new();
+        let effects = replay(&mut app, &[AppEvent::RunCommand("compare")]);
+        assert_eq!(app.state, AppState::CompareView);
+        assert!(matches!(
+            effects[..],
+            [Effect::FetchPreviousMonth((2026, 2))]
+        ));
+        replay(&mut app, &[AppEvent::Back]);
+        assert_eq!(app.state, AppState::Dashboard);
+        assert!(replay(&mut app, &[AppEvent::RunCommand("compare")]).is_empty());
+    }
+
+    #[test]
+    fn test_error_dialog() {
+        let mut app = AppStateManager::new();
+        app.state = AppState::ShowError {
+            message: "boom".to_string(),
+            debug_message: "details".to_string(),
+            show_debug: false,
+            endpoint: Some("https://api.github.com/user".to_string()),
+        };
+        replay(&mut app, &[AppEvent::ToggleDebug]);
+        assert!(matches!(
+            app.state,
+            AppState::ShowError {
+                show_debug: true,
+                ..
+            }
+        ));
+
+        // El informe copiado lleva el detalle, la versión y el endpoint
+        let effects = replay(&mut app, &[AppEvent::CopyError]);
+        let [Effect::Copy(report)] = &effects[..] else {
+            panic!("{:?}", effects);
+        };
+        assert!(report.starts_with(&format!("copilot-usage {}\n", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains(&format!("\nOS: {} (", std::env::consts::OS)));
+        assert!(report.contains("\nEndpoint: https://api.github.com/user\n"));
+        assert!(report.ends_with("\n\ndetails"));
+
+        // Sin exportación a la que volver no hace nada
+        assert!(replay(&mut app, &[AppEvent::OpenExport]).is_empty());
+        app.fallback_export = Some("/tmp/usage.json".into());
+        let effects = replay(&mut app, &[AppEvent::OpenExport]);
+        assert!(matches!(effects[..], [Effect::Quit]));
+        assert_eq!(app.action_taken.as_deref(), Some("export:/tmp/usage.json"));
+    }
+
+    #[test]
+    fn test_reconfigure_opens_settings_in_a_terminal() {
+        let mut app = AppStateManager::new();
+        // Sin terminal: se sale al asistente de siempre
+        replay(&mut app, &[AppEvent::RunCommand("reconfigure")]);
+        assert_eq!(app.state, AppState::ConfirmReconfigure);
+        let effects = replay(&mut app, &[AppEvent::ConfirmReconfigure]);
+        assert!(matches!(effects[..], [Effect::Quit]));
+
+        let mut app = AppStateManager::new();
+        app.inline_settings = true;
+        let effects = replay(&mut app, &[AppEvent::RunCommand("reconfigure")]);
+        assert!(matches!(effects[..], [Effect::OpenSettings]));
+    }
+
+    #[test]
+    fn test_settings_form() {
+        let mut app = AppStateManager::new();
+        app.open_settings(SettingsForm::new(&Config::default()));
+
+        // Token → tema: "dark" pasa a "nord"
+        let mut events = vec![AppEvent::Scroll(1)];
+        events.extend([AppEvent::SettingsEdit(Edit::Backspace); 4]);
+        events.extend(Edit::pasted("nord").map(AppEvent::SettingsEdit));
+        // TTL: "5" pasa a "x5" y luego a "x"
+        events.extend([AppEvent::Scroll(1), AppEvent::SettingsEdit(Edit::Home)]);
+        events.extend([Edit::Insert('x'), Edit::End, Edit::Backspace].map(AppEvent::SettingsEdit));
+        assert!(replay(&mut app, &events).is_empty());
+        assert_eq!(app.settings.theme.value(), "nord");
+
+        assert!(replay(&mut app, &[AppEvent::Activate]).is_empty());
+        assert_eq!(app.state, AppState::Settings);
+        assert!(app.settings.error.as_deref().unwrap().contains("'x'"));
+
+        let events = [
+            AppEvent::SettingsEdit(Edit::Backspace),
+            AppEvent::SettingsEdit(Edit::Insert('9')),
+            AppEvent::Activate,
+        ];
+        let effects = replay(&mut app, &events);
+        assert!(app.settings.error.is_none());
+        assert!(app.settings.saving);
+        let [Effect::SaveSettings(update)] = &effects[..] else {
+            panic!("expected a save, got {:?}", effects);
+        };
+        assert_eq!(update.theme, "nord");
+        assert_eq!(update.cache_ttl_minutes, 9);
+        assert_eq!(update.token, None);
+
+        // Un segundo Enter mientras se guarda no vuelve a guardar
+        assert!(replay(&mut app, &[AppEvent::Activate]).is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+//! How long the event loop may block in `event::poll` before something on
+//! screen has to change without a key press
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone};
+
+/// Spinner frame time while a loading dialog is shown (~30 FPS)
+pub const ANIMATION_FRAME: Duration = Duration::from_millis(33);
+
+/// Polling interval while a background task runs without a spinner (a
+/// revalidation, saving the theme or the settings): its result arrives over
+/// a channel `event::poll` can't see
+pub const BUSY_POLL: Duration = Duration::from_millis(250);
+
+/// Longest idle wait. Header and help-bar strings that depend on the clock
+/// (time until reset, data age) are at most this stale; the date rolls over
+/// on time, see `until_midnight`.
+pub const IDLE_MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// Poll timeout for the current state: the animation or busy interval when
+/// one applies, otherwise until the nearest deadline (a notice or indicator
+/// expiring), capped at `IDLE_MAX_WAIT`
+pub fn poll_timeout(
+    animating: bool,
+    busy: bool,
+    deadlines: impl IntoIterator<Item = Duration>,
+) -> Duration {
+    let cap = if animating {
+        ANIMATION_FRAME
+    } else if busy {
+        BUSY_POLL
+    } else {
+        IDLE_MAX_WAIT
+    };
+    deadlines.into_iter().fold(cap, Duration::min)
+}
+
+/// Time from `now` to the next midnight of its timezone, when the date on
+/// screen changes. A midnight skipped by DST waits the idle maximum instead.
+pub fn until_midnight<Tz: TimeZone>(now: &DateTime<Tz>) -> Duration {
+    now.date_naive()
+        .succ_opt()
+        .and_then(|tomorrow| {
+            now.timezone()
+                .from_local_datetime(&tomorrow.and_hms_opt(0, 0, 0)?)
+                .earliest()
+        })
+        .and_then(|midnight| (midnight - now.clone()).to_std().ok())
+        .unwrap_or(IDLE_MAX_WAIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+
+    /// Event-less run of the loop on a fake clock: every poll times out and
+    /// the clock jumps by the timeout. Returns the wakeup times.
+    struct FakeLoop {
+        now: Duration,
+        /// Absolute times at which something on screen expires
+        deadlines: Vec<Duration>,
+        /// The spinner runs until this time
+        animating_until: Duration,
+        /// A background refresh runs until this time
+        busy_until: Duration,
+    }
+
+    impl FakeLoop {
+        fn idle() -> Self {
+            Self {
+                now: Duration::ZERO,
+                deadlines: Vec::new(),
+                animating_until: Duration::ZERO,
+                busy_until: Duration::ZERO,
+            }
+        }
+
+        fn run_until(&mut self, end: Duration) -> Vec<Duration> {
+            let mut wakeups = Vec::new();
+            while self.now < end {
+                let now = self.now;
+                let remaining = self
+                    .deadlines
+                    .iter()
+                    .filter(|d| **d > now)
+                    .map(|d| *d - now);
+                let timeout =
+                    poll_timeout(now < self.animating_until, now < self.busy_until, remaining);
+                assert!(timeout > Duration::ZERO, "busy loop at {:?}", now);
+                self.now += timeout;
+                wakeups.push(self.now);
+            }
+            wakeups
+        }
+    }
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn test_idle_wakes_twice_a_minute() {
+        let wakeups = FakeLoop::idle().run_until(secs(600));
+        assert_eq!(wakeups.len(), 20);
+        assert!(wakeups.windows(2).all(|w| w[1] - w[0] == IDLE_MAX_WAIT));
+    }
+
+    #[test]
+    fn test_wakes_exactly_at_deadlines() {
+        // A notice expiring at 3s and the "updated" indicator at 5s
+        let mut fake = FakeLoop {
+            deadlines: vec![secs(3), secs(5)],
+            ..FakeLoop::idle()
+        };
+        assert_eq!(
+            fake.run_until(secs(65)),
+            vec![secs(3), secs(5), secs(35), secs(65)]
+        );
+    }
+
+    #[test]
+    fn test_spinner_keeps_its_frame_rate() {
+        let mut fake = FakeLoop {
+            animating_until: secs(1),
+            ..FakeLoop::idle()
+        };
+        let wakeups = fake.run_until(secs(31));
+        let animated = wakeups.iter().filter(|t| **t <= secs(1)).count();
+        assert_eq!(animated, 30);
+        // Back to the idle schedule once the spinner is gone
+        assert_eq!(wakeups.len(), 32);
+    }
+
+    #[test]
+    fn test_until_midnight() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 28, 23, 59, 30).unwrap();
+        assert_eq!(until_midnight(&now), secs(30));
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+        assert_eq!(until_midnight(&now), secs(86_400));
+
+        // La medianoche es la de la zona horaria de `now`
+        let cet = FixedOffset::east_opt(3600).unwrap();
+        let now = Utc
+            .with_ymd_and_hms(2026, 2, 28, 22, 0, 0)
+            .unwrap()
+            .with_timezone(&cet);
+        assert_eq!(until_midnight(&now), secs(3600));
+    }
+
+    #[test]
+    fn test_background_refresh_is_polled() {
+        let mut fake = FakeLoop {
+            busy_until: secs(2),
+            ..FakeLoop::idle()
+        };
+        let wakeups = fake.run_until(secs(2));
+        assert_eq!(wakeups.len(), 8);
+    }
+}
@@ -0,0 +1,346 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use crossterm::event::Event;
+use ratatui::Frame;
+
+use crate::api;
+use crate::i18n::{t, tf};
+use crate::models::{Theme, UsageStats};
+use crate::redact::redact;
+use crate::themes::ThemeColors;
+
+use super::async_handler::{AsyncHandler, AsyncResult, SavedSettings};
+use super::clipboard::{Clipboard, Copied};
+use super::components::trend::TrendData;
+use super::events::EventHandler;
+use super::reducer::{AppEvent, Effect, reduce};
+use super::schedule;
+use super::state::{AppState, AppStateManager, DayLoad, MonthLoad, SettingsForm};
+use super::{format_error_debug, format_error_for_user, load_trend, render_ui};
+
+/// Current time; tests pass a fixed one
+pub type Clock = fn() -> DateTime<Utc>;
+
+/// Everything the TUI loop works on, minus the terminal: the run loop polls
+/// crossterm and feeds it, a test can feed it a script
+pub struct Session {
+    pub app: AppStateManager,
+    stats: UsageStats,
+    daily_trend: TrendData,
+    /// Day `daily_trend` was loaded on; the next one reloads it
+    trend_day: NaiveDate,
+    theme: Theme,
+    colors: ThemeColors,
+    clock: Clock,
+    clipboard: Clipboard,
+}
+
+impl Session {
+    pub fn new(app: AppStateManager, stats: UsageStats, theme: Theme, clock: Clock) -> Self {
+        let now = clock();
+        Self {
+            daily_trend: load_trend(&app.active_profile, stats.billing_cycle_day, now),
+            app,
+            stats,
+            trend_day: now.date_naive(),
+            theme,
+            colors: ThemeColors::from_theme(theme),
+            clock,
+            clipboard: Clipboard::default(),
+        }
+    }
+
+    pub fn stats(&self) -> &UsageStats {
+        &self.stats
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    pub fn colors(&self) -> &ThemeColors {
+        &self.colors
+    }
+
+    /// Loading states with a spinner
+    pub fn is_animating(&self) -> bool {
+        matches!(
+            self.app.state,
+            AppState::LoadingRefresh | AppState::LoadingCache
+        ) || (self.app.state == AppState::DailyView && self.app.daily_loading())
+            || (self.app.state == AppState::CompareView && self.app.compare_loading())
+    }
+
+    /// Reloads what was computed for a date once that date is over, so a
+    /// dashboard left open overnight moves on without a refresh. Returns
+    /// true when something changed.
+    pub fn roll_over(&mut self) -> bool {
+        if (self.clock)().date_naive() == self.trend_day {
+            return false;
+        }
+        self.reload_trend();
+        true
+    }
+
+    /// Time until the date on screen changes: local midnight for the
+    /// header, UTC midnight for the trend
+    pub fn until_rollover(&self) -> Duration {
+        let now = (self.clock)();
+        schedule::until_midnight(&now).min(schedule::until_midnight(&now.with_timezone(&Local)))
+    }
+
+    fn reload_trend(&mut self) {
+        let now = (self.clock)();
+        self.daily_trend = load_trend(&self.app.active_profile, self.stats.billing_cycle_day, now);
+        self.trend_day = now.date_naive();
+    }
+
+    /// Stale cache: shows it now and revalidates in the background
+    pub fn revalidate(&mut self, async_handler: &AsyncHandler) {
+        if self.app.request_background_refresh() {
+            self.app.refresh_task = Some(async_handler.spawn_refresh());
+        }
+    }
+
+    /// Applies a key, mouse or paste event and runs its effects. Returns
+    /// true when the TUI should exit.
+    pub fn handle_event(&mut self, event: Event, async_handler: &AsyncHandler) -> bool {
+        EventHandler::app_events(&self.app, event)
+            .into_iter()
+            .any(|app_event| self.apply(app_event, async_handler))
+    }
+
+    fn apply(&mut self, app_event: AppEvent, async_handler: &AsyncHandler) -> bool {
+        let rows = self.stats.grouped(self.app.table_grouping);
+        let total_models = self.app.filtered_models(&rows.models).len();
+        let today = (self.clock)().date_naive();
+
+        for effect in reduce(&mut self.app, app_event, total_models, today) {
+            match effect {
+                Effect::SpawnRefresh => {
+                    self.app.refresh_task = Some(async_handler.spawn_refresh());
+                }
+                Effect::SpawnCacheInfo => async_handler.spawn_cache_info(),
+                Effect::AbortRefresh(task) => task.abort(),
+                // Profile chosen in the selector: fetch its data in the background
+                Effect::SwitchProfile(profile) => {
+                    self.app.refresh_in_flight = true;
+                    self.app.background_refresh = false;
+                    // El refresh del perfil anterior ya no sirve
+                    if let Some(task) = self
+                        .app
+                        .refresh_task
+                        .replace(async_handler.spawn_switch_profile(profile))
+                    {
+                        task.abort();
+                    }
+                    // Cada perfil tiene su token y su fecha
+                    self.app.token_expires = async_handler
+                        .context()
+                        .config()
+                        .ok()
+                        .and_then(|config| config.token_expires);
+                }
+                Effect::FetchDays(dates) => async_handler.spawn_daily_usage(dates),
+                Effect::FetchPreviousMonth(period) => async_handler.spawn_previous_month(period),
+                // Instant, in-place theme change
+                Effect::SaveTheme(theme) => {
+                    self.theme = theme;
+                    self.colors = ThemeColors::from_theme(theme);
+                    async_handler.spawn_save_theme(theme.as_str().to_string());
+                }
+                Effect::OpenSettings => {
+                    let config = async_handler.context().config().cloned();
+                    self.app
+                        .open_settings(SettingsForm::new(&config.unwrap_or_default()));
+                }
+                Effect::SaveSettings(update) => async_handler.spawn_save_settings(update),
+                Effect::Copy(text) => match self.clipboard.copy(&text) {
+                    Ok(Copied::Clipboard) => self.app.toast_info(t("toast.copied")),
+                    Ok(Copied::Terminal) => self.app.toast_info(t("toast.copied_terminal")),
+                    Err(e) => self.app.toast_warn(tf("toast.copy_failed", &[&e])),
+                },
+                Effect::Quit => return true,
+            }
+        }
+        false
+    }
+
+    /// Applies the result of a background task. Returns false when it was
+    /// dropped (another profile, a cancelled refresh) and nothing changed.
+    pub fn handle_result(
+        &mut self,
+        result: AsyncResult,
+        async_handler: &AsyncHandler,
+    ) -> io::Result<bool> {
+        let app = &mut self.app;
+
+        // Datos de un perfil que ya no está activo: se descartan
+        if let AsyncResult::RefreshComplete { profile, .. }
+        | AsyncResult::DayLoaded { profile, .. }
+        | AsyncResult::PreviousMonthLoaded { profile, .. } = &result
+            && *profile != app.active_profile
+        {
+            return Ok(false);
+        }
+        // Un refresh cancelado o sustituido por otro más nuevo
+        if let AsyncResult::RefreshComplete { generation, .. } = &result
+            && !app.is_current_refresh(*generation)
+        {
+            return Ok(false);
+        }
+
+        // Un refresh pedido mientras había otro en curso se lanza ahora
+        let is_refresh = matches!(result, AsyncResult::RefreshComplete { .. });
+        let run_queued = is_refresh && app.finish_refresh();
+        let background = is_refresh && std::mem::take(&mut app.background_refresh);
+
+        match result {
+            AsyncResult::RefreshComplete {
+                result: Ok(new_stats),
+                ..
+            } => {
+                // Otra cuenta (cambio de perfil) no es un cruce
+                if new_stats.username == self.stats.username
+                    && app.should_ring_bell(self.stats.percentage, new_stats.percentage)
+                {
+                    ring_bell()?;
+                }
+                self.stats = new_stats;
+                if let Some(warning) = self.stats.consistency_warning() {
+                    app.set_notice(tf("notice.inconsistent", &[&warning]));
+                }
+                // La tabla pudo quedarse con menos filas
+                let rows = self.stats.grouped(app.table_grouping);
+                let visible = app.visible_model_rows();
+                app.clamp_model_view(app.filtered_models(&rows.models).len(), visible);
+                let now = (self.clock)();
+                self.daily_trend =
+                    load_trend(&app.active_profile, self.stats.billing_cycle_day, now);
+                self.trend_day = now.date_naive();
+                app.record_refresh(now);
+                if background {
+                    app.toast_info(t("toast.refreshed"));
+                } else {
+                    app.state = AppState::Dashboard;
+                }
+            }
+            AsyncResult::RefreshComplete { result: Err(_), .. } if run_queued => {}
+            AsyncResult::RefreshComplete { result: Err(e), .. } if background => {
+                app.background_refresh_failed(&format_error_for_user(&e));
+            }
+            AsyncResult::RefreshComplete { result: Err(e), .. } => {
+                app.record_refresh_failure();
+                let error_msg = format_error_for_user(&e);
+                let endpoint = api::failed_endpoint(&e).map(|url| redact(&url));
+                let mut debug_msg = format_error_debug(&e);
+                if let Some(warning) = self.stats.consistency_warning() {
+                    debug_msg.push_str(&format!("\n\nWarning: {}", warning));
+                }
+                app.state = AppState::ShowError {
+                    message: error_msg,
+                    debug_message: debug_msg,
+                    show_debug: false,
+                    endpoint,
+                };
+            }
+            AsyncResult::DayLoaded { date, result, .. } => {
+                let requests = match result {
+                    Ok(requests) => DayLoad::Loaded(requests),
+                    Err(e) => {
+                        let error = format_error_for_user(&e);
+                        DayLoad::Failed(error.lines().next().unwrap_or_default().to_string())
+                    }
+                };
+                app.set_day(date, requests);
+            }
+            AsyncResult::PreviousMonthLoaded { period, result, .. } => {
+                let usage = match result {
+                    Ok(stats) => MonthLoad::Loaded(Box::new(stats)),
+                    Err(e) => {
+                        let error = format_error_for_user(&e);
+                        MonthLoad::Failed(error.lines().next().unwrap_or_default().to_string())
+                    }
+                };
+                app.set_previous_month(period, usage);
+            }
+            AsyncResult::CacheInfoReady(info) => {
+                if info.quarantined.is_some() {
+                    match &info.refetch_error {
+                        Some(error) => app.toast_error(tf("toast.cache_refetch_failed", &[error])),
+                        None => app.toast_warn(t("toast.cache_refetched")),
+                    }
+                }
+                app.state = AppState::ShowCacheInfo(info);
+            }
+            AsyncResult::ThemeSaved(Ok(())) => {
+                app.toast_info(t("toast.theme_saved"));
+            }
+            AsyncResult::ThemeSaved(Err(e)) => {
+                let error = format_error_for_user(&e);
+                let reason = error.lines().next().unwrap_or_default();
+                app.toast_error(tf("toast.theme_save_failed", &[&reason]));
+            }
+            AsyncResult::SettingsSaved(Ok(SavedSettings {
+                update,
+                token_overridden_by,
+            })) => {
+                async_handler.reload_context();
+                // "auto" se resuelve al arrancar: en raw mode no se puede
+                // preguntar el fondo a la terminal
+                if let Some(theme) = Theme::parse(&update.theme) {
+                    self.theme = theme;
+                    self.colors = ThemeColors::from_theme(theme);
+                }
+                app.settings.saving = false;
+                if app.state == AppState::Settings {
+                    app.state = AppState::Dashboard;
+                }
+                match token_overridden_by {
+                    Some(var) => app.toast_warn(tf("settings.token_overridden", &[&var])),
+                    None => app.toast_info(t("settings.saved")),
+                }
+                if update.token.is_some() {
+                    app.token_expires = None;
+                }
+                // Otro token puede ser otra cuenta
+                if update.token.is_some() && app.request_background_refresh() {
+                    app.refresh_task = Some(async_handler.spawn_refresh());
+                }
+            }
+            AsyncResult::SettingsSaved(Err(e)) => {
+                app.settings.saving = false;
+                app.settings.error = Some(format_error_for_user(&e));
+            }
+        }
+
+        if run_queued {
+            app.state = AppState::LoadingRefresh;
+            app.refresh_task = Some(async_handler.spawn_refresh());
+        }
+        Ok(true)
+    }
+
+    /// Draws the frame and keeps where the popup went, for mouse hit-testing
+    pub fn render(&mut self, f: &mut Frame) {
+        self.app.click_areas = render_ui(
+            f,
+            &self.stats,
+            &self.daily_trend,
+            &self.colors,
+            &self.app,
+            self.theme,
+            (self.clock)(),
+        );
+    }
+}
+
+/// BEL on the terminal the TUI draws to; terminals beep, flash or mark the
+/// window as urgent
+fn ring_bell() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(b"\x07")?;
+    stdout.flush()
+}
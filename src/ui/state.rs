@@ -1,4 +1,19 @@
-use crate::models::Theme;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, Utc};
+use ratatui::layout::{Position, Rect};
+
+use crate::history::periods;
+use crate::i18n::{t, tf};
+use crate::models::{
+    Config, ModelUsage, QuantityMode, RateLimitInfo, TableGrouping, Theme, UsageStats,
+};
+use crate::percent::{Zone, zone_for};
+use crate::themes;
+use crate::ui::async_handler::RefreshHandle;
+use crate::ui::components::text_input::{Edit, TextInput};
+use crate::ui::styles::format_elapsed;
 
 /// Estados de la aplicación
 #[derive(Debug, Clone, PartialEq)]
@@ -6,9 +21,21 @@ pub enum AppState {
     Dashboard,
     CommandMenu,
     ThemeSelector,
+    ProfileSelector,
+    /// Detail popup for the model at `selected_model`
+    ModelDetail,
+    /// Typing into `model_filter`
+    FilterModels,
+    /// Requests per day of the last `DAILY_DAYS` days
+    DailyView,
+    /// This month against `previous_month`
+    CompareView,
     ConfirmRefresh,
     ConfirmReconfigure,
+    /// Editing `settings`
+    Settings,
     ShowHelp,
+    ShowAbout,
     LoadingRefresh,
     LoadingCache,
     ShowCacheInfo(CacheInfo),
@@ -16,6 +43,8 @@ pub enum AppState {
         message: String,
         debug_message: String,
         show_debug: bool,
+        /// URL of the request that failed, for the copied report
+        endpoint: Option<String>,
     },
 }
 
@@ -25,6 +54,187 @@ pub struct CacheInfo {
     pub last_updated: Option<String>,
     pub is_fresh: bool,
     pub ttl_minutes: u64,
+    /// Where a corrupted cache file was moved, if one was found
+    pub quarantined: Option<String>,
+    /// Error of the refetch attempted after quarantining
+    pub refetch_error: Option<String>,
+    /// GitHub rate-limit state stored with the cached response
+    pub rate_limit: Option<RateLimitInfo>,
+}
+
+/// Where the data on screen came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataSource {
+    Api,
+    /// Within the cache TTL
+    Cache,
+    /// Expired cache, shown while it's revalidated in the background
+    StaleCache,
+}
+
+/// Origin and age of the data on screen, for the help bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataStatus {
+    pub source: DataSource,
+    /// When it was fetched from the API
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl DataStatus {
+    /// "Data: 3m ago (cache)" or "Data: just now (API)"
+    pub fn label(&self, now: DateTime<Utc>) -> String {
+        let age = (now - self.fetched_at).to_std().unwrap_or_default();
+        let age = if age < Duration::from_secs(60) {
+            t("status.just_now").to_string()
+        } else {
+            tf("status.ago", &[&format_elapsed(age)])
+        };
+        let source = match self.source {
+            DataSource::Api => t("status.api"),
+            DataSource::Cache | DataSource::StaleCache => t("status.cache"),
+        };
+        tf("status.data", &[&age, &source])
+    }
+}
+
+/// How a toast is colored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Short message in the top-right corner that goes away by itself and
+/// doesn't take the keyboard, unlike a dialog
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub message: String,
+    pub severity: ToastSeverity,
+    pub created_at: Instant,
+}
+
+impl Toast {
+    /// Time left on screen, None once it's gone
+    fn remaining(&self) -> Option<Duration> {
+        TOAST_DURATION
+            .checked_sub(self.created_at.elapsed())
+            .filter(|left| !left.is_zero())
+    }
+
+    /// In its last `TOAST_FADE`
+    pub fn is_fading(&self) -> bool {
+        self.remaining().is_some_and(|left| left <= TOAST_FADE)
+    }
+}
+
+/// Regiones clickables del último frame, para el ratón
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClickAreas {
+    /// Open popup; a click outside it dismisses it
+    pub popup: Option<Rect>,
+    /// Rows of the popup's list
+    pub list: Option<ListArea>,
+    /// Rows of the dashboard's model table, under any popup
+    pub table: Option<ListArea>,
+}
+
+/// Visible rows of a scrolled list
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ListArea {
+    pub area: Rect,
+    /// Index of the item on the first row
+    pub first: usize,
+    /// Items shown
+    pub len: usize,
+    /// Items in the whole list
+    pub total: usize,
+}
+
+impl ClickAreas {
+    pub fn popup(area: Rect) -> Self {
+        Self {
+            popup: Some(area),
+            ..Self::default()
+        }
+    }
+
+    /// Item index under the cursor, if it's on a list row
+    pub fn list_item_at(&self, column: u16, row: u16) -> Option<usize> {
+        let list = self.list?;
+        if !list.area.contains(Position::new(column, row)) {
+            return None;
+        }
+        let offset = (row - list.area.y) as usize;
+        (offset < list.len).then_some(list.first + offset)
+    }
+
+    pub fn is_outside_popup(&self, column: u16, row: u16) -> bool {
+        self.popup
+            .is_some_and(|area| !area.contains(Position::new(column, row)))
+    }
+}
+
+/// Ids de todos los comandos del menú, en orden
+pub const COMMAND_IDS: [&str; 10] = [
+    "refresh",
+    "daily",
+    "compare",
+    "profile",
+    "cache",
+    "theme",
+    "reconfigure",
+    "help",
+    "about",
+    "quit",
+];
+
+/// Commands that need live data, locked while showing an export
+const LIVE_COMMANDS: [&str; 5] = ["refresh", "daily", "compare", "reconfigure", "profile"];
+
+/// Days listed in the daily view, today included
+pub const DAILY_DAYS: u64 = 30;
+
+/// Rows of the daily view visible at once
+pub const DAILY_VISIBLE_ROWS: usize = 10;
+
+/// Rows of the model table assumed before the first frame is drawn
+pub const MODEL_VISIBLE_ROWS: usize = 8;
+
+/// How long a status notice stays in the help bar
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
+/// How long the "updated just now" indicator stays in the header
+const UPDATED_INDICATOR_DURATION: Duration = Duration::from_secs(5);
+
+/// How long the help bar marks a failed refresh
+const REFRESH_FAILED_DURATION: Duration = Duration::from_secs(60);
+
+/// How long a toast stays on screen
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// The last part of `TOAST_DURATION`, drawn dimmed
+pub const TOAST_FADE: Duration = Duration::from_secs(1);
+
+/// Toasts stacked at once; a new one pushes out the oldest
+pub const MAX_TOASTS: usize = 3;
+
+/// Secciones del menú de comandos, en orden de aparición
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandSection {
+    Data,
+    Appearance,
+    System,
+}
+
+impl CommandSection {
+    pub fn label(self) -> &'static str {
+        match self {
+            CommandSection::Data => t("menu.section.data"),
+            CommandSection::Appearance => t("menu.section.appearance"),
+            CommandSection::System => t("menu.section.system"),
+        }
+    }
 }
 
 /// Comandos disponibles en el menú
@@ -33,22 +243,289 @@ pub struct Command {
     pub id: &'static str,
     pub label: &'static str,
     pub shortcut: Option<char>,
+    /// Commands of a section are kept together, under its header
+    pub section: CommandSection,
+}
+
+/// Requests of one day in the daily view, fetched lazily
+#[derive(Debug, Clone, PartialEq)]
+pub enum DayLoad {
+    Loading,
+    Loaded(f64),
+    /// First line of the error
+    Failed(String),
+}
+
+/// Una fila de la vista diaria
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayRow {
+    pub date: NaiveDate,
+    pub requests: DayLoad,
+}
+
+/// Usage of the month before the current one, fetched the first time the
+/// compare view opens
+#[derive(Debug, Clone)]
+pub enum MonthLoad {
+    Loading,
+    Loaded(Box<UsageStats>),
+    /// First line of the error
+    Failed(String),
+}
+
+/// What the compare view holds for one month
+#[derive(Debug, Clone)]
+pub struct PreviousMonth {
+    /// Year and month
+    pub period: (i32, u32),
+    pub usage: MonthLoad,
+}
+
+/// Campos del formulario de ajustes, en orden
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsField {
+    Token,
+    Theme,
+    CacheTtl,
+    WaybarFormat,
+}
+
+impl SettingsField {
+    pub const ALL: [SettingsField; 4] = [
+        SettingsField::Token,
+        SettingsField::Theme,
+        SettingsField::CacheTtl,
+        SettingsField::WaybarFormat,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsField::Token => t("settings.token"),
+            SettingsField::Theme => t("settings.theme"),
+            SettingsField::CacheTtl => t("settings.cache_ttl"),
+            SettingsField::WaybarFormat => t("settings.waybar_format"),
+        }
+    }
+}
+
+/// The settings form, values as typed; they're checked on save
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingsForm {
+    /// New token, masked; left empty, the current one is kept
+    pub token: TextInput,
+    pub theme: TextInput,
+    pub cache_ttl: TextInput,
+    pub waybar_format: TextInput,
+    /// Index into `SettingsField::ALL`
+    pub focused: usize,
+    /// Why the last save was rejected
+    pub error: Option<String>,
+    /// The config is being written
+    pub saving: bool,
+}
+
+/// Checked settings, ready to be written to the config
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingsUpdate {
+    /// `None` keeps the current token
+    pub token: Option<String>,
+    pub theme: String,
+    pub cache_ttl_minutes: u64,
+    pub waybar_format: String,
+}
+
+impl SettingsUpdate {
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(token) = &self.token {
+            config.token = token.clone();
+            // La fecha era la del token anterior
+            config.token_expires = None;
+        }
+        config.theme = self.theme.clone();
+        config.cache_ttl_minutes = self.cache_ttl_minutes;
+        config.waybar_format = self.waybar_format.clone();
+    }
+}
+
+impl Default for SettingsForm {
+    fn default() -> Self {
+        Self {
+            token: TextInput::default().with_mask(),
+            theme: TextInput::default(),
+            cache_ttl: TextInput::default(),
+            waybar_format: TextInput::default(),
+            focused: 0,
+            error: None,
+            saving: false,
+        }
+    }
+}
+
+impl SettingsForm {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            theme: TextInput::new(config.theme.as_str()),
+            cache_ttl: TextInput::new(config.cache_ttl_minutes.to_string()),
+            waybar_format: TextInput::new(config.waybar_format.as_str()),
+            ..Self::default()
+        }
+    }
+
+    pub fn focused_field(&self) -> SettingsField {
+        SettingsField::ALL[self.focused]
+    }
+
+    pub fn input(&self, field: SettingsField) -> &TextInput {
+        match field {
+            SettingsField::Token => &self.token,
+            SettingsField::Theme => &self.theme,
+            SettingsField::CacheTtl => &self.cache_ttl,
+            SettingsField::WaybarFormat => &self.waybar_format,
+        }
+    }
+
+    fn focused_input_mut(&mut self) -> &mut TextInput {
+        match self.focused_field() {
+            SettingsField::Token => &mut self.token,
+            SettingsField::Theme => &mut self.theme,
+            SettingsField::CacheTtl => &mut self.cache_ttl,
+            SettingsField::WaybarFormat => &mut self.waybar_format,
+        }
+    }
+
+    /// Edits the focused field
+    pub fn edit(&mut self, edit: Edit) {
+        self.focused_input_mut().apply(edit);
+        self.error = None;
+    }
+
+    /// Moves to the next or previous field, wrapping around
+    pub fn move_focus(&mut self, down: bool) {
+        let len = SettingsField::ALL.len();
+        self.focused = if down {
+            (self.focused + 1) % len
+        } else {
+            (self.focused + len - 1) % len
+        };
+    }
+
+    /// Checks every field; the error names the first one that's wrong
+    pub fn validate(&self) -> Result<SettingsUpdate, String> {
+        let theme = self.theme.value().trim();
+        let theme = if theme.eq_ignore_ascii_case(themes::AUTO) {
+            themes::AUTO
+        } else {
+            Theme::parse(theme)
+                .ok_or_else(|| tf("settings.invalid_theme", &[&theme]))?
+                .as_str()
+        };
+
+        let cache_ttl = self.cache_ttl.value().trim();
+        let cache_ttl_minutes = cache_ttl
+            .parse()
+            .map_err(|_| tf("settings.invalid_ttl", &[&cache_ttl]))?;
+
+        let waybar_format = self.waybar_format.value().trim();
+        if waybar_format.is_empty() {
+            return Err(t("settings.empty_format").to_string());
+        }
+
+        let token = self.token.value().trim();
+        Ok(SettingsUpdate {
+            token: (!token.is_empty()).then(|| token.to_string()),
+            theme: theme.to_string(),
+            cache_ttl_minutes,
+            waybar_format: waybar_format.to_string(),
+        })
+    }
+}
+
+/// Una fila del menú de comandos: cabecera de sección o comando
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuRow {
+    Header(CommandSection),
+    /// Index into `commands`
+    Command(usize),
 }
 
 /// Estado completo de la aplicación UI
 pub struct AppStateManager {
     pub state: AppState,
     pub selected_command: usize,
+    /// First visible menu row, counting section headers
     pub command_scroll_offset: usize,
     pub selected_theme: usize,
     pub theme_scroll_offset: usize,
     pub model_scroll_offset: usize,
+    /// Highlighted row of the model table (index into the filtered models)
+    pub selected_model: Option<usize>,
+    /// Case-insensitive substring the model table is narrowed to
+    pub model_filter: String,
+    /// Quantity the Overall Usage block and the model table count
+    pub quantity_mode: QuantityMode,
+    /// Rows of the usage table: models or SKUs
+    pub table_grouping: TableGrouping,
+    pub selected_profile: usize,
+    pub profile_scroll_offset: usize,
+    /// Days of the daily view, newest first; empty until it's first opened
+    pub daily_rows: Vec<DayRow>,
+    pub daily_scroll_offset: usize,
+    /// Last month for the compare view; `None` until it's first opened
+    pub previous_month: Option<PreviousMonth>,
+    /// First line of the help dialog on screen
+    pub help_scroll_offset: usize,
     pub commands: Vec<Command>,
     pub themes: Vec<&'static str>,
+    /// Configured profiles, for the profile selector
+    pub profiles: Vec<String>,
+    /// Profile whose data is on screen
+    pub active_profile: String,
     pub action_taken: Option<String>,
     pub spinner_state: usize,
-    /// Theme change pending to be applied (for instant in-place theme switching)
-    pub pending_theme_change: Option<Theme>,
+    /// When this TUI session started
+    pub session_started: Instant,
+    /// Successful refreshes during this session
+    pub refresh_count: u32,
+    /// Local time of the last successful refresh
+    pub last_refresh_at: Option<DateTime<Local>>,
+    /// Origin and age of the data on screen; `None` for an export
+    pub data_status: Option<DataStatus>,
+    /// When the last refresh failed, until one succeeds
+    refresh_failed_at: Option<Instant>,
+    /// Show the session summary as a suffix in the help bar
+    pub show_session_in_help_bar: bool,
+    /// Ring the bell when a refresh crosses into the critical zone
+    pub alert_bell: bool,
+    /// Zones the bell already rang for; once per zone and session
+    bell_rung: Vec<Zone>,
+    /// Command ids disabled through `[menu] disabled` in the config
+    pub disabled_commands: Vec<String>,
+    /// `token_expires` of the profile, for the header's warning
+    pub token_expires: Option<NaiveDate>,
+    /// Short message shown in place of the help bar, with the time it was set
+    pub notice: Option<(String, Instant)>,
+    /// Toasts, oldest first; expired ones are dropped on the next push
+    pub toasts: Vec<Toast>,
+    /// A refresh task is running
+    pub refresh_in_flight: bool,
+    /// The running refresh task; its results are the only ones applied
+    pub refresh_task: Option<RefreshHandle>,
+    /// Another refresh was requested while one was running
+    pub refresh_pending: bool,
+    /// The running refresh revalidates stale data in the background:
+    /// it doesn't change the current view and its errors become a notice
+    pub background_refresh: bool,
+    /// Where the last frame drew the popup and its list
+    pub click_areas: ClickAreas,
+    /// Name of the export shown in read-only mode
+    pub read_only: Option<String>,
+    /// Recent JSON export the error dialog offers to open
+    pub fallback_export: Option<PathBuf>,
+    /// Settings form being edited
+    pub settings: SettingsForm,
+    /// Reconfigure opens the settings form; without a terminal on stdin
+    /// the TUI exits to the prompt-based setup instead
+    pub inline_settings: bool,
 }
 
 impl AppStateManager {
@@ -60,52 +537,104 @@ impl AppStateManager {
             selected_theme: 0,
             theme_scroll_offset: 0,
             model_scroll_offset: 0,
+            selected_model: None,
+            model_filter: String::new(),
+            quantity_mode: QuantityMode::default(),
+            table_grouping: TableGrouping::default(),
+            selected_profile: 0,
+            profile_scroll_offset: 0,
+            daily_rows: Vec::new(),
+            daily_scroll_offset: 0,
+            previous_month: None,
+            help_scroll_offset: 0,
             commands: vec![
                 Command {
                     id: "refresh",
-                    label: "Refresh Data",
+                    label: t("menu.refresh"),
                     shortcut: Some('r'),
+                    section: CommandSection::Data,
                 },
                 Command {
-                    id: "theme",
-                    label: "Change Theme",
-                    shortcut: Some('t'),
+                    id: "daily",
+                    label: t("menu.daily"),
+                    shortcut: Some('d'),
+                    section: CommandSection::Data,
                 },
                 Command {
-                    id: "reconfigure",
-                    label: "Reconfigure",
-                    shortcut: Some('c'),
+                    id: "compare",
+                    label: t("menu.compare"),
+                    shortcut: Some('m'),
+                    section: CommandSection::Data,
+                },
+                Command {
+                    id: "profile",
+                    label: t("menu.profile"),
+                    shortcut: Some('p'),
+                    section: CommandSection::Data,
                 },
                 Command {
                     id: "cache",
-                    label: "Cache Status",
+                    label: t("menu.cache"),
                     shortcut: Some('s'),
+                    section: CommandSection::Data,
+                },
+                Command {
+                    id: "theme",
+                    label: t("menu.theme"),
+                    shortcut: Some('t'),
+                    section: CommandSection::Appearance,
+                },
+                Command {
+                    id: "reconfigure",
+                    label: t("menu.reconfigure"),
+                    shortcut: Some('c'),
+                    section: CommandSection::System,
                 },
                 Command {
                     id: "help",
-                    label: "Help",
+                    label: t("menu.help"),
                     shortcut: Some('h'),
+                    section: CommandSection::System,
+                },
+                Command {
+                    id: "about",
+                    label: t("menu.about"),
+                    shortcut: Some('a'),
+                    section: CommandSection::System,
                 },
                 Command {
                     id: "quit",
-                    label: "Quit",
+                    label: t("menu.quit"),
                     shortcut: Some('q'),
+                    section: CommandSection::System,
                 },
             ],
-            themes: vec![
-                "dark",
-                "nord",
-                "monokai",
-                "gruvbox",
-                "catppuccin",
-                "onedark",
-                "tokyonight",
-                "solarized",
-                "kanagawa",
-            ],
+            themes: Theme::ALL.iter().map(|theme| theme.as_str()).collect(),
+            profiles: Vec::new(),
+            active_profile: String::new(),
             action_taken: None,
             spinner_state: 0,
-            pending_theme_change: None,
+            session_started: Instant::now(),
+            refresh_count: 0,
+            last_refresh_at: None,
+            data_status: None,
+            refresh_failed_at: None,
+            show_session_in_help_bar: false,
+            alert_bell: false,
+            bell_rung: Vec::new(),
+            disabled_commands: Vec::new(),
+            token_expires: None,
+            notice: None,
+            toasts: Vec::new(),
+            refresh_in_flight: false,
+            refresh_task: None,
+            refresh_pending: false,
+            background_refresh: false,
+            click_areas: ClickAreas::default(),
+            read_only: None,
+            fallback_export: None,
+            settings: SettingsForm::default(),
+            inline_settings: false,
         }
     }
 
@@ -124,18 +653,53 @@ impl AppStateManager {
         self.adjust_command_scroll(5);
     }
 
+    // El offset cuenta filas, cabeceras incluidas
     fn adjust_command_scroll(&mut self, visible_count: usize) {
-        if self.commands.len() > visible_count {
-            if self.selected_command >= self.command_scroll_offset + visible_count {
-                self.command_scroll_offset = self.selected_command - visible_count + 1;
-            } else if self.selected_command < self.command_scroll_offset {
-                self.command_scroll_offset = self.selected_command;
+        let rows = self.menu_rows();
+        let Some(row) = rows
+            .iter()
+            .position(|r| *r == MenuRow::Command(self.selected_command))
+        else {
+            return;
+        };
+        if rows.len() > visible_count {
+            // Al subir al primer comando de una sección se ve también su cabecera
+            let top = match row.checked_sub(1).map(|i| rows[i]) {
+                Some(MenuRow::Header(_)) => row - 1,
+                _ => row,
+            };
+            if row >= self.command_scroll_offset + visible_count {
+                self.command_scroll_offset = row - visible_count + 1;
+            } else if top < self.command_scroll_offset {
+                self.command_scroll_offset = top;
             }
         } else {
             self.command_scroll_offset = 0;
         }
     }
 
+    /// Menu rows in display order: each section's header, then its commands
+    pub fn menu_rows(&self) -> Vec<MenuRow> {
+        let mut rows = Vec::with_capacity(self.commands.len() + 3);
+        let mut section = None;
+        for (index, cmd) in self.commands.iter().enumerate() {
+            if section != Some(cmd.section) {
+                section = Some(cmd.section);
+                rows.push(MenuRow::Header(cmd.section));
+            }
+            rows.push(MenuRow::Command(index));
+        }
+        rows
+    }
+
+    /// Command on a menu row; headers aren't selectable
+    pub fn command_at_row(&self, row: usize) -> Option<usize> {
+        match self.menu_rows().get(row)? {
+            MenuRow::Command(index) => Some(*index),
+            MenuRow::Header(_) => None,
+        }
+    }
+
     // Navegación en selector de temas
     pub fn next_theme(&mut self) {
         self.selected_theme = (self.selected_theme + 1) % self.themes.len();
@@ -163,24 +727,564 @@ impl AppStateManager {
         }
     }
 
+    // Selector de perfiles
+    /// Loads the profile list and selects the active one
+    pub fn set_profiles(&mut self, profiles: Vec<String>, active: &str) {
+        self.selected_profile = profiles.iter().position(|p| p == active).unwrap_or(0);
+        self.profiles = profiles;
+        self.active_profile = active.to_string();
+        self.profile_scroll_offset = 0;
+        self.adjust_profile_scroll(5);
+    }
+
+    pub fn next_profile(&mut self) {
+        if self.profiles.is_empty() {
+            return;
+        }
+        self.selected_profile = (self.selected_profile + 1) % self.profiles.len();
+        self.adjust_profile_scroll(5);
+    }
+
+    pub fn previous_profile(&mut self) {
+        if self.profiles.is_empty() {
+            return;
+        }
+        if self.selected_profile == 0 {
+            self.selected_profile = self.profiles.len() - 1;
+        } else {
+            self.selected_profile -= 1;
+        }
+        self.adjust_profile_scroll(5);
+    }
+
+    fn adjust_profile_scroll(&mut self, visible_count: usize) {
+        if self.profiles.len() > visible_count {
+            if self.selected_profile >= self.profile_scroll_offset + visible_count {
+                self.profile_scroll_offset = self.selected_profile - visible_count + 1;
+            } else if self.selected_profile < self.profile_scroll_offset {
+                self.profile_scroll_offset = self.selected_profile;
+            }
+        } else {
+            self.profile_scroll_offset = 0;
+        }
+    }
+
+    /// Makes the selected profile the active one. Returns the profile to
+    /// switch to, None when it's already the active one.
+    pub fn choose_selected_profile(&mut self) -> Option<String> {
+        let profile = self.profiles.get(self.selected_profile)?;
+        if *profile == self.active_profile {
+            return None;
+        }
+        self.active_profile = profile.clone();
+        // Los días, el mes anterior y la sesión eran de la otra cuenta
+        self.daily_rows.clear();
+        self.previous_month = None;
+        self.session_started = Instant::now();
+        self.refresh_count = 0;
+        self.last_refresh_at = None;
+        Some(profile.clone())
+    }
+
+    // Ajustes
+    pub fn open_settings(&mut self, form: SettingsForm) {
+        self.settings = form;
+        self.state = AppState::Settings;
+    }
+
+    // Vista diaria
+    /// Opens the daily view ending at `today`. Returns the days to fetch:
+    /// all of them the first time, then those that failed or belong to a
+    /// day that wasn't listed yet.
+    pub fn open_daily_view(&mut self, today: NaiveDate) -> Vec<NaiveDate> {
+        let dates: Vec<NaiveDate> = (0..DAILY_DAYS)
+            .filter_map(|back| today.checked_sub_days(Days::new(back)))
+            .collect();
+
+        let previous = std::mem::take(&mut self.daily_rows);
+        // Hoy (y el "hoy" de la vez anterior) seguía sumando: se vuelve a
+        // pedir, y el caché de días aplica su TTL
+        let previous_today = previous.first().map(|row| row.date);
+        let mut to_fetch = Vec::new();
+        for date in dates {
+            let kept = previous
+                .iter()
+                .find(|row| row.date == date)
+                .map(|row| row.requests.clone());
+            let requests = match kept {
+                Some(DayLoad::Loading) => DayLoad::Loading,
+                Some(DayLoad::Loaded(requests))
+                    if date != today && Some(date) != previous_today =>
+                {
+                    DayLoad::Loaded(requests)
+                }
+                _ => {
+                    to_fetch.push(date);
+                    DayLoad::Loading
+                }
+            };
+            self.daily_rows.push(DayRow { date, requests });
+        }
+
+        self.daily_scroll_offset = 0;
+        self.state = AppState::DailyView;
+        to_fetch
+    }
+
+    /// Stores a fetched day; days no longer listed are ignored
+    pub fn set_day(&mut self, date: NaiveDate, requests: DayLoad) {
+        if let Some(row) = self.daily_rows.iter_mut().find(|row| row.date == date) {
+            row.requests = requests;
+        }
+    }
+
+    /// Some day of the daily view is still being fetched
+    pub fn daily_loading(&self) -> bool {
+        self.daily_rows
+            .iter()
+            .any(|row| row.requests == DayLoad::Loading)
+    }
+
+    pub fn scroll_daily_down(&mut self) {
+        let max = self.daily_rows.len().saturating_sub(DAILY_VISIBLE_ROWS);
+        self.daily_scroll_offset = (self.daily_scroll_offset + 1).min(max);
+    }
+
+    pub fn scroll_daily_up(&mut self) {
+        self.daily_scroll_offset = self.daily_scroll_offset.saturating_sub(1);
+    }
+
+    // Comparación con el mes anterior
+    /// Opens the compare view for the month before `today`'s. Returns that
+    /// month when it has to be fetched: the first time, after a failure or
+    /// once a new month started.
+    pub fn open_compare_view(&mut self, today: NaiveDate) -> Option<(i32, u32)> {
+        self.state = AppState::CompareView;
+        let period = periods::previous_month((today.year(), today.month()));
+        let kept = self.previous_month.as_ref().is_some_and(|previous| {
+            previous.period == period && !matches!(previous.usage, MonthLoad::Failed(_))
+        });
+        if kept {
+            return None;
+        }
+        self.previous_month = Some(PreviousMonth {
+            period,
+            usage: MonthLoad::Loading,
+        });
+        Some(period)
+    }
+
+    /// Stores the fetched month; a month no longer compared is ignored
+    pub fn set_previous_month(&mut self, period: (i32, u32), usage: MonthLoad) {
+        if let Some(previous) = &mut self.previous_month
+            && previous.period == period
+        {
+            previous.usage = usage;
+        }
+    }
+
+    /// The previous month is still being fetched
+    pub fn compare_loading(&self) -> bool {
+        self.previous_month
+            .as_ref()
+            .is_some_and(|previous| matches!(previous.usage, MonthLoad::Loading))
+    }
+
+    pub fn open_help(&mut self) {
+        self.help_scroll_offset = 0;
+        self.state = AppState::ShowHelp;
+    }
+
+    /// Stops at the last line the previous frame could show
+    pub fn scroll_help_down(&mut self) {
+        let max = self
+            .click_areas
+            .list
+            .map_or(0, |list| list.total.saturating_sub(list.len));
+        self.help_scroll_offset = (self.help_scroll_offset + 1).min(max);
+    }
+
+    pub fn scroll_help_up(&mut self) {
+        self.help_scroll_offset = self.help_scroll_offset.saturating_sub(1);
+    }
+
     // Scroll en tabla de modelos
-    pub fn scroll_models_down(&mut self, total_models: usize, visible_count: usize) {
-        if self.model_scroll_offset + visible_count < total_models {
-            self.model_scroll_offset += 1;
+    /// Moves the row highlight down, scrolling the table to keep it visible.
+    /// The first press highlights the top visible row.
+    pub fn select_next_model(&mut self, total_models: usize, visible_count: usize) {
+        if total_models == 0 {
+            return;
+        }
+        let next = match self.selected_model {
+            Some(i) => (i + 1).min(total_models - 1),
+            None => self.model_scroll_offset.min(total_models - 1),
+        };
+        self.selected_model = Some(next);
+        if next >= self.model_scroll_offset + visible_count {
+            self.model_scroll_offset = next + 1 - visible_count;
+        }
+    }
+
+    pub fn select_previous_model(&mut self) {
+        let previous = match self.selected_model {
+            Some(i) => i.saturating_sub(1),
+            None => self.model_scroll_offset,
+        };
+        self.selected_model = Some(previous);
+        if previous < self.model_scroll_offset {
+            self.model_scroll_offset = previous;
+        }
+    }
+
+    /// Model rows the last frame showed: a page of the table
+    pub fn visible_model_rows(&self) -> usize {
+        self.click_areas
+            .table
+            .map_or(MODEL_VISIBLE_ROWS, |table| table.len.max(1))
+    }
+
+    pub fn select_first_model(&mut self, total_models: usize) {
+        if total_models > 0 {
+            self.selected_model = Some(0);
+            self.model_scroll_offset = 0;
+        }
+    }
+
+    pub fn select_last_model(&mut self, total_models: usize, visible_count: usize) {
+        if total_models > 0 {
+            self.selected_model = Some(total_models - 1);
+            self.model_scroll_offset = total_models.saturating_sub(visible_count);
+        }
+    }
+
+    /// Keeps the highlight and the view inside a table that shrank, e.g.
+    /// after a refresh with fewer models
+    pub fn clamp_model_view(&mut self, total_models: usize, visible_count: usize) {
+        if total_models == 0 {
+            self.reset_model_view();
+            return;
         }
+        self.selected_model = self.selected_model.map(|i| i.min(total_models - 1));
+        self.model_scroll_offset = self
+            .model_scroll_offset
+            .min(total_models.saturating_sub(visible_count));
+    }
+
+    /// Models whose name contains the filter, in table order
+    pub fn filtered_models<'a>(&self, models: &'a [ModelUsage]) -> Vec<&'a ModelUsage> {
+        let needle = self.model_filter.to_lowercase();
+        models
+            .iter()
+            .filter(|model| model.name.to_lowercase().contains(&needle))
+            .collect()
+    }
+
+    pub fn push_filter_char(&mut self, c: char) {
+        self.model_filter.push(c);
+        self.reset_model_view();
+    }
+
+    pub fn pop_filter_char(&mut self) {
+        self.model_filter.pop();
+        self.reset_model_view();
+    }
+
+    pub fn clear_model_filter(&mut self) {
+        self.model_filter.clear();
+        self.reset_model_view();
+    }
+
+    /// Switches the table between models and SKUs
+    pub fn toggle_grouping(&mut self) {
+        self.table_grouping = self.table_grouping.toggle();
+        self.reset_model_view();
+    }
+
+    // La lista filtrada cambió: los índices anteriores ya no valen
+    fn reset_model_view(&mut self) {
+        self.model_scroll_offset = 0;
+        self.selected_model = None;
     }
 
-    pub fn scroll_models_up(&mut self) {
-        if self.model_scroll_offset > 0 {
-            self.model_scroll_offset -= 1;
+    /// Opens the detail popup for the highlighted row (or the top visible
+    /// one). Returns false when there are no models.
+    pub fn open_model_detail(&mut self, total_models: usize) -> bool {
+        if total_models == 0 {
+            return false;
         }
+        let index = self
+            .selected_model
+            .unwrap_or(self.model_scroll_offset)
+            .min(total_models - 1);
+        self.selected_model = Some(index);
+        self.state = AppState::ModelDetail;
+        true
     }
 
     // Utilidades
-    pub fn get_selected_command_id(&self) -> &str {
+    pub fn get_selected_command_id(&self) -> &'static str {
         self.commands[self.selected_command].id
     }
 
+    pub fn is_command_disabled(&self, id: &str) -> bool {
+        self.disabled_commands.iter().any(|d| d == id)
+    }
+
+    /// Shows an export instead of live data, with the live commands locked
+    pub fn set_read_only(&mut self, name: String) {
+        for id in LIVE_COMMANDS {
+            if !self.is_command_disabled(id) {
+                self.disabled_commands.push(id.to_string());
+            }
+        }
+        self.read_only = Some(name);
+        self.fallback_export = None;
+        self.alert_bell = false;
+    }
+
+    /// Explains in the help bar why a disabled command didn't run
+    pub fn notify_disabled(&mut self, id: &str) {
+        let label = self
+            .commands
+            .iter()
+            .find(|cmd| cmd.id == id)
+            .map_or(id, |cmd| cmd.label);
+        let key = if self.read_only.is_some() {
+            "notice.read_only"
+        } else {
+            "notice.disabled"
+        };
+        self.set_notice(tf(key, &[&label]));
+    }
+
+    // Avisos
+    pub fn set_notice(&mut self, message: String) {
+        self.notice = Some((message, Instant::now()));
+    }
+
+    pub fn active_notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, set_at)| set_at.elapsed() < NOTICE_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// What `y` on the error dialog copies for a bug report: the redacted
+    /// details plus version, OS and the endpoint that failed
+    pub fn error_report(&self) -> Option<String> {
+        let AppState::ShowError {
+            debug_message,
+            endpoint,
+            ..
+        } = &self.state
+        else {
+            return None;
+        };
+        Some(format!(
+            "copilot-usage {}\nOS: {} ({})\nEndpoint: {}\n\n{}",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            endpoint.as_deref().unwrap_or("none"),
+            debug_message
+        ))
+    }
+
+    // Toasts
+    pub fn toast_info(&mut self, message: impl Into<String>) {
+        self.push_toast(message.into(), ToastSeverity::Info);
+    }
+
+    pub fn toast_warn(&mut self, message: impl Into<String>) {
+        self.push_toast(message.into(), ToastSeverity::Warning);
+    }
+
+    pub fn toast_error(&mut self, message: impl Into<String>) {
+        self.push_toast(message.into(), ToastSeverity::Error);
+    }
+
+    fn push_toast(&mut self, message: String, severity: ToastSeverity) {
+        self.toasts.retain(|toast| toast.remaining().is_some());
+        self.toasts.push(Toast {
+            message,
+            severity,
+            created_at: Instant::now(),
+        });
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+    }
+
+    /// Toasts still on screen, newest first
+    pub fn active_toasts(&self) -> impl Iterator<Item = &Toast> {
+        self.toasts
+            .iter()
+            .rev()
+            .filter(|toast| toast.remaining().is_some())
+    }
+
+    // Refresh
+    /// Registers a refresh request. Returns true when it should start now;
+    /// while one is in flight the request is queued instead.
+    pub fn request_refresh(&mut self) -> bool {
+        if self.refresh_in_flight {
+            self.refresh_pending = true;
+            false
+        } else {
+            self.refresh_in_flight = true;
+            true
+        }
+    }
+
+    /// Marks the running refresh as finished, successful or not.
+    /// Returns true when a queued refresh should start now.
+    pub fn finish_refresh(&mut self) -> bool {
+        self.refresh_task = None;
+        if self.refresh_pending {
+            self.refresh_pending = false;
+            true
+        } else {
+            self.refresh_in_flight = false;
+            false
+        }
+    }
+
+    /// True for the result of the refresh that's running; cancelled and
+    /// superseded ones must not overwrite newer data
+    pub fn is_current_refresh(&self, generation: u64) -> bool {
+        self.refresh_task
+            .as_ref()
+            .is_some_and(|task| task.generation == generation)
+    }
+
+    /// Esc on the loading dialog: drops the running refresh and any queued
+    /// one, returning the task to abort. A profile switch isn't cancelled,
+    /// it carries on in the background instead.
+    pub fn cancel_refresh(&mut self) -> Option<RefreshHandle> {
+        if self
+            .refresh_task
+            .as_ref()
+            .is_some_and(|task| task.profile_switch)
+        {
+            self.background_refresh = true;
+            return None;
+        }
+        self.refresh_in_flight = false;
+        self.refresh_pending = false;
+        self.background_refresh = false;
+        self.refresh_task.take()
+    }
+
+    /// Starts revalidating stale data without leaving the current view.
+    /// Returns true when the refresh task should be spawned.
+    pub fn request_background_refresh(&mut self) -> bool {
+        let start = self.request_refresh();
+        if start {
+            self.background_refresh = true;
+        }
+        start
+    }
+
+    /// A failed background refresh keeps the stale data on screen
+    pub fn background_refresh_failed(&mut self, error: &str) {
+        self.record_refresh_failure();
+        let reason = error.lines().next().unwrap_or(error);
+        self.toast_warn(tf("notice.refresh_failed", &[&reason]));
+    }
+
+    /// True for a few seconds after a successful refresh
+    pub fn updated_just_now(&self) -> bool {
+        self.last_refresh_at.is_some_and(|last| {
+            (Local::now() - last)
+                .to_std()
+                .is_ok_and(|age| age < UPDATED_INDICATOR_DURATION)
+        })
+    }
+
+    /// True for a while after a refresh failed, unless another succeeded
+    pub fn refresh_failed_recently(&self) -> bool {
+        self.refresh_failed_at
+            .is_some_and(|failed_at| failed_at.elapsed() < REFRESH_FAILED_DURATION)
+    }
+
+    /// Time until each thing on screen that expires by itself does so
+    /// (the notice, the "updated just now" indicator, the failed refresh
+    /// marker, toasts). A toast fades in one step, so it needs a frame when
+    /// it starts fading and one when it's gone, not an animation.
+    pub fn upcoming_deadlines(&self) -> impl Iterator<Item = Duration> + '_ {
+        let notice = self
+            .notice
+            .as_ref()
+            .and_then(|(_, set_at)| NOTICE_DURATION.checked_sub(set_at.elapsed()));
+        let indicator = self
+            .last_refresh_at
+            .and_then(|last| (Local::now() - last).to_std().ok())
+            .and_then(|age| UPDATED_INDICATOR_DURATION.checked_sub(age));
+        let failed = self
+            .refresh_failed_at
+            .and_then(|failed_at| REFRESH_FAILED_DURATION.checked_sub(failed_at.elapsed()));
+        let toasts = self.active_toasts().flat_map(|toast| {
+            let left = toast.remaining().unwrap_or_default();
+            [
+                left.checked_sub(TOAST_FADE).filter(|d| !d.is_zero()),
+                Some(left),
+            ]
+            .into_iter()
+            .flatten()
+        });
+        notice
+            .into_iter()
+            .chain(indicator)
+            .chain(failed)
+            .chain(toasts)
+    }
+
+    // Sesión
+    /// Fresh data from the API, fetched at `now`
+    pub fn record_refresh(&mut self, now: DateTime<Utc>) {
+        self.refresh_count += 1;
+        self.last_refresh_at = Some(Local::now());
+        self.data_status = Some(DataStatus {
+            source: DataSource::Api,
+            fetched_at: now,
+        });
+        self.refresh_failed_at = None;
+    }
+
+    pub fn record_refresh_failure(&mut self) {
+        self.refresh_failed_at = Some(Instant::now());
+    }
+
+    /// Whether a refresh from `before` to `after` percent rings the bell:
+    /// usage entered the critical zone and it hasn't rung for it yet
+    pub fn should_ring_bell(&mut self, before: f64, after: f64) -> bool {
+        let zone = zone_for(after);
+        if !self.alert_bell
+            || zone != Zone::Critical
+            || zone_for(before) == zone
+            || self.bell_rung.contains(&zone)
+        {
+            return false;
+        }
+        self.bell_rung.push(zone);
+        true
+    }
+
+    /// e.g. "open for 6h 12m, data refreshed 14×, last at 16:02"
+    pub fn session_summary(&self) -> String {
+        let mut summary = tf(
+            "session.summary",
+            &[
+                &format_elapsed(self.session_started.elapsed()),
+                &self.refresh_count,
+            ],
+        );
+        if let Some(last) = self.last_refresh_at {
+            summary.push_str(&tf("session.last_at", &[&last.format("%H:%M")]));
+        }
+        summary
+    }
+
     // Spinner animation
     pub fn advance_spinner(&mut self) {
         self.spinner_state = (self.spinner_state + 1) % 10;
@@ -197,3 +1301,670 @@ impl Default for AppStateManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn test_command_ids_match_menu() {
+        let app = AppStateManager::new();
+        let ids: Vec<&str> = app.commands.iter().map(|cmd| cmd.id).collect();
+        assert_eq!(ids, COMMAND_IDS);
+    }
+
+    #[test]
+    fn test_shortcuts_are_unique_across_sections() {
+        let app = AppStateManager::new();
+        let mut shortcuts: Vec<char> = app.commands.iter().filter_map(|c| c.shortcut).collect();
+        let count = shortcuts.len();
+        shortcuts.sort_unstable();
+        shortcuts.dedup();
+        assert_eq!(shortcuts.len(), count, "duplicated quick-jump letter");
+    }
+
+    #[test]
+    fn test_menu_rows_group_sections() {
+        let app = AppStateManager::new();
+        let rows = app.menu_rows();
+
+        // Una cabecera por sección, y cada sección aparece una sola vez
+        let headers: Vec<CommandSection> = rows
+            .iter()
+            .filter_map(|row| match row {
+                MenuRow::Header(section) => Some(*section),
+                MenuRow::Command(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            headers,
+            [
+                CommandSection::Data,
+                CommandSection::Appearance,
+                CommandSection::System
+            ]
+        );
+        assert_eq!(rows.len(), app.commands.len() + headers.len());
+
+        assert_eq!(rows[0], MenuRow::Header(CommandSection::Data));
+        assert_eq!(app.command_at_row(0), None);
+        assert_eq!(app.command_at_row(1), Some(0));
+        assert_eq!(
+            app.command_at_row(rows.len() - 1),
+            Some(app.commands.len() - 1)
+        );
+        assert_eq!(app.command_at_row(rows.len()), None);
+    }
+
+    #[test]
+    fn test_command_navigation_skips_headers() {
+        let mut app = AppStateManager::new();
+        let rows = app.menu_rows();
+
+        // Recorrer el menú entero pasa por cada comando y nunca por una cabecera
+        for step in 1..=app.commands.len() {
+            app.next_command();
+            assert_eq!(app.selected_command, step % app.commands.len());
+            let row = rows
+                .iter()
+                .position(|r| *r == MenuRow::Command(app.selected_command))
+                .unwrap();
+            assert!(row >= app.command_scroll_offset);
+            assert!(row < app.command_scroll_offset + 5);
+        }
+
+        // Al volver arriba la cabecera de la primera sección queda visible
+        assert_eq!(app.command_scroll_offset, 0);
+
+        app.previous_command();
+        assert_eq!(app.selected_command, app.commands.len() - 1);
+        assert_eq!(app.command_scroll_offset, rows.len() - 5);
+
+        // Subir hasta "theme" muestra también la cabecera de Appearance
+        let theme = app.commands.iter().position(|c| c.id == "theme").unwrap();
+        while app.selected_command != theme {
+            app.previous_command();
+        }
+        assert_eq!(
+            rows[app.command_scroll_offset],
+            MenuRow::Header(CommandSection::Appearance)
+        );
+    }
+
+    #[test]
+    fn test_click_areas_hit_testing() {
+        let areas = ClickAreas {
+            popup: Some(Rect::new(10, 5, 20, 10)),
+            list: Some(ListArea {
+                area: Rect::new(11, 6, 18, 8),
+                first: 3,
+                len: 2,
+                total: 6,
+            }),
+            ..ClickAreas::default()
+        };
+
+        assert_eq!(areas.list_item_at(11, 6), Some(3));
+        assert_eq!(areas.list_item_at(28, 7), Some(4));
+        // Filas vacías bajo el último elemento y el borde del popup
+        assert_eq!(areas.list_item_at(11, 8), None);
+        assert_eq!(areas.list_item_at(10, 6), None);
+
+        assert!(!areas.is_outside_popup(10, 5));
+        assert!(areas.is_outside_popup(30, 5));
+        assert!(areas.is_outside_popup(12, 15));
+        // Sin popup no hay nada que cerrar
+        assert!(!ClickAreas::default().is_outside_popup(0, 0));
+    }
+
+    #[test]
+    fn test_notify_disabled_uses_label() {
+        let mut app = AppStateManager::new();
+        app.disabled_commands = vec!["reconfigure".to_string()];
+
+        assert!(app.is_command_disabled("reconfigure"));
+        assert!(!app.is_command_disabled("refresh"));
+
+        app.notify_disabled("reconfigure");
+        assert_eq!(
+            app.active_notice(),
+            Some("Reconfigure is disabled in config ([menu] disabled)")
+        );
+    }
+
+    #[test]
+    fn test_read_only_locks_live_commands() {
+        let mut app = AppStateManager::new();
+        app.disabled_commands = vec!["profile".to_string()];
+        app.set_read_only("usage.json".to_string());
+
+        for id in LIVE_COMMANDS {
+            assert!(app.is_command_disabled(id));
+        }
+        assert!(!app.is_command_disabled("theme"));
+        assert_eq!(app.disabled_commands.len(), 5);
+
+        app.notify_disabled("refresh");
+        assert_eq!(
+            app.active_notice(),
+            Some("Refresh Data is not available in read-only mode")
+        );
+    }
+
+    #[test]
+    fn test_bell_rings_once_on_critical_crossing() {
+        let mut app = AppStateManager::new();
+        // Desactivado por defecto
+        assert!(!app.should_ring_bell(80.0, 95.0));
+
+        app.alert_bell = true;
+        assert!(!app.should_ring_bell(50.0, 80.0));
+        assert!(!app.should_ring_bell(80.0, 89.4));
+        // 89.5 ya se muestra como 90%: cuenta como cruce
+        assert!(app.should_ring_bell(80.0, 89.5));
+
+        // Una vez por sesión, aunque baje y vuelva a cruzar
+        assert!(!app.should_ring_bell(95.0, 97.0));
+        assert!(!app.should_ring_bell(70.0, 92.0));
+    }
+
+    #[test]
+    fn test_bell_needs_a_crossing() {
+        let mut app = AppStateManager::new();
+        app.alert_bell = true;
+        // Ya estaba en crítico al abrir: no es un cruce
+        assert!(!app.should_ring_bell(91.0, 93.0));
+        assert!(app.should_ring_bell(60.0, 120.0));
+
+        let mut app = AppStateManager::new();
+        app.alert_bell = true;
+        app.set_read_only("usage.json".to_string());
+        assert!(!app.should_ring_bell(60.0, 95.0));
+    }
+
+    /// Stand-in for the async fetcher: counts started refreshes and lets the
+    /// test decide when each one completes and how
+    #[derive(Default)]
+    struct FakeFetcher {
+        started: u32,
+        running: bool,
+    }
+
+    impl FakeFetcher {
+        fn request(&mut self, app: &mut AppStateManager) {
+            if app.request_refresh() {
+                self.start();
+            }
+        }
+
+        fn start(&mut self) {
+            assert!(!self.running, "two refreshes in flight");
+            self.started += 1;
+            self.running = true;
+        }
+
+        fn complete(&mut self, app: &mut AppStateManager, _ok: bool) {
+            self.running = false;
+            if app.finish_refresh() {
+                self.start();
+            }
+        }
+    }
+
+    #[test]
+    fn test_refresh_queued_after_success() {
+        let mut app = AppStateManager::new();
+        let mut fetcher = FakeFetcher::default();
+
+        fetcher.request(&mut app);
+        fetcher.request(&mut app);
+        fetcher.request(&mut app);
+        assert_eq!(fetcher.started, 1);
+        assert!(app.refresh_pending);
+
+        // Several requests while in flight collapse into one follow-up
+        fetcher.complete(&mut app, true);
+        assert_eq!(fetcher.started, 2);
+        assert!(!app.refresh_pending);
+
+        fetcher.complete(&mut app, true);
+        assert_eq!(fetcher.started, 2);
+        assert!(!app.refresh_in_flight);
+    }
+
+    #[test]
+    fn test_refresh_queued_after_failure() {
+        let mut app = AppStateManager::new();
+        let mut fetcher = FakeFetcher::default();
+
+        fetcher.request(&mut app);
+        fetcher.request(&mut app);
+        fetcher.complete(&mut app, false);
+        assert_eq!(fetcher.started, 2);
+        assert!(app.refresh_in_flight);
+
+        fetcher.complete(&mut app, false);
+        assert!(!app.refresh_in_flight);
+
+        // Nothing in flight: the next request starts right away
+        fetcher.request(&mut app);
+        assert_eq!(fetcher.started, 3);
+    }
+
+    #[test]
+    fn test_background_refresh_success_keeps_view() {
+        let mut app = AppStateManager::new();
+        app.state = AppState::ThemeSelector;
+
+        assert!(app.request_background_refresh());
+        assert!(app.background_refresh);
+        assert_eq!(app.state, AppState::ThemeSelector);
+        assert!(!app.updated_just_now());
+
+        assert!(!app.finish_refresh());
+        app.record_refresh(Utc::now());
+        assert!(app.updated_just_now());
+
+        app.last_refresh_at = Some(Local::now() - chrono::Duration::seconds(6));
+        assert!(!app.updated_just_now());
+    }
+
+    #[test]
+    fn test_background_refresh_failure_is_a_toast() {
+        let mut app = AppStateManager::new();
+        assert!(app.request_background_refresh());
+        assert!(!app.finish_refresh());
+
+        app.background_refresh_failed("Network error\nCaused by: timeout");
+        assert_eq!(app.state, AppState::Dashboard);
+        let toast = app.active_toasts().next().unwrap();
+        assert_eq!(
+            toast.message,
+            "Showing cached data, refresh failed: Network error"
+        );
+        assert_eq!(toast.severity, ToastSeverity::Warning);
+        assert!(!app.updated_just_now());
+
+        // La marca del help bar dura hasta el siguiente refresh bueno;
+        // el toast se atenúa y luego se va
+        assert!(app.refresh_failed_recently());
+        assert_eq!(app.upcoming_deadlines().count(), 3);
+        app.record_refresh(Utc::now());
+        assert!(!app.refresh_failed_recently());
+        assert_eq!(app.data_status.unwrap().source, DataSource::Api);
+    }
+
+    #[test]
+    fn test_data_status_label() {
+        let now = Utc::now();
+        let status = |source, minutes| DataStatus {
+            source,
+            fetched_at: now - chrono::Duration::minutes(minutes),
+        };
+        assert_eq!(
+            status(DataSource::Api, 0).label(now),
+            "Data: just now (API)"
+        );
+        assert_eq!(
+            status(DataSource::Cache, 3).label(now),
+            "Data: 3m ago (cache)"
+        );
+        assert_eq!(
+            status(DataSource::StaleCache, 150).label(now),
+            "Data: 2h 30m ago (cache)"
+        );
+        // Un reloj que va por detrás no da edades negativas
+        assert_eq!(
+            status(DataSource::Api, -2).label(now),
+            "Data: just now (API)"
+        );
+    }
+
+    #[test]
+    fn test_profile_selection() {
+        let mut app = AppStateManager::new();
+        app.set_profiles(vec!["personal".to_string(), "work".to_string()], "work");
+        assert_eq!(app.selected_profile, 1);
+
+        // Choosing the profile already on screen does nothing
+        assert_eq!(app.choose_selected_profile(), None);
+
+        app.next_profile();
+        assert_eq!(app.selected_profile, 0);
+        assert_eq!(app.choose_selected_profile().as_deref(), Some("personal"));
+        assert_eq!(app.active_profile, "personal");
+
+        app.previous_profile();
+        assert_eq!(app.selected_profile, 1);
+    }
+
+    #[test]
+    fn test_upcoming_deadlines() {
+        let mut app = AppStateManager::new();
+        assert_eq!(app.upcoming_deadlines().count(), 0);
+
+        app.set_notice("hi".to_string());
+        app.record_refresh(Utc::now());
+        let deadlines: Vec<Duration> = app.upcoming_deadlines().collect();
+        assert_eq!(deadlines.len(), 2);
+        assert!(deadlines[0] <= NOTICE_DURATION && deadlines[0] > Duration::from_secs(2));
+        assert!(deadlines[1] <= UPDATED_INDICATOR_DURATION);
+
+        // Expired ones no longer wake the loop
+        app.notice = Some(("old".to_string(), Instant::now() - NOTICE_DURATION * 2));
+        app.last_refresh_at = Some(Local::now() - chrono::Duration::seconds(60));
+        assert_eq!(app.upcoming_deadlines().count(), 0);
+    }
+
+    #[test]
+    fn test_toasts_stack_up_to_the_limit() {
+        let mut app = AppStateManager::new();
+        app.toast_info("one");
+        app.toast_warn("two");
+        app.toast_error("three");
+        app.toast_info("four");
+
+        let messages: Vec<&str> = app.active_toasts().map(|t| t.message.as_str()).collect();
+        assert_eq!(messages, ["four", "three", "two"]);
+        assert_eq!(app.toasts.len(), MAX_TOASTS);
+    }
+
+    #[test]
+    fn test_toasts_fade_and_expire() {
+        let mut app = AppStateManager::new();
+        app.toast_info("saved");
+        assert!(!app.active_toasts().next().unwrap().is_fading());
+        // Dos despertares: al empezar a atenuarse y al irse
+        let deadlines: Vec<Duration> = app.upcoming_deadlines().collect();
+        assert_eq!(deadlines.len(), 2);
+        assert!(deadlines[0] <= TOAST_DURATION - TOAST_FADE);
+        assert!(deadlines[1] > TOAST_DURATION - TOAST_FADE);
+
+        app.toasts[0].created_at = Instant::now() - (TOAST_DURATION - TOAST_FADE / 2);
+        assert!(app.active_toasts().next().unwrap().is_fading());
+        assert_eq!(app.upcoming_deadlines().count(), 1);
+
+        app.toasts[0].created_at = Instant::now() - TOAST_DURATION;
+        assert_eq!(app.active_toasts().count(), 0);
+        assert_eq!(app.upcoming_deadlines().count(), 0);
+
+        // Los caducados se limpian al llegar otro
+        app.toast_error("failed");
+        assert_eq!(app.toasts.len(), 1);
+    }
+
+    #[test]
+    fn test_notice_expires() {
+        let mut app = AppStateManager::new();
+        app.notice = Some((
+            "old".to_string(),
+            Instant::now() - NOTICE_DURATION - Duration::from_millis(1),
+        ));
+        assert_eq!(app.active_notice(), None);
+    }
+
+    #[test]
+    fn test_model_selection_follows_scroll() {
+        let mut app = AppStateManager::new();
+        app.model_scroll_offset = 2;
+
+        // First press highlights the top visible row
+        app.select_next_model(12, 8);
+        assert_eq!(app.selected_model, Some(2));
+
+        for _ in 0..8 {
+            app.select_next_model(12, 8);
+        }
+        assert_eq!(app.selected_model, Some(10));
+        assert_eq!(app.model_scroll_offset, 3);
+
+        for _ in 0..5 {
+            app.select_next_model(12, 8);
+        }
+        assert_eq!(app.selected_model, Some(11));
+        assert_eq!(app.model_scroll_offset, 4);
+
+        for _ in 0..9 {
+            app.select_previous_model();
+        }
+        assert_eq!(app.selected_model, Some(2));
+        assert_eq!(app.model_scroll_offset, 2);
+    }
+
+    #[test]
+    fn test_model_table_jumps_and_clamps() {
+        let mut app = AppStateManager::new();
+        app.select_last_model(12, 8);
+        assert_eq!(app.selected_model, Some(11));
+        assert_eq!(app.model_scroll_offset, 4);
+
+        // Un refresh con menos modelos no deja la vista fuera de la tabla
+        app.clamp_model_view(10, 8);
+        assert_eq!(app.selected_model, Some(9));
+        assert_eq!(app.model_scroll_offset, 2);
+        app.clamp_model_view(5, 8);
+        assert_eq!(app.selected_model, Some(4));
+        assert_eq!(app.model_scroll_offset, 0);
+        app.clamp_model_view(0, 8);
+        assert_eq!(app.selected_model, None);
+
+        app.model_scroll_offset = 3;
+        app.select_first_model(12);
+        assert_eq!(app.selected_model, Some(0));
+        assert_eq!(app.model_scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_model_detail_keeps_scroll() {
+        let mut app = AppStateManager::new();
+        assert!(!app.open_model_detail(0));
+        assert_eq!(app.state, AppState::Dashboard);
+
+        app.model_scroll_offset = 3;
+        assert!(app.open_model_detail(12));
+        assert_eq!(app.state, AppState::ModelDetail);
+        assert_eq!(app.selected_model, Some(3));
+        assert_eq!(app.model_scroll_offset, 3);
+    }
+
+    #[test]
+    fn test_model_filter_is_case_insensitive() {
+        let models: Vec<ModelUsage> = ["Auto: gpt-4.1", "claude-sonnet-4", "Claude-Opus-4", "o3"]
+            .iter()
+            .map(|name| testing::model(name, 1.0))
+            .collect();
+        let names = |app: &AppStateManager| -> Vec<String> {
+            app.filtered_models(&models)
+                .iter()
+                .map(|m| m.name.clone())
+                .collect()
+        };
+
+        let mut app = AppStateManager::new();
+        assert_eq!(names(&app).len(), 4);
+
+        app.model_scroll_offset = 2;
+        app.selected_model = Some(3);
+        for c in "CLAU".chars() {
+            app.push_filter_char(c);
+        }
+        assert_eq!(names(&app), vec!["claude-sonnet-4", "Claude-Opus-4"]);
+        assert_eq!(app.model_scroll_offset, 0);
+        assert_eq!(app.selected_model, None);
+
+        app.pop_filter_char();
+        app.push_filter_char('x');
+        assert!(names(&app).is_empty());
+
+        app.clear_model_filter();
+        assert_eq!(names(&app).len(), 4);
+    }
+
+    #[test]
+    fn test_daily_view_fetches_lazily() {
+        let mut app = AppStateManager::new();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+
+        // La primera vez se piden todos, del más reciente al más antiguo
+        let to_fetch = app.open_daily_view(day(20));
+        assert_eq!(app.state, AppState::DailyView);
+        assert_eq!(to_fetch.len(), DAILY_DAYS as usize);
+        assert_eq!(to_fetch[0], day(20));
+        assert_eq!(to_fetch[1], day(19));
+        assert!(app.daily_loading());
+
+        for date in to_fetch {
+            app.set_day(date, DayLoad::Loaded(3.0));
+        }
+        app.set_day(day(18), DayLoad::Failed("timeout".to_string()));
+        assert!(!app.daily_loading());
+
+        // Al reabrir solo hoy y lo que falló
+        app.state = AppState::Dashboard;
+        assert_eq!(app.open_daily_view(day(20)), [day(20), day(18)]);
+
+        // Otro día: el nuevo hoy y el hoy anterior, que quedó a medias
+        for date in [day(20), day(18)] {
+            app.set_day(date, DayLoad::Loaded(1.0));
+        }
+        assert_eq!(app.open_daily_view(day(21)), [day(21), day(20)]);
+        assert_eq!(app.daily_rows.len(), DAILY_DAYS as usize);
+
+        // Lo que sigue en curso no se pide dos veces
+        assert!(app.open_daily_view(day(21)).is_empty());
+    }
+
+    #[test]
+    fn test_daily_scroll_stops_at_the_ends() {
+        let mut app = AppStateManager::new();
+        app.open_daily_view(NaiveDate::from_ymd_opt(2026, 3, 20).unwrap());
+        app.scroll_daily_up();
+        assert_eq!(app.daily_scroll_offset, 0);
+        for _ in 0..100 {
+            app.scroll_daily_down();
+        }
+        assert_eq!(
+            app.daily_scroll_offset,
+            DAILY_DAYS as usize - DAILY_VISIBLE_ROWS
+        );
+    }
+
+    #[test]
+    fn test_help_scroll_stops_at_the_last_frame_end() {
+        let mut app = AppStateManager::new();
+        app.open_help();
+        // Sin frame dibujado no hay nada que desplazar
+        app.scroll_help_down();
+        assert_eq!(app.help_scroll_offset, 0);
+
+        // 30 líneas, 10 a la vista
+        app.click_areas.list = Some(ListArea {
+            area: Rect::new(0, 0, 40, 10),
+            first: 0,
+            len: 10,
+            total: 30,
+        });
+        for _ in 0..50 {
+            app.scroll_help_down();
+        }
+        assert_eq!(app.help_scroll_offset, 20);
+        app.scroll_help_up();
+        assert_eq!(app.help_scroll_offset, 19);
+
+        // Reabrir vuelve al principio
+        app.open_help();
+        assert_eq!(app.help_scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_profile_switch_forgets_the_other_account() {
+        let mut app = AppStateManager::new();
+        app.set_profiles(vec!["default".into(), "work".into()], "default");
+        app.open_daily_view(NaiveDate::from_ymd_opt(2026, 3, 20).unwrap());
+        app.record_refresh(Utc::now());
+        app.record_refresh(Utc::now());
+        let started = app.session_started - Duration::from_secs(3600);
+        app.session_started = started;
+
+        app.next_profile();
+        assert!(app.choose_selected_profile().is_some());
+        assert!(app.daily_rows.is_empty());
+        // El resumen de sesión empieza de cero con la otra cuenta
+        assert_eq!(app.refresh_count, 0);
+        assert_eq!(app.last_refresh_at, None);
+        assert!(app.session_started > started);
+    }
+
+    #[test]
+    fn test_compare_view_fetches_the_previous_month_once() {
+        let mut app = AppStateManager::new();
+        let day = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+
+        assert_eq!(app.open_compare_view(day(1, 20)), Some((2025, 12)));
+        assert_eq!(app.state, AppState::CompareView);
+        assert!(app.compare_loading());
+        // Sigue en curso: no se pide otra vez
+        assert_eq!(app.open_compare_view(day(1, 20)), None);
+
+        app.set_previous_month((2025, 12), MonthLoad::Failed("offline".into()));
+        assert!(!app.compare_loading());
+        assert_eq!(app.open_compare_view(day(1, 21)), Some((2025, 12)));
+
+        // Un resultado de otro mes no se guarda
+        app.set_previous_month((2025, 11), MonthLoad::Failed("late".into()));
+        assert!(app.compare_loading());
+
+        // Empezó otro mes: su anterior es otro
+        assert_eq!(app.open_compare_view(day(2, 1)), Some((2026, 1)));
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let mut form = SettingsForm::new(&Config::default());
+        form.token = TextInput::new("  ghp_new ");
+        form.theme = TextInput::new("Auto");
+        form.cache_ttl = TextInput::new(" 15");
+        let update = form.validate().unwrap();
+        assert_eq!(update.token.as_deref(), Some("ghp_new"));
+        assert_eq!(update.theme, "auto");
+        assert_eq!(update.cache_ttl_minutes, 15);
+
+        // Lo que el formulario no edita se conserva
+        let mut config = Config {
+            alert_bell: true,
+            token_expires: NaiveDate::from_ymd_opt(2026, 12, 1),
+            ..Config::default()
+        };
+        update.apply(&mut config);
+        assert_eq!(config.token, "ghp_new");
+        assert_eq!(config.cache_ttl_minutes, 15);
+        assert!(config.alert_bell);
+        // La fecha de caducidad era del token anterior
+        assert_eq!(config.token_expires, None);
+
+        form.theme = TextInput::new("Dracula");
+        assert_eq!(form.validate().unwrap_err(), "Unknown theme 'Dracula'");
+        form.theme = TextInput::new("nord");
+        form.cache_ttl = TextInput::new("-1");
+        assert!(form.validate().unwrap_err().contains("'-1'"));
+        form.cache_ttl = TextInput::new("5");
+        form.waybar_format = TextInput::new("  ");
+        assert!(form.validate().is_err());
+    }
+
+    #[test]
+    fn test_settings_focus_wraps() {
+        let mut form = SettingsForm::default();
+        form.move_focus(false);
+        assert_eq!(form.focused_field(), SettingsField::WaybarFormat);
+        form.move_focus(true);
+        assert_eq!(form.focused_field(), SettingsField::Token);
+
+        form.error = Some("old".to_string());
+        form.edit(Edit::Insert('x'));
+        assert_eq!(form.token.value(), "x");
+        assert!(form.token.is_masked());
+        assert!(form.error.is_none());
+    }
+}
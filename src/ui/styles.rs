@@ -1,10 +1,15 @@
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
 };
 
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::percent::{Zone, format_percent, zone_for};
 use crate::themes::ThemeColors;
 
 // ============================================================================
@@ -28,7 +33,9 @@ pub const BAR_FILLED: &str = "■ ";
 pub const BAR_EMPTY: &str = "· ";
 
 /// Legacy progress bar characters (solid style)
+#[allow(dead_code)]
 pub const BAR_SOLID_FILLED: &str = "█";
+#[allow(dead_code)]
 pub const BAR_SOLID_EMPTY: &str = "░";
 
 /// Braille patterns (btop style)
@@ -36,8 +43,7 @@ pub const BAR_BRAILLE_FILLED: &str = "⣿";
 pub const BAR_BRAILLE_EMPTY: &str = "⠀";
 
 /// Color zone thresholds (percentages)
-pub const ZONE_SUCCESS_END: f64 = 75.0;
-pub const ZONE_WARNING_END: f64 = 90.0;
+pub use crate::percent::{ZONE_CRITICAL_START, ZONE_WARNING_START};
 
 /// Constant warning color (orange)
 pub const WARNING_COLOR: Color = Color::Rgb(255, 184, 108);
@@ -46,18 +52,110 @@ pub const WARNING_COLOR: Color = Color::Rgb(255, 184, 108);
 pub const ERROR_COLOR: Color = Color::Rgb(255, 85, 85);
 
 // ============================================================================
-// Icons (Nerd Fonts / Unicode)
+// Icons (Nerd Fonts / Unicode, or plain ASCII)
 // ============================================================================
 
-/// Nerd Font Icons
-pub const ICON_USER: &str = "";
-pub const ICON_CALENDAR: &str = "";
-pub const ICON_RESET: &str = "↻";
-pub const ICON_THEME: &str = "";
-pub const ICON_ROBOT: &str = "";
-pub const ICON_GITHUB: &str = "";
-pub const ICON_USAGE: &str = "";
-pub const ICON_LIST: &str = "";
+/// Symbols drawn next to titles and labels
+#[derive(Debug, PartialEq, Eq)]
+pub struct Icons {
+    pub user: &'static str,
+    pub calendar: &'static str,
+    pub reset: &'static str,
+    pub theme: &'static str,
+    pub robot: &'static str,
+    pub github: &'static str,
+    pub usage: &'static str,
+    pub list: &'static str,
+    pub trend: &'static str,
+    pub lock: &'static str,
+    pub warning: &'static str,
+    /// Usage of a model outside the plan's limit
+    pub unmetered: &'static str,
+    /// More or fewer requests than last month
+    pub up: &'static str,
+    pub down: &'static str,
+    /// Swatch of the theme colors in the header
+    pub dot: &'static str,
+    /// Between the items of a status line
+    pub separator: &'static str,
+}
+
+/// Nerd Font icons
+pub const NERD_ICONS: Icons = Icons {
+    user: "",
+    calendar: "",
+    reset: "↻",
+    theme: "",
+    robot: "",
+    github: "",
+    usage: "",
+    list: "",
+    trend: "",
+    lock: "",
+    warning: "⚠",
+    unmetered: "∞",
+    up: "▲",
+    down: "▼",
+    dot: "●",
+    separator: " • ",
+};
+
+/// For the Linux console, `TERM=dumb` and locales that aren't UTF-8
+pub const ASCII_ICONS: Icons = Icons {
+    user: "@",
+    calendar: "#",
+    reset: "~",
+    theme: "*",
+    robot: ">",
+    github: "gh",
+    usage: "%",
+    list: "=",
+    trend: "^",
+    lock: "(ro)",
+    warning: "!",
+    unmetered: "inf",
+    up: "+",
+    down: "-",
+    dot: "o",
+    separator: " | ",
+};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Chosen once at startup, like the language
+pub fn set_ascii_icons(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// The icon set in use
+pub fn icons() -> &'static Icons {
+    icon_set(ASCII.load(Ordering::Relaxed))
+}
+
+pub fn icon_set(ascii: bool) -> &'static Icons {
+    if ascii { &ASCII_ICONS } else { &NERD_ICONS }
+}
+
+/// The `ascii_icons` config key wins; otherwise ASCII for terminals that
+/// can't draw the icons (`TERM=dumb`, the Linux console) and for locales
+/// that aren't UTF-8, judged by the first of `LC_ALL`, `LC_CTYPE`, `LANG`
+/// that is set
+pub fn detect_ascii_icons(configured: Option<bool>, env: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(ascii) = configured {
+        return ascii;
+    }
+    if env("TERM").is_some_and(|term| term == "dumb" || term == "linux") {
+        return true;
+    }
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(env)
+        .find(|value| !value.is_empty())
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            !locale.contains("utf-8") && !locale.contains("utf8")
+        })
+}
 
 // ============================================================================
 // Style Builders
@@ -76,7 +174,6 @@ pub fn muted_style(colors: &ThemeColors) -> Style {
 }
 
 /// Creates a success style
-#[allow(dead_code)]
 pub fn success_style(colors: &ThemeColors) -> Style {
     Style::default().fg(colors.success)
 }
@@ -136,45 +233,35 @@ pub fn border_style(colors: &ThemeColors) -> Style {
 /// - 75-90%: warning color (constant orange)
 /// - 90-100%: error color (constant red)
 pub fn get_usage_color(percentage: f64, colors: &ThemeColors) -> Color {
-    if percentage >= ZONE_WARNING_END {
-        ERROR_COLOR
-    } else if percentage >= ZONE_SUCCESS_END {
-        WARNING_COLOR
-    } else {
-        colors.success
-    }
+    UsageZone::from_percentage(percentage).to_color(colors)
 }
 
 /// Calculates zone boundaries for segmented progress bars
 /// Returns (success_end, warning_end) in cell counts
 pub fn calculate_zone_boundaries(total_cells: usize) -> (usize, usize) {
-    let success_end = ((ZONE_SUCCESS_END / 100.0) * total_cells as f64) as usize;
-    let warning_end = ((ZONE_WARNING_END / 100.0) * total_cells as f64) as usize;
+    let success_end = ((ZONE_WARNING_START / 100.0) * total_cells as f64) as usize;
+    let warning_end = ((ZONE_CRITICAL_START / 100.0) * total_cells as f64) as usize;
     (success_end, warning_end)
 }
 
 /// Determines which zone a percentage falls into
 #[derive(Debug, Clone, Copy, PartialEq)]
-#[allow(dead_code)]
 pub enum UsageZone {
     Success, // 0-75%
     Warning, // 75-90%
     Error,   // 90-100%
 }
 
-#[allow(dead_code)]
 impl UsageZone {
     pub fn from_percentage(percentage: f64) -> Self {
-        if percentage >= ZONE_WARNING_END {
-            UsageZone::Error
-        } else if percentage >= ZONE_SUCCESS_END {
-            UsageZone::Warning
-        } else {
-            UsageZone::Success
+        match zone_for(percentage) {
+            Zone::Critical => UsageZone::Error,
+            Zone::Warning => UsageZone::Warning,
+            Zone::Low | Zone::Normal => UsageZone::Success,
         }
     }
 
-    pub fn to_color(&self, colors: &ThemeColors) -> Color {
+    pub fn to_color(self, colors: &ThemeColors) -> Color {
         match self {
             UsageZone::Success => colors.success,
             UsageZone::Warning => WARNING_COLOR,
@@ -201,7 +288,7 @@ pub fn with_horizontal_margin(area: Rect) -> Rc<[Rect]> {
 pub fn vertical_layout_with_margin(area: Rect, constraints: &[Constraint]) -> Rc<[Rect]> {
     Layout::default()
         .direction(Direction::Vertical)
-        .constraints(constraints.iter().cloned().collect::<Vec<_>>())
+        .constraints(constraints.to_vec())
         .horizontal_margin(UI_MARGIN)
         .split(area)
 }
@@ -223,7 +310,7 @@ pub fn model_table_constraints() -> [Constraint; 4] {
 
 /// Formats a percentage value (e.g., 51.0 -> "51.0%")
 pub fn format_percentage(percentage: f64) -> String {
-    format!("{:.1}%", percentage)
+    format!("{}%", format_percent(percentage, 1))
 }
 
 /// Formats a count value (e.g., 153 -> "  153")
@@ -237,16 +324,276 @@ pub fn format_usage_with_limit(used: f64, limit: f64) -> String {
     format!("{:.0}/{:.0}", used, limit)
 }
 
+/// Formats an elapsed duration compactly (e.g., "45s", "12m", "6h 12m", "2d 4h")
+pub fn format_elapsed(elapsed: std::time::Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Time left until the reset by hours (e.g., "2d 14h", "6h"); minutes only
+/// in the last hour
+pub fn format_countdown(left: chrono::Duration) -> String {
+    let secs = left.num_seconds().max(0);
+    let (days, hours, minutes) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h", hours)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// The countdown turns green in the last day: the quota is about to refill
+pub fn countdown_style(left: chrono::Duration, colors: &ThemeColors) -> Style {
+    if left < chrono::Duration::days(1) {
+        success_style(colors)
+    } else {
+        muted_style(colors)
+    }
+}
+
 /// Calculates responsive bar width based on available space
 /// Returns the number of cells that fit, respecting MAX_BAR_CELLS and MIN_BAR_CELLS
 pub fn calculate_responsive_bar_width(available_width: u16) -> usize {
-    let max_chars = (available_width as usize / CELL_WIDTH)
-        .min(MAX_BAR_CELLS)
-        .max(MIN_BAR_CELLS);
-    max_chars
+    (available_width as usize / CELL_WIDTH).clamp(MIN_BAR_CELLS, MAX_BAR_CELLS)
 }
 
-/// Calculates filled cells for a progress bar
+/// Calculates filled cells for a progress bar. Past 100% the bar is just
+/// full: callers show the overage some other way.
 pub fn calculate_filled_cells(percentage: f64, total_cells: usize) -> usize {
-    ((percentage / 100.0) * total_cells as f64) as usize
+    ((percentage / 100.0) * total_cells as f64).clamp(0.0, total_cells as f64) as usize
+}
+
+/// Shortens `text` to `width` terminal cells by replacing its middle with
+/// "…", so both the family and the date/variant suffix stay visible
+/// (e.g., "claude-3-7-sonnet-thinking-extended" -> "claude-3-7…extended")
+pub fn truncate_middle(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    // La elipsis ocupa una celda; el resto se reparte, con el extra al principio
+    let budget = width - 1;
+    let head = take_width(text.graphemes(true), budget - budget / 2);
+    let mut tail: Vec<&str> = take_width(text.graphemes(true).rev(), budget / 2);
+    tail.reverse();
+
+    let mut truncated = head.concat();
+    truncated.push('…');
+    truncated.extend(tail);
+    truncated
+}
+
+/// Cuts `text` to at most `width` terminal cells, ending in `marker` when
+/// something was cut (e.g., "octocat-with-a-long-name", 12, ".." ->
+/// "octocat-wi.."). Never splits a character or an emoji sequence.
+pub fn truncate_end(text: &str, width: usize, marker: &str) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    // Sin sitio para el marcador, se corta sin él
+    let marker = if marker.width() <= width { marker } else { "" };
+    let mut truncated = take_width(text.graphemes(true), width - marker.width()).concat();
+    truncated.push_str(marker);
+    truncated
+}
+
+/// `text` padded with spaces to `width` cells; longer text is left as is
+pub fn pad_to_width(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(text.width())))
+}
+
+/// Splits `text` in pieces of at most `width` cells, for names without
+/// spaces to break at
+pub fn split_by_width(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if used + grapheme_width > width.max(1) && used > 0 {
+            lines.push(String::new());
+            used = 0;
+        }
+        lines.last_mut().unwrap().push_str(grapheme);
+        used += grapheme_width;
+    }
+    lines
+}
+
+/// Longest run of `graphemes` that fits in `width` cells
+fn take_width<'a>(graphemes: impl Iterator<Item = &'a str>, width: usize) -> Vec<&'a str> {
+    let mut used = 0;
+    graphemes
+        .take_while(|grapheme| {
+            used += grapheme.width();
+            used <= width
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_zone_colors_match_displayed_percentage() {
+        let colors = ThemeColors::dark();
+
+        assert_eq!(format_percentage(74.95), "75.0%");
+        assert_eq!(get_usage_color(74.95, &colors), WARNING_COLOR);
+        assert_eq!(format_percentage(89.95), "90.0%");
+        assert_eq!(get_usage_color(89.95, &colors), ERROR_COLOR);
+        assert_eq!(format_percentage(99.95), "100.0%");
+        assert_eq!(get_usage_color(99.95, &colors), ERROR_COLOR);
+        assert_eq!(get_usage_color(74.4, &colors), colors.success);
+    }
+
+    #[test]
+    fn test_filled_cells_never_overflow() {
+        assert_eq!(calculate_filled_cells(50.0, 40), 20);
+        assert_eq!(calculate_filled_cells(100.0, 40), 40);
+        assert_eq!(calculate_filled_cells(100.1, 40), 40);
+        assert_eq!(calculate_filled_cells(200.0, 40), 40);
+        assert_eq!(calculate_filled_cells(-5.0, 40), 0);
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(45)), "45s");
+        assert_eq!(format_elapsed(Duration::from_secs(12 * 60 + 5)), "12m");
+        assert_eq!(format_elapsed(Duration::from_secs(6 * 3600 + 12 * 60)), "6h 12m");
+        assert_eq!(format_elapsed(Duration::from_secs(2 * 86_400 + 4 * 3600)), "2d 4h");
+    }
+
+    #[test]
+    fn test_format_countdown() {
+        let left = |hours: i64, minutes: i64| chrono::Duration::minutes(hours * 60 + minutes);
+        assert_eq!(format_countdown(left(62, 30)), "2d 14h");
+        assert_eq!(format_countdown(left(6, 59)), "6h");
+        assert_eq!(format_countdown(left(0, 45)), "45m");
+        assert_eq!(format_countdown(left(-3, 0)), "0m");
+
+        let colors = ThemeColors::dark();
+        assert_eq!(countdown_style(left(24, 0), &colors), muted_style(&colors));
+        assert_eq!(
+            countdown_style(left(23, 59), &colors),
+            success_style(&colors)
+        );
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        let name = "claude-3-7-sonnet-thinking-extended-preview-20250115";
+        assert_eq!(truncate_middle(name, 60), name);
+        assert_eq!(truncate_middle(name, name.len()), name);
+        assert_eq!(truncate_middle(name, 21), "claude-3-7…w-20250115");
+        assert_eq!(truncate_middle(name, 21).width(), 21);
+        assert_eq!(truncate_middle(name, 1), "…");
+        assert_eq!(truncate_middle(name, 0), "");
+
+        // Los caracteres anchos ocupan dos celdas y no se parten
+        let wide = "模型-名称-很长-的-模型";
+        let truncated = truncate_middle(wide, 10);
+        assert!(truncated.width() <= 10);
+        assert!(truncated.starts_with("模型") && truncated.contains('…'));
+    }
+
+    #[test]
+    fn test_split_by_width() {
+        assert_eq!(split_by_width("gpt-4.1", 20), vec!["gpt-4.1"]);
+        assert_eq!(split_by_width("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(split_by_width("模型名称", 3), vec!["模", "型", "名", "称"]);
+        assert_eq!(split_by_width("", 3), vec![""]);
+    }
+
+    /// Family emoji: three people joined by zero-width joiners, one glyph
+    const FAMILY: &str = "👨\u{200d}👩\u{200d}👧";
+
+    #[test]
+    fn test_truncate_end() {
+        assert_eq!(truncate_end("octocat", 12, ".."), "octocat");
+        let long = "octocat-with-a-long-name";
+        assert_eq!(truncate_end(long, 12, ".."), "octocat-wi..");
+        assert_eq!(truncate_end("", 0, ".."), "");
+        assert_eq!(truncate_end("octocat", 0, ".."), "");
+        assert_eq!(truncate_end("octocat", 1, ".."), "o");
+        assert_eq!(truncate_end("octocat", 2, ".."), "..");
+
+        // Un emoji o un carácter CJK ocupan dos celdas y no se parten
+        let emoji = format!("{}octo🐙cat", FAMILY);
+        assert_eq!(truncate_end(&emoji, 4, ""), format!("{}oc", FAMILY));
+        assert_eq!(truncate_end(&emoji, 1, ""), "");
+        assert_eq!(truncate_end("模型名称", 5, "…"), "模型…");
+        assert_eq!(truncate_end("模型名称", 3, ""), "模");
+        for width in 0..6 {
+            assert!(truncate_end(&emoji, width, "..").width() <= width);
+            assert!(truncate_end("模型名称", width, "…").width() <= width);
+        }
+    }
+
+    #[test]
+    fn test_pad_to_width() {
+        assert_eq!(pad_to_width("ab", 4), "ab  ");
+        assert_eq!(pad_to_width("模型", 6), "模型  ");
+        assert_eq!(pad_to_width("", 2), "  ");
+        // Más largo que el ancho: sin relleno, y sin desbordar la resta
+        assert_eq!(pad_to_width("abcdef", 2), "abcdef");
+        assert_eq!(pad_to_width(FAMILY, 0), FAMILY);
+    }
+
+    #[test]
+    fn test_truncate_middle_keeps_emoji_whole() {
+        let name = format!("{}-model-{}", FAMILY, FAMILY);
+        let truncated = truncate_middle(&name, 6);
+        assert!(truncated.width() <= 6);
+        assert!(truncated.starts_with(FAMILY) && truncated.ends_with(FAMILY));
+    }
+
+    #[test]
+    fn test_icon_set_follows_the_flag() {
+        assert_eq!(icon_set(false), &NERD_ICONS);
+        assert_eq!(icon_set(true), &ASCII_ICONS);
+        // Todos los campos a la vez, a través de Debug
+        assert!(format!("{:?}", icon_set(true)).is_ascii());
+        assert!(!format!("{:?}", icon_set(false)).is_ascii());
+    }
+
+    #[test]
+    fn test_detect_ascii_icons() {
+        fn detect(configured: Option<bool>, vars: &[(&str, &str)]) -> bool {
+            detect_ascii_icons(configured, |name| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            })
+        }
+        let utf8 = [("TERM", "xterm-256color"), ("LANG", "en_US.UTF-8")];
+        assert!(!detect(None, &utf8));
+        assert!(detect(Some(true), &utf8));
+        assert!(detect(None, &[("TERM", "dumb"), ("LANG", "C.UTF-8")]));
+        assert!(detect(None, &[("TERM", "linux")]));
+        assert!(!detect(Some(false), &[("TERM", "dumb"), ("LANG", "C")]));
+        // LC_ALL gana a LANG; vacía no cuenta
+        assert!(detect(None, &[("LC_ALL", "C"), ("LANG", "es_ES.utf8")]));
+        assert!(!detect(None, &[("LC_ALL", ""), ("LANG", "es_ES.utf8")]));
+        assert!(detect(None, &[("LANG", "POSIX")]));
+        // Sin nada que lo diga, los iconos de siempre
+        assert!(!detect(None, &[]));
+    }
 }
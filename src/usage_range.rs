@@ -0,0 +1,505 @@
+//! Usage summed over a range of calendar months, for `copilot-usage stats`:
+//! `--period 2025-03`, `--period 2025` or `--from 2025-01 --to 2025-06`.
+//! Months are fetched one at a time through the API's `year`/`month`
+//! filters; finished ones come from the month cache after the first time.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::api::{ApiClient, config_stats};
+use crate::cache::MonthCache;
+use crate::compare;
+use crate::export::csv_line;
+use crate::history::periods;
+use crate::models::{Config, UsageStats};
+use crate::percent::format_quantity;
+
+/// Year and month
+pub type Month = (i32, u32);
+
+/// Why a month of the range isn't in the totals
+pub const SKIPPED_FUTURE: &str = "in the future";
+pub const SKIPPED_BEFORE_DATA: &str = "before the account's first data";
+
+/// A month or a whole year, as given on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodArg {
+    Month(i32, u32),
+    Year(i32),
+}
+
+impl PeriodArg {
+    fn first(self) -> Month {
+        match self {
+            PeriodArg::Month(year, month) => (year, month),
+            PeriodArg::Year(year) => (year, 1),
+        }
+    }
+
+    fn last(self) -> Month {
+        match self {
+            PeriodArg::Month(year, month) => (year, month),
+            PeriodArg::Year(year) => (year, 12),
+        }
+    }
+}
+
+/// `YYYY-MM` or `YYYY`, for clap
+pub fn parse_period(value: &str) -> Result<PeriodArg, String> {
+    let invalid = || format!("invalid period '{}', expected YYYY-MM or YYYY", value);
+    let value = value.trim();
+    let (year, month) = match value.split_once('-') {
+        Some((year, month)) => (year, Some(month)),
+        None => (value, None),
+    };
+    if year.len() != 4 {
+        return Err(invalid());
+    }
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+    match month {
+        None => Ok(PeriodArg::Year(year)),
+        Some(month) => match month.parse::<u32>() {
+            Ok(number @ 1..=12) if month.len() == 2 => Ok(PeriodArg::Month(year, number)),
+            _ => Err(invalid()),
+        },
+    }
+}
+
+/// First and last month, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonthRange {
+    pub from: Month,
+    pub to: Month,
+}
+
+impl MonthRange {
+    /// `--period`, or `--from` / `--to`. `--from` alone runs to the current
+    /// month, `--to` alone starts in January of its year, and without any
+    /// of them it's the year so far.
+    pub fn resolve(
+        period: Option<PeriodArg>,
+        from: Option<PeriodArg>,
+        to: Option<PeriodArg>,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        let current = periods::current_period(now);
+        let range = match (period, from, to) {
+            (Some(period), _, _) => Self {
+                from: period.first(),
+                to: period.last(),
+            },
+            (None, from, to) => {
+                let to = to.map_or(current, PeriodArg::last);
+                let from = from.map_or((to.0, 1), PeriodArg::first);
+                Self { from, to }
+            }
+        };
+        if range.from > range.to {
+            anyhow::bail!(
+                "--from {} is after --to {}",
+                month_key(range.from),
+                month_key(range.to)
+            );
+        }
+        Ok(range)
+    }
+
+    /// Every month of the range, oldest first
+    pub fn months(&self) -> Vec<Month> {
+        let mut months = Vec::new();
+        let mut month = self.to;
+        while month >= self.from {
+            months.push(month);
+            month = periods::previous_month(month);
+        }
+        months.reverse();
+        months
+    }
+}
+
+/// "2025-03"
+pub fn month_key((year, month): Month) -> String {
+    format!("{:04}-{:02}", year, month)
+}
+
+/// "March 2025"
+fn month_label((year, month): Month) -> String {
+    format!("{} {}", periods::month_name(year, month), year)
+}
+
+/// Months of `range` there's something to fetch for, and those that start
+/// after `now`
+pub fn split_future(range: &MonthRange, now: DateTime<Utc>) -> (Vec<Month>, Vec<Month>) {
+    let current = periods::current_period(now);
+    range
+        .months()
+        .into_iter()
+        .partition(|month| *month <= current)
+}
+
+/// Requests and cost of one month, or of one model over the range
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RangeRow {
+    /// "2025-03" for a month, the model's name for a model
+    pub name: String,
+    pub requests: f64,
+    pub estimated_cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedMonth {
+    pub month: String,
+    pub reason: &'static str,
+}
+
+/// What `stats` prints and exports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RangeUsage {
+    pub from: String,
+    pub to: String,
+    pub months: Vec<RangeRow>,
+    /// Most requests first
+    pub models: Vec<RangeRow>,
+    pub total: RangeRow,
+    pub skipped: Vec<SkippedMonth>,
+}
+
+/// Sums `fetched` (oldest first) per model. Requests count every model,
+/// unmetered ones included; costs are each model's billed requests. Empty
+/// months before the first one with usage predate the account's data and
+/// are skipped, like the `future` ones.
+pub fn aggregate(
+    range: &MonthRange,
+    fetched: &[(Month, UsageStats)],
+    future: &[Month],
+) -> RangeUsage {
+    let has_usage = |stats: &UsageStats| stats.models.iter().any(|model| model.used > 0.0);
+    // Sin ningún mes con uso no se puede saber cuándo empezó la cuenta
+    let first_data = fetched
+        .iter()
+        .position(|(_, stats)| has_usage(stats))
+        .unwrap_or(0);
+
+    let mut skipped: Vec<SkippedMonth> = fetched[..first_data]
+        .iter()
+        .map(|(month, _)| SkippedMonth {
+            month: month_key(*month),
+            reason: SKIPPED_BEFORE_DATA,
+        })
+        .collect();
+    skipped.extend(future.iter().map(|month| SkippedMonth {
+        month: month_key(*month),
+        reason: SKIPPED_FUTURE,
+    }));
+
+    let mut months = Vec::new();
+    let mut per_model: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for (month, stats) in &fetched[first_data..] {
+        let mut row = RangeRow {
+            name: month_key(*month),
+            requests: 0.0,
+            estimated_cost: 0.0,
+        };
+        for model in &stats.models {
+            row.requests += model.used;
+            row.estimated_cost += model.cost;
+            let sums = per_model.entry(&model.name).or_default();
+            sums.0 += model.used;
+            sums.1 += model.cost;
+        }
+        months.push(row);
+    }
+
+    let mut models: Vec<RangeRow> = per_model
+        .into_iter()
+        .filter(|(_, (requests, _))| *requests > 0.0)
+        .map(|(name, (requests, estimated_cost))| RangeRow {
+            name: name.to_string(),
+            requests,
+            estimated_cost,
+        })
+        .collect();
+    // Estable: a igual uso, por nombre
+    models.sort_by(|a, b| b.requests.total_cmp(&a.requests));
+
+    let total = RangeRow {
+        name: "Total".to_string(),
+        requests: months.iter().map(|row| row.requests).sum(),
+        estimated_cost: months.iter().map(|row| row.estimated_cost).sum(),
+    };
+
+    RangeUsage {
+        from: month_key(range.from),
+        to: month_key(range.to),
+        months,
+        models,
+        total,
+        skipped,
+    }
+}
+
+/// Fetches `months` in order, reporting each one to `progress` before its
+/// request. Stats go through the config like the dashboard's.
+pub async fn fetch_months(
+    client: &ApiClient,
+    cache: &MonthCache,
+    username: &str,
+    config: &Config,
+    months: &[Month],
+    mut progress: impl FnMut(usize, Month),
+) -> Result<Vec<(Month, UsageStats)>> {
+    let mut fetched = Vec::with_capacity(months.len());
+    for (index, month) in months.iter().enumerate() {
+        progress(index, *month);
+        let data = compare::month_usage(client, cache, username, *month).await?;
+        fetched.push((*month, config_stats(&data, config)));
+    }
+    Ok(fetched)
+}
+
+/// Months, then models, then the total, and a line per skipped month
+pub fn to_table(usage: &RangeUsage) -> String {
+    let month_labels: Vec<(String, &RangeRow)> = usage
+        .months
+        .iter()
+        .map(|row| (label_of_key(&row.name), row))
+        .collect();
+    let width = month_labels
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .chain(usage.models.iter().map(|row| row.name.chars().count()))
+        .max()
+        .unwrap_or(0)
+        .max("Month".len());
+
+    let line = |name: &str, requests: String, cost: String| {
+        format!(
+            "{:<width$} {:>10} {:>10}\n",
+            name,
+            requests,
+            cost,
+            width = width
+        )
+    };
+    let row = |name: &str, row: &RangeRow| {
+        line(
+            name,
+            format_quantity(row.requests),
+            format!("${:.2}", row.estimated_cost),
+        )
+    };
+
+    let mut table = line("Month", "Requests".into(), "Cost".into());
+    for (label, month) in &month_labels {
+        table.push_str(&row(label, month));
+    }
+    table.push('\n');
+    table.push_str(&line("Model", "Requests".into(), "Cost".into()));
+    for model in &usage.models {
+        table.push_str(&row(&model.name, model));
+    }
+    table.push_str(&row(&usage.total.name, &usage.total));
+
+    if !usage.skipped.is_empty() {
+        table.push('\n');
+    }
+    for skipped in &usage.skipped {
+        table.push_str(&format!("Skipped {} ({})\n", skipped.month, skipped.reason));
+    }
+    table
+}
+
+/// "2025-03" → "March 2025"
+fn label_of_key(key: &str) -> String {
+    match parse_period(key) {
+        Ok(PeriodArg::Month(year, month)) => month_label((year, month)),
+        _ => key.to_string(),
+    }
+}
+
+/// RFC 4180 CSV: one line per model, then the total
+pub fn to_csv(usage: &RangeUsage) -> String {
+    let mut csv = csv_line(&[
+        "Model".to_string(),
+        "Requests".to_string(),
+        "Estimated cost (USD)".to_string(),
+    ]);
+    csv.push_str("\r\n");
+    for row in usage.models.iter().chain([&usage.total]) {
+        csv.push_str(&csv_line(&[
+            row.name.clone(),
+            format_quantity(row.requests),
+            format!("{:.2}", row.estimated_cost),
+        ]));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+pub fn to_json(usage: &RangeUsage) -> String {
+    serde_json::to_string_pretty(usage).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::calculate_stats;
+    use crate::models::{TimePeriod, UsageData, UsageItem};
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 16, 12, 0, 0).unwrap()
+    }
+
+    fn stats(models: &[(&str, f64, f64)]) -> UsageStats {
+        let usage_items = models
+            .iter()
+            .map(|&(model, gross, net)| UsageItem {
+                product: "copilot".to_string(),
+                sku: "premium".to_string(),
+                model: model.to_string(),
+                unit_type: "request".to_string(),
+                price_per_unit: 0.04,
+                gross_quantity: gross,
+                gross_amount: gross * 0.04,
+                discount_quantity: gross - net,
+                discount_amount: (gross - net) * 0.04,
+                net_quantity: net,
+                net_amount: net * 0.04,
+                date: None,
+            })
+            .collect();
+        let data = UsageData {
+            time_period: TimePeriod {
+                year: 2026,
+                month: Some(1),
+                day: None,
+            },
+            user: "testuser".to_string(),
+            usage_items,
+            note: None,
+        };
+        calculate_stats(&data, 300)
+    }
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("2025-03"), Ok(PeriodArg::Month(2025, 3)));
+        assert_eq!(parse_period("2025"), Ok(PeriodArg::Year(2025)));
+        for invalid in ["2025-13", "2025-3", "25-03", "march", "2025-03-01", ""] {
+            assert!(parse_period(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_resolve_ranges() {
+        let resolve = |period, from, to| MonthRange::resolve(period, from, to, now()).unwrap();
+        let range = |from, to| MonthRange { from, to };
+
+        // Sin nada: el año hasta hoy
+        assert_eq!(resolve(None, None, None), range((2026, 1), (2026, 3)));
+        assert_eq!(
+            resolve(Some(PeriodArg::Year(2025)), None, None),
+            range((2025, 1), (2025, 12))
+        );
+        assert_eq!(
+            resolve(None, Some(PeriodArg::Month(2025, 11)), None),
+            range((2025, 11), (2026, 3))
+        );
+        assert_eq!(
+            resolve(None, None, Some(PeriodArg::Month(2025, 6))),
+            range((2025, 1), (2025, 6))
+        );
+        assert!(
+            MonthRange::resolve(
+                None,
+                Some(PeriodArg::Month(2025, 6)),
+                Some(PeriodArg::Month(2025, 1)),
+                now()
+            )
+            .is_err()
+        );
+
+        let months = range((2025, 11), (2026, 2)).months();
+        assert_eq!(months, [(2025, 11), (2025, 12), (2026, 1), (2026, 2)]);
+    }
+
+    #[test]
+    fn test_future_months_are_split_off() {
+        let range = MonthRange {
+            from: (2026, 2),
+            to: (2026, 5),
+        };
+        let (fetch, future) = split_future(&range, now());
+        assert_eq!(fetch, [(2026, 2), (2026, 3)]);
+        assert_eq!(future, [(2026, 4), (2026, 5)]);
+    }
+
+    #[test]
+    fn test_aggregate_sums_models_and_skips_months() {
+        let range = MonthRange {
+            from: (2025, 12),
+            to: (2026, 4),
+        };
+        let fetched = [
+            ((2025, 12), stats(&[])),
+            (
+                (2026, 1),
+                stats(&[("gpt-4.1", 100.0, 0.0), ("o3", 20.0, 0.0)]),
+            ),
+            ((2026, 2), stats(&[])),
+            ((2026, 3), stats(&[("gpt-4.1", 250.0, 50.0)])),
+        ];
+        let usage = aggregate(&range, &fetched, &[(2026, 4)]);
+
+        let months: Vec<(&str, f64)> = usage
+            .months
+            .iter()
+            .map(|row| (row.name.as_str(), row.requests))
+            .collect();
+        // Un mes vacío después de haber datos sí cuenta
+        assert_eq!(
+            months,
+            [("2026-01", 120.0), ("2026-02", 0.0), ("2026-03", 250.0)]
+        );
+        assert_eq!(usage.models[0].name, "gpt-4.1");
+        assert_eq!(usage.models[0].requests, 350.0);
+        assert!((usage.models[0].estimated_cost - 2.0).abs() < 1e-9);
+        assert_eq!(usage.total.requests, 370.0);
+        assert_eq!(
+            usage.skipped,
+            [
+                SkippedMonth {
+                    month: "2025-12".to_string(),
+                    reason: SKIPPED_BEFORE_DATA,
+                },
+                SkippedMonth {
+                    month: "2026-04".to_string(),
+                    reason: SKIPPED_FUTURE,
+                },
+            ]
+        );
+
+        let table = to_table(&usage);
+        assert!(table.contains("January 2026"), "{}", table);
+        assert!(
+            table.contains("Skipped 2026-04 (in the future)"),
+            "{}",
+            table
+        );
+        assert_eq!(
+            to_csv(&usage),
+            "Model,Requests,Estimated cost (USD)\r\n\
+             gpt-4.1,350,2.00\r\n\
+             o3,20,0.00\r\n\
+             Total,370,2.00\r\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&to_json(&usage)).unwrap();
+        assert_eq!(json["from"], "2025-12");
+        assert_eq!(json["total"]["requests"], 370.0);
+        assert_eq!(json["skipped"][1]["reason"], "in the future");
+    }
+}
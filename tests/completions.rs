@@ -0,0 +1,75 @@
+//! What the completion scripts call back into: the hidden subcommands run
+//! as the shell runs them, and the scripts that call them.
+
+use std::process::Command;
+
+use copilot_usage::config::ConfigManager;
+use copilot_usage::models::{Config, Theme};
+use tempfile::TempDir;
+
+fn run(args: &[&str], home: Option<&TempDir>) -> String {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_copilot-usage"));
+    command.args(args);
+    if let Some(home) = home {
+        command.env("XDG_CONFIG_HOME", home.path().join("config"));
+    }
+    let output = command.output().unwrap();
+    assert!(output.status.success(), "{:?} failed", args);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_complete_themes() {
+    let expected: String = std::iter::once("auto")
+        .chain(Theme::ALL.iter().map(|theme| theme.as_str()))
+        .map(|name| format!("{}\n", name))
+        .collect();
+    assert_eq!(run(&["__complete-themes"], None), expected);
+}
+
+#[test]
+fn test_complete_profiles() {
+    let home = TempDir::new().unwrap();
+    // Sin config: nada, y sin error
+    assert_eq!(run(&["__complete-profiles"], Some(&home)), "");
+
+    // SAFETY: the only test of this binary that reads the environment
+    unsafe { std::env::set_var("XDG_CONFIG_HOME", home.path().join("config")) };
+    let manager = ConfigManager::new().unwrap();
+    for profile in ["work", "default", "oss"] {
+        manager
+            .clone()
+            .with_profile(profile)
+            .save(&Config::default())
+            .unwrap();
+    }
+    assert_eq!(
+        run(&["__complete-profiles"], Some(&home)),
+        "default\noss\nwork\n"
+    );
+}
+
+#[test]
+fn test_scripts_call_back() {
+    for shell in ["bash", "zsh", "fish"] {
+        let script = run(&["completions", shell], None);
+        assert!(
+            script.contains("copilot-usage __complete-themes"),
+            "{} script doesn't complete themes",
+            shell
+        );
+        assert!(
+            script.contains("copilot-usage __complete-profiles"),
+            "{} script doesn't complete profiles",
+            shell
+        );
+        // Solo como llamada, nunca ofrecidos como subcomando
+        let mentions = script.matches("__complete-").count();
+        let calls = script.matches("copilot-usage __complete-").count();
+        assert_eq!(
+            mentions, calls,
+            "{} script lists the hidden commands",
+            shell
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! The library path an embedder takes: client against a mock GitHub, the
+//! cache in front of it and the stats out of it, without the CLI.
+
+use copilot_usage::{ApiClient, Cache, CacheStatus, Config, UsageStats, config_stats};
+use tempfile::TempDir;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TOKEN: &str = "ghp_library";
+const USAGE_PATH: &str = "/users/octocat/settings/billing/premium_request/usage";
+
+fn fixture() -> serde_json::Value {
+    serde_json::from_str(include_str!("fixtures/usage_duplicated_rows.json")).unwrap()
+}
+
+/// What the crate docs do: the cache while it's fresh, GitHub otherwise
+async fn fetch_stats(server: &MockServer, cache: &Cache, config: &Config) -> UsageStats {
+    let data = match cache.status() {
+        CacheStatus::Fresh(data) => data,
+        _ => {
+            let client = ApiClient::for_config(TOKEN.to_string(), config)
+                .unwrap()
+                .with_base_url(&server.uri());
+            let username = client.get_authenticated_user().await.unwrap();
+            let data = client.fetch_usage(&username).await.unwrap();
+            cache.set(&data, client.last_rate_limit()).unwrap();
+            data
+        }
+    };
+    config_stats(&data, config)
+}
+
+#[tokio::test]
+async fn test_fetches_once_then_serves_the_cache() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/user"))
+        .and(header("Authorization", format!("Bearer {}", TOKEN)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "login": "octocat"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(USAGE_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(fixture())
+                .insert_header("x-ratelimit-remaining", "4999"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let cache = Cache::with_path(dir.path().join("usage.json"), 5);
+    let config = Config {
+        premium_request_limit: 300,
+        ..Config::default()
+    };
+
+    let fetched = fetch_stats(&server, &cache, &config).await;
+    assert_eq!(fetched.username, "octocat");
+    // La fila repetida del fixture se cuenta una vez
+    assert_eq!(fetched.total_used, 15.0);
+    assert_eq!(fetched.total_limit, 300.0);
+    assert_eq!(fetched.models[0].name, "gpt-4.1");
+    assert_eq!(
+        cache.rate_limit().and_then(|limit| limit.remaining),
+        Some(4999)
+    );
+
+    // La segunda vez no llega a GitHub: `expect(1)` lo comprueba al soltar el server
+    let cached = fetch_stats(&server, &cache, &config).await;
+    assert_eq!(cached.total_used, fetched.total_used);
+
+    // Las stats se pueden guardar y leer tal cual
+    let json = serde_json::to_string(&cached).unwrap();
+    let loaded: UsageStats = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.models.len(), cached.models.len());
+    assert_eq!(loaded.reset_date, cached.reset_date);
+}
+
+#[tokio::test]
+async fn test_errors_instead_of_panicking() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/user"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    let client = ApiClient::new(TOKEN.to_string())
+        .unwrap()
+        .with_base_url(&server.uri());
+    let err = client.get_authenticated_user().await.unwrap_err();
+    assert!(err.to_string().contains("401"), "{}", err);
+
+    // Un caché que no existe es `Missing`, no un error
+    let dir = TempDir::new().unwrap();
+    let cache = Cache::with_path(dir.path().join("missing.json"), 5);
+    assert!(matches!(cache.status(), CacheStatus::Missing));
+}
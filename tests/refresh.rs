@@ -0,0 +1,93 @@
+//! A refresh through `AsyncHandler` against `MockProvider`: the stats it
+//! reports and what it leaves in the cache. Needs the `test-utils` feature.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use copilot_usage::api::calculate_stats;
+use copilot_usage::config::ConfigManager;
+use copilot_usage::models::{CacheEntry, Config, UsageData, UsageStats};
+use copilot_usage::testing::MockProvider;
+use copilot_usage::ui::async_handler::{AppContext, AsyncHandler, AsyncResult};
+use tempfile::TempDir;
+
+/// Tiempo máximo de espera por una tarea en background
+const TASK_TIMEOUT: Duration = Duration::from_secs(5);
+
+const FIXTURE: &str = "usage_duplicated_rows.json";
+
+/// Config and cache live in a temp dir; the XDG variables are process-wide,
+/// so this binary has a single test
+fn configure(home: &TempDir) -> ConfigManager {
+    // SAFETY: set before any thread of this binary reads the environment
+    unsafe {
+        std::env::set_var("XDG_CONFIG_HOME", home.path().join("config"));
+        std::env::set_var("XDG_CACHE_HOME", home.path().join("cache"));
+        std::env::set_var("XDG_DATA_HOME", home.path().join("data"));
+    }
+    let config_manager = ConfigManager::new().unwrap();
+    let config = Config {
+        token: "ghp_mock".to_string(),
+        ..Config::default()
+    };
+    config_manager.save(&config).unwrap();
+    config_manager
+}
+
+async fn refresh(handler: &AsyncHandler) -> anyhow::Result<UsageStats> {
+    handler.spawn_refresh();
+    let deadline = Instant::now() + TASK_TIMEOUT;
+    loop {
+        if let Some(AsyncResult::RefreshComplete { result, .. }) = handler.try_recv() {
+            return result;
+        }
+        assert!(Instant::now() < deadline, "no refresh result");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+fn cached(home: &TempDir) -> CacheEntry {
+    let path = home
+        .path()
+        .join("cache")
+        .join("copilot-usage")
+        .join("usage-default.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_refresh_through_the_handler() {
+    let home = TempDir::new().unwrap();
+    let config_manager = configure(&home);
+
+    let provider = Arc::new(MockProvider::from_fixture(FIXTURE).unwrap());
+    let fixture =
+        serde_json::from_str::<UsageData>(include_str!("fixtures/usage_duplicated_rows.json"))
+            .unwrap();
+    let handler = AsyncHandler::new(AppContext::load(config_manager.clone()))
+        .with_provider(Arc::clone(&provider));
+
+    let stats = refresh(&handler).await.unwrap();
+    let expected = calculate_stats(&fixture, 300);
+    assert_eq!(provider.fetches(), 1);
+    assert_eq!(stats.username, expected.username);
+    assert_eq!(stats.total_used, expected.total_used);
+    assert_eq!(stats.models.len(), expected.models.len());
+
+    let entry = cached(&home);
+    assert_eq!(entry.data.user, fixture.user);
+    assert_eq!(entry.data.usage_items.len(), fixture.usage_items.len());
+    // Sin validadores, cada refresh vuelve a pedirlo todo
+    assert!(entry.validators.etag.is_none());
+    refresh(&handler).await.unwrap();
+    assert_eq!(provider.fetches(), 2);
+
+    // Un fallo llega como error, no como panic
+    let failing = AsyncHandler::new(AppContext::load(config_manager))
+        .with_provider(MockProvider::failing(&fixture.user, "boom"));
+    let error = refresh(&failing).await.unwrap_err();
+    assert!(format!("{:#}", error).contains("boom"));
+    // y el cache anterior sigue ahí
+    assert_eq!(cached(&home).data.user, fixture.user);
+}
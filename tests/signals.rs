@@ -0,0 +1,122 @@
+//! `--waybar --loop` as a bar runs it: a child process against a mock API,
+//! poked with SIGUSR1 and SIGUSR2, printing a line for each.
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use copilot_usage::config::ConfigManager;
+use copilot_usage::models::{Config, Plan, WaybarOutput};
+use tempfile::TempDir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Tiempo máximo de espera por una línea del hijo
+const LINE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const USAGE_PATH: &str = "/users/octocat/settings/billing/premium_request/usage";
+
+/// Config in a temp dir; the XDG variables are process-wide, so this binary
+/// has a single test
+fn configure(home: &TempDir, format: &str) -> ConfigManager {
+    // SAFETY: set before any thread of this binary reads the environment
+    unsafe {
+        std::env::set_var("XDG_CONFIG_HOME", home.path().join("config"));
+        std::env::set_var("XDG_CACHE_HOME", home.path().join("cache"));
+        std::env::set_var("XDG_DATA_HOME", home.path().join("data"));
+    }
+    let config_manager = ConfigManager::new().unwrap();
+    save(&config_manager, format);
+    config_manager
+}
+
+fn save(config_manager: &ConfigManager, format: &str) {
+    let config = Config {
+        username: Some("octocat".to_string()),
+        plan: Some(Plan::Pro),
+        cache_ttl_minutes: 60,
+        api_max_attempts: 1,
+        waybar_format: format.to_string(),
+        ..Config::default()
+    };
+    config_manager.save(&config).unwrap();
+}
+
+/// The child's stdout, a line at a time, read on its own thread
+fn lines(child: &mut Child) -> Receiver<String> {
+    let stdout = child.stdout.take().unwrap();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if tx.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+fn next_text(lines: &Receiver<String>) -> String {
+    let line = lines.recv_timeout(LINE_TIMEOUT).expect("no new line");
+    serde_json::from_str::<WaybarOutput>(&line).unwrap().text
+}
+
+fn send(child: &Child, signal: libc::c_int) {
+    // SAFETY: kill only sends a signal to our own child
+    assert_eq!(unsafe { libc::kill(child.id() as libc::pid_t, signal) }, 0);
+}
+
+async fn usage_requests(server: &MockServer) -> usize {
+    let requests = server.received_requests().await.unwrap_or_default();
+    requests
+        .iter()
+        .filter(|request| request.url.path() == USAGE_PATH)
+        .count()
+}
+
+#[tokio::test]
+async fn test_loop_answers_signals() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(USAGE_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(include_str!("fixtures/usage_duplicated_rows.json")),
+        )
+        .mount(&server)
+        .await;
+
+    let home = TempDir::new().unwrap();
+    let config_manager = configure(&home, "{percentage}%");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_copilot-usage"))
+        .args(["--waybar", "--loop"])
+        .env("GITHUB_API_URL", server.uri())
+        .env("GITHUB_TOKEN", "ghp_mock")
+        .env("NO_PROXY", "127.0.0.1")
+        .env_remove("COPILOT_USAGE_TOKEN")
+        .env_remove("COPILOT_USAGE_CACHE_TTL")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let lines = lines(&mut child);
+
+    let first = next_text(&lines);
+    assert_eq!(usage_requests(&server).await, 1);
+
+    // SIGUSR1: otra línea aunque el cache siga fresco, con un fetch nuevo
+    send(&child, libc::SIGUSR1);
+    assert_eq!(next_text(&lines), first);
+    assert_eq!(usage_requests(&server).await, 2);
+
+    // SIGUSR2: el formato nuevo, sin tocar la API
+    save(&config_manager, "used {used}");
+    send(&child, libc::SIGUSR2);
+    assert!(next_text(&lines).starts_with("used "));
+    assert_eq!(usage_requests(&server).await, 2);
+
+    send(&child, libc::SIGTERM);
+    assert!(child.wait().unwrap().success());
+}
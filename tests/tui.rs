@@ -0,0 +1,220 @@
+//! Drives the whole TUI loop headlessly: key events go through the reducer,
+//! the effects spawn real background tasks against a fixture source, and
+//! the results are applied and drawn on a `TestBackend`.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use copilot_usage::api::{ConditionalUsage, calculate_stats};
+use copilot_usage::config::ConfigManager;
+use copilot_usage::models::{CacheEntry, CacheValidators, Config, Theme, UsageData};
+use copilot_usage::ui::async_handler::{
+    AppContext, AsyncHandler, FetchFuture, Fetched, UsageSource,
+};
+use copilot_usage::ui::session::Session;
+use copilot_usage::ui::state::{AppState, AppStateManager};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::{Terminal, backend::TestBackend};
+use tempfile::TempDir;
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 30;
+
+/// Tiempo máximo de espera por una tarea en background
+const TASK_TIMEOUT: Duration = Duration::from_secs(5);
+
+const FIXTURE_ETAG: &str = "\"fixture\"";
+
+fn fixture() -> UsageData {
+    serde_json::from_str(include_str!("fixtures/usage_duplicated_rows.json")).unwrap()
+}
+
+/// Mediados del periodo del fixture (febrero de 2026)
+fn clock() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 2, 15, 12, 0, 0).unwrap()
+}
+
+/// Answers every refresh with the fixture, no network. Like GitHub, a
+/// request carrying the fixture's ETag gets a 304.
+struct FixtureSource(UsageData);
+
+impl UsageSource for FixtureSource {
+    fn fetch<'a>(&'a self, _: &'a AppContext, validators: &'a CacheValidators) -> FetchFuture<'a> {
+        let usage = if validators.etag.as_deref() == Some(FIXTURE_ETAG) {
+            ConditionalUsage::NotModified
+        } else {
+            let validators = CacheValidators {
+                etag: Some(FIXTURE_ETAG.to_string()),
+                last_modified: None,
+            };
+            ConditionalUsage::Fresh(self.0.clone(), validators)
+        };
+        Box::pin(async move {
+            Ok(Fetched {
+                usage,
+                rate_limit: None,
+            })
+        })
+    }
+}
+
+struct TestHarness {
+    home: TempDir,
+    async_handler: AsyncHandler,
+    session: Session,
+    terminal: Terminal<TestBackend>,
+}
+
+impl TestHarness {
+    /// Config, cache and history all live in a temp dir. Only one harness
+    /// per test binary: the XDG variables are process-wide.
+    fn new() -> Self {
+        let home = TempDir::new().unwrap();
+        // SAFETY: set before any thread of this binary reads the environment
+        unsafe {
+            std::env::set_var("XDG_CONFIG_HOME", home.path().join("config"));
+            std::env::set_var("XDG_CACHE_HOME", home.path().join("cache"));
+            std::env::set_var("XDG_DATA_HOME", home.path().join("data"));
+        }
+
+        let config_manager = ConfigManager::new().unwrap();
+        let config = Config {
+            token: "ghp_fixture".to_string(),
+            ..Config::default()
+        };
+        config_manager.save(&config).unwrap();
+
+        // Arranca sin uso; el refresh trae el fixture
+        let mut empty = fixture();
+        empty.usage_items.clear();
+        let stats = calculate_stats(&empty, config.premium_request_limit);
+
+        let mut app = AppStateManager::new();
+        app.set_profiles(
+            vec![config_manager.profile().to_string()],
+            config_manager.profile(),
+        );
+        let async_handler = AsyncHandler::new(AppContext::load(config_manager))
+            .with_source(FixtureSource(fixture()));
+
+        Self {
+            home,
+            async_handler,
+            session: Session::new(app, stats, Theme::Dark, clock),
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap(),
+        }
+    }
+
+    /// Returns true when the key quits the TUI
+    fn press(&mut self, code: KeyCode) -> bool {
+        let quit = self
+            .session
+            .handle_event(Event::Key(KeyEvent::from(code)), &self.async_handler);
+        self.draw();
+        quit
+    }
+
+    /// Waits for the next background result and applies it
+    fn complete(&mut self) {
+        let deadline = Instant::now() + TASK_TIMEOUT;
+        loop {
+            if let Some(result) = self.async_handler.try_recv() {
+                self.session
+                    .handle_result(result, &self.async_handler)
+                    .unwrap();
+                self.draw();
+                return;
+            }
+            assert!(Instant::now() < deadline, "no background result");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn draw(&mut self) {
+        self.terminal.draw(|f| self.session.render(f)).unwrap();
+    }
+
+    fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        (0..HEIGHT)
+            .map(|y| {
+                (0..WIDTH)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn path(&self, parts: &[&str]) -> PathBuf {
+        parts
+            .iter()
+            .fold(self.home.path().to_path_buf(), |path, part| path.join(part))
+    }
+}
+
+fn read(path: &Path) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_scripted_session() {
+    let mut harness = TestHarness::new();
+    harness.draw();
+    assert!(harness.screen().contains("octocat"));
+    assert!(!harness.screen().contains("gpt-4.1"));
+
+    // Menú → refresh → confirmar
+    assert!(!harness.press(KeyCode::Char(':')));
+    assert_eq!(harness.session.app.state, AppState::CommandMenu);
+    harness.press(KeyCode::Char('r'));
+    assert_eq!(harness.session.app.state, AppState::ConfirmRefresh);
+    harness.press(KeyCode::Char('y'));
+    assert_eq!(harness.session.app.state, AppState::LoadingRefresh);
+    harness.complete();
+    assert_eq!(harness.session.app.state, AppState::Dashboard);
+    assert!(harness.screen().contains("gpt-4.1"));
+    assert!(harness.screen().contains("claude-sonnet-4"));
+    assert_eq!(
+        harness.session.stats().total_used,
+        calculate_stats(&fixture(), 300).total_used
+    );
+
+    // El refresh dejó el fixture en el cache del perfil
+    let cache_path = harness.path(&["cache", "copilot-usage", "usage-default.json"]);
+    let cache: CacheEntry = serde_json::from_str(&read(&cache_path)).unwrap();
+    assert_eq!(cache.data.usage_items.len(), fixture().usage_items.len());
+    assert_eq!(cache.validators.etag.as_deref(), Some(FIXTURE_ETAG));
+
+    // Otro refresh: 304, se conservan los datos y se renueva la marca de tiempo
+    harness.press(KeyCode::Char(':'));
+    harness.press(KeyCode::Char('r'));
+    harness.press(KeyCode::Char('y'));
+    harness.complete();
+    assert_eq!(harness.session.app.state, AppState::Dashboard);
+    assert!(harness.screen().contains("gpt-4.1"));
+    let revalidated: CacheEntry = serde_json::from_str(&read(&cache_path)).unwrap();
+    assert_eq!(
+        revalidated.data.usage_items.len(),
+        fixture().usage_items.len()
+    );
+    assert_eq!(revalidated.validators, cache.validators);
+    assert!(revalidated.timestamp > cache.timestamp);
+
+    // Menú → tema → el siguiente de la lista
+    harness.press(KeyCode::Char(':'));
+    harness.press(KeyCode::Char('t'));
+    assert_eq!(harness.session.app.state, AppState::ThemeSelector);
+    harness.press(KeyCode::Down);
+    harness.press(KeyCode::Enter);
+    assert_eq!(harness.session.theme(), Theme::Light);
+    harness.complete();
+    let config = read(&harness.path(&["config", "copilot-usage", "config.toml"]));
+    assert!(config.contains("theme = \"light\""));
+
+    // Menú → salir
+    harness.press(KeyCode::Char(':'));
+    assert!(harness.press(KeyCode::Char('q')));
+}
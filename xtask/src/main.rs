@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use copilot_usage::anonymize::{anonymize, validate_fixture};
+use copilot_usage::api::ApiClient;
+
+#[derive(Parser)]
+#[command(name = "xtask")]
+#[command(about = "Development tasks for copilot-usage", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Task,
+}
+
+#[derive(Subcommand)]
+enum Task {
+    /// Fetch the live usage payload and save it anonymized under tests/fixtures/
+    FetchFixture {
+        /// Fixture name, written as tests/fixtures/<name>.json
+        name: String,
+        /// GitHub token (defaults to $GITHUB_TOKEN). Never written to disk.
+        #[arg(long, env = "GITHUB_TOKEN", hide_env_values = true)]
+        token: String,
+        /// GitHub username (defaults to the token's owner)
+        #[arg(long)]
+        user: Option<String>,
+        /// Overwrite an existing fixture
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    match Cli::parse().command {
+        Task::FetchFixture {
+            name,
+            token,
+            user,
+            force,
+        } => fetch_fixture(&name, token, user, force).await,
+    }
+}
+
+async fn fetch_fixture(name: &str, token: String, user: Option<String>, force: bool) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        bail!("Fixture name must only contain letters, digits, '-' and '_'");
+    }
+
+    let path = fixtures_dir().join(format!("{}.json", name));
+    if path.exists() && !force {
+        bail!(
+            "{} already exists (use --force to overwrite)",
+            path.display()
+        );
+    }
+
+    let client = ApiClient::new(token)?;
+    let user = match user {
+        Some(user) => user,
+        None => client
+            .get_authenticated_user()
+            .await
+            .context("Could not determine the username, pass --user")?,
+    };
+    let data = client.fetch_usage(&user).await?;
+
+    let anonymized = anonymize(&data, &mut rand::rng());
+    let json = serde_json::to_string_pretty(&anonymized)?;
+
+    // Schema-validation pass before anything touches the tree
+    validate_fixture(&json)?;
+    if json.contains(&user) {
+        bail!("Anonymized fixture still contains the username, not writing it");
+    }
+
+    fs::write(&path, format!("{}\n", json))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!(
+        "Wrote {} ({} usage rows, user {})",
+        path.display(),
+        anonymized.usage_items.len(),
+        anonymized.user
+    );
+    Ok(())
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives inside the workspace")
+        .join("tests")
+        .join("fixtures")
+}